@@ -25,14 +25,29 @@ pub(crate) fn regist_windows(shared_state: &mut SharedState) -> Vec<Box<dyn Wind
         Box::new(tools::FogWindow::default()),
         Box::new(tools::PostProcessingMenu::default()),
         Box::new(tools::MaterialInspector::new(shared_state)),
+        Box::new(tools::InterfaceList::default()),
+        Box::new(tools::ConsoleWindow::new()),
+        Box::new(tools::CvarBrowser::default()),
+        Box::new(tools::EventMonitor::default()),
+        Box::new(tools::EntityInspector::default()),
         // TODO: sounds emitter window
     ]
 }
 
 
-pub(crate) fn regist_events(engine: &Engine, _shared_state: &mut SharedState) {
+/// Game events forwarded to the windows as `OverlayEvent::GameEvent`.
+const FORWARDED_EVENTS: [&str; 3] = ["server_spawn", "portal_fired", "player_death"];
+
+pub(crate) fn regist_events(engine: &Engine, shared_state: &mut SharedState) {
     // https://developer.valvesoftware.com/wiki/Logic_eventlistener
 
+    // Event schemas let `GameEvent::snapshot` copy every field of an event.
+    let fs = &shared_state.valve_fs;
+    match engine.game_event_manager().load_schemas(|path| fs.read_str(path, "GAME", false)) {
+        Ok(count) => log::info!("Loaded {} game event schemas", count),
+        Err(e) => log::warn!("Failed to load game event schemas: {}", e),
+    }
+
     // EXAMPLES:
     // engine.game_event_manager().listen("server_spawn", |event| {
     //     toasts::info(
@@ -42,9 +57,11 @@ pub(crate) fn regist_events(engine: &Engine, _shared_state: &mut SharedState) {
     //     log::info!(target: "toast", "SERVER SPAWNED!"); // OR this way
     // });
 
-    engine.game_event_manager().listen("server_spawn", |_| {
-        events::push_event(OverlayEvent::GameEvent("server_spawn".to_string())); // todo: we lose all event-data
-    });
+    for name in FORWARDED_EVENTS {
+        engine.game_event_manager().listen(name, |event| {
+            events::push_event(OverlayEvent::GameEvent(event.snapshot()));
+        });
+    }
 
     // engine.game_event_manager().listen("server_cvar", |event| {
     //     if event.get_string("cvarname", "") == "sv_cheats" {
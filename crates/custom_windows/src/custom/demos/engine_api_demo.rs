@@ -22,6 +22,7 @@ pub struct EngineApiDemoWindow {
     cvar_float_input: f32,
     cvar_int_input: i32,
     view_angles_buffer: QAngle,
+    event_name_input: String,
 }
 impl Default for EngineApiDemoWindow {
     fn default() -> Self {
@@ -39,6 +40,7 @@ impl Default for EngineApiDemoWindow {
             cvar_float_input: 0.0,
             cvar_int_input: 0,
             view_angles_buffer: QAngle::default(),
+            event_name_input: "portal_player_portaled".to_string(),
         }
     }
 }
@@ -194,6 +196,26 @@ impl Window for EngineApiDemoWindow {
                         ui.add(TextEdit::singleline(&mut self.load_model_input));
                     });
                 });
+                // === Section: Game Events ===
+                CollapsingHeader::new("Game Events").default_open(false).show(ui, |ui| {
+                    let events = engine.game_event_manager();
+                    ui.horizontal(|ui| {
+                        ui.label("Event Name:");
+                        ui.add(TextEdit::singleline(&mut self.event_name_input));
+                    });
+                    ui.horizontal(|ui| {
+                        let fire = ui.button("Fire Event").clicked();
+                        let fire_client = ui.button("Fire Client Side").clicked();
+                        if fire || fire_client {
+                            let fired = match events.create_event(&self.event_name_input) {
+                                Some(event) if fire => event.fire(),
+                                Some(event) => event.fire_client_side(),
+                                None => false,
+                            };
+                            log::info!(target: "toast", "DEMO: Fire '{}' result: {}", self.event_name_input, fired);
+                        }
+                    });
+                });
             });
     }
 }
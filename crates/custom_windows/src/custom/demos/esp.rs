@@ -69,28 +69,22 @@ impl Esp {
         let screen_center_bottom = screen_rect.center_bottom();
 
         let ents = engine.entities();
-        let local_player = ents.find_by_classname(None, "player");
+        let local_player = ents.query().class("player").first();
         let local_player_origin = local_player.map(|p| p.get_origin()).unwrap_or_default();
 
         let debug_overlay = engine.debug_overlay();
 
-        for ent in ents.iter() {
-            let classname = ent.get_classname();
-
-            // Filter out junk entities
-            if classname == "player" || classname == "worldspawn" || classname.is_empty() {
-                continue;
-            }
-
-            // To prevent screen clutter, only show specific entity types
-            if !classname.starts_with("prop_")  {
-                continue;
-            }
+        // To prevent screen clutter, only show specific entity types
+        let nearby = ents.query()
+            .class_glob("prop_*")
+            .within(local_player_origin, self.max_distance);
 
+        for ent in nearby {
+            let classname = ent.get_classname();
             let origin = ent.get_origin();
             let dist = local_player_origin.distance(&origin);
 
-            if dist > self.max_distance || dist <= 0.0 {
+            if dist <= 0.0 {
                 continue;
             }
 
@@ -0,0 +1,329 @@
+use std::collections::VecDeque;
+
+use egui::{Color32, Context, Key, Modifiers, RichText, ScrollArea, TextEdit, TextStyle};
+use portal2_sdk::{ConsoleLine, Engine};
+
+use crate::{SharedState, Window};
+
+/// Lines kept by the engine-side capture and by the window.
+const CAPACITY: usize = 4096;
+const HISTORY_LIMIT: usize = 100;
+const MAX_SUGGESTIONS: usize = 12;
+
+/// A searchable copy of the developer console, with history and autocomplete.
+pub struct ConsoleWindow {
+    is_open: bool,
+    lines: VecDeque<ConsoleLine>,
+    last_id: Option<u64>,
+
+    filter: String,
+    show_developer: bool,
+    search: String,
+    /// Index into the visible lines of the search match to scroll to.
+    search_hit: Option<usize>,
+    scroll_to_hit: bool,
+
+    input: String,
+    history: Vec<String>,
+    /// Position in `history` while browsing it with the arrow keys.
+    history_pos: Option<usize>,
+    /// Every command and variable name, gathered when the window opens.
+    command_names: Option<Vec<String>>,
+}
+
+impl ConsoleWindow {
+    pub fn new() -> Self {
+        // Started here rather than on first draw, so the window also has what was
+        // printed before it was opened.
+        portal2_sdk::get_engine().cvar_system().capture_console(CAPACITY);
+
+        Self {
+            is_open: false,
+            lines: VecDeque::new(),
+            last_id: None,
+            filter: String::new(),
+            show_developer: true,
+            search: String::new(),
+            search_hit: None,
+            scroll_to_hit: false,
+            input: String::new(),
+            history: Vec::new(),
+            history_pos: None,
+            command_names: None,
+        }
+    }
+}
+
+impl Window for ConsoleWindow {
+    fn name(&self) -> &'static str { "Console" }
+
+    fn set_open(&mut self, open: bool) {
+        if open && !self.is_open {
+            self.command_names = None;
+        }
+        self.is_open = open;
+    }
+
+    fn is_open(&self) -> bool { self.is_open }
+
+    fn is_should_render(&self, shared_state: &SharedState, _engine: &Engine) -> bool {
+        shared_state.is_overlay_focused
+    }
+
+    fn draw(&mut self, ctx: &Context, _shared_state: &mut SharedState, engine: &Engine) {
+        self.pull_lines(engine);
+        let mut open = self.is_open;
+
+        egui::Window::new(self.name())
+            .open(&mut open)
+            .resizable(true)
+            .default_width(640.0)
+            .default_height(420.0)
+            .show(ctx, |ui| {
+                self.draw_toolbar(ui);
+                ui.separator();
+
+                let scroll_to = self.search_hit.filter(|_| std::mem::take(&mut self.scroll_to_hit));
+                self.draw_output(ui, &self.visible_lines(), scroll_to);
+                ui.separator();
+
+                self.draw_input(ui, engine);
+            });
+
+        self.is_open = open;
+    }
+}
+
+impl ConsoleWindow {
+    /// Appends what the engine printed since the last frame.
+    fn pull_lines(&mut self, engine: &Engine) {
+        let new = engine.cvar_system().console_lines_after(self.last_id);
+        if let Some(last) = new.last() {
+            self.last_id = Some(last.id);
+        }
+        self.lines.extend(new);
+        while self.lines.len() > CAPACITY {
+            self.lines.pop_front();
+        }
+    }
+
+    fn visible_lines(&self) -> Vec<&ConsoleLine> {
+        let filter = self.filter.to_lowercase();
+        self.lines
+            .iter()
+            .filter(|line| self.show_developer || !line.developer)
+            .filter(|line| filter.is_empty() || line.text.to_lowercase().contains(&filter))
+            .collect()
+    }
+
+    fn draw_toolbar(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.add(TextEdit::singleline(&mut self.filter).hint_text("Filter").desired_width(160.0));
+            ui.checkbox(&mut self.show_developer, "Developer");
+
+            ui.separator();
+            let search = ui.add(TextEdit::singleline(&mut self.search).hint_text("Search").desired_width(140.0));
+            if search.changed() {
+                self.search_hit = None;
+            }
+            let enter = search.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter));
+            if ui.small_button("⏶").on_hover_text("Previous match").clicked() {
+                self.step_search(-1);
+            }
+            if ui.small_button("⏷").on_hover_text("Next match").clicked() || enter {
+                self.step_search(1);
+            }
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.button("Clear").clicked() {
+                    self.lines.clear();
+                    self.search_hit = None;
+                }
+                if ui.button("📋 Copy").on_hover_text("Copy the visible lines").clicked() {
+                    let text = self.visible_lines().iter().map(|l| l.text.as_str()).collect::<Vec<_>>().join("\n");
+                    ui.ctx().copy_text(text);
+                }
+            });
+        });
+    }
+
+    /// Moves the search hit to the next (`1`) or previous (`-1`) matching visible line.
+    fn step_search(&mut self, direction: isize) {
+        if self.search.is_empty() {
+            return;
+        }
+        let needle = self.search.to_lowercase();
+        let matches: Vec<usize> = self.visible_lines()
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.text.to_lowercase().contains(&needle))
+            .map(|(i, _)| i)
+            .collect();
+        if matches.is_empty() {
+            self.search_hit = None;
+            return;
+        }
+
+        let next = match (self.search_hit, direction > 0) {
+            (None, true) => matches[0],
+            (None, false) => matches[matches.len() - 1],
+            (Some(hit), true) => matches.iter().copied().find(|&i| i > hit).unwrap_or(matches[0]),
+            (Some(hit), false) => matches.iter().rev().copied().find(|&i| i < hit).unwrap_or(matches[matches.len() - 1]),
+        };
+        self.search_hit = Some(next);
+        self.scroll_to_hit = true;
+    }
+
+    fn draw_output(&self, ui: &mut egui::Ui, visible: &[&ConsoleLine], scroll_to: Option<usize>) {
+        let row_height = ui.text_style_height(&TextStyle::Monospace);
+        let input_height = ui.spacing().interact_size.y * 2.0 + ui.spacing().item_spacing.y * 4.0;
+        let needle = self.search.to_lowercase();
+
+        let mut area = ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .max_height(ui.available_height() - input_height)
+            .stick_to_bottom(true);
+        if let Some(hit) = scroll_to {
+            let spacing = ui.spacing().item_spacing.y;
+            area = area.vertical_scroll_offset(hit as f32 * (row_height + spacing));
+        }
+
+        area.show_rows(ui, row_height, visible.len(), |ui, rows| {
+            for index in rows {
+                let line = visible[index];
+                let mut text = RichText::new(&line.text).monospace();
+                text = match (line.color, line.developer) {
+                    (Some(c), _) => text.color(Color32::from_rgb(c.r, c.g, c.b)),
+                    (None, true) => text.weak(),
+                    (None, false) => text,
+                };
+                if Some(index) == self.search_hit {
+                    text = text.background_color(Color32::from_rgb(120, 100, 20));
+                } else if !needle.is_empty() && line.text.to_lowercase().contains(&needle) {
+                    text = text.background_color(Color32::from_rgb(60, 50, 10));
+                }
+                ui.add(egui::Label::new(text).wrap_mode(egui::TextWrapMode::Extend));
+            }
+        });
+    }
+
+    fn draw_input(&mut self, ui: &mut egui::Ui, engine: &Engine) {
+        let input_id = ui.id().with("console_input");
+        let focused = ui.memory(|m| m.has_focus(input_id));
+        let suggestions = self.suggestions(engine);
+
+        // Taken before the text edit sees them: it would move the cursor or the focus.
+        let (up, down, tab) = match focused {
+            true => ui.input_mut(|i| (
+                i.consume_key(Modifiers::NONE, Key::ArrowUp),
+                i.consume_key(Modifiers::NONE, Key::ArrowDown),
+                i.consume_key(Modifiers::NONE, Key::Tab),
+            )),
+            false => (false, false, false),
+        };
+        if up || down {
+            self.browse_history(up);
+        }
+        if let (true, Some(first)) = (tab, suggestions.first()) {
+            self.input = format!("{} ", first);
+        }
+
+        let mut submitted = false;
+        ui.horizontal(|ui| {
+            let edit = TextEdit::singleline(&mut self.input)
+                .id(input_id)
+                .font(TextStyle::Monospace)
+                .hint_text("Command")
+                .desired_width(ui.available_width() - 48.0);
+            let response = ui.add(edit);
+            if tab {
+                // Puts the cursor after the completed name.
+                if let Some(mut state) = TextEdit::load_state(ui.ctx(), input_id) {
+                    let end = egui::text::CCursor::new(self.input.chars().count());
+                    state.cursor.set_char_range(Some(egui::text::CCursorRange::one(end)));
+                    state.store(ui.ctx(), input_id);
+                }
+            }
+            if response.changed() {
+                self.history_pos = None;
+            }
+            let enter = response.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter));
+            if ui.button("Run").clicked() || enter {
+                submitted = true;
+                response.request_focus();
+            }
+        });
+
+        if focused && !suggestions.is_empty() {
+            ui.horizontal_wrapped(|ui| {
+                ui.weak("Tab:");
+                for name in &suggestions {
+                    if ui.small_button(RichText::new(name).monospace()).clicked() {
+                        self.input = format!("{} ", name);
+                        ui.memory_mut(|m| m.request_focus(input_id));
+                    }
+                }
+            });
+        }
+
+        if submitted {
+            self.submit(engine);
+        }
+    }
+
+    fn browse_history(&mut self, older: bool) {
+        if self.history.is_empty() {
+            return;
+        }
+        let pos = match (self.history_pos, older) {
+            (None, true) => Some(self.history.len() - 1),
+            (None, false) => None,
+            (Some(pos), true) => Some(pos.saturating_sub(1)),
+            (Some(pos), false) => (pos + 1 < self.history.len()).then_some(pos + 1),
+        };
+        self.history_pos = pos;
+        self.input = pos.map(|p| self.history[p].clone()).unwrap_or_default();
+    }
+
+    /// Command and variable names starting with what is typed, while the name is typed.
+    fn suggestions(&mut self, engine: &Engine) -> Vec<String> {
+        let typed = self.input.trim_start();
+        if typed.is_empty() || typed.contains(' ') {
+            return Vec::new();
+        }
+        let typed = typed.to_lowercase();
+
+        let names = self.command_names.get_or_insert_with(|| {
+            let mut names: Vec<String> = engine.cvar_system().iter().map(|b| b.get_name().to_string()).collect();
+            names.sort_by_key(|n| n.to_lowercase());
+            names.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+            names
+        });
+
+        names.iter()
+            .filter(|name| name.to_lowercase().starts_with(&typed) && name.len() > typed.len())
+            .take(MAX_SUGGESTIONS)
+            .cloned()
+            .collect()
+    }
+
+    fn submit(&mut self, engine: &Engine) {
+        let command = self.input.trim().to_string();
+        self.input.clear();
+        self.history_pos = None;
+        if command.is_empty() {
+            return;
+        }
+
+        if self.history.last() != Some(&command) {
+            self.history.push(command.clone());
+            if self.history.len() > HISTORY_LIMIT {
+                self.history.remove(0);
+            }
+        }
+
+        // Echoed like the in-game console does, so it shows up in both.
+        engine.cvar_system().console_print(&format!("] {}\n", command));
+        engine.client().execute_client_cmd_unrestricted(&command);
+    }
+}
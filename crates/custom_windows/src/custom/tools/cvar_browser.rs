@@ -0,0 +1,291 @@
+use egui::{Color32, Context, RichText, ScrollArea, TextEdit};
+use portal2_sdk::{ConVar, CvarFlags, Engine};
+
+use crate::{SharedState, Window};
+
+/// The flags offered as filters, with their labels.
+const FLAG_FILTERS: [(CvarFlags, &str); 4] = [
+    (CvarFlags::CHEAT, "Cheat"),
+    (CvarFlags::ARCHIVE, "Archive"),
+    (CvarFlags::DEVELOPMENTONLY, "Dev-only"),
+    (CvarFlags::HIDDEN, "Hidden"),
+];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+enum Kind {
+    #[default]
+    All,
+    Variables,
+    Commands,
+}
+
+/// What is known about a command or variable without reading its value.
+struct Entry {
+    name: String,
+    help: String,
+    flags: CvarFlags,
+    is_command: bool,
+    /// The `ConVar`, for variables. Valid while it stays registered, so the list is
+    /// rebuilt whenever the window opens.
+    var: Option<usize>,
+}
+
+impl Entry {
+    fn var(&self) -> Option<&'static ConVar> {
+        self.var.map(|addr| unsafe { &*(addr as *const ConVar) })
+    }
+}
+
+/// Lists every registered command and variable, including the ones hidden from the
+/// console's `find`, with fuzzy search and inline editing.
+#[derive(Default)]
+pub struct CvarBrowser {
+    is_open: bool,
+    query: String,
+    kind: Kind,
+    /// Flags an entry must have all of to be listed.
+    required: CvarFlags,
+    entries: Option<Vec<Entry>>,
+    /// Index into `entries`.
+    selected: Option<usize>,
+    edit_value: String,
+    command_args: String,
+}
+
+impl Window for CvarBrowser {
+    fn name(&self) -> &'static str { "ConVar Browser" }
+
+    fn set_open(&mut self, open: bool) {
+        if open && !self.is_open {
+            self.entries = None;
+        }
+        self.is_open = open;
+    }
+
+    fn is_open(&self) -> bool { self.is_open }
+
+    fn is_should_render(&self, shared_state: &SharedState, _engine: &Engine) -> bool {
+        shared_state.is_overlay_focused
+    }
+
+    fn draw(&mut self, ctx: &Context, _shared_state: &mut SharedState, engine: &Engine) {
+        let mut open = self.is_open;
+
+        egui::Window::new(self.name())
+            .open(&mut open)
+            .resizable(true)
+            .default_width(560.0)
+            .default_height(520.0)
+            .show(ctx, |ui| {
+                if self.entries.is_none() {
+                    self.entries = Some(collect_entries(engine));
+                    self.selected = None;
+                }
+
+                self.draw_filters(ui);
+                ui.separator();
+
+                egui::TopBottomPanel::bottom("cvar_browser_details")
+                    .resizable(true)
+                    .min_height(140.0)
+                    .show_inside(ui, |ui| self.draw_details(ui, engine));
+
+                egui::CentralPanel::default().show_inside(ui, |ui| self.draw_list(ui));
+            });
+
+        self.is_open = open;
+    }
+}
+
+impl CvarBrowser {
+    fn draw_filters(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.add(TextEdit::singleline(&mut self.query).hint_text("🔍 Fuzzy search").desired_width(200.0));
+            ui.selectable_value(&mut self.kind, Kind::All, "All");
+            ui.selectable_value(&mut self.kind, Kind::Variables, "Variables");
+            ui.selectable_value(&mut self.kind, Kind::Commands, "Commands");
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.button("🔄 Refresh").clicked() {
+                    self.entries = None;
+                }
+            });
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Only with:");
+            for (flag, label) in FLAG_FILTERS {
+                let mut on = self.required.contains(flag);
+                if ui.toggle_value(&mut on, label).changed() {
+                    self.required.set(flag, on);
+                }
+            }
+        });
+    }
+
+    /// Indices of the listed entries, best fuzzy match first while searching.
+    fn listed(&self) -> Vec<usize> {
+        let Some(entries) = &self.entries else { return Vec::new() };
+
+        let mut listed: Vec<(usize, i32)> = entries.iter()
+            .enumerate()
+            .filter(|(_, e)| match self.kind {
+                Kind::All => true,
+                Kind::Variables => !e.is_command,
+                Kind::Commands => e.is_command,
+            })
+            .filter(|(_, e)| e.flags.contains(self.required))
+            .filter_map(|(i, e)| fuzzy_score(&e.name, &self.query).map(|score| (i, score)))
+            .collect();
+
+        listed.sort_by(|(a, a_score), (b, b_score)| {
+            b_score.cmp(a_score).then_with(|| entries[*a].name.cmp(&entries[*b].name))
+        });
+        listed.into_iter().map(|(i, _)| i).collect()
+    }
+
+    fn draw_list(&mut self, ui: &mut egui::Ui) {
+        let listed = self.listed();
+        let Some(entries) = &self.entries else { return };
+        ui.weak(format!("{} of {}", listed.len(), entries.len()));
+
+        let row_height = ui.spacing().interact_size.y;
+        let mut clicked = None;
+        ScrollArea::vertical().auto_shrink([false, false]).show_rows(ui, row_height, listed.len(), |ui, rows| {
+            for &index in &listed[rows] {
+                let entry = &entries[index];
+                ui.horizontal(|ui| {
+                    let name = RichText::new(&entry.name).monospace();
+                    if ui.selectable_label(self.selected == Some(index), name).clicked() {
+                        clicked = Some(index);
+                    }
+
+                    match entry.var() {
+                        Some(var) => {
+                            let value = var.get_string();
+                            let text = RichText::new(&value).monospace();
+                            match value == var.get_default() {
+                                true => ui.weak(text),
+                                false => ui.label(text.color(Color32::YELLOW)).on_hover_text("Changed from the default"),
+                            };
+                        }
+                        None => { ui.weak("command"); }
+                    }
+
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        for (flag, label) in FLAG_FILTERS {
+                            if entry.flags.contains(flag) {
+                                ui.small(label);
+                            }
+                        }
+                    });
+                });
+            }
+        });
+
+        if let Some(index) = clicked {
+            self.select(index);
+        }
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected = Some(index);
+        self.command_args.clear();
+        self.edit_value = self.entries.as_ref()
+            .and_then(|entries| entries[index].var())
+            .map(|var| var.get_string())
+            .unwrap_or_default();
+    }
+
+    fn draw_details(&mut self, ui: &mut egui::Ui, engine: &Engine) {
+        let (Some(entries), Some(index)) = (&self.entries, self.selected) else {
+            ui.weak("Select a command or variable to see its details.");
+            return;
+        };
+        let entry = &entries[index];
+
+        ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+            ui.heading(RichText::new(&entry.name).monospace());
+            if !entry.help.is_empty() {
+                ui.label(&entry.help);
+            }
+            let flags: Vec<String> = entry.flags.iter().map(|f| format!("{:?}", f)).collect();
+            ui.weak(format!("Flags: {}", if flags.is_empty() { "none".to_string() } else { flags.join(", ") }));
+            ui.add_space(4.0);
+
+            let Some(var) = entry.var() else {
+                ui.horizontal(|ui| {
+                    let args = ui.add(TextEdit::singleline(&mut self.command_args).hint_text("Arguments").desired_width(240.0));
+                    let enter = args.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if ui.button("Run").clicked() || enter {
+                        let command = format!("{} {}", entry.name, self.command_args);
+                        engine.client().execute_client_cmd_unrestricted(command.trim_end());
+                    }
+                });
+                return;
+            };
+
+            let bounds = |bound: Option<f32>| bound.map_or("-".to_string(), |v| v.to_string());
+            ui.label(format!("Default: {}    Min: {}    Max: {}", var.get_default(), bounds(var.get_min()), bounds(var.get_max())));
+
+            ui.horizontal(|ui| {
+                let edit = ui.add(TextEdit::singleline(&mut self.edit_value).font(egui::TextStyle::Monospace).desired_width(240.0));
+                let enter = edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                // Set directly, so development-only and cheat variables can be changed too.
+                if ui.button("Set").clicked() || enter {
+                    var.set_value_str(&self.edit_value);
+                    self.edit_value = var.get_string();
+                }
+                if ui.button("Reset to default").clicked() {
+                    var.reset();
+                    self.edit_value = var.get_string();
+                }
+            });
+        });
+    }
+}
+
+fn collect_entries(engine: &Engine) -> Vec<Entry> {
+    let mut entries: Vec<Entry> = engine.cvar_system()
+        .iter()
+        .map(|base| Entry {
+            name: base.get_name().to_string(),
+            help: base.get_help_text().to_string(),
+            flags: base.get_flags(),
+            is_command: base.is_command(),
+            var: base.as_var().map(|var| var as *mut ConVar as usize),
+        })
+        .collect();
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    entries
+}
+
+/// Scores `name` as a case-insensitive subsequence match of `query`, or `None` if it is
+/// not one. Consecutive letters and matches at the start of a word score higher, and
+/// shorter names break ties.
+fn fuzzy_score(name: &str, query: &str) -> Option<i32> {
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let name_lower = name.to_ascii_lowercase();
+    let name = name_lower.as_bytes();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut previous: Option<usize> = None;
+
+    for wanted in query.to_ascii_lowercase().bytes().filter(|b| !b.is_ascii_whitespace()) {
+        let found = pos + name[pos..].iter().position(|&c| c == wanted)?;
+        score += 1;
+        if previous.is_some_and(|p| p + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || name[found - 1] == b'_' {
+            score += 3;
+        }
+        previous = Some(found);
+        pos = found + 1;
+    }
+
+    Some(score * 100 - name.len() as i32)
+}
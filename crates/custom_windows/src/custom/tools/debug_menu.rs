@@ -29,7 +29,7 @@ impl Window for DebugMenu {
         shared_state.is_overlay_focused
     }
 
-    fn draw(&mut self, ctx: &Context, _shared_state: &mut SharedState, engine: &Engine) {
+    fn draw(&mut self, ctx: &Context, shared_state: &mut SharedState, engine: &Engine) {
         let mut open = self.is_open;
 
         egui::Window::new(self.name())
@@ -55,7 +55,7 @@ impl Window for DebugMenu {
                         DebugTab::Performance => self.render_performance(ui, engine),
                         DebugTab::MatSys => self.render_matsys(ui, engine),
                         DebugTab::Renderer => self.render_renderer(ui, engine),
-                        DebugTab::Entities => self.render_entities(ui, engine),
+                        DebugTab::Entities => self.render_entities(ui, engine, shared_state),
                     }
                 });
             });
@@ -161,7 +161,7 @@ impl DebugMenu {
             });
     }
 
-    fn render_entities(&self, ui: &mut Ui, engine: &Engine) {
+    fn render_entities(&self, ui: &mut Ui, engine: &Engine, shared_state: &mut SharedState) {
         let trace_result = get_entity_under_crosshair(engine);
 
         // Render the smart target info box
@@ -187,6 +187,20 @@ impl DebugMenu {
                 });
             });
 
+        let entities = engine.entities();
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("Selected:").strong());
+            match shared_state.selected_entity.and_then(|handle| handle.resolve(&entities).map(|ent| (handle, ent))) {
+                Some((handle, ent)) => {
+                    ui.label(egui::RichText::new(format!("#{} {} {}", handle.index(), ent.get_classname(), ent.get_name())).color(egui::Color32::GREEN));
+                    if ui.small_button("Clear").clicked() {
+                        shared_state.selected_entity = None;
+                    }
+                }
+                None => { ui.label(egui::RichText::new("Click an entity in the world to select it").color(egui::Color32::DARK_GRAY)); }
+            }
+        });
+
         ui.add_space(12.0);
         ui.label("Point your crosshair at the entity you wish to affect before pressing these buttons!");
         ui.add_space(12.0);
@@ -264,8 +278,7 @@ fn draw_cvar_slider_int(ui: &mut Ui, engine: &Engine, cvar_name: &str, label: &s
 }
 
 fn get_entity_under_crosshair(engine: &Engine) -> Option<(String, f32)> {
-    let e = engine.entities();
-    let local_player = e.find_by_classname(None, "player");
+    let local_player = engine.entities().query().class("player").first();
 
     let server_tools = engine.server_tools();
     if let Some((pos, angles)) = server_tools.get_player_position(None) {
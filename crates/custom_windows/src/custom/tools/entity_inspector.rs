@@ -0,0 +1,282 @@
+use std::collections::BTreeMap;
+
+use egui::{Color32, Context, DragValue, RichText, ScrollArea, TextEdit};
+use overlay_types::toasts;
+use portal2_sdk::types::{CBaseEntity, Handle, Vector};
+use portal2_sdk::Engine;
+
+use crate::{SharedState, Window};
+
+/// How long the bounds drawn by "Show bounds" stay visible, in seconds.
+const BOUNDS_DURATION: f32 = 10.0;
+
+/// One entity of the list, read when the list was built.
+struct Row {
+    handle: Handle,
+    name: String,
+}
+
+/// Lists every entity on the server, grouped by class, and shows the selected one's
+/// keyvalues for live editing.
+#[derive(Default)]
+pub struct EntityInspector {
+    is_open: bool,
+    query: String,
+    /// Rows by classname. Rebuilt when the window opens and on refresh.
+    groups: Option<BTreeMap<String, Vec<Row>>>,
+    /// The selection the details were last drawn for. The selection itself is
+    /// [`SharedState::selected_entity`], so that picking in the world selects here too.
+    shown: Option<Handle>,
+    key_filter: String,
+    /// The keyvalue being edited and its unsaved text.
+    editing: Option<(String, String)>,
+    input: String,
+    input_param: String,
+}
+
+impl Window for EntityInspector {
+    fn name(&self) -> &'static str { "Entity Inspector" }
+
+    fn set_open(&mut self, open: bool) {
+        if open && !self.is_open {
+            self.groups = None;
+        }
+        self.is_open = open;
+    }
+
+    fn is_open(&self) -> bool { self.is_open }
+
+    fn is_should_render(&self, shared_state: &SharedState, _engine: &Engine) -> bool {
+        shared_state.is_overlay_focused
+    }
+
+    fn draw(&mut self, ctx: &Context, shared_state: &mut SharedState, engine: &Engine) {
+        let mut open = self.is_open;
+
+        egui::Window::new(self.name())
+            .open(&mut open)
+            .resizable(true)
+            .default_width(760.0)
+            .default_height(540.0)
+            .show(ctx, |ui| {
+                if self.groups.is_none() {
+                    self.groups = Some(collect_groups(engine));
+                }
+
+                egui::SidePanel::left("entity_inspector_list")
+                    .resizable(true)
+                    .default_width(260.0)
+                    .show_inside(ui, |ui| self.draw_list(ui, shared_state));
+
+                if self.shown != shared_state.selected_entity {
+                    self.shown = shared_state.selected_entity;
+                    self.editing = None;
+                    self.input.clear();
+                    self.input_param.clear();
+                }
+                egui::CentralPanel::default().show_inside(ui, |ui| self.draw_details(ui, engine));
+            });
+
+        self.is_open = open;
+    }
+}
+
+impl EntityInspector {
+    fn draw_list(&mut self, ui: &mut egui::Ui, shared_state: &mut SharedState) {
+        ui.horizontal(|ui| {
+            ui.add(TextEdit::singleline(&mut self.query).hint_text("🔍 Class or name").desired_width(170.0));
+            if ui.button("🔄").on_hover_text("Refresh the list").clicked() {
+                self.groups = None;
+            }
+        });
+
+        let Some(groups) = &self.groups else { return };
+        let query = self.query.to_ascii_lowercase();
+        let total: usize = groups.values().map(Vec::len).sum();
+        ui.weak(format!("{} entities, {} classes", total, groups.len()));
+
+        let mut clicked = None;
+        ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+            for (classname, rows) in groups {
+                let class_matches = classname.to_ascii_lowercase().contains(&query);
+                let listed: Vec<&Row> = rows.iter()
+                    .filter(|row| class_matches || row.name.to_ascii_lowercase().contains(&query))
+                    .collect();
+                if listed.is_empty() {
+                    continue;
+                }
+
+                egui::CollapsingHeader::new(RichText::new(format!("{} ({})", classname, listed.len())).monospace())
+                    .id_salt(classname)
+                    .default_open(!query.is_empty())
+                    .show(ui, |ui| {
+                        for row in listed {
+                            let label = match row.name.is_empty() {
+                                true => format!("#{}", row.handle.index()),
+                                false => format!("#{} {}", row.handle.index(), row.name),
+                            };
+                            let selected = shared_state.selected_entity == Some(row.handle);
+                            if ui.selectable_label(selected, RichText::new(label).monospace()).clicked() {
+                                clicked = Some(row.handle);
+                            }
+                        }
+                    });
+            }
+        });
+
+        if clicked.is_some() {
+            shared_state.selected_entity = clicked;
+        }
+    }
+
+    fn draw_details(&mut self, ui: &mut egui::Ui, engine: &Engine) {
+        let Some(handle) = self.shown else {
+            ui.weak("Select an entity to inspect it, or click one in the world.");
+            return;
+        };
+        let mut entities = engine.entities();
+        let Some(ent) = handle.resolve_mut(&mut entities) else {
+            ui.colored_label(Color32::YELLOW, format!("The entity {:?} no longer exists.", handle));
+            return;
+        };
+
+        ui.heading(RichText::new(ent.get_classname()).monospace());
+        egui::Grid::new("entity_inspector_info").num_columns(2).show(ui, |ui| {
+            ui.label("Name");
+            ui.label(RichText::new(ent.get_name()).monospace());
+            ui.end_row();
+            ui.label("Handle");
+            ui.label(RichText::new(format!("index {}, serial {}", handle.index(), handle.serial())).monospace());
+            ui.end_row();
+            ui.label("Hammer ID");
+            ui.label(RichText::new(ent.get_key_value("hammerid").unwrap_or_default()).monospace());
+            ui.end_row();
+            ui.label("Server class");
+            ui.label(RichText::new(ent.get_server_class().map(|class| class.get_name()).unwrap_or_default()).monospace());
+            ui.end_row();
+        });
+        ui.separator();
+
+        self.draw_transform(ui, engine, ent);
+        ui.separator();
+        self.draw_actions(ui, engine, ent);
+        ui.separator();
+        self.draw_key_values(ui, ent);
+    }
+
+    fn draw_transform(&mut self, ui: &mut egui::Ui, engine: &Engine, ent: &mut CBaseEntity) {
+        let tools = engine.server_tools();
+        egui::Grid::new("entity_inspector_transform").num_columns(2).show(ui, |ui| {
+            let mut origin = ent.get_origin();
+            ui.label("Origin");
+            if drag_xyz(ui, &mut origin.x, &mut origin.y, &mut origin.z, 1.0) {
+                tools.set_key_value_vec(ent, "origin", &origin);
+            }
+            ui.end_row();
+
+            let mut angles = ent.get_angles();
+            ui.label("Angles");
+            if drag_xyz(ui, &mut angles.x, &mut angles.y, &mut angles.z, 0.5) {
+                ent.set_key_value("angles", &format!("{} {} {}", angles.x, angles.y, angles.z));
+            }
+            ui.end_row();
+        });
+    }
+
+    fn draw_actions(&mut self, ui: &mut egui::Ui, engine: &Engine, ent: &mut CBaseEntity) {
+        let (mins, maxs) = bounds(ent);
+
+        ui.horizontal(|ui| {
+            if ui.button("Teleport to").on_hover_text("Moves the player on top of the entity").clicked() {
+                let tools = engine.server_tools();
+                let angles = tools.get_player_position(None).map(|(_, angles)| angles).unwrap_or_default();
+                let destination = ent.get_origin() + Vector::new(0.0, 0.0, maxs.z + 8.0);
+                tools.snap_player_to_position(&destination, &angles, None);
+            }
+            if ui.button("Show bounds").clicked() {
+                engine.debug_overlay().add_box_overlay(&ent.get_origin(), &mins, &maxs, &ent.get_angles(), 255, 160, 0, 48, BOUNDS_DURATION);
+            }
+        });
+
+        let inputs = ent.input_names();
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_salt("entity_inspector_input")
+                .selected_text(if self.input.is_empty() { "Input" } else { &self.input })
+                .width(150.0)
+                .show_ui(ui, |ui| {
+                    for input in &inputs {
+                        ui.selectable_value(&mut self.input, input.clone(), input);
+                    }
+                });
+            ui.add(TextEdit::singleline(&mut self.input_param).hint_text("Parameter").desired_width(140.0));
+            let fire = ui.add_enabled(!self.input.is_empty(), egui::Button::new("🔥 Fire"))
+                .on_hover_text("Fires the input at this entity only");
+            if fire.clicked()
+                && let Err(err) = ent.fire_input(&self.input, &self.input_param)
+            {
+                toasts::error(err, 3000);
+            }
+        });
+    }
+
+    fn draw_key_values(&mut self, ui: &mut egui::Ui, ent: &mut CBaseEntity) {
+        ui.add(TextEdit::singleline(&mut self.key_filter).hint_text("🔍 Filter keyvalues").desired_width(f32::INFINITY));
+        let filter = self.key_filter.to_ascii_lowercase();
+        let keys: Vec<String> = ent.key_names().into_iter()
+            .filter(|key| key.to_ascii_lowercase().contains(&filter))
+            .collect();
+
+        ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+            egui::Grid::new("entity_inspector_key_values").num_columns(2).striped(true).show(ui, |ui| {
+                for key in keys {
+                    let live = ent.get_key_value(&key).unwrap_or_default();
+                    let mut text = match &self.editing {
+                        Some((editing, value)) if *editing == key => value.clone(),
+                        _ => live.clone(),
+                    };
+
+                    ui.label(RichText::new(&key).monospace());
+                    let edit = ui.add(TextEdit::singleline(&mut text).font(egui::TextStyle::Monospace).desired_width(f32::INFINITY));
+                    if edit.gained_focus() || edit.changed() {
+                        self.editing = Some((key.clone(), text));
+                    }
+                    // Applied when the field loses focus, e.g. on Enter.
+                    if edit.lost_focus() && let Some((_, value)) = self.editing.take_if(|(editing, _)| *editing == key)
+                        && value != live && !ent.set_key_value(&key, &value)
+                    {
+                        toasts::error(format!("The entity rejected {} = {}", key, value), 3000);
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+    }
+}
+
+fn collect_groups(engine: &Engine) -> BTreeMap<String, Vec<Row>> {
+    let mut groups: BTreeMap<String, Vec<Row>> = BTreeMap::new();
+    for ent in engine.entities().iter() {
+        groups.entry(ent.get_classname())
+            .or_default()
+            .push(Row { handle: ent.get_handle(), name: ent.get_name() });
+    }
+    groups
+}
+
+/// The entity's collision bounds, relative to its origin.
+fn bounds(ent: &CBaseEntity) -> (Vector, Vector) {
+    match (ent.prop::<Vector>("m_vecMins"), ent.prop::<Vector>("m_vecMaxs")) {
+        (Some(mins), Some(maxs)) => (mins, maxs),
+        _ => (Vector::new(-8.0, -8.0, -8.0), Vector::new(8.0, 8.0, 8.0)),
+    }
+}
+
+fn drag_xyz(ui: &mut egui::Ui, x: &mut f32, y: &mut f32, z: &mut f32, speed: f32) -> bool {
+    ui.horizontal(|ui| {
+        let mut changed = false;
+        for value in [x, y, z] {
+            changed |= ui.add(DragValue::new(value).speed(speed).max_decimals(2)).changed();
+        }
+        changed
+    }).inner
+}
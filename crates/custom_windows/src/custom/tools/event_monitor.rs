@@ -0,0 +1,269 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use egui::{Context, RichText, ScrollArea, TextEdit};
+use overlay_types::toasts;
+use portal2_sdk::game_events::{EventSchema, GameEventData, ListenerId};
+use portal2_sdk::Engine;
+
+use crate::{SharedState, Window};
+
+/// The oldest entries are dropped past this many.
+const MAX_ENTRIES: usize = 5000;
+/// Entries waiting for the next draw. While the window is not drawn, events fired
+/// past this many are dropped rather than piling up.
+const MAX_PENDING: usize = MAX_ENTRIES;
+
+/// One fired event, recorded by the listener on the game thread.
+struct Entry {
+    /// Seconds since the monitor was created.
+    time: f32,
+    /// Wall-clock time, in milliseconds since the Unix epoch.
+    unix_ms: u64,
+    map: String,
+    event: GameEventData,
+}
+
+impl Entry {
+    fn fields_line(&self) -> String {
+        self.event.fields.iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "time": self.time,
+            "unix_ms": self.unix_ms,
+            "map": self.map,
+            "name": self.event.name,
+            "fields": self.event.fields,
+        })
+    }
+}
+
+/// Lists every event declared in the game's event files, subscribes to the ticked
+/// ones and shows a timeline of the events they fire, with all of their fields.
+pub struct EventMonitor {
+    is_open: bool,
+    /// Sorted by name. Filled on first draw, after `regist_events` loaded the schemas.
+    schemas: Vec<EventSchema>,
+    query: String,
+    subscriptions: HashMap<String, ListenerId>,
+    timeline: VecDeque<Entry>,
+    paused: bool,
+    export_path: String,
+    started: Instant,
+    sender: mpsc::SyncSender<Entry>,
+    receiver: mpsc::Receiver<Entry>,
+}
+
+impl Default for EventMonitor {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::sync_channel(MAX_PENDING);
+        Self {
+            is_open: false,
+            schemas: Vec::new(),
+            query: String::new(),
+            subscriptions: HashMap::new(),
+            timeline: VecDeque::new(),
+            paused: false,
+            export_path: "event_timeline.json".to_string(),
+            started: Instant::now(),
+            sender,
+            receiver,
+        }
+    }
+}
+
+impl Window for EventMonitor {
+    fn name(&self) -> &'static str { "Event Monitor" }
+
+    fn set_open(&mut self, open: bool) { self.is_open = open; }
+    fn is_open(&self) -> bool { self.is_open }
+
+    fn is_should_render(&self, shared_state: &SharedState, _engine: &Engine) -> bool {
+        shared_state.is_overlay_focused
+    }
+
+    fn draw(&mut self, ctx: &Context, _shared_state: &mut SharedState, engine: &Engine) {
+        self.drain();
+        let mut open = self.is_open;
+
+        egui::Window::new(self.name())
+            .open(&mut open)
+            .resizable(true)
+            .default_width(720.0)
+            .default_height(480.0)
+            .show(ctx, |ui| {
+                if self.schemas.is_empty() {
+                    self.schemas = engine.game_event_manager().schemas().iter().cloned().collect();
+                    self.schemas.sort_by(|a, b| a.name.cmp(&b.name));
+                }
+
+                self.draw_toolbar(ui, engine);
+                ui.separator();
+
+                egui::SidePanel::left("event_monitor_events")
+                    .resizable(true)
+                    .default_width(230.0)
+                    .show_inside(ui, |ui| self.draw_events(ui, engine));
+
+                egui::CentralPanel::default().show_inside(ui, |ui| self.draw_timeline(ui));
+            });
+
+        self.is_open = open;
+    }
+}
+
+impl EventMonitor {
+    /// Moves what the listeners recorded into the timeline.
+    fn drain(&mut self) {
+        for entry in self.receiver.try_iter() {
+            if self.paused {
+                continue;
+            }
+            if self.timeline.len() == MAX_ENTRIES {
+                self.timeline.pop_front();
+            }
+            self.timeline.push_back(entry);
+        }
+    }
+
+    fn draw_toolbar(&mut self, ui: &mut egui::Ui, engine: &Engine) {
+        ui.horizontal(|ui| {
+            ui.toggle_value(&mut self.paused, "⏸ Pause");
+            if ui.button("🗑 Clear").clicked() {
+                self.timeline.clear();
+            }
+            ui.weak(format!("{} events, {} subscribed", self.timeline.len(), self.subscriptions.len()));
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.button("💾 Export JSON").clicked() {
+                    self.export(engine);
+                }
+                ui.add(TextEdit::singleline(&mut self.export_path).desired_width(180.0))
+                    .on_hover_text("Relative paths are resolved against the game directory");
+            });
+        });
+    }
+
+    fn draw_events(&mut self, ui: &mut egui::Ui, engine: &Engine) {
+        if self.schemas.is_empty() {
+            ui.weak("No event schemas were loaded from the game's resource/*events.res files.");
+            return;
+        }
+
+        ui.add(TextEdit::singleline(&mut self.query).hint_text("🔍 Filter events").desired_width(f32::INFINITY));
+        let query = self.query.to_ascii_lowercase();
+        let listed: Vec<usize> = (0..self.schemas.len())
+            .filter(|&i| self.schemas[i].name.to_ascii_lowercase().contains(&query))
+            .collect();
+
+        ui.horizontal(|ui| {
+            if ui.button("All").on_hover_text("Subscribe to every listed event").clicked() {
+                for &i in &listed {
+                    self.set_subscribed(i, true, engine);
+                }
+            }
+            if ui.button("None").on_hover_text("Unsubscribe from every listed event").clicked() {
+                for &i in &listed {
+                    self.set_subscribed(i, false, engine);
+                }
+            }
+        });
+
+        let row_height = ui.spacing().interact_size.y;
+        ScrollArea::vertical().auto_shrink([false, false]).show_rows(ui, row_height, listed.len(), |ui, rows| {
+            for &i in &listed[rows] {
+                let schema = &self.schemas[i];
+                let mut subscribed = self.subscriptions.contains_key(&schema.name);
+                let fields = schema.fields.iter()
+                    .map(|(name, ty)| format!("{}: {:?}", name, ty))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                let response = ui.checkbox(&mut subscribed, RichText::new(&schema.name).monospace())
+                    .on_hover_text(if fields.is_empty() { "No fields".to_string() } else { fields });
+                if response.changed() {
+                    self.set_subscribed(i, subscribed, engine);
+                }
+            }
+        });
+    }
+
+    fn set_subscribed(&mut self, schema: usize, subscribed: bool, engine: &Engine) {
+        let name = &self.schemas[schema].name;
+        let events = engine.game_event_manager();
+        match (subscribed, self.subscriptions.contains_key(name)) {
+            (true, false) => {
+                let sender = self.sender.clone();
+                let started = self.started;
+                let id = events.listen(name, move |event| {
+                    let unix_ms = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as u64);
+                    // A full channel means nobody is draining it; the event is dropped.
+                    let _ = sender.try_send(Entry {
+                        time: started.elapsed().as_secs_f32(),
+                        unix_ms,
+                        map: portal2_sdk::get_engine().client().get_level_name_short(),
+                        event: event.snapshot(),
+                    });
+                });
+                self.subscriptions.insert(name.clone(), id);
+            }
+            (false, true) => {
+                if let Some(id) = self.subscriptions.remove(name) {
+                    events.unlisten(id);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn draw_timeline(&mut self, ui: &mut egui::Ui) {
+        if self.timeline.is_empty() {
+            ui.weak("Tick events on the left to record them here.");
+            return;
+        }
+
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace) + ui.spacing().item_spacing.y;
+        ScrollArea::both()
+            .auto_shrink([false, false])
+            .stick_to_bottom(true)
+            .show_rows(ui, row_height, self.timeline.len(), |ui, rows| {
+                for entry in self.timeline.range(rows) {
+                    let line = format!("[{:>9.3}s] {:<20} {:<28} {}", entry.time, entry.map, entry.event.name, entry.fields_line());
+                    ui.add(egui::Label::new(RichText::new(line).monospace()).extend())
+                        .on_hover_ui(|ui| {
+                            ui.strong(&entry.event.name);
+                            egui::Grid::new("event_fields").striped(true).show(ui, |ui| {
+                                for (key, value) in &entry.event.fields {
+                                    ui.label(RichText::new(key).monospace());
+                                    ui.label(RichText::new(value.to_string()).monospace());
+                                    ui.end_row();
+                                }
+                            });
+                        });
+                }
+            });
+    }
+
+    fn export(&self, engine: &Engine) {
+        let mut path = PathBuf::from(&self.export_path);
+        if path.is_relative() {
+            path = PathBuf::from(engine.engine_server().get_game_dir()).join(path);
+        }
+
+        let events: Vec<serde_json::Value> = self.timeline.iter().map(Entry::to_json).collect();
+        let result = serde_json::to_string_pretty(&events)
+            .map_err(|e| e.to_string())
+            .and_then(|json| std::fs::write(&path, json).map_err(|e| e.to_string()));
+
+        match result {
+            Ok(()) => toasts::success(format!("Exported {} events to {}", events.len(), path.display()), 4000),
+            Err(e) => toasts::error(format!("Failed to export the timeline: {}", e), 4000),
+        }
+    }
+}
@@ -1,7 +1,10 @@
 //// This file was generated by Google Gemini
 
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
 use egui::{color_picker, Color32, RichText, Slider};
-use portal2_sdk::Engine;
+use portal2_sdk::{ChangeListenerId, CvarHandle, Engine};
 
 use crate::{SharedState, Window};
 
@@ -17,11 +20,44 @@ const FOG_END_SKYBOX: &str = "fog_endskybox";
 const FOG_COLOR_SKYBOX: &str = "fog_colorskybox";
 
 const R_FARZ: &str = "r_farz";
+
+static SV_CHEATS: CvarHandle<bool> = CvarHandle::new("sv_cheats");
+static FOG_OVERRIDE_VAR: CvarHandle<bool> = CvarHandle::new(FOG_OVERRIDE);
 // -------------------------------------------------- \\
 
+/// A boolean cvar mirrored into the window, updated by a change listener instead of
+/// being looked up every frame. The listener goes with the window.
+#[derive(Debug, Default)]
+struct Mirrored {
+    value: Arc<AtomicBool>,
+    subscription: Option<(&'static CvarHandle<bool>, ChangeListenerId)>,
+}
+
+impl Mirrored {
+    fn get(&mut self, handle: &'static CvarHandle<bool>) -> bool {
+        if self.subscription.is_none() {
+            self.value.store(handle.get().unwrap_or(false), Ordering::Relaxed);
+            let value = self.value.clone();
+            let id = handle.subscribe(move |_, new| value.store(new, Ordering::Relaxed));
+            self.subscription = Some((handle, id));
+        }
+        self.value.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for Mirrored {
+    fn drop(&mut self) {
+        if let Some((handle, id)) = self.subscription.take() {
+            handle.unsubscribe(id);
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct FogWindow {
     is_open: bool,
+    cheats_enabled: Mirrored,
+    fog_override: Mirrored,
 }
 
 impl Window for FogWindow {
@@ -34,7 +70,8 @@ impl Window for FogWindow {
         let client = engine.client();
         let cvar_system = engine.cvar_system();
 
-        let is_cheats_enabled = cvar_system.find_var("sv_cheats").unwrap().get_int() > 0;
+        let is_cheats_enabled = self.cheats_enabled.get(&SV_CHEATS);
+        let mut fog_override = self.fog_override.get(&FOG_OVERRIDE_VAR);
         let color = if is_cheats_enabled {
             egui::Color32::GRAY
         } else {
@@ -48,7 +85,9 @@ impl Window for FogWindow {
             .show(ctx, |ui| { ui.add_enabled_ui(is_cheats_enabled, |ui| {
                 // --- Main Switches ---
                 ui.label( RichText::new("Requires sv_cheats 1").color(color) );
-                draw_cvar_checkbox(engine, ui, FOG_OVERRIDE, "Fog Override");
+                if ui.checkbox(&mut fog_override, "Fog Override").changed() {
+                    client.execute_client_cmd_unrestricted(&format!("{} {}", FOG_OVERRIDE, fog_override as i32));
+                }
 
                 // Copy values from the active map fog controller
                 if ui.button("Copy from env_fog_controller").clicked() {
@@ -0,0 +1,77 @@
+use egui::{Context, Grid, ScrollArea, TextEdit, Vec2};
+use portal2_sdk::Engine;
+use portal2_sdk::platform::{self, Module, RegisteredInterface};
+
+use crate::{SharedState, Window};
+
+/// Lists every interface the engine modules register with `CreateInterface`.
+#[derive(Default)]
+pub struct InterfaceList {
+    is_open: bool,
+    filter: String,
+    /// Snapshot per module, taken when the window is first drawn or on "Refresh".
+    interfaces: Option<Vec<(Module, Vec<RegisteredInterface>)>>,
+}
+
+impl Window for InterfaceList {
+    fn name(&self) -> &'static str { "Interfaces" }
+    fn set_open(&mut self, open: bool) { self.is_open = open; }
+    fn is_open(&self) -> bool { self.is_open }
+
+    fn is_should_render(&self, shared_state: &SharedState, _engine: &Engine) -> bool {
+        shared_state.is_overlay_focused
+    }
+
+    fn draw(&mut self, ctx: &Context, _shared_state: &mut SharedState, _engine: &Engine) {
+        let mut open = self.is_open;
+
+        egui::Window::new(self.name())
+            .open(&mut open)
+            .resizable(true)
+            .default_width(420.0)
+            .default_height(400.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("Refresh").clicked() {
+                        self.interfaces = None;
+                    }
+                    ui.add(TextEdit::singleline(&mut self.filter).hint_text("Filter").desired_width(f32::INFINITY));
+                });
+                ui.separator();
+
+                let interfaces = self.interfaces.get_or_insert_with(|| {
+                    Module::ALL.into_iter().map(|m| (m, platform::registered_interfaces(m))).collect()
+                });
+                let filter = self.filter.to_lowercase();
+
+                ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                    for (module, list) in interfaces.iter() {
+                        let shown: Vec<_> = list.iter()
+                            .filter(|i| i.name.to_lowercase().contains(&filter))
+                            .collect();
+
+                        ui.collapsing(format!("{} ({})", module.name(), shown.len()), |ui| {
+                            if list.is_empty() {
+                                ui.weak("Module not loaded, or its registry was not found.");
+                                return;
+                            }
+                            Grid::new(module.name())
+                                .num_columns(2)
+                                .striped(true)
+                                .spacing(Vec2::new(24.0, 4.0))
+                                .show(ui, |ui| {
+                                    for interface in shown {
+                                        ui.monospace(&interface.name);
+                                        ui.monospace(format!("{:p}", interface.factory()))
+                                            .on_hover_text("Factory function");
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+                    }
+                });
+            });
+
+        self.is_open = open;
+    }
+}
@@ -2,8 +2,18 @@ mod debug_menu;
 mod fogui;
 mod post_processing_menu;
 mod mat_inspector;
+mod interface_list;
+mod console;
+mod cvar_browser;
+mod event_monitor;
+mod entity_inspector;
 
 pub use debug_menu::DebugMenu;
 pub use fogui::FogWindow;
 pub use post_processing_menu::PostProcessingMenu;
 pub use mat_inspector::MaterialInspector;
+pub use interface_list::InterfaceList;
+pub use console::ConsoleWindow;
+pub use cvar_browser::CvarBrowser;
+pub use event_monitor::EventMonitor;
+pub use entity_inspector::EntityInspector;
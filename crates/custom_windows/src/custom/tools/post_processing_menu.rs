@@ -62,7 +62,7 @@ impl Window for PostProcessingMenu {
 
     fn on_event(&mut self, event: &overlay_types::events::OverlayEvent, _shared_state: &mut SharedState) {
         match event {
-            OverlayEvent::GameEvent(s) if s == "server_spawn" => {
+            OverlayEvent::GameEvent(e) if e.name == "server_spawn" => {
                 self.last_hacky_respawn_time = 0.0;
             }
             _ => {}
@@ -8,6 +8,7 @@ use std::sync::{OnceLock, mpsc};
 use overlay_types::{HotkeyManager, events::OverlayEvent};
 use source_fs::{DummyVpk, P2GameInfo};
 use portal2_sdk::Engine;
+use portal2_sdk::types::{CBaseEntity, Handle, MaskFlags};
 
 /// Base font scale factor
 pub const BASE_TEXT_SCALE: f32 = 1.25;
@@ -22,6 +23,11 @@ pub static STATE_ACTION_TX: OnceLock<mpsc::Sender<SharedStateAction>> = OnceLock
 pub struct SharedState {
     pub is_overlay_focused: bool,
     pub allow_inspect_mode: bool,
+    /// Whether a left click in the world, while the overlay is focused, selects the
+    /// entity under the cursor.
+    pub allow_pick_mode: bool,
+    /// The entity picked in the world or chosen in a tool, shared by every window.
+    pub selected_entity: Option<Handle>,
     pub hotkeys: HotkeyManager,
     pub valve_fs: source_fs::FileSystem<DummyVpk>,
 
@@ -38,6 +44,8 @@ impl Default for SharedState {
         Self {
             is_overlay_focused: false,
             allow_inspect_mode: true,
+            allow_pick_mode: true,
+            selected_entity: None,
             hotkeys: HotkeyManager::default(),
             valve_fs,
         }
@@ -57,6 +65,21 @@ where
     }
 }
 
+/// How far a pick trace reaches, in units.
+const PICK_DISTANCE: f32 = 16384.0;
+
+/// The entity under the screen position `x`, `y` (in pixels), traced through the
+/// last rendered view. `None` if the ray hits the world or nothing.
+pub fn pick_entity(engine: &Engine, x: f32, y: f32) -> Option<Handle> {
+    let (start, direction) = engine.client().screen_to_world_ray(x, y)?;
+    let player = engine.entities().query().class("player").first();
+    let trace = engine.engine_trace().line_trace(start, start + direction * PICK_DISTANCE, MaskFlags::SOLID, player.as_deref());
+    if trace.did_hit_world() {
+        return None;
+    }
+    trace.hit_entity().map(CBaseEntity::get_handle)
+}
+
 /// Trait that every window must implement.
 #[allow(dead_code)]
 pub trait Window {
@@ -45,6 +45,8 @@ pub struct UiManager {
     egui_wants_keyboard: bool,
     egui_wants_pointer: bool,
     is_inspecting: bool,
+    /// Cursor position of a world click waiting to be traced on the next frame.
+    pending_pick: Option<(f32, f32)>,
 
     state_action_rx: mpsc::Receiver<SharedStateAction>,
     shared_state: custom_windows::SharedState,
@@ -75,6 +77,7 @@ impl UiManager {
             egui_wants_keyboard: false,
             egui_wants_pointer: false,
             is_inspecting: false,
+            pending_pick: None,
             event_receiver: receiver,
             state_action_rx: ss_rx,
         }
@@ -142,6 +145,16 @@ impl UiManager {
     }
 
 
+    /// Traces a queued world click and makes the entity under it the selection.
+    /// A click that hits no entity keeps the current selection.
+    pub(crate) fn handle_pick(&mut self) {
+        if let Some((x, y)) = self.pending_pick.take()
+            && let Some(handle) = custom_windows::pick_entity(self.engine_instance, x, y)
+        {
+            self.shared_state.selected_entity = Some(handle);
+        }
+    }
+
     pub(crate) fn draw_ui(&mut self, ctx: &egui::Context) {
         // Apply zoom factor to text styles
         use custom_windows::BASE_TEXT_SCALE;
@@ -175,7 +188,7 @@ impl UiManager {
     }
 
     /// Raw input routing. Returns true to pass input to the game, false to consume.
-    pub fn on_input(&mut self, umsg: u32, wparam: WPARAM, _lparam: LPARAM) -> bool {
+    pub fn on_input(&mut self, umsg: u32, wparam: WPARAM, lparam: LPARAM) -> bool {
 
         let mut should_pass_to_game = true;
         if umsg == WM_KEYUP {
@@ -200,6 +213,15 @@ impl UiManager {
             self.is_inspecting = false;
         }
 
+        // Handle pick mode: a click that egui does not use selects the entity under the cursor
+        if self.shared_state.allow_pick_mode && is_focused && !self.is_inspecting
+            && umsg == WM_LBUTTONDOWN && !self.egui_wants_pointer
+        {
+            let x = (lparam.0 & 0xFFFF) as i16 as f32;
+            let y = ((lparam.0 >> 16) & 0xFFFF) as i16 as f32;
+            self.pending_pick = Some((x, y));
+        }
+
         let ui_demands_cursor = (is_focused && !self.is_inspecting) || self.egui_wants_pointer;
 
         if ui_demands_cursor != self.cursor_visible_in_gui {
@@ -305,6 +327,7 @@ pub fn on_present(device: &IDirect3DDevice9) {
                     if let Ok(mut app) = app.lock() {
                         app.handle_events();
                         app.handle_state_editing();
+                        app.handle_pick();
                         app.draw_ui(ctx);
                     }
                 }
@@ -379,8 +402,12 @@ pub fn uninstall_overlay() {
         if let Ok(mut app) = app_mutex.try_lock() {
             std::mem::take(&mut app.windows).into_iter().for_each(drop);
             app.engine_instance.game_event_manager().shutdown_all_listeners();
+            app.engine_instance.cvar_system().shutdown_change_listeners();
+            app.engine_instance.cvar_system().stop_console_capture();
         }
     }
+    // Nothing in the engine may call into our detours once we are gone.
+    portal2_sdk::hooks::uninstall_all();
 
     unsafe {
         if let Some(SyncHWND(hwnd)) = FOCUS_HWND.get().copied() {
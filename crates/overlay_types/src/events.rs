@@ -1,6 +1,8 @@
 use std::sync::mpsc;
 use std::sync::OnceLock;
 
+use portal2_sdk::game_events::GameEventData;
+
 use crate::KeyCode;
 
 /// The universal event enum for the overlay framework.
@@ -23,8 +25,8 @@ pub enum OverlayEvent {
     /// Sends a command to the game engine.
     EngineCommand(String),
 
-    /// Game events triggered by the Source Engine.
-    GameEvent(String),
+    /// Game events triggered by the Source Engine, with all of their fields.
+    GameEvent(GameEventData),
 
     /// Presses a key.
     PressKey(KeyCode), // todo: only for UI for now
@@ -2,60 +2,44 @@ use std::ffi::{c_char, c_int, CStr, CString};
 
 use crate::types::ModelT;
 
-use super::types::{PlayerInfo, QAngle};
-use crate::platform::abi::vfn;
-
-// Opaque type for the `this` pointer.
-#[repr(C)] pub(crate) struct RawIVEngineClient { _private: [u8; 0] }
-
-type FnGetScreenSize = vfn!((this: *const IVEngineClient, w: *mut c_int, h: *mut c_int));
-type FnServerCmd = vfn!((this: *mut RawIVEngineClient, cmd: *const c_char, reliable: bool));
-type FnClientCmd = vfn!((this: *mut RawIVEngineClient, cmd: *const c_char));
-type FnGetPlayerInfo = vfn!((this: *mut RawIVEngineClient, ent_num: c_int, p_info: *mut PlayerInfo) -> bool);
-type FnGetPlayerForUserID = vfn!((this: *const RawIVEngineClient, user_id: c_int) -> c_int);
-type FnGetLastTimeStamp = vfn!((this: *mut RawIVEngineClient) -> f32);
-type FnGetViewAngles = vfn!((this: *mut RawIVEngineClient, va: *mut QAngle));
-type FnSetViewAngles = vfn!((this: *mut RawIVEngineClient, va: *const QAngle));
-type FnGetMaxClients = vfn!((this: *mut RawIVEngineClient) -> c_int);
-type FnIsInGame = vfn!((this: *mut RawIVEngineClient) -> bool);
-type FnIsConnected = vfn!((this: *mut RawIVEngineClient) -> bool);
-type FnIsDrawingLoadingImage = vfn!((this: *mut RawIVEngineClient) -> bool);
-type FnGetLevelName = vfn!((this: *mut RawIVEngineClient) -> *const c_char);
-type FnExecuteClientCmdUnrestricted = vfn!((this: *mut RawIVEngineClient, cmd: *const c_char));
-type FnIsSinglplayer = vfn!((this: *mut RawIVEngineClient) -> bool);
-type FnConIsVisible = vfn!((this: *const RawIVEngineClient) -> bool);
-type FnGetLocalPlayer = vfn!((this: *const RawIVEngineClient) -> c_int);
-type FnLoadModel = vfn!((this: *const RawIVEngineClient, name: *const c_char, is_prop: bool) -> *const ModelT);
-type FnKeyLookupBinding = vfn!((this: *const RawIVEngineClient, binding: *const c_char) -> *const c_char);
-type FnIsPaused = vfn!((this: *const RawIVEngineClient) -> bool);
-
-/// Represents an instance of the IVEngineClient interface.
-/// Instead of a vtable, it holds a 'this' pointer to the C++ object
-/// and direct pointers to the functions we need.
-pub struct IVEngineClient {
-    pub(crate) this: *mut RawIVEngineClient,
-
-    pub(crate) get_screen_size: FnGetScreenSize,
-    pub(crate) server_cmd: FnServerCmd,
-    pub(crate) client_cmd: FnClientCmd,
-    pub(crate) get_player_info: FnGetPlayerInfo,
-    pub(crate) get_player_for_user_id: FnGetPlayerForUserID,
-    pub(crate) get_last_time_stamp: FnGetLastTimeStamp,
-    pub(crate) get_view_angles: FnGetViewAngles,
-    pub(crate) set_view_angles: FnSetViewAngles,
-    pub(crate) get_max_clients: FnGetMaxClients,
-    pub(crate) is_in_game: FnIsInGame,
-    pub(crate) is_connected: FnIsConnected,
-    pub(crate) is_paused: FnIsPaused,
-    pub(crate) is_drawing_loading_image: FnIsDrawingLoadingImage,
-    pub(crate) get_level_name: FnGetLevelName,
-    pub(crate) get_level_name_short: FnGetLevelName,
-    pub(crate) execute_client_cmd_unrestricted: FnExecuteClientCmdUnrestricted,
-    pub(crate) is_singlplayer: FnIsSinglplayer,
-    pub(crate) con_is_visible: FnConIsVisible,
-    pub(crate) get_local_player: FnGetLocalPlayer,
-    pub(crate) load_model: FnLoadModel,
-    pub(crate) key_lookup_binding: FnKeyLookupBinding,
+use super::types::{PlayerInfo, QAngle, VMatrix, Vector};
+use crate::interface::interface;
+
+interface! {
+    /// Represents an instance of the IVEngineClient interface.
+    /// Instead of a vtable, it holds a 'this' pointer to the C++ object
+    /// and direct pointers to the functions we need.
+    pub struct IVEngineClient: "IVEngineClient" {
+        fn server_cmd(cmd: *const c_char, reliable: bool) = sig "IVEngineClient::ServerCmd";
+        fn client_cmd(cmd: *const c_char) = sig "IVEngineClient::ClientCmd";
+        fn get_player_info(ent_num: c_int, p_info: *mut PlayerInfo) -> bool = sig "IVEngineClient::GetPlayerInfo";
+        /// Returns the timestamp of the last packet received from the server.
+        [safe] fn get_last_time_stamp() -> f32 = sig "IVEngineClient::GetLastTimeStamp";
+        fn get_view_angles(va: *mut QAngle) = sig "IVEngineClient::GetViewAngles";
+        fn set_view_angles(va: *const QAngle) = sig "IVEngineClient::SetViewAngles";
+        /// Returns `true` if the player is fully connected and in the game.
+        [safe] fn is_in_game() -> bool = sig "IVEngineClient::IsInGame";
+        /// Returns `true` if the client is connected to a server.
+        [safe] fn is_connected() -> bool = sig "IVEngineClient::IsConnected";
+        /// Returns `true` if the game is in singleplayer mode.
+        [safe] fn is_singlplayer() -> bool = sig "IVEngineClient::IsSingleplayer";
+        fn get_screen_size(w: *mut c_int, h: *mut c_int) = sig "IVEngineClient::GetScreenSize";
+        fn get_player_for_user_id(user_id: c_int) -> c_int = sig "IVEngineClient::GetPlayerForUserId";
+        fn get_local_player() -> c_int = sig "IVEngineClient::GetLocalPlayer";
+        fn load_model(name: *const c_char, is_prop: bool) -> *const ModelT = sig "IVEngineClient::LoadModel";
+        fn key_lookup_binding(binding: *const c_char) -> *const c_char = sig "IVEngineClient::KeyLookupBinding";
+        fn execute_client_cmd_unrestricted(cmd: *const c_char) = sig "IVEngineClient::ExecuteClientCmdUnrestricted";
+
+        // Unique cases of too short functions. They cannot be found by signature, using vtable indexes
+        fn con_is_visible() -> bool = 11;
+        fn get_max_clients() -> c_int = 20;
+        fn is_drawing_loading_image() -> bool = 27;
+        fn world_to_screen_matrix() -> *const VMatrix = 37;
+        fn get_level_name() -> *const c_char = 52;
+        fn get_level_name_short() -> *const c_char = 53;
+        /// Returns `true` if game in pause.
+        [safe] fn is_paused() -> bool = 86;
+    }
 }
 
 /// This implementation provides safe, idiomatic Rust methods to interact with the game's engine client interface.
@@ -87,11 +71,6 @@ impl IVEngineClient {
         if success { Some(info) } else { None }
     }
 
-    /// Returns the timestamp of the last packet received from the server.
-    pub fn get_last_time_stamp(&self) -> f32 {
-        unsafe { (self.get_last_time_stamp)(self.this) }
-    }
-
     /// Returns the player's current view angles.
     pub fn get_view_angles(&self) -> QAngle {
         let mut angles = QAngle::default();
@@ -111,21 +90,6 @@ impl IVEngineClient {
         unsafe { (self.get_max_clients)(self.this) as i32 }
     }
 
-    /// Returns `true` if the player is fully connected and in the game.
-    pub fn is_in_game(&self) -> bool {
-        unsafe { (self.is_in_game)(self.this) }
-    }
-
-    /// Is the game paused?
-    pub fn is_connected(&self) -> bool {
-        unsafe { (self.is_connected)(self.this) }
-    }
-
-    /// Returns `true` if game in pause.
-    pub fn is_paused(&self) -> bool {
-        unsafe { (self.is_paused)(self.this) }
-    }
-
     /// Returns the name of the current map (e.g., "maps/de_dust2.bsp").
     pub fn get_level_name(&self) -> String {
         unsafe {
@@ -156,11 +120,6 @@ impl IVEngineClient {
         }
     }
 
-    /// Returns `true` if the game is in singleplayer mode.
-    pub fn is_singlplayer(&self) -> bool {
-        unsafe { (self.is_singlplayer)(self.this) }
-    }
-
     /// Returns `true` if the game is currently showing a loading screen.
     pub fn is_loading_map(&self) -> bool {
         unsafe { (self.is_drawing_loading_image)(self.this) }
@@ -169,13 +128,13 @@ impl IVEngineClient {
     /// Returns `true` if the console is visible.
     pub fn con_is_visible(&self) -> bool {
         // SAFETY: `this` is guaranteed to be a valid pointer.
-        unsafe { (self.con_is_visible)(self.this as *const _) }
+        unsafe { (self.con_is_visible)(self.this) }
     }
 
     /// Returns the entity index of the local player.
     pub fn get_local_player(&self) -> i32 {
         // SAFETY: `this` is guaranteed to be a valid pointer.
-        unsafe { (self.get_local_player)(self.this as *const _) as i32 }
+        unsafe { (self.get_local_player)(self.this) as i32 }
     }
 
     /// Loads a model by its name.
@@ -188,7 +147,7 @@ impl IVEngineClient {
 
         // SAFETY: `this` is a valid pointer.
         // `c_str` is a valid, null-terminated C-style string.
-        let model_ptr = unsafe { (self.load_model)(self.this as *const _, c_str.as_ptr(), is_prop) };
+        let model_ptr = unsafe { (self.load_model)(self.this, c_str.as_ptr(), is_prop) };
 
         if model_ptr.is_null() {
             None
@@ -207,7 +166,7 @@ impl IVEngineClient {
 
         // SAFETY: `this` is a valid pointer.
         // `c_str` is a valid, null-terminated C-style string.
-        let result_ptr = unsafe { (self.key_lookup_binding)(self.this as *const _, c_str.as_ptr()) };
+        let result_ptr = unsafe { (self.key_lookup_binding)(self.this, c_str.as_ptr()) };
 
         if result_ptr.is_null() {
             return String::new();
@@ -226,14 +185,64 @@ impl IVEngineClient {
 
         // SAFETY: `this` is a valid pointer.
         // `&mut width` and `&mut height` are valid pointers to `c_int`.
-        unsafe { (self.get_screen_size)(self.this as *const _, &mut width, &mut height) };
+        unsafe { (self.get_screen_size)(self.this, &mut width, &mut height) };
 
         (width as i32, height as i32)
     }
 
+    /// The projection of the last rendered view, from world space to the screen.
+    pub fn world_to_screen_matrix(&self) -> VMatrix {
+        // SAFETY: the engine returns a reference to its own matrix, which is always valid.
+        unsafe { *(self.world_to_screen_matrix)(self.this) }
+    }
+
+    /// The ray through the screen position `x`, `y`, in pixels from the top left,
+    /// as its start on the near plane and its unit direction.
+    pub fn screen_to_world_ray(&self, x: f32, y: f32) -> Option<(Vector, Vector)> {
+        let (width, height) = self.get_screen_size();
+        if width <= 0 || height <= 0 {
+            return None;
+        }
+        let ndc_x = 2.0 * x / width as f32 - 1.0;
+        let ndc_y = 1.0 - 2.0 * y / height as f32;
+
+        let matrix = self.world_to_screen_matrix();
+        let near = matrix.unproject(ndc_x, ndc_y, 0.0)?;
+        let mut direction = matrix.unproject(ndc_x, ndc_y, 0.5)? - near;
+        (direction.norm() > 0.0).then_some((near, direction))
+    }
+
     /// Gets the player's entity index for a given user ID.
     pub fn get_player_for_user_id(&self, user_id: i32) -> i32 {
         // SAFETY: `this` is guaranteed to be a valid pointer.
-        unsafe { (self.get_player_for_user_id)(self.this as *const _, user_id as c_int) as i32 }
+        unsafe { (self.get_player_for_user_id)(self.this, user_id as c_int) as i32 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::c_void;
+    use std::path::Path;
+
+    use super::*;
+    use crate::interface::{locate, Location};
+    use crate::signatures::SignatureDatabase;
+
+    #[test]
+    fn world_to_screen_matrix_slot_can_be_overridden() {
+        let method = IVEngineClient::METHODS.iter().find(|m| m.name == "world_to_screen_matrix").unwrap();
+        assert_eq!(method.location, Location::Vtable { msvc: 37, itanium: 37 });
+
+        // Every slot holds its own index, so the slot read can be told apart.
+        let vtable: Vec<*const c_void> = (0..64).map(|i| i as *const c_void).collect();
+        let mut object = vtable.as_ptr();
+        let this = &mut object as *mut _ as *mut c_void;
+
+        let db = SignatureDatabase::builtin();
+        assert_eq!(unsafe { locate(this, "IVEngineClient", method, &db) }, Ok(37 as *const c_void));
+
+        let mut db = SignatureDatabase::builtin();
+        db.apply_str("version = 1\n[[build]]\n[build.vtables.IVEngineClient]\nworld_to_screen_matrix = 38\n", Path::new("test.toml"), None).unwrap();
+        assert_eq!(unsafe { locate(this, "IVEngineClient", method, &db) }, Ok(38 as *const c_void));
     }
 }
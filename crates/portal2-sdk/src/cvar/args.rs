@@ -0,0 +1,562 @@
+//! Typed parsing of console command arguments.
+//!
+//! An [`ArgParser`] declares what a command takes: positional arguments, `--switch`es,
+//! `--option value`s and a trailing rest. It turns the raw tokens of a [`CCommand`]
+//! into [`ParsedArgs`], and writes the usage and help text from the same declaration.
+//! [`FromArgs`] maps the result onto a struct, for use with
+//! [`ConCommandBuilder::typed`](super::ConCommandBuilder::typed).
+//!
+//! The engine has already split the command line into tokens, honoring quotes, so a
+//! vector can be given either as three tokens (`1 2 3`) or as one (`"1 2 3"`).
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use super::CCommand;
+use crate::types::{QAngle, Vector};
+
+/// The type of value an argument takes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgKind {
+    Int,
+    Float,
+    /// `1`/`0`, `true`/`false`, `on`/`off` or `yes`/`no`.
+    Bool,
+    String,
+    /// Three numbers: `x y z`.
+    Vector,
+    /// Three numbers: `pitch yaw roll`.
+    Angle,
+}
+
+impl ArgKind {
+    /// How the value is written in usage text.
+    fn placeholder(self) -> &'static str {
+        match self {
+            ArgKind::Int => "int",
+            ArgKind::Float => "number",
+            ArgKind::Bool => "0|1",
+            ArgKind::String => "text",
+            ArgKind::Vector => "x y z",
+            ArgKind::Angle => "pitch yaw roll",
+        }
+    }
+
+    /// What was expected, for error messages.
+    fn expected(self) -> &'static str {
+        match self {
+            ArgKind::Int => "an integer",
+            ArgKind::Float => "a number",
+            ArgKind::Bool => "0 or 1",
+            ArgKind::String => "some text",
+            ArgKind::Vector => "three numbers (x y z)",
+            ArgKind::Angle => "three numbers (pitch yaw roll)",
+        }
+    }
+
+    /// Parses the value at the start of `tokens`, returning it with the number of tokens
+    /// used, or the offending text. `tokens` is not empty.
+    fn parse(self, tokens: &[&str]) -> Result<(ArgValue, usize), String> {
+        let first = tokens[0];
+        let invalid = |text: &str| text.to_string();
+        let value = match self {
+            ArgKind::Int => ArgValue::Int(first.parse().map_err(|_| invalid(first))?),
+            ArgKind::Float => ArgValue::Float(parse_f32(first).ok_or_else(|| invalid(first))?),
+            ArgKind::Bool => ArgValue::Bool(parse_bool(first).ok_or_else(|| invalid(first))?),
+            ArgKind::String => ArgValue::String(first.to_string()),
+            ArgKind::Vector | ArgKind::Angle => {
+                let (parts, used): (Vec<&str>, usize) = match first.contains(|c: char| c.is_whitespace() || c == ',') {
+                    true => (first.split(|c: char| c.is_whitespace() || c == ',').filter(|p| !p.is_empty()).collect(), 1),
+                    false => (tokens.iter().take(3).copied().collect(), tokens.len().min(3)),
+                };
+                let given = tokens[..used].join(" ");
+                let [x, y, z] = parts[..] else { return Err(given) };
+                let (Some(x), Some(y), Some(z)) = (parse_f32(x), parse_f32(y), parse_f32(z)) else { return Err(given) };
+                let value = match self {
+                    ArgKind::Vector => ArgValue::Vector(Vector::new(x, y, z)),
+                    _ => ArgValue::Angle(QAngle::new(x, y, z)),
+                };
+                return Ok((value, used));
+            }
+        };
+        Ok((value, 1))
+    }
+}
+
+/// Unlike the engine's `atof`, rejects anything that is not entirely a number.
+fn parse_f32(text: &str) -> Option<f32> {
+    text.parse().ok().filter(|v: &f32| v.is_finite())
+}
+
+fn parse_bool(text: &str) -> Option<bool> {
+    match text.to_ascii_lowercase().as_str() {
+        "1" | "true" | "on" | "yes" => Some(true),
+        "0" | "false" | "off" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+/// A parsed argument value.
+#[derive(Debug, Clone)]
+pub enum ArgValue {
+    Int(i32),
+    Float(f32),
+    Bool(bool),
+    String(String),
+    Vector(Vector),
+    Angle(QAngle),
+}
+
+/// A Rust type an argument can be declared and read as.
+pub trait ArgType: Sized {
+    const KIND: ArgKind;
+    fn from_value(value: &ArgValue) -> Option<Self>;
+}
+
+impl ArgType for i32 {
+    const KIND: ArgKind = ArgKind::Int;
+    fn from_value(value: &ArgValue) -> Option<Self> {
+        match *value { ArgValue::Int(v) => Some(v), _ => None }
+    }
+}
+
+impl ArgType for f32 {
+    const KIND: ArgKind = ArgKind::Float;
+    fn from_value(value: &ArgValue) -> Option<Self> {
+        match *value {
+            ArgValue::Float(v) => Some(v),
+            ArgValue::Int(v) => Some(v as f32),
+            _ => None,
+        }
+    }
+}
+
+impl ArgType for bool {
+    const KIND: ArgKind = ArgKind::Bool;
+    fn from_value(value: &ArgValue) -> Option<Self> {
+        match *value { ArgValue::Bool(v) => Some(v), _ => None }
+    }
+}
+
+impl ArgType for String {
+    const KIND: ArgKind = ArgKind::String;
+    fn from_value(value: &ArgValue) -> Option<Self> {
+        match value { ArgValue::String(v) => Some(v.clone()), _ => None }
+    }
+}
+
+impl ArgType for Vector {
+    const KIND: ArgKind = ArgKind::Vector;
+    fn from_value(value: &ArgValue) -> Option<Self> {
+        match *value { ArgValue::Vector(v) => Some(v), _ => None }
+    }
+}
+
+impl ArgType for QAngle {
+    const KIND: ArgKind = ArgKind::Angle;
+    fn from_value(value: &ArgValue) -> Option<Self> {
+        match *value { ArgValue::Angle(v) => Some(v), _ => None }
+    }
+}
+
+/// Why a command line could not be parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgError {
+    /// A required positional argument was not given.
+    Missing(String),
+    /// `--option` was given without its value.
+    MissingValue(String),
+    /// A value could not be read as the argument's type.
+    Invalid { name: String, value: String, expected: &'static str },
+    /// An `--option` the command does not have.
+    UnknownOption(String),
+    /// More positional arguments than the command takes.
+    Unexpected(String),
+    /// `--help` was given; the caller should print the help text.
+    HelpRequested,
+    /// Rejected by [`FromArgs::from_args`].
+    Custom(String),
+}
+
+impl fmt::Display for ArgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArgError::Missing(name) => write!(f, "missing argument <{}>", name),
+            ArgError::MissingValue(name) => write!(f, "--{} needs a value", name),
+            ArgError::Invalid { name, value, expected } => write!(f, "invalid {} '{}', expected {}", name, value, expected),
+            ArgError::UnknownOption(name) => write!(f, "unknown option --{}", name),
+            ArgError::Unexpected(value) => write!(f, "unexpected argument '{}'", value),
+            ArgError::HelpRequested => write!(f, "help requested"),
+            ArgError::Custom(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for ArgError {}
+
+struct Positional {
+    name: String,
+    kind: ArgKind,
+    help: String,
+    required: bool,
+}
+
+struct Named {
+    name: String,
+    /// `None` for switches, which take no value.
+    kind: Option<ArgKind>,
+    help: String,
+}
+
+/// Declares the arguments of a console command.
+///
+/// # Example
+///
+/// ```rust
+/// use portal2_sdk::{ArgParser, types::Vector};
+///
+/// let parser = ArgParser::new("tp_to")
+///     .arg::<Vector>("target", "Where to teleport to")
+///     .optional::<f32>("yaw", "Direction to face afterwards")
+///     .switch("quiet", "Don't print anything")
+///     .option::<f32>("speed", "Velocity to keep");
+///
+/// let args = parser.parse_args(&["1", "2", "3", "--speed", "200"]).unwrap();
+/// assert_eq!(args.get::<Vector>("target").unwrap().y, 2.0);
+/// assert_eq!(args.get::<f32>("yaw"), None);
+/// assert_eq!(args.get::<f32>("speed"), Some(200.0));
+/// assert!(!args.switch("quiet"));
+///
+/// assert_eq!(parser.usage(), "Usage: tp_to <target: x y z> [yaw: number] [--quiet] [--speed <number>]");
+/// ```
+pub struct ArgParser {
+    command: String,
+    positionals: Vec<Positional>,
+    named: Vec<Named>,
+    rest: Option<(String, String)>,
+}
+
+impl ArgParser {
+    /// Creates a parser for the command `command`, which takes no arguments yet.
+    pub fn new(command: &str) -> Self {
+        Self { command: command.to_string(), positionals: Vec::new(), named: Vec::new(), rest: None }
+    }
+
+    /// The command's name.
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    /// Adds a required positional argument. Positionals are read in the order they are added.
+    pub fn arg<T: ArgType>(mut self, name: &str, help: &str) -> Self {
+        self.positionals.push(Positional { name: name.to_string(), kind: T::KIND, help: help.to_string(), required: true });
+        self
+    }
+
+    /// Adds an optional positional argument. It should come after the required ones.
+    pub fn optional<T: ArgType>(mut self, name: &str, help: &str) -> Self {
+        self.positionals.push(Positional { name: name.to_string(), kind: T::KIND, help: help.to_string(), required: false });
+        self
+    }
+
+    /// Adds `--name`, which takes no value. Read it with [`ParsedArgs::switch`].
+    pub fn switch(mut self, name: &str, help: &str) -> Self {
+        self.named.push(Named { name: name.to_string(), kind: None, help: help.to_string() });
+        self
+    }
+
+    /// Adds `--name value` (or `--name=value`), which may be left out.
+    pub fn option<T: ArgType>(mut self, name: &str, help: &str) -> Self {
+        self.named.push(Named { name: name.to_string(), kind: Some(T::KIND), help: help.to_string() });
+        self
+    }
+
+    /// Collects every positional token after the declared ones, instead of rejecting them.
+    pub fn rest(mut self, name: &str, help: &str) -> Self {
+        self.rest = Some((name.to_string(), help.to_string()));
+        self
+    }
+
+    /// The one-line usage, e.g. `Usage: cmd <a: int> [b: number] [--flag]`.
+    pub fn usage(&self) -> String {
+        let mut usage = format!("Usage: {}", self.command);
+        for arg in &self.positionals {
+            let (open, close) = if arg.required { ('<', '>') } else { ('[', ']') };
+            usage += &format!(" {}{}: {}{}", open, arg.name, arg.kind.placeholder(), close);
+        }
+        for named in &self.named {
+            match named.kind {
+                Some(kind) => usage += &format!(" [--{} <{}>]", named.name, kind.placeholder()),
+                None => usage += &format!(" [--{}]", named.name),
+            }
+        }
+        if let Some((name, _)) = &self.rest {
+            usage += &format!(" [{}...]", name);
+        }
+        usage
+    }
+
+    /// The usage followed by one aligned line per argument, for `help <command>`.
+    pub fn help(&self) -> String {
+        let mut rows: Vec<(String, &str)> = Vec::new();
+        for arg in &self.positionals {
+            rows.push((arg.name.clone(), &arg.help));
+        }
+        if let Some((name, help)) = &self.rest {
+            rows.push((format!("{}...", name), help));
+        }
+        for named in &self.named {
+            let label = match named.kind {
+                Some(kind) => format!("--{} <{}>", named.name, kind.placeholder()),
+                None => format!("--{}", named.name),
+            };
+            rows.push((label, &named.help));
+        }
+
+        let width = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+        let mut help = self.usage();
+        for (label, text) in rows {
+            help += &format!("\n  {:width$}  {}", label, text, width = width);
+        }
+        help
+    }
+
+    /// Parses a command's arguments, skipping the command name.
+    pub fn parse(&self, command: &CCommand) -> Result<ParsedArgs, ArgError> {
+        let tokens: Vec<&str> = (1..command.arg_count()).map(|i| command.arg_str(i)).collect();
+        self.parse_args(&tokens)
+    }
+
+    /// Parses the arguments of a command, without its name.
+    ///
+    /// `--help` anywhere before `--` gives [`ArgError::HelpRequested`], and `--` makes
+    /// every following token positional.
+    pub fn parse_args(&self, tokens: &[&str]) -> Result<ParsedArgs, ArgError> {
+        let mut parsed = ParsedArgs::default();
+        let mut positional: Vec<&str> = Vec::new();
+        let mut options_done = false;
+        let mut pos = 0;
+
+        while pos < tokens.len() {
+            let token = tokens[pos];
+            pos += 1;
+
+            let Some(option) = token.strip_prefix("--").filter(|_| !options_done) else {
+                positional.push(token);
+                continue;
+            };
+            if option.is_empty() {
+                options_done = true;
+                continue;
+            }
+
+            let (name, inline) = match option.split_once('=') {
+                Some((name, value)) => (name, Some(value)),
+                None => (option, None),
+            };
+            if name.eq_ignore_ascii_case("help") {
+                return Err(ArgError::HelpRequested);
+            }
+            let named = self.named
+                .iter()
+                .find(|n| n.name.eq_ignore_ascii_case(name))
+                .ok_or_else(|| ArgError::UnknownOption(name.to_string()))?;
+
+            match (named.kind, inline) {
+                (None, None) => {
+                    parsed.switches.insert(named.name.clone());
+                }
+                (None, Some(value)) => {
+                    let on = parse_bool(value).ok_or_else(|| invalid(&named.name, value, ArgKind::Bool))?;
+                    if on {
+                        parsed.switches.insert(named.name.clone());
+                    }
+                }
+                (Some(kind), Some(value)) => {
+                    let (value, _) = kind.parse(&[value]).map_err(|value| invalid(&named.name, &value, kind))?;
+                    parsed.values.insert(named.name.clone(), value);
+                }
+                (Some(kind), None) => {
+                    if pos >= tokens.len() {
+                        return Err(ArgError::MissingValue(named.name.clone()));
+                    }
+                    let (value, used) = kind.parse(&tokens[pos..]).map_err(|value| invalid(&named.name, &value, kind))?;
+                    parsed.values.insert(named.name.clone(), value);
+                    pos += used;
+                }
+            }
+        }
+
+        let mut next = 0;
+        for arg in &self.positionals {
+            if next >= positional.len() {
+                if arg.required {
+                    return Err(ArgError::Missing(arg.name.clone()));
+                }
+                break;
+            }
+            let (value, used) = arg.kind.parse(&positional[next..]).map_err(|value| invalid(&arg.name, &value, arg.kind))?;
+            parsed.values.insert(arg.name.clone(), value);
+            next += used;
+        }
+
+        let rest = &positional[next..];
+        match (&self.rest, rest.first()) {
+            (Some(_), _) => parsed.rest = rest.iter().map(|s| s.to_string()).collect(),
+            (None, Some(extra)) => return Err(ArgError::Unexpected(extra.to_string())),
+            (None, None) => {}
+        }
+        Ok(parsed)
+    }
+}
+
+fn invalid(name: &str, value: &str, kind: ArgKind) -> ArgError {
+    ArgError::Invalid { name: name.to_string(), value: value.to_string(), expected: kind.expected() }
+}
+
+/// The values of a parsed command line, by argument name.
+#[derive(Debug, Clone, Default)]
+pub struct ParsedArgs {
+    values: HashMap<String, ArgValue>,
+    switches: HashSet<String>,
+    rest: Vec<String>,
+}
+
+impl ParsedArgs {
+    /// The value of an argument or option, or `None` if it was left out or has another type.
+    pub fn get<T: ArgType>(&self, name: &str) -> Option<T> {
+        self.values.get(name).and_then(T::from_value)
+    }
+
+    /// Like [`get`](Self::get), but an error naming the argument if it is absent.
+    /// Meant for required arguments in [`FromArgs::from_args`].
+    pub fn value<T: ArgType>(&self, name: &str) -> Result<T, ArgError> {
+        self.get(name).ok_or_else(|| ArgError::Missing(name.to_string()))
+    }
+
+    /// Whether the value of `name` was given.
+    pub fn has(&self, name: &str) -> bool {
+        self.values.contains_key(name)
+    }
+
+    /// Whether the switch `--name` was given.
+    pub fn switch(&self, name: &str) -> bool {
+        self.switches.contains(name)
+    }
+
+    /// The tokens collected by [`ArgParser::rest`].
+    pub fn rest(&self) -> &[String] {
+        &self.rest
+    }
+}
+
+/// A struct built from a command's arguments.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use portal2_sdk::{ArgError, ArgParser, ConCommand, FromArgs, ParsedArgs, types::Vector, con_print};
+///
+/// struct Teleport {
+///     target: Vector,
+///     yaw: Option<f32>,
+///     quiet: bool,
+/// }
+///
+/// impl FromArgs for Teleport {
+///     fn parser(parser: ArgParser) -> ArgParser {
+///         parser
+///             .arg::<Vector>("target", "Where to teleport to")
+///             .optional::<f32>("yaw", "Direction to face afterwards")
+///             .switch("quiet", "Don't print anything")
+///     }
+///
+///     fn from_args(args: &ParsedArgs) -> Result<Self, ArgError> {
+///         Ok(Self { target: args.value("target")?, yaw: args.get("yaw"), quiet: args.switch("quiet") })
+///     }
+/// }
+///
+/// ConCommand::builder("tp_to")
+///     .help_text("Teleports the player.")
+///     .typed(|tp: Teleport| {
+///         if !tp.quiet {
+///             con_print!("Teleporting to {:?}\n", tp.target);
+///         }
+///     })
+///     .register();
+/// ```
+pub trait FromArgs: Sized {
+    /// Declares the arguments on `parser`, which already has the command's name.
+    fn parser(parser: ArgParser) -> ArgParser;
+    /// Builds the struct from what was parsed.
+    fn from_args(args: &ParsedArgs) -> Result<Self, ArgError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parser() -> ArgParser {
+        ArgParser::new("test_cmd")
+            .arg::<i32>("count", "How many")
+            .optional::<Vector>("origin", "Where")
+            .switch("force", "Do it anyway")
+            .option::<QAngle>("angles", "Facing")
+            .option::<String>("name", "Label")
+    }
+
+    #[test]
+    fn parses_positionals_switches_and_options() {
+        let args = parser().parse_args(&["3", "1", "2.5", "-3", "--force", "--name=box", "--angles", "0 90 0"]).unwrap();
+        assert_eq!(args.get::<i32>("count"), Some(3));
+        let origin: Vector = args.get("origin").unwrap();
+        assert_eq!((origin.x, origin.y, origin.z), (1.0, 2.5, -3.0));
+        assert!(args.switch("force"));
+        assert_eq!(args.get::<String>("name").as_deref(), Some("box"));
+        assert_eq!(args.get::<QAngle>("angles").map(|a| a.y), Some(90.0));
+
+        let args = parser().parse_args(&["7", "1,2,3"]).unwrap();
+        assert_eq!(args.get::<Vector>("origin").map(|v| v.z), Some(3.0));
+        assert!(!args.switch("force"));
+        assert_eq!(args.get::<f32>("count"), Some(7.0));
+    }
+
+    #[test]
+    fn reports_bad_input() {
+        let p = parser();
+        assert_eq!(p.parse_args(&[]).unwrap_err(), ArgError::Missing("count".into()));
+        assert_eq!(
+            p.parse_args(&["x"]).unwrap_err(),
+            ArgError::Invalid { name: "count".into(), value: "x".into(), expected: "an integer" },
+        );
+        assert!(matches!(p.parse_args(&["1", "2", "3"]), Err(ArgError::Invalid { value, .. }) if value == "2 3"));
+        assert_eq!(p.parse_args(&["1", "--nope"]).unwrap_err(), ArgError::UnknownOption("nope".into()));
+        assert_eq!(p.parse_args(&["1", "--name"]).unwrap_err(), ArgError::MissingValue("name".into()));
+        assert_eq!(p.parse_args(&["1", "0", "0", "0", "extra"]).unwrap_err(), ArgError::Unexpected("extra".into()));
+        assert_eq!(p.parse_args(&["--help"]).unwrap_err(), ArgError::HelpRequested);
+        assert_eq!(ArgError::MissingValue("name".into()).to_string(), "--name needs a value");
+    }
+
+    #[test]
+    fn collects_the_rest_after_a_double_dash() {
+        let p = ArgParser::new("say_as").arg::<String>("who", "Speaker").rest("text", "What to say");
+        let args = p.parse_args(&["bob", "--", "--not-an-option", "hi"]).unwrap();
+        assert_eq!(args.get::<String>("who").as_deref(), Some("bob"));
+        assert_eq!(args.rest(), ["--not-an-option", "hi"]);
+        assert!(p.parse_args(&["-5"]).is_ok());
+    }
+
+    #[test]
+    fn generates_usage_and_help() {
+        let p = parser();
+        assert_eq!(
+            p.usage(),
+            "Usage: test_cmd <count: int> [origin: x y z] [--force] [--angles <pitch yaw roll>] [--name <text>]",
+        );
+        let help = p.help();
+        let lines: Vec<&str> = help.lines().collect();
+        assert_eq!(lines[0], p.usage());
+        assert_eq!(lines[1], "  count                      How many");
+        assert_eq!(lines[4], "  --angles <pitch yaw roll>  Facing");
+    }
+}
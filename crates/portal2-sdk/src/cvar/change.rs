@@ -0,0 +1,204 @@
+//! Change notifications for console variables.
+//!
+//! The engine reports changes through `FnChangeCallback_t`, either those stored in
+//! a `ConVar` or the global ones installed on `ICvar`. Closures live in a registry
+//! here, and one plain function, [`dispatch_global_change`], installed as a global
+//! callback, runs them: for listeners added with [`ICvar::listen_changes`], and for
+//! variables built with [`ConVarBuilder::on_change`](super::ConVarBuilder::on_change),
+//! as a listener filtered on their name.
+
+use std::ffi::{c_char, c_void, CStr};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
+
+use super::{ConVar, ICvar};
+
+/// The engine's `FnChangeCallback_t`. The variable arrives as its `IConVar` base.
+pub(crate) type FnChangeCallback = extern "C" fn(var: *mut c_void, old_value: *const c_char, old_float: f32);
+
+/// A unique ID for a registered change listener. Use this to unregister later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChangeListenerId(usize);
+
+/// What a change callback is told: the variable, already holding its new value, and
+/// the value it had before.
+pub struct ConVarChange<'a> {
+    var: &'a ConVar,
+    old_value: &'a str,
+    old_float: f32,
+}
+
+impl<'a> ConVarChange<'a> {
+    /// The variable that changed.
+    pub fn var(&self) -> &'a ConVar {
+        self.var
+    }
+
+    /// The variable's name.
+    pub fn name(&self) -> &'a str {
+        self.var.get_name()
+    }
+
+    /// The previous value, as a string.
+    pub fn old_value(&self) -> &'a str {
+        self.old_value
+    }
+
+    /// The previous value, as the engine parsed it into a float.
+    pub fn old_float(&self) -> f32 {
+        self.old_float
+    }
+
+    /// The current value, as a string.
+    pub fn new_value(&self) -> String {
+        self.var.get_string()
+    }
+}
+
+/// A closure run when a variable changes.
+pub(crate) type ChangeCallback = Arc<dyn Fn(&ConVarChange) + Send + Sync + 'static>;
+
+/// A global listener, with the name of the only variable it cares about, if any.
+type GlobalListener = (ChangeListenerId, Option<String>, ChangeCallback);
+
+static GLOBAL_LISTENERS: OnceLock<RwLock<Vec<GlobalListener>>> = OnceLock::new();
+static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+/// Whether [`dispatch_global_change`] has been installed on `ICvar`.
+static GLOBAL_INSTALLED: AtomicBool = AtomicBool::new(false);
+
+fn global_listeners() -> &'static RwLock<Vec<GlobalListener>> {
+    GLOBAL_LISTENERS.get_or_init(|| RwLock::new(Vec::new()))
+}
+
+/// Recovers the `ConVar` from the `IConVar` base the engine hands to callbacks.
+fn convar_from_iconvar(var: *mut c_void) -> *const ConVar {
+    (var as usize - std::mem::offset_of!(ConVar, iconvar_vtable)) as *const ConVar
+}
+
+/// Builds the [`ConVarChange`] for a callback invocation, if its arguments make sense.
+fn with_change(var: *mut c_void, old_value: *const c_char, old_float: f32, f: impl FnOnce(&ConVarChange)) {
+    if var.is_null() {
+        return;
+    }
+    let var = unsafe { &*convar_from_iconvar(var) };
+    let old_value = match old_value.is_null() {
+        true => std::borrow::Cow::Borrowed(""),
+        false => unsafe { CStr::from_ptr(old_value) }.to_string_lossy(),
+    };
+    f(&ConVarChange { var, old_value: &old_value, old_float });
+}
+
+/// The global `FnChangeCallback_t`, run by the engine after any variable changes.
+extern "C" fn dispatch_global_change(var: *mut c_void, old_value: *const c_char, old_float: f32) {
+    with_change(var, old_value, old_float, |change| {
+        let callbacks: Vec<ChangeCallback> = {
+            let Ok(listeners) = global_listeners().read() else { return };
+            listeners
+                .iter()
+                .filter(|(_, filter, _)| filter.as_deref().is_none_or(|name| name.eq_ignore_ascii_case(change.name())))
+                .map(|(_, _, callback)| callback.clone())
+                .collect()
+        };
+        for callback in callbacks {
+            callback(change);
+        }
+    });
+}
+
+impl ICvar {
+    /// Runs `callback` after any console variable changes, whoever changed it.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use portal2_sdk::con_print;
+    /// # let cvar_system = portal2_sdk::get_engine().cvar_system();
+    /// let id = cvar_system.listen_changes(|change| {
+    ///     con_print!("{}: {} -> {}\n", change.name(), change.old_value(), change.new_value());
+    /// });
+    /// // ...
+    /// cvar_system.unlisten_changes(id);
+    /// ```
+    pub fn listen_changes<F>(&self, callback: F) -> ChangeListenerId
+    where
+        F: Fn(&ConVarChange) + Send + Sync + 'static,
+    {
+        self.add_change_listener(None, Arc::new(callback))
+    }
+
+    /// Like [`listen_changes`](Self::listen_changes), for the variable `name` only.
+    pub fn listen_var_changes<F>(&self, name: &str, callback: F) -> ChangeListenerId
+    where
+        F: Fn(&ConVarChange) + Send + Sync + 'static,
+    {
+        self.add_change_listener(Some(name.to_string()), Arc::new(callback))
+    }
+
+    /// Removes a change listener by its ID.
+    pub fn unlisten_changes(&self, id: ChangeListenerId) {
+        let removed: Vec<_> = {
+            let mut listeners = global_listeners().write().unwrap();
+            let (removed, kept) = std::mem::take(&mut *listeners).into_iter().partition(|(lid, _, _)| *lid == id);
+            *listeners = kept;
+            removed
+        };
+        // Dropped after the lock is released, in case the closure's captures touch the listeners.
+        drop(removed);
+    }
+
+    /// Removes every change listener and the engine's reference to our dispatcher.
+    pub fn shutdown_change_listeners(&self) {
+        if GLOBAL_INSTALLED.swap(false, Ordering::SeqCst) {
+            unsafe { (self.remove_global_change_callback)(self.this, dispatch_global_change) };
+        }
+        let removed = std::mem::take(&mut *global_listeners().write().unwrap());
+        drop(removed);
+    }
+
+    pub(super) fn add_change_listener(&self, filter: Option<String>, callback: ChangeCallback) -> ChangeListenerId {
+        let id = ChangeListenerId(NEXT_ID.fetch_add(1, Ordering::SeqCst));
+        global_listeners().write().unwrap().push((id, filter, callback));
+
+        // The first listener tells the engine about the dispatcher.
+        if !GLOBAL_INSTALLED.swap(true, Ordering::SeqCst) {
+            unsafe { (self.install_global_change_callback)(self.this, dispatch_global_change) };
+        }
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// A `ConVar` with just enough filled in for the dispatchers.
+    fn fake_convar(name: &'static CStr, value: &'static CStr) -> Box<ConVar> {
+        let mut var: Box<ConVar> = Box::new(unsafe { std::mem::zeroed() });
+        var.base.name = name.as_ptr();
+        var.string = value.as_ptr() as *mut c_char;
+        var
+    }
+
+    fn iconvar(var: &ConVar) -> *mut c_void {
+        &var.iconvar_vtable as *const _ as *mut c_void
+    }
+
+    #[test]
+    fn filters_global_listeners_by_name() {
+        let (a, b) = (fake_convar(c"test_global_a", c"x"), fake_convar(c"test_global_b", c"y"));
+        let hits = Arc::new(Mutex::new(Vec::new()));
+
+        let (all, only_a) = (ChangeListenerId(usize::MAX - 1), ChangeListenerId(usize::MAX));
+        let sink = hits.clone();
+        global_listeners().write().unwrap().push((all, None, Arc::new(move |c: &ConVarChange| sink.lock().unwrap().push(format!("all:{}", c.name())))));
+        let sink = hits.clone();
+        global_listeners().write().unwrap().push((only_a, Some("TEST_GLOBAL_A".into()), Arc::new(move |c: &ConVarChange| sink.lock().unwrap().push(format!("a:{}", c.name())))));
+
+        dispatch_global_change(iconvar(&a), std::ptr::null(), 0.0);
+        dispatch_global_change(iconvar(&b), std::ptr::null(), 0.0);
+        global_listeners().write().unwrap().retain(|(id, _, _)| *id != all && *id != only_a);
+
+        assert_eq!(*hits.lock().unwrap(), ["all:test_global_a", "a:test_global_a", "all:test_global_b"]);
+    }
+}
@@ -1,11 +1,13 @@
-use std::ffi::{c_char, c_void, CStr};
-use super::{CCommand, ConCommandBase, CvarFlags};
+use std::collections::HashMap;
+use std::ffi::{c_char, c_int, c_void, CStr};
+use std::sync::{Arc, OnceLock, RwLock};
+use super::{ArgError, ArgParser, CCommand, Color, ConCommandBase, CvarFlags, FromArgs, ParsedArgs};
 
 /// Represents a console command (`ConCommand`) in the Source engine (`portal2`).
 ///
 /// A `ConCommand` is an executable command registered in the engine's console system (`ICvar`).
 /// When invoked by the user or an engine script (e.g., `] my_command arg1 arg2`), the engine
-/// dispatches execution to the registered `extern "C"` Rust function callback.
+/// dispatches execution to the registered Rust callback, a closure or a plain function.
 ///
 /// # Example
 ///
@@ -40,18 +42,122 @@ pub struct ConCommand {
     pub flags_and_bitfields: u32,                            // +0x20
 }
 
+/// A closure run when a command is dispatched.
+type CommandCallback = Arc<dyn Fn(&CCommand) + Send + Sync + 'static>;
+/// A closure producing autocomplete suggestions from what follows the command name.
+type CompletionCallback = Arc<dyn Fn(&str) -> Vec<String> + Send + Sync + 'static>;
+
+/// Everything [`dispatch_command`] and [`dispatch_completion`] need for one command.
+struct CommandEntry {
+    name: String,
+    callback: Option<CommandCallback>,
+    completion: Option<CompletionCallback>,
+}
+
+/// Closures of every command registered through the builder, keyed by the command's address.
+static COMMAND_REGISTRY: OnceLock<RwLock<HashMap<usize, CommandEntry>>> = OnceLock::new();
+
+fn get_registry() -> &'static RwLock<HashMap<usize, CommandEntry>> {
+    COMMAND_REGISTRY.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Finds the entry for `name`. The engine matches command names case-insensitively.
+fn find_entry<'r>(registry: &'r HashMap<usize, CommandEntry>, name: &str) -> Option<&'r CommandEntry> {
+    registry.values().find(|entry| entry.name.eq_ignore_ascii_case(name))
+}
+
+/// `COMMAND_COMPLETION_MAXITEMS`: how many suggestions the console takes.
+const COMPLETION_MAX_ITEMS: usize = 64;
+/// `COMMAND_COMPLETION_ITEM_LENGTH`: the size of each suggestion, terminator included.
+const COMPLETION_ITEM_LENGTH: usize = 64;
+
+type CompletionBuffer = [[c_char; COMPLETION_ITEM_LENGTH]; COMPLETION_MAX_ITEMS];
+
+/// The engine's `FnCommandCallback_t` for every builder-registered command.
+///
+/// The engine does not tell the callback which command it belongs to, so the closure
+/// is looked up by `argv[0]`. The lock is released before the closure runs, which
+/// lets a command unregister itself or others.
+extern "C" fn dispatch_command(command: &CCommand) {
+    let callback = {
+        let Ok(registry) = get_registry().read() else { return };
+        find_entry(&registry, command.arg_str(0)).and_then(|entry| entry.callback.clone())
+    };
+    if let Some(callback) = callback {
+        callback(command);
+    }
+}
+
+/// The engine's `FnCommandCompletionCallback` for every builder-registered command.
+///
+/// `partial` is the whole line typed so far, command name included.
+extern "C" fn dispatch_completion(partial: *const c_char, commands: *mut CompletionBuffer) -> c_int {
+    if partial.is_null() || commands.is_null() {
+        return 0;
+    }
+    let partial = unsafe { CStr::from_ptr(partial) }.to_string_lossy();
+    let (name, rest) = partial.split_once(' ').unwrap_or((&partial, ""));
+
+    let (name, completion) = {
+        let Ok(registry) = get_registry().read() else { return 0 };
+        match find_entry(&registry, name).and_then(|entry| Some((entry.name.clone(), entry.completion.clone()?))) {
+            Some(found) => found,
+            None => return 0,
+        }
+    };
+
+    let suggestions = completion(rest);
+    fill_suggestions(&name, &suggestions, unsafe { &mut *commands }) as c_int
+}
+
+/// Writes `name suggestion` lines into the console's buffer, since the console
+/// replaces the whole line with the one picked. Returns how many were written.
+///
+/// Suggestions past the buffer's capacity are dropped, and overlong ones are cut
+/// at a character boundary.
+fn fill_suggestions(name: &str, suggestions: &[String], buffer: &mut CompletionBuffer) -> usize {
+    let mut count = 0;
+    for (slot, suggestion) in buffer.iter_mut().zip(suggestions) {
+        let line = format!("{} {}", name, suggestion);
+        let mut len = line.len().min(COMPLETION_ITEM_LENGTH - 1);
+        while !line.is_char_boundary(len) {
+            len -= 1;
+        }
+        for (dst, &src) in slot.iter_mut().zip(&line.as_bytes()[..len]) {
+            *dst = src as c_char;
+        }
+        slot[len] = 0;
+        count += 1;
+    }
+    count
+}
+
 /// A high-level builder (`ConCommandBuilder`) for constructing and registering custom `ConCommand`s cleanly.
 ///
+/// Callbacks are closures, so a command can carry its own state instead of reaching
+/// for globals.
+///
 /// # Example
 ///
 /// ```rust,no_run
-/// use portal2_sdk::{ConCommand, CCommand, CvarFlags};
-///
-/// extern "C" fn callback(cmd: &CCommand) {}
+/// use std::sync::atomic::{AtomicU32, Ordering};
+/// use portal2_sdk::{ConCommand, CvarFlags, con_print};
 ///
-/// let cmd = ConCommand::builder("custom_reload", callback)
+/// let reloads = AtomicU32::new(0);
+/// let cmd = ConCommand::builder("custom_reload")
 ///     .help_text("Reloads configuration files")
 ///     .flags(CvarFlags::NONE)
+///     .callback(move |args| {
+///         let n = reloads.fetch_add(1, Ordering::Relaxed) + 1;
+///         con_print!("Reloading {} (#{})\n", args.arg_str(1), n);
+///     })
+///     .completion(|partial| {
+///         ["maps", "binds", "all"]
+///             .into_iter()
+///             .filter(|s| s.starts_with(partial))
+///             .map(String::from)
+///             .collect()
+///     })
 ///     .register()
 ///     .expect("Failed to register custom_reload");
 /// ```
@@ -59,17 +165,22 @@ pub struct ConCommandBuilder<'a> {
     name: &'a str,
     help_string: &'a str,
     flags: CvarFlags,
-    callback: extern "C" fn(cmd: &CCommand),
+    callback: Option<CommandCallback>,
+    completion: Option<CompletionCallback>,
+    /// Help generated from an [`ArgParser`], appended to `help_string`.
+    arg_help: Option<String>,
 }
 
 impl<'a> ConCommandBuilder<'a> {
-    /// Creates a new `ConCommandBuilder` with the specified command name and execution callback.
-    pub fn new(name: &'a str, callback: extern "C" fn(cmd: &CCommand)) -> Self {
+    /// Creates a new `ConCommandBuilder` with the specified command name.
+    pub fn new(name: &'a str) -> Self {
         Self {
             name,
             help_string: "",
             flags: CvarFlags::NONE,
-            callback,
+            callback: None,
+            completion: None,
+            arg_help: None,
         }
     }
 
@@ -85,13 +196,88 @@ impl<'a> ConCommandBuilder<'a> {
         self
     }
 
+    /// Sets the closure run when the command is executed. A command without one does nothing.
+    pub fn callback<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&CCommand) + Send + Sync + 'static,
+    {
+        self.callback = Some(Arc::new(callback));
+        self
+    }
+
+    /// Sets a callback that receives the arguments parsed by `parser`.
+    ///
+    /// The parser's help is appended to the help text. `--help` prints it, and bad input
+    /// prints the error in red with the usage line, without running `callback`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use portal2_sdk::{ArgParser, ConCommand, con_print};
+    ///
+    /// let parser = ArgParser::new("repeat_say")
+    ///     .arg::<i32>("times", "How many times")
+    ///     .rest("text", "What to say");
+    ///
+    /// ConCommand::builder("repeat_say")
+    ///     .parsed(parser, |args| {
+    ///         for _ in 0..args.get::<i32>("times").unwrap() {
+    ///             con_print!("{}\n", args.rest().join(" "));
+    ///         }
+    ///     })
+    ///     .register();
+    /// ```
+    pub fn parsed<F>(mut self, parser: ArgParser, callback: F) -> Self
+    where
+        F: Fn(&ParsedArgs) + Send + Sync + 'static,
+    {
+        self.arg_help = Some(parser.help());
+        self.callback(move |cmd| match parser.parse(cmd) {
+            Ok(args) => callback(&args),
+            Err(error) => report_arg_error(&parser, &error),
+        })
+    }
+
+    /// Like [`parsed`](Self::parsed), with the arguments mapped onto `T`. See [`FromArgs`].
+    pub fn typed<T, F>(mut self, callback: F) -> Self
+    where
+        T: FromArgs,
+        F: Fn(T) + Send + Sync + 'static,
+    {
+        let parser = T::parser(ArgParser::new(self.name));
+        self.arg_help = Some(parser.help());
+        self.callback(move |cmd| match parser.parse(cmd).and_then(|args| T::from_args(&args)) {
+            Ok(value) => callback(value),
+            Err(error) => report_arg_error(&parser, &error),
+        })
+    }
+
+    /// Sets the closure offering autocomplete suggestions in the developer console.
+    ///
+    /// It receives what was typed after the command name and returns the arguments to
+    /// suggest; the console shows at most 64 of them.
+    pub fn completion<F>(mut self, completion: F) -> Self
+    where
+        F: Fn(&str) -> Vec<String> + Send + Sync + 'static,
+    {
+        self.completion = Some(Arc::new(completion));
+        self
+    }
+
     /// Allocates and registers the `ConCommand` in the Source engine using vtables borrowed from an existing command.
     ///
-    /// Returns `Some(&'static mut ConCommand)` on success, or `None` if the engine interface is unavailable.
+    /// Returns `Some(&'static mut ConCommand)` on success, or `None` if the engine interface is unavailable
+    /// or the engine already knows a command or variable of the same name.
     pub fn register(self) -> Option<&'static mut ConCommand> {
         let engine = crate::get_engine();
         let cvar_system = engine.cvar_system();
 
+        // Commands are dispatched by name, so a second one by the same name could never run.
+        if find_entry(&get_registry().read().unwrap(), self.name).is_some() || cvar_system.find_command_base(self.name).is_some() {
+            log::warn!("Not registering the command '{}': the name is taken", self.name);
+            return None;
+        }
+
         // Borrow vtable from a known valid ConCommand in the engine (e.g. "echo" or "help")
         let dummy = cvar_system
             .find_command_base("echo")
@@ -99,7 +285,17 @@ impl<'a> ConCommandBuilder<'a> {
         let base_vtable = dummy.vtable;
 
         let name = std::ffi::CString::new(self.name).ok()?.into_raw();
-        let help_string = std::ffi::CString::new(self.help_string).ok()?.into_raw();
+        let help_string = match (&self.arg_help, self.help_string.is_empty()) {
+            (Some(arg_help), true) => arg_help.clone(),
+            (Some(arg_help), false) => format!("{}\n{}", self.help_string, arg_help),
+            (None, _) => self.help_string.to_string(),
+        };
+        let help_string = std::ffi::CString::new(help_string).ok()?.into_raw();
+
+        let (completion_callback, has_completion) = match self.completion {
+            Some(_) => (dispatch_completion as *const c_void, 1),
+            None => (std::ptr::null(), 0),
+        };
 
         let command_box = Box::new(ConCommand {
             base: ConCommandBase {
@@ -111,41 +307,65 @@ impl<'a> ConCommandBuilder<'a> {
                 help_string,
                 flags: self.flags.bits(),
             },
-            callback: Some(self.callback),
-            completion_callback: std::ptr::null(),
-            // m_bHasCompletionCallback (bit 0) = 0
+            callback: Some(dispatch_command),
+            completion_callback,
+            // m_bHasCompletionCallback (bit 0) = set with a completion closure
             // m_bUsingNewCommandCallback (bit 1) = 1 -> 0x02
             // m_bUsingCommandCallbackInterface (bit 2) = 0
-            flags_and_bitfields: 2,
+            flags_and_bitfields: 2 | has_completion,
         });
 
         let command_ptr = Box::leak(command_box);
+        let key = command_ptr as *mut ConCommand as usize;
+
+        get_registry().write().unwrap().insert(
+            key,
+            CommandEntry { name: self.name.to_string(), callback: self.callback, completion: self.completion },
+        );
 
         cvar_system.register_con_command(&mut command_ptr.base);
 
+        // The engine marks a command registered before looking for a clash, and only
+        // warns about one, so what counts is whether the name now leads to ours.
+        let linked = cvar_system.find_command_base(self.name).is_some_and(|base| std::ptr::eq(base, &command_ptr.base));
+        if !command_ptr.base.is_registered || !linked {
+            log::warn!("The engine did not register the command '{}'", self.name);
+            let entry = get_registry().write().unwrap().remove(&key);
+            drop(entry);
+            return None;
+        }
+
         Some(command_ptr)
     }
 }
 
+/// Prints why a command line was rejected, or the help if it was asked for.
+fn report_arg_error(parser: &ArgParser, error: &ArgError) {
+    match error {
+        ArgError::HelpRequested => crate::con_print!("{}\n", parser.help()),
+        _ => {
+            crate::con_color_print!(Color::RED, "{}: {}\n", parser.command(), error);
+            crate::con_print!("{}\n", parser.usage());
+        }
+    }
+}
+
 impl ConCommand {
     /// Returns a high-level builder (`ConCommandBuilder`) for configuring and registering a new console command.
     ///
     /// # Example
     ///
     /// ```rust,no_run
-    /// use portal2_sdk::{ConCommand, CCommand};
-    ///
-    /// extern "C" fn my_callback(cmd: &CCommand) {}
+    /// use portal2_sdk::{ConCommand, con_print};
     ///
-    /// let cmd = ConCommand::builder("test_cmd", my_callback)
+    /// let greeting = String::from("Hello");
+    /// let cmd = ConCommand::builder("test_cmd")
     ///     .help_text("Description of test_cmd")
+    ///     .callback(move |args| con_print!("{}, {}!\n", greeting, args.arg_str(1)))
     ///     .register();
     /// ```
-    pub fn builder<'a>(
-        name: &'a str,
-        callback: extern "C" fn(cmd: &CCommand),
-    ) -> ConCommandBuilder<'a> {
-        ConCommandBuilder::new(name, callback)
+    pub fn builder(name: &str) -> ConCommandBuilder<'_> {
+        ConCommandBuilder::new(name)
     }
 
     /// High-level shortcut to register a simple `ConCommand` immediately without manual padding or pointers.
@@ -170,12 +390,36 @@ impl ConCommand {
         flags: CvarFlags,
         callback: extern "C" fn(cmd: &CCommand),
     ) -> Option<&'static mut ConCommand> {
-        ConCommandBuilder::new(name, callback)
+        ConCommandBuilder::new(name)
             .help_text(help_string)
             .flags(flags)
+            .callback(move |cmd| callback(cmd))
             .register()
     }
 
+    /// Removes the command from the engine's `ICvar` registry and frees its closures.
+    ///
+    /// The `ConCommand` itself stays allocated, as the engine may still be walking the
+    /// command list. Calling this on an unregistered command only drops the closures.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use portal2_sdk::ConCommand;
+    /// let cmd = ConCommand::builder("temporary_cmd").callback(|_| {}).register().unwrap();
+    /// // ...
+    /// cmd.unregister();
+    /// ```
+    pub fn unregister(&mut self) {
+        if self.base.is_registered {
+            crate::get_engine().cvar_system().unregister_con_command(&mut self.base);
+        }
+        let entry = get_registry().write().unwrap().remove(&(self as *mut ConCommand as usize));
+        // Dropped after the lock is released, in case something the closures captured
+        // touches the registry when it goes.
+        drop(entry);
+    }
+
     /// Checks if the `ConCommand` has been registered with the engine's `ICvar` system.
     pub fn is_registered(&self) -> bool {
         self.base.is_registered
@@ -197,3 +441,57 @@ impl ConCommand {
         unsafe { CStr::from_ptr(self.base.help_string).to_str().unwrap_or("") }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn suggestion(buffer: &CompletionBuffer, index: usize) -> &str {
+        unsafe { CStr::from_ptr(buffer[index].as_ptr()) }.to_str().unwrap()
+    }
+
+    #[test]
+    fn fills_prefixed_and_truncated_suggestions() {
+        let mut buffer = [[0; COMPLETION_ITEM_LENGTH]; COMPLETION_MAX_ITEMS];
+        let long = format!("x{}", "é".repeat(40));
+        let suggestions = vec!["maps".to_string(), long];
+
+        assert_eq!(fill_suggestions("reload", &suggestions, &mut buffer), 2);
+        assert_eq!(suggestion(&buffer, 0), "reload maps");
+        // 63 bytes would split an `é`, so the cut falls one byte earlier.
+        assert_eq!(suggestion(&buffer, 1).len(), 62);
+
+        let many = vec![String::new(); COMPLETION_MAX_ITEMS + 10];
+        assert_eq!(fill_suggestions("reload", &many, &mut buffer), COMPLETION_MAX_ITEMS);
+    }
+
+    #[test]
+    fn dispatches_by_name_to_the_closures() {
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        let key = 0x1000;
+        get_registry().write().unwrap().insert(key, CommandEntry {
+            name: "test_dispatch".to_string(),
+            callback: Some(Arc::new(|cmd: &CCommand| {
+                CALLS.fetch_add(cmd.arg_count(), Ordering::SeqCst);
+            })),
+            completion: Some(Arc::new(|partial: &str| vec![format!("{}!", partial)])),
+        });
+
+        let mut command: CCommand = unsafe { std::mem::zeroed() };
+        command.argc = 2;
+        command.argv[0] = c"TEST_Dispatch".as_ptr();
+        command.argv[1] = c"arg".as_ptr();
+        dispatch_command(&command);
+        assert_eq!(CALLS.load(Ordering::SeqCst), 2);
+
+        let mut buffer = [[0; COMPLETION_ITEM_LENGTH]; COMPLETION_MAX_ITEMS];
+        assert_eq!(dispatch_completion(c"test_dispatch ab".as_ptr(), &mut buffer), 1);
+        assert_eq!(suggestion(&buffer, 0), "test_dispatch ab!");
+        assert_eq!(dispatch_completion(c"unknown ab".as_ptr(), &mut buffer), 0);
+
+        get_registry().write().unwrap().remove(&key);
+        dispatch_command(&command);
+        assert_eq!(CALLS.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,251 @@
+//! Capture of everything the engine prints to its console.
+//!
+//! `ICvar` forwards console output to every installed `IConsoleDisplayFunc`, the
+//! interface the in-game console and the dedicated server window implement. We
+//! install one more, split what it receives into lines and keep the most recent ones
+//! in a ring buffer that the overlay reads at its own pace.
+
+use std::collections::VecDeque;
+use std::ffi::{c_char, c_void, CStr};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use super::{Color, ICvar};
+use crate::platform::abi::{vfn, vfn_impl};
+
+/// One line of console output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConsoleLine {
+    /// Increases by one with every captured line, across [`ICvar::clear_console_capture`].
+    pub id: u64,
+    pub text: String,
+    /// The color the line was printed with, or `None` for uncolored prints.
+    pub color: Option<Color>,
+    /// Whether the line came from `DevMsg` and friends, only shown with `developer 1`.
+    pub developer: bool,
+}
+
+/// The part of a line printed so far, waiting for its newline.
+struct Partial {
+    text: String,
+    color: Option<Color>,
+    developer: bool,
+}
+
+/// The ring buffer and the line being assembled.
+struct Capture {
+    lines: VecDeque<ConsoleLine>,
+    capacity: usize,
+    partial: Option<Partial>,
+    next_id: u64,
+}
+
+impl Capture {
+    const fn new() -> Self {
+        Self { lines: VecDeque::new(), capacity: 0, partial: None, next_id: 0 }
+    }
+
+    /// Adds a printed fragment, which may end a line, hold several, or neither. A line
+    /// printed in several pieces keeps the color and kind of its first one.
+    fn push(&mut self, message: &str, color: Option<Color>, developer: bool) {
+        let mut rest = message;
+        while !rest.is_empty() {
+            let (piece, ends_line) = match rest.find('\n') {
+                Some(end) => (&rest[..end], true),
+                None => (rest, false),
+            };
+            rest = &rest[piece.len() + ends_line as usize..];
+
+            let partial = self.partial.get_or_insert_with(|| Partial { text: String::new(), color, developer });
+            partial.text.push_str(piece.strip_suffix('\r').unwrap_or(piece));
+            if ends_line {
+                let Partial { text, color, developer } = self.partial.take().unwrap();
+                self.push_line(text, color, developer);
+            }
+        }
+    }
+
+    fn push_line(&mut self, text: String, color: Option<Color>, developer: bool) {
+        if self.capacity == 0 {
+            return;
+        }
+        while self.lines.len() >= self.capacity {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(ConsoleLine { id: self.next_id, text, color, developer });
+        self.next_id += 1;
+    }
+
+    fn lines_after(&self, after: Option<u64>) -> Vec<ConsoleLine> {
+        let first = self.lines.partition_point(|line| after.is_some_and(|after| line.id <= after));
+        self.lines.range(first..).cloned().collect()
+    }
+}
+
+static CAPTURE: Mutex<Capture> = Mutex::new(Capture::new());
+/// Whether [`CONSOLE_DISPLAY`] is installed on `ICvar`.
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+/// Adds a message the engine printed. Nothing here may print or log: that would come
+/// straight back in, with the lock held.
+fn capture(message: *const c_char, color: Option<Color>, developer: bool) {
+    if message.is_null() {
+        return;
+    }
+    let message = unsafe { CStr::from_ptr(message) }.to_string_lossy();
+    if let Ok(mut capture) = CAPTURE.lock() {
+        capture.push(&message, color, developer);
+    }
+}
+
+#[repr(C)]
+struct ConsoleDisplayFuncVtable {
+    color_print: vfn!((this: *mut c_void, color: *const Color, message: *const c_char)),
+    print: vfn!((this: *mut c_void, message: *const c_char)),
+    dprint: vfn!((this: *mut c_void, message: *const c_char)),
+    get_console_text: vfn!((this: *mut c_void, text: *mut c_char, size: usize)),
+}
+
+/// Our `IConsoleDisplayFunc`. It has no state: the vtable is all the engine needs.
+#[repr(C)]
+struct ConsoleDisplay {
+    vtable: *const ConsoleDisplayFuncVtable,
+}
+
+unsafe impl Sync for ConsoleDisplay {}
+
+static CONSOLE_DISPLAY_VTABLE: ConsoleDisplayFuncVtable = ConsoleDisplayFuncVtable {
+    color_print: console_display_color_print,
+    print: console_display_print,
+    dprint: console_display_dprint,
+    get_console_text: console_display_get_console_text,
+};
+
+static CONSOLE_DISPLAY: ConsoleDisplay = ConsoleDisplay { vtable: &CONSOLE_DISPLAY_VTABLE };
+
+vfn_impl! {
+    fn console_display_color_print(_this: *mut c_void, color: *const Color, message: *const c_char) {
+        capture(message, unsafe { color.as_ref() }.copied(), false);
+    }
+
+    fn console_display_print(_this: *mut c_void, message: *const c_char) {
+        capture(message, None, false);
+    }
+
+    fn console_display_dprint(_this: *mut c_void, message: *const c_char) {
+        capture(message, None, true);
+    }
+
+    /// Only used by the engine to copy the console's contents elsewhere; we have none to offer.
+    fn console_display_get_console_text(_this: *mut c_void, text: *mut c_char, size: usize) {
+        if !text.is_null() && size > 0 {
+            unsafe { text.write(0) };
+        }
+    }
+}
+
+impl ICvar {
+    /// Starts capturing console output, keeping the last `capacity` lines.
+    ///
+    /// Calling it again only changes the capacity.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # let cvar_system = portal2_sdk::get_engine().cvar_system();
+    /// cvar_system.capture_console(4096);
+    /// // ... later, every frame:
+    /// for line in cvar_system.console_lines_after(None) {
+    ///     println!("{}", line.text);
+    /// }
+    /// ```
+    pub fn capture_console(&self, capacity: usize) {
+        {
+            let mut capture = CAPTURE.lock().unwrap();
+            capture.capacity = capacity;
+            while capture.lines.len() > capacity {
+                capture.lines.pop_front();
+            }
+        }
+        if !INSTALLED.swap(true, Ordering::SeqCst) {
+            let display = &CONSOLE_DISPLAY as *const ConsoleDisplay as *mut c_void;
+            unsafe { (self.install_console_display_func)(self.this, display) };
+        }
+    }
+
+    /// Stops capturing and removes our display function from the engine. The lines
+    /// captured so far stay available.
+    pub fn stop_console_capture(&self) {
+        if INSTALLED.swap(false, Ordering::SeqCst) {
+            let display = &CONSOLE_DISPLAY as *const ConsoleDisplay as *mut c_void;
+            unsafe { (self.remove_console_display_func)(self.this, display) };
+        }
+    }
+
+    /// The captured lines newer than the line `after`, or all of them for `None`.
+    pub fn console_lines_after(&self, after: Option<u64>) -> Vec<ConsoleLine> {
+        CAPTURE.lock().unwrap().lines_after(after)
+    }
+
+    /// Forgets every captured line.
+    pub fn clear_console_capture(&self) {
+        CAPTURE.lock().unwrap().lines.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(capture: &Capture) -> Vec<&str> {
+        capture.lines.iter().map(|l| l.text.as_str()).collect()
+    }
+
+    #[test]
+    fn splits_and_joins_fragments_into_lines() {
+        let mut capture = Capture::new();
+        capture.capacity = 10;
+
+        capture.push("[tag] ", Some(Color::RED), false);
+        capture.push("first\r\nsecond\n", None, false);
+        capture.push("dev\n", None, true);
+        capture.push("unfinished", None, false);
+
+        assert_eq!(texts(&capture), ["[tag] first", "second", "dev"]);
+        assert_eq!(capture.lines[0].color, Some(Color::RED));
+        assert_eq!(capture.lines[1].color, None);
+        assert!(capture.lines[2].developer);
+
+        capture.push("\n\n", None, false);
+        assert_eq!(texts(&capture)[3..], ["unfinished", ""]);
+    }
+
+    #[test]
+    fn keeps_only_the_newest_lines() {
+        let mut capture = Capture::new();
+        capture.capacity = 2;
+        capture.push("a\nb\nc\n", None, false);
+
+        assert_eq!(texts(&capture), ["b", "c"]);
+        assert_eq!(capture.lines_after(None).iter().map(|l| l.id).collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(capture.lines_after(Some(1)).len(), 1);
+        assert!(capture.lines_after(Some(2)).is_empty());
+    }
+
+    #[test]
+    fn captures_through_the_display_vtable() {
+        let this = &CONSOLE_DISPLAY as *const ConsoleDisplay as *mut c_void;
+        CAPTURE.lock().unwrap().capacity = 16;
+        let before = CAPTURE.lock().unwrap().next_id;
+
+        unsafe {
+            (CONSOLE_DISPLAY_VTABLE.color_print)(this, &Color::GREEN, c"green\n".as_ptr());
+            (CONSOLE_DISPLAY_VTABLE.dprint)(this, c"developer\n".as_ptr());
+        }
+
+        let lines = CAPTURE.lock().unwrap().lines_after(before.checked_sub(1));
+        assert_eq!(lines[0].text, "green");
+        assert_eq!(lines[0].color, Some(Color::GREEN));
+        assert!(lines[1].developer);
+    }
+}
@@ -1,5 +1,8 @@
 use std::ffi::{c_char, c_int, c_void, CStr};
 
+use std::sync::Arc;
+
+use super::change::{ChangeCallback, ConVarChange};
 use super::{ConCommandBase, CvarFlags};
 
 /// Represents a console variable (`ConVar`) in the Source engine (`portal2`).
@@ -34,22 +37,47 @@ pub struct ConVar {
     /// Base fields inherited from `ConCommandBase` (`vtable`, `name`, `help_string`, `flags`, `is_registered`).
     pub base: ConCommandBase,            // Size: 0x18
 
-    pub iconvar_vtable: *const c_void,
-    pub parent: *mut ConVar,             // +0x18
-    pub default_value: *const c_char,    // +0x1C
-    pub string: *mut c_char,             // +0x20
-    pub string_length: c_int,            // +0x24
-    pub float_value: f32,                // +0x28
-    pub int_value: i32,                  // +0x2C
-    pub has_min: bool,                   // +0x30
-
-    pub _pad_min: [u8; 3],               // +0x31
-    pub min_val: f32,                    // +0x34
-    pub has_max: bool,                   // +0x38
-    pub _pad_max: [u8; 3],               // +0x39
-
-    pub max_val: f32,                    // +0x3C
-    pub change_callback: *const c_void,  // +0x40
+    pub iconvar_vtable: *const c_void,   // +0x18
+    pub parent: *mut ConVar,             // +0x1C
+    pub default_value: *const c_char,    // +0x20
+    pub string: *mut c_char,             // +0x24
+    pub string_length: c_int,            // +0x28
+    pub float_value: f32,                // +0x2C
+    pub int_value: i32,                  // +0x30
+    pub has_min: bool,                   // +0x34
+
+    pub _pad_min: [u8; 3],               // +0x35
+    pub min_val: f32,                    // +0x38
+    pub has_max: bool,                   // +0x3C
+    pub _pad_max: [u8; 3],               // +0x3D
+
+    pub max_val: f32,                    // +0x40
+    pub change_callbacks: ChangeCallbacks, // +0x44
+}
+
+/// `CUtlVector<FnChangeCallback_t>`: the callbacks the engine runs when one variable changes.
+///
+/// Variables registered through [`ConVarBuilder`] leave it empty, so that the engine
+/// never reallocates memory it did not allocate; their closures are run by the
+/// global dispatcher instead.
+#[repr(C)]
+pub struct ChangeCallbacks {
+    pub memory: *mut c_void,             // +0x00
+    pub alloc_count: c_int,              // +0x04
+    pub grow_size: c_int,                // +0x08
+    pub size: c_int,                     // +0x0C
+    pub elements: *mut c_void,           // +0x10
+}
+
+impl ChangeCallbacks {
+    /// An empty vector, as the engine's `CUtlVector` constructor leaves it.
+    pub const EMPTY: Self = Self {
+        memory: std::ptr::null_mut(),
+        alloc_count: 0,
+        grow_size: 0,
+        size: 0,
+        elements: std::ptr::null_mut(),
+    };
 }
 
 /// A high-level builder for constructing and registering new custom `ConVar`s cleanly.
@@ -77,6 +105,7 @@ pub struct ConVarBuilder<'a> {
     flags: CvarFlags,
     min: Option<f32>,
     max: Option<f32>,
+    on_change: Option<ChangeCallback>,
 }
 
 impl<'a> ConVarBuilder<'a> {
@@ -89,6 +118,7 @@ impl<'a> ConVarBuilder<'a> {
             flags: CvarFlags::NONE,
             min: None,
             max: None,
+            on_change: None,
         }
     }
 
@@ -116,6 +146,26 @@ impl<'a> ConVarBuilder<'a> {
         self
     }
 
+    /// Sets a closure run whenever the value changes, through the global change listeners
+    /// of [`ICvar`](super::ICvar).
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use portal2_sdk::{ConVar, con_print};
+    ///
+    /// let cvar = ConVar::builder("my_overlay_scale", "1.0")
+    ///     .on_change(|change| con_print!("Scale: {} -> {}\n", change.old_value(), change.new_value()))
+    ///     .register();
+    /// ```
+    pub fn on_change<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&ConVarChange) + Send + Sync + 'static,
+    {
+        self.on_change = Some(Arc::new(callback));
+        self
+    }
+
     /// Allocates and registers the `ConVar` inside the Source engine (`ICvar`).
     ///
     /// Returns `Some(&'static mut ConVar)` upon success, or `None` if engine interfaces aren't initialized.
@@ -169,13 +219,16 @@ impl<'a> ConVarBuilder<'a> {
             has_max,
             _pad_max: [0; 3],
             max_val,
-            change_callback: std::ptr::null(),
+            change_callbacks: ChangeCallbacks::EMPTY,
         });
 
         let convar_ptr = Box::leak(convar_box);
         convar_ptr.parent = convar_ptr as *mut ConVar;
 
         cvar_system.register_con_command(&mut convar_ptr.base);
+        if let Some(callback) = self.on_change {
+            cvar_system.add_change_listener(Some(self.name.to_string()), callback);
+        }
 
         Some(convar_ptr)
     }
@@ -332,25 +385,31 @@ impl ConVar {
         if self.has_max { Some(self.max_val) } else { None }
     }
 
+    /// The pointer the engine's setters are called with. The engine writes the value
+    /// fields itself, so the setters only need `&self`.
+    fn as_mut_ptr(&self) -> *mut ConVar {
+        self as *const ConVar as *mut ConVar
+    }
+
     /// Sets the ConVar value using a string, correctly invoking engine callbacks.
-    pub fn set_value_str(&mut self, value: &str) {
+    pub fn set_value_str(&self, value: &str) {
         if let Ok(c_str) = std::ffi::CString::new(value) {
-            unsafe { (self.vtable().set_value_str)(self, c_str.as_ptr()) };
+            unsafe { (self.vtable().set_value_str)(self.as_mut_ptr(), c_str.as_ptr()) };
         }
     }
 
     /// Sets the `ConVar` value using a float, correctly invoking engine callbacks.
-    pub fn set_value_float(&mut self, value: f32) {
-        unsafe { (self.vtable().set_value_float)(self, value) };
+    pub fn set_value_float(&self, value: f32) {
+        unsafe { (self.vtable().set_value_float)(self.as_mut_ptr(), value) };
     }
 
     /// Sets the ConVar value using an integer, correctly invoking engine callbacks.
-    pub fn set_value_int(&mut self, value: i32) {
-        unsafe { (self.vtable().set_value_int)(self, value) };
+    pub fn set_value_int(&self, value: i32) {
+        unsafe { (self.vtable().set_value_int)(self.as_mut_ptr(), value) };
     }
 
     /// Resets the `ConVar` back to its default value (`default_value`).
-    pub fn reset(&mut self) {
+    pub fn reset(&self) {
         let default_str = self.get_default();
 
         if let Ok(int_val) = default_str.parse::<i32>() {
@@ -17,7 +17,7 @@ bitflags! {
     /// }
     /// ```
     #[repr(transparent)]
-    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
     pub struct CvarFlags: i32 {
         /// The default, no flags at all.
         const NONE = 0;
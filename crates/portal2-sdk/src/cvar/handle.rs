@@ -0,0 +1,145 @@
+use std::marker::PhantomData;
+use std::sync::OnceLock;
+
+use super::{ChangeListenerId, ConVar};
+
+/// A type a console variable can be read and written as.
+pub trait CvarValue: Sized + 'static {
+    /// Reads the variable's current value.
+    fn get(var: &ConVar) -> Self;
+    /// Writes `self` through the engine's setters, so change callbacks run.
+    fn set(self, var: &ConVar);
+    /// Interprets a value given as a string, the way the engine does when one is set.
+    fn parse(value: &str) -> Self;
+}
+
+/// The engine stores `atof` of the string as the float value, ignoring anything after
+/// the number.
+fn parse_float(value: &str) -> f32 {
+    let value = value.trim_start();
+    let end = value
+        .char_indices()
+        .find(|&(i, c)| !(c.is_ascii_digit() || c == '.' || ((c == '-' || c == '+') && i == 0)))
+        .map_or(value.len(), |(i, _)| i);
+    value[..end].parse().unwrap_or(0.0)
+}
+
+impl CvarValue for f32 {
+    fn get(var: &ConVar) -> Self { var.get_float() }
+    fn set(self, var: &ConVar) { var.set_value_float(self) }
+    fn parse(value: &str) -> Self { parse_float(value) }
+}
+
+impl CvarValue for i32 {
+    fn get(var: &ConVar) -> Self { var.get_int() }
+    fn set(self, var: &ConVar) { var.set_value_int(self) }
+    // The int value is the float one, truncated.
+    fn parse(value: &str) -> Self { parse_float(value) as i32 }
+}
+
+impl CvarValue for bool {
+    fn get(var: &ConVar) -> Self { var.get_bool() }
+    fn set(self, var: &ConVar) { var.set_value_int(self as i32) }
+    fn parse(value: &str) -> Self { i32::parse(value) != 0 }
+}
+
+impl CvarValue for String {
+    fn get(var: &ConVar) -> Self { var.get_string() }
+    fn set(self, var: &ConVar) { var.set_value_str(&self) }
+    fn parse(value: &str) -> Self { value.to_string() }
+}
+
+/// A typed handle to a console variable, looked up by name once it is first needed.
+///
+/// Handles are cheap to keep around, and can be `static`. The lookup is retried until
+/// the variable exists, so a handle can be created before the engine is up.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use portal2_sdk::{CvarHandle, con_print};
+///
+/// static TIMESCALE: CvarHandle<f32> = CvarHandle::new("host_timescale");
+///
+/// let current = TIMESCALE.get().unwrap_or(1.0);
+/// TIMESCALE.set(current / 2.0).expect("host_timescale is missing");
+///
+/// TIMESCALE.subscribe(|old, new| con_print!("Timescale: {} -> {}\n", old, new));
+/// ```
+#[derive(Debug)]
+pub struct CvarHandle<T> {
+    name: &'static str,
+    /// The variable's address, once found.
+    var: OnceLock<usize>,
+    _value: PhantomData<fn() -> T>,
+}
+
+impl<T: CvarValue> CvarHandle<T> {
+    /// Creates a handle to the variable `name`. Nothing is looked up yet.
+    pub const fn new(name: &'static str) -> Self {
+        Self { name, var: OnceLock::new(), _value: PhantomData }
+    }
+
+    /// The variable's name.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The variable itself, or `None` if the engine is not up yet or has no such variable.
+    ///
+    /// Only a shared reference is handed out, since any number of callers can hold one
+    /// from the same `static`; the value setters on [`ConVar`] work through `&self`.
+    pub fn var(&self) -> Option<&'static ConVar> {
+        if let Some(&var) = self.var.get() {
+            return unsafe { (var as *const ConVar).as_ref() };
+        }
+        let var: &'static ConVar = crate::ENGINE.get()?.cvar_system().find_var(self.name)?;
+        let _ = self.var.set(var as *const ConVar as usize);
+        Some(var)
+    }
+
+    /// The current value, or `None` if the variable cannot be found.
+    pub fn get(&self) -> Option<T> {
+        self.var().map(|var| T::get(var))
+    }
+
+    /// Sets the value, running the engine's change callbacks.
+    pub fn set(&self, value: T) -> Result<(), String> {
+        let var = self.var().ok_or_else(|| format!("ConVar '{}' not found", self.name))?;
+        value.set(var);
+        Ok(())
+    }
+
+    /// Runs `callback` with the old and the new value whenever the variable changes.
+    ///
+    /// Works whether or not the variable exists yet, but needs the engine to be up.
+    pub fn subscribe<F>(&self, callback: F) -> ChangeListenerId
+    where
+        F: Fn(T, T) + Send + Sync + 'static,
+    {
+        crate::get_engine().cvar_system().listen_var_changes(self.name, move |change| {
+            callback(T::parse(change.old_value()), T::get(change.var()))
+        })
+    }
+
+    /// Removes a listener added with [`subscribe`](Self::subscribe).
+    pub fn unsubscribe(&self, id: ChangeListenerId) {
+        crate::get_engine().cvar_system().unlisten_changes(id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_values_like_the_engine() {
+        assert_eq!(f32::parse("0.5"), 0.5);
+        assert_eq!(f32::parse("  -2.5 units"), -2.5);
+        assert_eq!(f32::parse("junk"), 0.0);
+        assert_eq!(i32::parse("3.9"), 3);
+        assert!(bool::parse("1"));
+        assert!(!bool::parse("0.4"));
+        assert_eq!(String::parse("255 0 0"), "255 0 0");
+    }
+}
@@ -1,13 +1,24 @@
 #![allow(unused)]
 use std::ffi::{c_char, c_int, c_void, CStr};
-use crate::platform::abi::vfn;
+use std::marker::PhantomData;
+use crate::platform::abi::{vfn, vslot};
+use crate::interface::interface;
 
 mod flags;
 mod convar;
 mod concommand;
+mod change;
+mod handle;
+mod console;
+mod args;
 pub use flags::CvarFlags;
-pub use convar::{ConVar, ConVarBuilder};
+pub use convar::{ChangeCallbacks, ConVar, ConVarBuilder};
 pub use concommand::{ConCommand, ConCommandBuilder};
+pub use change::{ChangeListenerId, ConVarChange};
+pub use handle::{CvarHandle, CvarValue};
+pub use console::ConsoleLine;
+pub use args::{ArgError, ArgKind, ArgParser, ArgType, ArgValue, FromArgs, ParsedArgs};
+use change::FnChangeCallback;
 
 /// RGBA Color structure used for colored developer console printing via `ConsoleColorPrintf`.
 ///
@@ -73,17 +84,14 @@ impl Color {
 type FnSetValueStr = vfn!((this: *mut ConVar, value: *const c_char));
 type FnSetValueFloat = vfn!((this: *mut ConVar, value: f32));
 type FnSetValueInt = vfn!((this: *mut ConVar, value: i32));
-type FnFindVar = vfn!((this: *mut RawICvar, var_name: *const c_char) -> *mut ConVar);
-type FnFindCommandBase = vfn!((this: *mut RawICvar, name: *const c_char) -> *mut ConCommandBase);
-type FnRegisterConCommand = vfn!((this: *mut RawICvar, base: *mut ConCommandBase));
-type FnUnregisterConCommand = vfn!((this: *mut RawICvar, base: *mut ConCommandBase));
-type FnConsoleColorPrintf = unsafe extern "C" fn(this: *mut RawICvar, color: *const Color, format: *const c_char, msg: *const c_char);
-type FnConsolePrintf = unsafe extern "C" fn(this: *mut RawICvar, format: *const c_char, msg: *const c_char);
 
 /// Defines the virtual method table (`vtable`) for a `ConVar` object inheriting from `IConVar`.
+///
+/// `ConCommandBase` declares a virtual destructor first, so everything sits one slot
+/// later on the Itanium ABI.
 #[repr(C)]
 pub struct ConVarVTable {
-    _pad0: [usize; 12],
+    _pad0: [usize; vslot(12, Some(0))],
     pub set_value_str: FnSetValueStr,
     pub set_value_float: FnSetValueFloat,
     pub set_value_int: FnSetValueInt,
@@ -111,8 +119,8 @@ impl ConCommandBase {
     pub fn is_command(&self) -> bool {
         type FnIsCommand = vfn!((this: *const ConCommandBase) -> bool);
         unsafe {
-            let vtable = *(self.vtable as *const *const usize);
-            let func_ptr = vtable.add(1).read();
+            let vtable = self.vtable as *const usize;
+            let func_ptr = vtable.add(vslot(1, Some(0))).read();
             let is_command_fn: FnIsCommand = std::mem::transmute(func_ptr);
             is_command_fn(self)
         }
@@ -123,6 +131,27 @@ impl ConCommandBase {
         self.is_registered
     }
 
+    /// Returns the `CvarFlags` bitmask of the command or variable.
+    pub fn get_flags(&self) -> CvarFlags {
+        CvarFlags::from_bits_truncate(self.flags)
+    }
+
+    /// Returns the variable this is the base of, or `None` for a command.
+    pub fn as_var(&mut self) -> Option<&mut ConVar> {
+        match self.is_command() {
+            true => None,
+            false => Some(unsafe { &mut *(self as *mut ConCommandBase as *mut ConVar) }),
+        }
+    }
+
+    /// Returns the command this is the base of, or `None` for a variable.
+    pub fn as_command(&mut self) -> Option<&mut ConCommand> {
+        match self.is_command() {
+            true => Some(unsafe { &mut *(self as *mut ConCommandBase as *mut ConCommand) }),
+            false => None,
+        }
+    }
+
     /// Returns the name of the command or variable as a string slice (`&str`).
     pub fn get_name(&self) -> &str {
         if self.name.is_null() {
@@ -239,39 +268,60 @@ impl CCommand {
     }
 }
 
-// Opaque type for the `this` pointer.
-#[repr(C)] pub(crate) struct RawICvar { _private: [u8; 0] }
+/// Iterator over every registered command and variable, returned by [`ICvar::iter`].
+pub struct ConCommandBaseIter<'a> {
+    current: *mut ConCommandBase,
+    _list: PhantomData<&'a mut ConCommandBase>,
+}
 
-/// Represents an instance of the `ICvar` interface (the central console subsystem in Portal 2).
-///
-/// Provides methods for finding existing console variables (`ConVar`) and commands (`ConCommand`),
-/// registering new custom variables/commands, and printing messages directly to the developer console (`~`).
-///
-/// # Example
-///
-/// ```rust,no_run
-/// use portal2_sdk::{Color, con_print, con_color_print};
-///
-/// let engine = portal2_sdk::get_engine();
-/// let cvar_system = engine.cvar_system();
-///
-/// // Find and modify an existing engine cvar:
-/// if let Some(cheats) = cvar_system.find_var("sv_cheats") {
-///     cheats.set_value_int(1);
-/// }
-///
-/// // Print directly to the developer console:
-/// cvar_system.console_print("Hello from Rust SDK!\n");
-/// cvar_system.console_color_print(Color::rgb(0, 255, 0), "[SUCCESS] Mod initialized!\n");
-/// ```
-pub struct ICvar {
-    pub(crate) this: *mut RawICvar,
-    pub(crate) find_var: FnFindVar,
-    pub(crate) find_command_base: FnFindCommandBase,
-    pub(crate) register_con_command: FnRegisterConCommand,
-    pub(crate) unregister_con_command: FnUnregisterConCommand,
-    pub(crate) console_color_printf: FnConsoleColorPrintf,
-    pub(crate) console_printf: FnConsolePrintf,
+impl<'a> Iterator for ConCommandBaseIter<'a> {
+    type Item = &'a mut ConCommandBase;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let base = unsafe { self.current.as_mut()? };
+        self.current = base.next;
+        Some(base)
+    }
+}
+
+/// Offset of `CCvar::m_pConCommandList`, the head of the registered command list.
+const COMMAND_LIST_OFFSET: usize = 0x30;
+
+interface! {
+    /// Represents an instance of the `ICvar` interface (the central console subsystem in Portal 2).
+    ///
+    /// Provides methods for finding existing console variables (`ConVar`) and commands (`ConCommand`),
+    /// registering new custom variables/commands, and printing messages directly to the developer console (`~`).
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use portal2_sdk::{Color, con_print, con_color_print};
+    ///
+    /// let engine = portal2_sdk::get_engine();
+    /// let cvar_system = engine.cvar_system();
+    ///
+    /// // Find and modify an existing engine cvar:
+    /// if let Some(cheats) = cvar_system.find_var("sv_cheats") {
+    ///     cheats.set_value_int(1);
+    /// }
+    ///
+    /// // Print directly to the developer console:
+    /// cvar_system.console_print("Hello from Rust SDK!\n");
+    /// cvar_system.console_color_print(Color::rgb(0, 255, 0), "[SUCCESS] Mod initialized!\n");
+    /// ```
+    pub struct ICvar: "ICvar" {
+        fn find_var(var_name: *const c_char) -> *mut ConVar = sig "ICvar::FindVar";
+        fn find_command_base(name: *const c_char) -> *mut ConCommandBase = 13;
+        fn register_con_command(base: *mut ConCommandBase) = 9;
+        fn unregister_con_command(base: *mut ConCommandBase) = 10;
+        fn install_global_change_callback(callback: FnChangeCallback) = 19;
+        fn remove_global_change_callback(callback: FnChangeCallback) = 20;
+        fn install_console_display_func(display: *mut c_void) = 22;
+        fn remove_console_display_func(display: *mut c_void) = 23;
+        fn console_color_printf(color: *const Color, format: *const c_char, ...) = 24;
+        fn console_printf(format: *const c_char, ...) = 25;
+    }
 }
 
 impl ICvar {
@@ -320,6 +370,50 @@ impl ICvar {
         }
     }
 
+    /// Returns the head of the engine's list of every registered command and variable.
+    ///
+    /// Follow the rest of the list with [`ConCommandBase::get_next`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # let cvar_system = portal2_sdk::get_engine().cvar_system();
+    /// let mut names = Vec::new();
+    /// let mut current = cvar_system.first_command_base();
+    /// while let Some(base) = current {
+    ///     names.push(base.get_name().to_string());
+    ///     current = base.get_next();
+    /// }
+    /// ```
+    pub fn first_command_base<'a>(&self) -> Option<&'a mut ConCommandBase> {
+        unsafe {
+            let head = (self.this as *const u8).add(COMMAND_LIST_OFFSET) as *const *mut ConCommandBase;
+            head.read().as_mut()
+        }
+    }
+
+    /// Iterates over every registered command and variable, hidden and development-only
+    /// ones included, in the engine's registration order.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use portal2_sdk::{CvarFlags, con_print};
+    /// # let cvar_system = portal2_sdk::get_engine().cvar_system();
+    /// for base in cvar_system.iter().filter(|b| b.get_flags().contains(CvarFlags::DEVELOPMENTONLY)) {
+    ///     if let Some(var) = base.as_var() {
+    ///         con_print!("{} = {} (default {})\n", var.get_name(), var.get_string(), var.get_default());
+    ///     }
+    /// }
+    /// ```
+    pub fn iter<'a>(&self) -> ConCommandBaseIter<'a> {
+        let current = match self.first_command_base() {
+            Some(base) => base as *mut ConCommandBase,
+            None => std::ptr::null_mut(),
+        };
+        ConCommandBaseIter { current, _list: PhantomData }
+    }
+
     /// Registers a custom `ConCommandBase` (`ConVar` or `ConCommand`) into the engine's `ICvar` registry.
     ///
     /// Note: Generally you should prefer using high-level builders `ConVar::builder()` or `ConCommand::builder()`
@@ -375,3 +469,42 @@ impl ICvar {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base(name: &'static CStr, next: *mut ConCommandBase) -> ConCommandBase {
+        ConCommandBase {
+            vtable: std::ptr::null(),
+            next,
+            is_registered: true,
+            _pad0: [0; 3],
+            name: name.as_ptr(),
+            help_string: std::ptr::null(),
+            flags: CvarFlags::CHEAT.bits(),
+        }
+    }
+
+    #[test]
+    fn iterates_the_linked_list() {
+        let mut last = base(c"last", std::ptr::null_mut());
+        let mut first = base(c"first", &mut last);
+
+        let iter = ConCommandBaseIter { current: &mut first, _list: PhantomData };
+        let names: Vec<_> = iter.map(|b| (b.get_name().to_string(), b.get_flags())).collect();
+        assert_eq!(names, [("first".to_string(), CvarFlags::CHEAT), ("last".to_string(), CvarFlags::CHEAT)]);
+
+        let mut empty = ConCommandBaseIter { current: std::ptr::null_mut(), _list: PhantomData };
+        assert!(empty.next().is_none());
+    }
+
+    #[cfg(target_pointer_width = "32")]
+    #[test]
+    fn convar_matches_the_engine_layout() {
+        assert_eq!(std::mem::offset_of!(ConVar, iconvar_vtable), 0x18);
+        assert_eq!(std::mem::offset_of!(ConVar, max_val), 0x40);
+        assert_eq!(std::mem::offset_of!(ConVar, change_callbacks), 0x44);
+        assert_eq!(size_of::<ConVar>(), 0x58);
+    }
+}
@@ -1,29 +1,19 @@
 use std::ffi::{c_char, c_int, CString};
 use crate::types::{Vector, QAngle};
-use crate::platform::abi::vfn;
-
-// Opaque type for the `this` pointer.
-#[repr(C)] pub(crate) struct RawIVDebugOverlay { _private: [u8; 0] }
-
-type FnAddBoxOverlay = vfn!((this: *mut RawIVDebugOverlay, origin: *const Vector, mins: *const Vector, max: *const Vector, orientation: *const QAngle, r: c_int, g: c_int, b: c_int, a: c_int, duration: f32));
-type FnAddSphereOverlay = vfn!((this: *mut RawIVDebugOverlay, origin: *const Vector, radius: f32, theta: c_int, phi: c_int, r: c_int, g: c_int, b: c_int, a: c_int, duration: f32));
-type FnAddLineOverlay = vfn!((this: *mut RawIVDebugOverlay, start: *const Vector, end: *const Vector, r: c_int, g: c_int, b: c_int, no_depth_test: bool, duration: f32));
-type FnAddTextOverlay = unsafe extern "C" fn(this: *mut RawIVDebugOverlay, origin: *const Vector, duration: f32, format: *const c_char, ...);
-type FnAddScreenTextOverlay = vfn!((this: *mut RawIVDebugOverlay, x: f32, y: f32, duration: f32, r: c_int, g: c_int, b: c_int, a: c_int, text: *const c_char));
-type FnScreenPosition = vfn!((this: *mut RawIVDebugOverlay, point: *const Vector, screen: *mut Vector) -> c_int);
-type FnClearAllOverlays = vfn!((this: *mut RawIVDebugOverlay));
-
-/// Interface for drawing debug shapes and text in the 3D world.
-pub struct IVDebugOverlay {
-    pub(crate) this: *mut RawIVDebugOverlay,
-
-    pub(crate) add_box_overlay: FnAddBoxOverlay,
-    pub(crate) add_sphere_overlay: FnAddSphereOverlay,
-    pub(crate) add_line_overlay: FnAddLineOverlay,
-    pub(crate) add_text_overlay: FnAddTextOverlay,
-    pub(crate) add_screen_text_overlay: FnAddScreenTextOverlay,
-    pub(crate) screen_position: FnScreenPosition,
-    pub(crate) clear_all_overlays: FnClearAllOverlays,
+use crate::interface::interface;
+
+interface! {
+    /// Interface for drawing debug shapes and text in the 3D world.
+    pub struct IVDebugOverlay: "IVDebugOverlay" {
+        fn add_box_overlay(origin: *const Vector, mins: *const Vector, max: *const Vector, orientation: *const QAngle, r: c_int, g: c_int, b: c_int, a: c_int, duration: f32) = 1;
+        fn add_sphere_overlay(origin: *const Vector, radius: f32, theta: c_int, phi: c_int, r: c_int, g: c_int, b: c_int, a: c_int, duration: f32) = 2;
+        fn add_line_overlay(start: *const Vector, end: *const Vector, r: c_int, g: c_int, b: c_int, no_depth_test: bool, duration: f32) = 4;
+        fn add_text_overlay(origin: *const Vector, duration: f32, format: *const c_char, ...) = 5;
+        fn add_screen_text_overlay(x: f32, y: f32, duration: f32, r: c_int, g: c_int, b: c_int, a: c_int, text: *const c_char) = 7;
+        fn screen_position(point: *const Vector, screen: *mut Vector) -> c_int = 12;
+        /// Removes all active overlays.
+        [safe] fn clear_all_overlays() = 16;
+    }
 }
 
 impl IVDebugOverlay {
@@ -66,8 +56,4 @@ impl IVDebugOverlay {
         if result == 0 { Some(screen) } else { None }
     }
 
-    /// Removes all active overlays.
-    pub fn clear_all_overlays(&self) {
-        unsafe { (self.clear_all_overlays)(self.this) }
-    }
 }
@@ -1,23 +1,15 @@
 use std::ffi::{c_int, c_void};
 use crate::types::{Ray_t, Trace_t, TraceFilter, Vector, ICollideable, MaskFlags, CBaseEntity};
-use crate::platform::abi::vfn;
-
-// Opaque type for the `this` pointer.
-#[repr(C)] pub(crate) struct RawIEngineTrace { _private: [u8; 0] }
-
-type FnGetPointContents = vfn!((this: *mut RawIEngineTrace, abs_pos: *const Vector, mask: c_int, entity: *mut *mut c_void) -> c_int);
-type FnClipRayToEntity = vfn!((this: *mut RawIEngineTrace, ray: *const Ray_t, mask: u32, entity: *mut c_void, trace: *mut Trace_t));
-type FnTraceRay = vfn!((this: *mut RawIEngineTrace, ray: *const Ray_t, mask: u32, filter: *mut c_void, trace: *mut Trace_t));
-type FnGetCollideable = vfn!((this: *mut RawIEngineTrace, entity: *mut c_void) -> *mut ICollideable);
-
-/// Interface for ray tracing and collision testing.
-pub struct IEngineTrace {
-    pub(crate) this: *mut RawIEngineTrace,
-
-    pub(crate) get_point_contents: FnGetPointContents,
-    pub(crate) clip_ray_to_entity: FnClipRayToEntity,
-    pub(crate) trace_ray: FnTraceRay,
-    pub(crate) get_collideable: FnGetCollideable,
+use crate::interface::interface;
+
+interface! {
+    /// Interface for ray tracing and collision testing.
+    pub struct IEngineTrace: "IEngineTrace" {
+        fn get_point_contents(abs_pos: *const Vector, mask: c_int, entity: *mut *mut c_void) -> c_int = 0;
+        fn clip_ray_to_entity(ray: *const Ray_t, mask: u32, entity: *mut c_void, trace: *mut Trace_t) = 3;
+        fn trace_ray(ray: *const Ray_t, mask: u32, filter: *mut c_void, trace: *mut Trace_t) = 5;
+        fn get_collideable(entity: *mut c_void) -> *mut ICollideable = 12;
+    }
 }
 
 impl IEngineTrace {
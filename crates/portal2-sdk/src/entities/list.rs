@@ -0,0 +1,158 @@
+//! Slot lookups in the server's entity list.
+//!
+//! `CGlobalEntityList` keeps one `CEntInfo` per handle slot, so a handle resolves
+//! with a single array access and a serial number comparison. No interface hands
+//! the array out:
+//!
+//! - on the Windows build, `IServerTools::get_iserver_entity` indexes it directly,
+//!   and its address is read out of that function's code, at the byte offset
+//!   `IServerTools::ent_ptr_array_ref` of the signature database's vtable section;
+//! - on the Linux build, `server.so` exports `gEntList`, and the array follows its
+//!   vtable pointer.
+//!
+//! Slots are numbered and handles split as in [`crate::types::handle`]. Whatever is
+//! found is checked against the entities `first_entity`/`next_entity` walk before it
+//! is trusted. Until a check passes, lookups fall back to walking the
+//! list.
+
+use std::ffi::{c_char, c_int};
+use std::sync::OnceLock;
+
+use crate::memory::contains_range;
+use crate::platform::{self, Abi, Module};
+use crate::server_tools::IServerTools;
+use crate::types::{CBaseEntity, CBaseHandle, NUM_ENT_ENTRIES};
+
+/// Byte offset of the array's address in the code of `CServerTools::GetIServerEntity`.
+const ENT_PTR_ARRAY_REF: usize = 61;
+/// Entities compared against the array before it is trusted.
+const ENTITIES_CHECKED: usize = 16;
+
+/// One slot of `CBaseEntityList::m_EntPtrArray`. This engine branch keeps the
+/// entity's `string_t` name and class name in it as well.
+#[repr(C)]
+struct EntInfo {
+    entity: *mut CBaseEntity,
+    serial: c_int,
+    _prev: *mut EntInfo,
+    _next: *mut EntInfo,
+    _name: *const c_char,
+    _class_name: *const c_char,
+}
+
+/// The address of the array, once there were entities to check it against.
+/// `None` inside if the check failed.
+static ENT_INFOS: OnceLock<Option<usize>> = OnceLock::new();
+
+/// The entity in `handle`'s slot, if it still has `handle`'s serial number.
+/// `Err(())` if the array could not be found.
+pub(super) fn lookup(tools: &IServerTools, handle: CBaseHandle) -> Result<Option<*mut CBaseEntity>, ()> {
+    let infos = match ENT_INFOS.get() {
+        Some(infos) => *infos,
+        None => match find(tools) {
+            // Nothing to check against yet; try again later.
+            None if tools.first_entity().is_none() => return Err(()),
+            found => {
+                if found.is_none() {
+                    log::warn!("Could not find the server entity list; handles resolve by walking it");
+                }
+                *ENT_INFOS.get_or_init(|| found)
+            }
+        },
+    };
+    let infos = infos.ok_or(())? as *const EntInfo;
+    Ok(unsafe { lookup_in(infos, handle) })
+}
+
+/// # Safety
+/// `infos` must point to `NUM_ENT_ENTRIES` readable slots.
+unsafe fn lookup_in(infos: *const EntInfo, handle: CBaseHandle) -> Option<*mut CBaseEntity> {
+    if !handle.is_valid() || handle.index() >= NUM_ENT_ENTRIES {
+        return None;
+    }
+    let info = unsafe { &*infos.add(handle.index()) };
+    (!info.entity.is_null() && info.serial as u32 == handle.serial()).then_some(info.entity)
+}
+
+/// Locates the array and checks it against the live entities.
+fn find(tools: &IServerTools) -> Option<usize> {
+    let candidate = match Abi::CURRENT {
+        Abi::Msvc => {
+            let offset = crate::get_engine().signatures().vtable_index(IServerTools::NAME, "ent_ptr_array_ref", ENT_PTR_ARRAY_REF);
+            let code = tools.get_iserver_entity as *const u8;
+            let (base, size) = platform::module_range(Module::Server)?;
+            if !contains_range(base, size, code.wrapping_add(offset), 4) {
+                return None;
+            }
+            // Decoded from code, so never read from unless it lies inside `server`.
+            let infos = unsafe { crate::memory::deref_absolute(code, offset) };
+            if !contains_range(base, size, infos, NUM_ENT_ENTRIES * size_of::<EntInfo>()) {
+                return None;
+            }
+            infos
+        }
+        Abi::Itanium => {
+            // Named by the module's own symbol table. It lies in `.bss`, outside the
+            // code `module_range` covers on Linux, so it is not checked against that.
+            let list = platform::find_symbol(Module::Server, c"gEntList") as *const u8;
+            if list.is_null() {
+                return None;
+            }
+            // After `CBaseEntityList`'s vtable pointer.
+            list.wrapping_add(size_of::<usize>())
+        }
+    };
+
+    let mut walked = Vec::new();
+    let mut current = tools.first_entity();
+    while let Some(entity) = current
+        && walked.len() < ENTITIES_CHECKED
+    {
+        walked.push((entity as *mut CBaseEntity, entity.as_server_entity().get_handle()));
+        current = tools.next_entity(entity);
+    }
+    let infos = candidate as *const EntInfo;
+    (!walked.is_empty() && walked.iter().all(|&(entity, handle)| unsafe { lookup_in(infos, handle) } == Some(entity)))
+        .then_some(candidate as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::NUM_SERIAL_NUM_SHIFT_BITS;
+
+    #[cfg(target_pointer_width = "32")]
+    #[test]
+    fn slots_match_the_engine_layout() {
+        assert_eq!(size_of::<EntInfo>(), 24);
+    }
+
+    #[test]
+    fn resolves_slots_by_serial_number() {
+        let mut infos: Vec<EntInfo> = (0..NUM_ENT_ENTRIES)
+            .map(|_| EntInfo {
+                entity: std::ptr::null_mut(),
+                serial: 0,
+                _prev: std::ptr::null_mut(),
+                _next: std::ptr::null_mut(),
+                _name: std::ptr::null(),
+                _class_name: std::ptr::null(),
+            })
+            .collect();
+        let entity = 0x1000 as *mut CBaseEntity;
+        infos[42].entity = entity;
+        infos[42].serial = 7;
+        infos[NUM_ENT_ENTRIES - 1].entity = entity;
+        infos[NUM_ENT_ENTRIES - 1].serial = 3;
+
+        let handle = |serial: u32, index: u32| CBaseHandle((serial << NUM_SERIAL_NUM_SHIFT_BITS) | index);
+        unsafe {
+            assert_eq!(lookup_in(infos.as_ptr(), handle(7, 42)), Some(entity));
+            assert_eq!(lookup_in(infos.as_ptr(), handle(6, 42)), None, "the slot was reused");
+            assert_eq!(lookup_in(infos.as_ptr(), handle(0, 43)), None, "the slot is empty");
+            assert_eq!(lookup_in(infos.as_ptr(), CBaseHandle::INVALID), None);
+            assert_eq!(lookup_in(infos.as_ptr(), handle(3, NUM_ENT_ENTRIES as u32 - 1)), Some(entity));
+            assert_eq!(lookup_in(infos.as_ptr(), handle(3, NUM_ENT_ENTRIES as u32)), None, "past the last slot");
+        }
+    }
+}
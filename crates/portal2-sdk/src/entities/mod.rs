@@ -1,6 +1,12 @@
-use crate::{server_tools::IServerTools, types::{CBaseEntity, Vector}};
+use crate::{server_tools::IServerTools, types::{CBaseEntity, CBaseHandle, Vector}};
 use std::marker::PhantomData;
 
+mod list;
+mod query;
+pub use query::*;
+/// Re-exported for [`EntityQuery::class_regex`] and [`EntityQuery::name_regex`].
+pub use regex::Regex;
+
 /// A convenient wrapper for entity searching and iteration.
 pub struct Entities<'a> {
     tools: &'a IServerTools,
@@ -74,6 +80,28 @@ impl<'a> Entities<'a> {
         None
     }
 
+    /// The entity `handle` refers to, if it still exists. Looks the handle's slot up in
+    /// the server's entity list and compares serial numbers, so it costs the same
+    /// however many entities there are.
+    pub fn get_by_handle(&self, handle: CBaseHandle) -> Option<&CBaseEntity> {
+        self.lookup(handle).map(|ent| unsafe { &*ent })
+    }
+
+    /// Same as [`get_by_handle`](Self::get_by_handle), for changing the entity.
+    pub fn get_by_handle_mut(&mut self, handle: CBaseHandle) -> Option<&mut CBaseEntity> {
+        self.lookup(handle).map(|ent| unsafe { &mut *ent })
+    }
+
+    fn lookup(&self, handle: CBaseHandle) -> Option<*mut CBaseEntity> {
+        match list::lookup(self.tools, handle) {
+            Ok(found) => found,
+            // The list could not be found; walk it instead.
+            Err(()) => self.iter()
+                .find(|ent| ent.as_server_entity().get_handle() == handle)
+                .map(|ent| ent as *mut CBaseEntity),
+        }
+    }
+
     // --------------------------------------------------------------------
     // Standard Rust iterators
     // --------------------------------------------------------------------
@@ -0,0 +1,192 @@
+use regex::Regex;
+
+use super::Entities;
+use crate::types::{CBaseEntity, Vector};
+
+type Predicate<'a> = Box<dyn Fn(&CBaseEntity) -> bool + 'a>;
+
+/// A composable entity search, built by [`Entities::query`].
+///
+/// Filters are checked in the order they were added, so put the cheap and selective
+/// ones (class names) first.
+///
+/// ```no_run
+/// use portal2_sdk::types::Vector;
+///
+/// let engine = portal2_sdk::get_engine();
+/// let player = Vector::new(0.0, 0.0, 64.0);
+/// let nearest_cubes = engine.entities().query()
+///     .class_glob("prop_weighted_*")
+///     .within(player, 512.0)
+///     .where_kv("spawnflags", |flags| flags != "0")
+///     .sort_by_distance(player)
+///     .limit(3);
+///
+/// for cube in nearest_cubes {
+///     println!("{} at {}", cube.get_name(), cube.get_origin());
+/// }
+/// ```
+pub struct EntityQuery<'a> {
+    entities: Entities<'a>,
+    filters: Vec<Predicate<'a>>,
+    sort_from: Option<Vector>,
+    limit: Option<usize>,
+}
+
+impl<'a> Entities<'a> {
+    /// Starts a search over all entities on the server.
+    pub fn query(&self) -> EntityQuery<'a> {
+        EntityQuery { entities: Entities::new(self.tools), filters: Vec::new(), sort_from: None, limit: None }
+    }
+}
+
+impl<'a> EntityQuery<'a> {
+    /// Keeps entities matching `predicate`.
+    pub fn filter(mut self, predicate: impl Fn(&CBaseEntity) -> bool + 'a) -> Self {
+        self.filters.push(Box::new(predicate));
+        self
+    }
+
+    /// Keeps entities whose class name is `classname`, ignoring ASCII case. Unlike
+    /// [`Entities::find_by_classname`], which compares exactly,
+    /// `class("Prop_Weighted_Cube")` also finds `prop_weighted_cube`.
+    pub fn class(self, classname: &str) -> Self {
+        let classname = classname.to_string();
+        self.filter(move |ent| ent.get_classname().eq_ignore_ascii_case(&classname))
+    }
+
+    /// Keeps entities whose class name matches a `*`/`?` wildcard pattern, e.g. `prop_*`.
+    pub fn class_glob(self, pattern: &str) -> Self {
+        let pattern = pattern.to_string();
+        self.filter(move |ent| glob_match(&pattern, &ent.get_classname()))
+    }
+
+    /// Keeps entities whose class name matches `regex`. The match is case-sensitive
+    /// unless the regex says otherwise, e.g. with `(?i)`.
+    pub fn class_regex(self, regex: Regex) -> Self {
+        self.filter(move |ent| regex.is_match(&ent.get_classname()))
+    }
+
+    /// Keeps entities whose target name is `name`, ignoring ASCII case. Unlike
+    /// [`Entities::find_by_name`], which compares exactly, `name("Exit_Door")` also
+    /// finds `exit_door`.
+    pub fn name(self, name: &str) -> Self {
+        let name = name.to_string();
+        self.filter(move |ent| ent.get_name().eq_ignore_ascii_case(&name))
+    }
+
+    /// Keeps entities whose target name matches a `*`/`?` wildcard pattern.
+    pub fn name_glob(self, pattern: &str) -> Self {
+        let pattern = pattern.to_string();
+        self.filter(move |ent| glob_match(&pattern, &ent.get_name()))
+    }
+
+    /// Keeps entities whose target name matches `regex`. The match is case-sensitive
+    /// unless the regex says otherwise, e.g. with `(?i)`.
+    pub fn name_regex(self, regex: Regex) -> Self {
+        self.filter(move |ent| regex.is_match(&ent.get_name()))
+    }
+
+    /// Keeps entities whose origin is at most `radius` units from `center`.
+    pub fn within(self, center: Vector, radius: f32) -> Self {
+        self.filter(move |ent| ent.get_origin().distance(&center) <= radius)
+    }
+
+    /// Keeps entities whose origin is inside the box spanned by `min` and `max`.
+    pub fn in_box(self, min: Vector, max: Vector) -> Self {
+        self.filter(move |ent| {
+            let origin = ent.get_origin();
+            (min.x..=max.x).contains(&origin.x)
+                && (min.y..=max.y).contains(&origin.y)
+                && (min.z..=max.z).contains(&origin.z)
+        })
+    }
+
+    /// Keeps entities whose keyvalue `key` exists and satisfies `predicate`.
+    pub fn where_kv(self, key: &str, predicate: impl Fn(&str) -> bool + 'a) -> Self {
+        let key = key.to_string();
+        self.filter(move |ent| ent.get_key_value(&key).is_some_and(|value| predicate(&value)))
+    }
+
+    /// Yields the nearest entities to `point` first.
+    pub fn sort_by_distance(mut self, point: Vector) -> Self {
+        self.sort_from = Some(point);
+        self
+    }
+
+    /// Yields at most `count` entities.
+    pub fn limit(mut self, count: usize) -> Self {
+        self.limit = Some(count);
+        self
+    }
+
+    /// The first match, or the nearest one when sorting by distance.
+    pub fn first(self) -> Option<&'a mut CBaseEntity> {
+        self.limit(1).into_iter().next()
+    }
+}
+
+impl<'a> IntoIterator for EntityQuery<'a> {
+    type Item = &'a mut CBaseEntity;
+    type IntoIter = Box<dyn Iterator<Item = &'a mut CBaseEntity> + 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let limit = self.limit.unwrap_or(usize::MAX);
+        let filters = self.filters;
+        let matches = self.entities.iter().filter(move |ent| filters.iter().all(|filter| filter(ent)));
+
+        match self.sort_from {
+            None => Box::new(matches.take(limit)),
+            Some(point) => {
+                let mut sorted: Vec<(f32, &'a mut CBaseEntity)> = matches
+                    .map(|ent| (ent.get_origin().distance(&point), ent))
+                    .collect();
+                sorted.sort_by(|a, b| a.0.total_cmp(&b.0));
+                Box::new(sorted.into_iter().map(|(_, ent)| ent).take(limit))
+            }
+        }
+    }
+}
+
+/// Case-insensitive wildcard matching: `*` matches any run of characters, `?` any one.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().map(|c| c.to_ascii_lowercase()).collect();
+    let text: Vec<char> = text.chars().map(|c| c.to_ascii_lowercase()).collect();
+
+    // Greedy matching that backtracks to the last `*` on a mismatch.
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_globs() {
+        assert!(glob_match("prop_*", "prop_weighted_cube"));
+        assert!(glob_match("PROP_*_cube", "prop_weighted_cube"));
+        assert!(glob_match("*button", "prop_floor_button"));
+        assert!(glob_match("trigger_?atapult", "trigger_catapult"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("prop_*", "player"));
+        assert!(!glob_match("prop_?", "prop_"));
+        assert!(!glob_match("*_cube", "prop_weighted_cube_dropper"));
+    }
+}
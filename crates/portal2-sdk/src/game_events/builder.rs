@@ -0,0 +1,110 @@
+use std::ffi::CString;
+use std::ptr::NonNull;
+
+use super::{EventValue, GameEvent, GameEventData, IGameEvent, IGameEventManager2};
+
+/// A new game event being filled in, made with [`IGameEventManager2::create_event`].
+///
+/// Fields are set in place on the engine's event. Firing hands the event to the
+/// engine, which frees it; an event dropped without being fired is freed here.
+pub struct GameEventBuilder<'a> {
+    manager: &'a IGameEventManager2,
+    raw: NonNull<IGameEvent>,
+}
+
+impl<'a> GameEventBuilder<'a> {
+    pub fn set_bool(mut self, key: &str, value: bool) -> Self {
+        let key_c = CString::new(key).unwrap_or_default();
+        unsafe { self.raw.as_mut().set_bool(key_c.as_ptr(), value) };
+        self
+    }
+
+    pub fn set_int(mut self, key: &str, value: i32) -> Self {
+        let key_c = CString::new(key).unwrap_or_default();
+        unsafe { self.raw.as_mut().set_int(key_c.as_ptr(), value) };
+        self
+    }
+
+    pub fn set_uint64(mut self, key: &str, value: u64) -> Self {
+        let key_c = CString::new(key).unwrap_or_default();
+        unsafe { self.raw.as_mut().set_uint64(key_c.as_ptr(), value) };
+        self
+    }
+
+    pub fn set_float(mut self, key: &str, value: f32) -> Self {
+        let key_c = CString::new(key).unwrap_or_default();
+        unsafe { self.raw.as_mut().set_float(key_c.as_ptr(), value) };
+        self
+    }
+
+    pub fn set_string(mut self, key: &str, value: &str) -> Self {
+        let key_c = CString::new(key).unwrap_or_default();
+        let value_c = CString::new(value).unwrap_or_default();
+        unsafe { self.raw.as_mut().set_string(key_c.as_ptr(), value_c.as_ptr()) };
+        self
+    }
+
+    /// Sets a field with the setter matching the value's type.
+    pub fn set(self, key: &str, value: &EventValue) -> Self {
+        match value {
+            EventValue::Bool(v) => self.set_bool(key, *v),
+            EventValue::Int(v) => self.set_int(key, *v),
+            EventValue::Float(v) => self.set_float(key, *v),
+            EventValue::Uint64(v) => self.set_uint64(key, *v),
+            EventValue::String(v) => self.set_string(key, v),
+        }
+    }
+
+    /// Reads the event back, e.g. to [`snapshot`](GameEvent::snapshot) what is about to be fired.
+    pub fn event(&self) -> GameEvent<'_> {
+        GameEvent { raw: unsafe { self.raw.as_ref() } }
+    }
+
+    /// Fires the event to the server's listeners and broadcasts it to the clients.
+    /// Returns whether the engine accepted it.
+    pub fn fire(self) -> bool {
+        let manager = self.manager;
+        unsafe { (manager.fire_event)(manager.this, self.into_raw(), false) }
+    }
+
+    /// Fires the event to the server's listeners only, without networking it.
+    pub fn fire_server_only(self) -> bool {
+        let manager = self.manager;
+        unsafe { (manager.fire_event)(manager.this, self.into_raw(), true) }
+    }
+
+    /// Fires the event to the client's listeners only, e.g. for HUD events.
+    pub fn fire_client_side(self) -> bool {
+        let manager = self.manager;
+        unsafe { (manager.fire_event_client_side)(manager.this, self.into_raw()) }
+    }
+
+    /// Gives up ownership; the engine frees events when they are fired.
+    fn into_raw(self) -> *mut IGameEvent {
+        let raw = self.raw.as_ptr();
+        std::mem::forget(self);
+        raw
+    }
+}
+
+impl Drop for GameEventBuilder<'_> {
+    fn drop(&mut self) {
+        unsafe { (self.manager.free_event)(self.manager.this, self.raw.as_ptr()) }
+    }
+}
+
+impl IGameEventManager2 {
+    /// Creates an event to fill in and fire. The event is created even if nothing
+    /// listens for it; `None` means the engine does not know the event name.
+    pub fn create_event(&self, name: &str) -> Option<GameEventBuilder<'_>> {
+        let name_c = CString::new(name).ok()?;
+        let raw = unsafe { (self.create_event)(self.this, name_c.as_ptr(), true, std::ptr::null_mut()) };
+        Some(GameEventBuilder { manager: self, raw: NonNull::new(raw)? })
+    }
+
+    /// Creates an event holding every field of `data`, e.g. to replay a snapshot.
+    pub fn create_event_from(&self, data: &GameEventData) -> Option<GameEventBuilder<'_>> {
+        let builder = self.create_event(&data.name)?;
+        Some(data.fields.iter().fold(builder, |builder, (key, value)| builder.set(key, value)))
+    }
+}
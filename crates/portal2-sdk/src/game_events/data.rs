@@ -0,0 +1,241 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::types::KeyValuesTree;
+
+/// The type of a game event field, as declared in the `.res` event files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventFieldType {
+    String,
+    Bool,
+    Byte,
+    Short,
+    Long,
+    Float,
+    Uint64,
+    /// Not networked. Also used for unknown type names; read back as a string.
+    Local,
+}
+
+impl EventFieldType {
+    /// Parses a type name from an event file. Unknown names become [`Local`](Self::Local).
+    pub fn from_name(name: &str) -> Self {
+        match name.to_ascii_lowercase().as_str() {
+            "string" => EventFieldType::String,
+            "bool" => EventFieldType::Bool,
+            "byte" => EventFieldType::Byte,
+            "short" => EventFieldType::Short,
+            "long" => EventFieldType::Long,
+            "float" => EventFieldType::Float,
+            "uint64" => EventFieldType::Uint64,
+            _ => EventFieldType::Local,
+        }
+    }
+}
+
+/// The declared fields of one game event, in file order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventSchema {
+    pub name: String,
+    pub fields: Vec<(String, EventFieldType)>,
+}
+
+/// Every known game event, loaded from the engine's event files.
+///
+/// Files are applied in order and a later declaration of an event replaces the
+/// earlier one, the same way the engine lets `modevents.res` override events.
+#[derive(Debug, Clone, Default)]
+pub struct EventSchemas {
+    events: HashMap<String, EventSchema>,
+}
+
+impl EventSchemas {
+    /// The event files the engine reads, in load order.
+    pub const FILES: [&'static str; 3] = [
+        "resource/serverevents.res",
+        "resource/gameevents.res",
+        "resource/modevents.res",
+    ];
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads every file in [`FILES`](Self::FILES) through `read_file`, which returns a
+    /// file's text or `None` if it does not exist. Missing files are skipped.
+    pub fn load<F>(read_file: F) -> Result<Self, String>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let mut schemas = Self::new();
+        for path in Self::FILES {
+            if let Some(text) = read_file(path) {
+                schemas.add_file(&text).map_err(|e| format!("{}: {}", path, e))?;
+            }
+        }
+        Ok(schemas)
+    }
+
+    /// Adds the events declared in the text of one event file, returning how many it held.
+    pub fn add_file(&mut self, text: &str) -> Result<usize, String> {
+        let root = KeyValuesTree::parse(text)?;
+        let mut count = 0;
+        for event in root.children().iter().filter(|e| e.is_section()) {
+            let fields = event.children().iter()
+                .map(|field| (field.name.clone(), EventFieldType::from_name(field.as_str().unwrap_or(""))))
+                .collect();
+            self.events.insert(event.name.clone(), EventSchema { name: event.name.clone(), fields });
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    pub fn get(&self, event_name: &str) -> Option<&EventSchema> {
+        self.events.get(event_name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &EventSchema> {
+        self.events.values()
+    }
+}
+
+/// The value of one field of a [`GameEventData`]. Serializes as the bare value.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum EventValue {
+    Bool(bool),
+    /// `byte`, `short` and `long` fields.
+    Int(i32),
+    Float(f32),
+    Uint64(u64),
+    /// `string` and `local` fields.
+    String(String),
+}
+
+impl fmt::Display for EventValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EventValue::Bool(v) => write!(f, "{}", v),
+            EventValue::Int(v) => write!(f, "{}", v),
+            EventValue::Float(v) => write!(f, "{}", v),
+            EventValue::Uint64(v) => write!(f, "{}", v),
+            EventValue::String(v) => f.write_str(v),
+        }
+    }
+}
+
+/// An owned copy of a game event, safe to keep and to send to other threads.
+///
+/// Made with [`GameEvent::snapshot`](super::GameEvent::snapshot). Events without a
+/// loaded schema only carry their name.
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct GameEventData {
+    pub name: String,
+    pub fields: BTreeMap<String, EventValue>,
+}
+
+impl GameEventData {
+    pub fn new(name: &str) -> Self {
+        Self { name: name.to_string(), fields: BTreeMap::new() }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&EventValue> {
+        self.fields.get(key)
+    }
+
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        match self.get(key)? {
+            EventValue::Bool(v) => Some(*v),
+            EventValue::Int(v) => Some(*v != 0),
+            _ => None,
+        }
+    }
+
+    pub fn get_int(&self, key: &str) -> Option<i32> {
+        match self.get(key)? {
+            EventValue::Int(v) => Some(*v),
+            EventValue::Bool(v) => Some(*v as i32),
+            _ => None,
+        }
+    }
+
+    pub fn get_float(&self, key: &str) -> Option<f32> {
+        match self.get(key)? {
+            EventValue::Float(v) => Some(*v),
+            EventValue::Int(v) => Some(*v as f32),
+            _ => None,
+        }
+    }
+
+    pub fn get_uint64(&self, key: &str) -> Option<u64> {
+        match self.get(key)? {
+            EventValue::Uint64(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        match self.get(key)? {
+            EventValue::String(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GAME_EVENTS: &str = r#"
+        "gameevents"
+        {
+            "portal_fired"
+            {
+                "userid"     "short"
+                "leftportal" "bool"
+            }
+            "server_spawn"
+            {
+                "hostname"  "string"
+                "maxplayers" "byte"
+            }
+        }
+    "#;
+
+    #[test]
+    fn parses_event_files_in_order() {
+        let mod_events = r#""modevents" { "server_spawn" { "mapname" "string" "cookie" "uint64" "note" "local" } }"#;
+        let schemas = EventSchemas::load(|path| match path {
+            "resource/gameevents.res" => Some(GAME_EVENTS.to_string()),
+            "resource/modevents.res" => Some(mod_events.to_string()),
+            _ => None,
+        }).unwrap();
+
+        assert_eq!(schemas.len(), 2);
+        let fired = schemas.get("portal_fired").unwrap();
+        assert_eq!(fired.fields, vec![
+            ("userid".to_string(), EventFieldType::Short),
+            ("leftportal".to_string(), EventFieldType::Bool),
+        ]);
+        // modevents.res replaces the whole declaration.
+        let spawn = schemas.get("server_spawn").unwrap();
+        assert_eq!(spawn.fields, vec![
+            ("mapname".to_string(), EventFieldType::String),
+            ("cookie".to_string(), EventFieldType::Uint64),
+            ("note".to_string(), EventFieldType::Local),
+        ]);
+
+        let err = EventSchemas::load(|_| Some("\"gameevents\" {".to_string())).unwrap_err();
+        assert!(err.starts_with("resource/serverevents.res: "), "{}", err);
+    }
+}
@@ -0,0 +1,417 @@
+use std::collections::HashMap;
+use std::ffi::{c_char, c_int, c_void, CStr};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{OnceLock, RwLock};
+use crate::interface::interface;
+use crate::platform::abi::{vfn, vfn_impl, DTOR_SLOTS};
+
+mod builder;
+mod data;
+pub use builder::*;
+pub use data::*;
+
+/// A unique ID for a registered listener. Use this to unregister later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ListenerId(usize);
+
+/// A safe wrapper around a Source Engine Game Event.
+pub struct GameEvent<'a> {
+    raw: &'a IGameEvent,
+}
+
+impl<'a> GameEvent<'a> {
+    pub fn name(&self) -> &str {
+        unsafe {
+            let name_ptr = self.raw.get_name();
+            if name_ptr.is_null() {
+                return "";
+            }
+            CStr::from_ptr(name_ptr).to_str().unwrap_or("")
+        }
+    }
+
+    pub fn get_bool(&self, key: &str, default: bool) -> bool {
+        let key_c = std::ffi::CString::new(key).unwrap_or_default();
+        unsafe { self.raw.get_bool(key_c.as_ptr(), default) }
+    }
+
+    pub fn get_int(&self, key: &str, default: i32) -> i32 {
+        let key_c = std::ffi::CString::new(key).unwrap_or_default();
+        unsafe { self.raw.get_int(key_c.as_ptr(), default) }
+    }
+
+    pub fn get_uint64(&self, key: &str, default: u64) -> u64 {
+        let key_c = std::ffi::CString::new(key).unwrap_or_default();
+        unsafe { self.raw.get_uint64(key_c.as_ptr(), default) }
+    }
+
+    pub fn get_float(&self, key: &str, default: f32) -> f32 {
+        let key_c = std::ffi::CString::new(key).unwrap_or_default();
+        unsafe { self.raw.get_float(key_c.as_ptr(), default) }
+    }
+
+    pub fn get_string(&self, key: &str, default: &str) -> String {
+        let key_c = std::ffi::CString::new(key).unwrap_or_default();
+        let def_c = std::ffi::CString::new(default).unwrap_or_default();
+        unsafe {
+            let ptr = self.raw.get_string(key_c.as_ptr(), def_c.as_ptr());
+            if ptr.is_null() {
+                return default.to_string();
+            }
+            CStr::from_ptr(ptr).to_string_lossy().into_owned()
+        }
+    }
+
+    /// Copies every field of the event into an owned [`GameEventData`], using the
+    /// schemas installed with [`IGameEventManager2::load_schemas`].
+    pub fn snapshot(&self) -> GameEventData {
+        let schemas = get_schemas().read().unwrap();
+        self.snapshot_with(&schemas)
+    }
+
+    /// Same as [`snapshot`](Self::snapshot), with an explicit set of schemas.
+    pub fn snapshot_with(&self, schemas: &EventSchemas) -> GameEventData {
+        let mut data = GameEventData::new(self.name());
+        let Some(schema) = schemas.get(&data.name) else {
+            return data;
+        };
+
+        for (key, ty) in &schema.fields {
+            let value = match ty {
+                EventFieldType::Bool => EventValue::Bool(self.get_bool(key, false)),
+                EventFieldType::Byte | EventFieldType::Short | EventFieldType::Long => EventValue::Int(self.get_int(key, 0)),
+                EventFieldType::Float => EventValue::Float(self.get_float(key, 0.0)),
+                EventFieldType::Uint64 => EventValue::Uint64(self.get_uint64(key, 0)),
+                EventFieldType::String | EventFieldType::Local => EventValue::String(self.get_string(key, "")),
+            };
+            data.fields.insert(key.clone(), value);
+        }
+        data
+    }
+}
+
+// --- INTERNAL BRIDGE ---
+
+/// A closure type for game event callbacks.
+pub type EventCallback = Box<dyn Fn(&GameEvent) + Send + Sync + 'static>;
+
+/// Registry stores callbacks with their IDs indexed by event name.
+static EVENT_REGISTRY: OnceLock<RwLock<HashMap<String, Vec<(ListenerId, EventCallback)>>>> = OnceLock::new();
+static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// The event schemas used by [`GameEvent::snapshot`].
+static EVENT_SCHEMAS: OnceLock<RwLock<EventSchemas>> = OnceLock::new();
+
+fn get_schemas() -> &'static RwLock<EventSchemas> {
+    EVENT_SCHEMAS.get_or_init(|| RwLock::new(EventSchemas::new()))
+}
+
+fn get_registry() -> &'static RwLock<HashMap<String, Vec<(ListenerId, EventCallback)>>> {
+    EVENT_REGISTRY.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+#[repr(C)]
+pub struct MasterListener {
+    vtable: *const IGameEventListener2Vtable,
+}
+
+static MASTER_LISTENER_VTABLE: IGameEventListener2Vtable = IGameEventListener2Vtable {
+    destructor: [master_listener_destructor; DTOR_SLOTS],
+    fire_game_event: master_listener_fire_game_event,
+    get_event_debug_id: master_listener_get_debug_id,
+};
+
+vfn_impl! {
+    fn master_listener_fire_game_event(_this: *mut c_void, event: *mut IGameEvent) {
+        if event.is_null() { return; }
+        let safe_event = GameEvent { raw: unsafe { &*event } };
+        let event_name = safe_event.name();
+
+        // Dispatch ONLY to listeners interested in THIS specific event
+        if let Ok(registry) = get_registry().read() {
+            if let Some(callbacks) = registry.get(event_name) {
+                for (_, callback) in callbacks {
+                    (callback)(&safe_event);
+                }
+            }
+        }
+    }
+
+    fn master_listener_destructor(_this: *mut c_void, _flags: i32) {}
+
+    fn master_listener_get_debug_id(_this: *mut c_void) -> i32 { 42 }
+}
+
+// FFI STRUCTURES
+
+#[repr(C)]
+pub(crate) struct IGameEvent {
+    vtable: *const IGameEventVtable,
+}
+
+#[allow(unsafe_op_in_unsafe_fn)]
+impl IGameEvent {
+    unsafe fn get_name(&self) -> *const c_char { ((*self.vtable).get_name)(self as *const _ as *mut _) }
+    unsafe fn get_bool(&self, key: *const c_char, def: bool) -> bool { ((*self.vtable).get_bool)(self as *const _ as *mut _, key, def) }
+    unsafe fn get_int(&self, key: *const c_char, def: i32) -> i32 { ((*self.vtable).get_int)(self as *const _ as *mut _, key, def) }
+    unsafe fn get_uint64(&self, key: *const c_char, def: u64) -> u64 { ((*self.vtable).get_uint64)(self as *const _ as *mut _, key, def) }
+    unsafe fn get_float(&self, key: *const c_char, def: f32) -> f32 { ((*self.vtable).get_float)(self as *const _ as *mut _, key, def) }
+    unsafe fn get_string(&self, key: *const c_char, def: *const c_char) -> *const c_char { ((*self.vtable).get_string)(self as *const _ as *mut _, key, def) }
+    unsafe fn set_bool(&mut self, key: *const c_char, val: bool) { ((*self.vtable).set_bool)(self as *mut _ as *mut _, key, val) }
+    unsafe fn set_int(&mut self, key: *const c_char, val: i32) { ((*self.vtable).set_int)(self as *mut _ as *mut _, key, val) }
+    unsafe fn set_uint64(&mut self, key: *const c_char, val: u64) { ((*self.vtable).set_uint64)(self as *mut _ as *mut _, key, val) }
+    unsafe fn set_float(&mut self, key: *const c_char, val: f32) { ((*self.vtable).set_float)(self as *mut _ as *mut _, key, val) }
+    unsafe fn set_string(&mut self, key: *const c_char, val: *const c_char) { ((*self.vtable).set_string)(self as *mut _ as *mut _, key, val) }
+}
+
+#[repr(C)]
+struct IGameEventVtable {
+    destructor: [vfn!((this: *mut c_void, flags: i32)); DTOR_SLOTS],
+    get_name: vfn!((this: *mut c_void) -> *const c_char),
+    is_reliable: vfn!((this: *mut c_void) -> bool),
+    is_local: vfn!((this: *mut c_void) -> bool),
+    is_empty: vfn!((this: *mut c_void, key_name: *const c_char) -> bool),
+    get_bool: vfn!((this: *mut c_void, key_name: *const c_char, default_val: bool) -> bool),
+    get_int: vfn!((this: *mut c_void, key_name: *const c_char, default_val: i32) -> i32),
+    get_uint64: vfn!((this: *mut c_void, key_name: *const c_char, default_val: u64) -> u64),
+    get_float: vfn!((this: *mut c_void, key_name: *const c_char, default_val: f32) -> f32),
+    get_string: vfn!((this: *mut c_void, key_name: *const c_char, default_val: *const c_char) -> *const c_char),
+    set_bool: vfn!((this: *mut c_void, key_name: *const c_char, val: bool)),
+    set_int: vfn!((this: *mut c_void, key_name: *const c_char, val: i32)),
+    set_uint64: vfn!((this: *mut c_void, key_name: *const c_char, val: u64)),
+    set_float: vfn!((this: *mut c_void, key_name: *const c_char, val: f32)),
+    set_string: vfn!((this: *mut c_void, key_name: *const c_char, val: *const c_char)),
+}
+
+interface! {
+    pub struct IGameEventManager2: "IGameEventManager2" {
+        destructor = 0;
+        // The listener instance. Injected during initialization.
+        let listener: *mut MasterListener = create_master_listener();
+
+        fn add_listener(listener: *mut c_void, name: *const c_char, server_side: bool) -> bool = 3;
+        fn remove_listener(listener: *mut c_void) = 5;
+        fn create_event(name: *const c_char, force: bool, cookie: *mut c_int) -> *mut IGameEvent = 6;
+        fn fire_event(event: *mut IGameEvent, dont_broadcast: bool) -> bool = 7;
+        fn fire_event_client_side(event: *mut IGameEvent) -> bool = 8;
+        fn free_event(event: *mut IGameEvent) = 10;
+    }
+}
+
+impl IGameEventManager2 {
+    /// Registers a closure and returns a ListenerId.
+    pub fn listen<F>(&self, event_name: &str, callback: F) -> ListenerId
+    where
+        F: Fn(&GameEvent) + Send + Sync + 'static
+    {
+        let mut registry = get_registry().write().unwrap();
+
+        // If this is the first time we listen to this event, tell the engine
+        if !registry.contains_key(event_name) {
+            let name_c = std::ffi::CString::new(event_name).unwrap();
+            unsafe {
+                (self.add_listener)(self.this, self.listener as *mut c_void, name_c.as_ptr(), true);
+            }
+        }
+
+        let id = ListenerId(NEXT_ID.fetch_add(1, Ordering::SeqCst));
+        registry.entry(event_name.to_string()).or_default().push((id, Box::new(callback)));
+        id
+    }
+
+    /// Loads the event schemas from the engine's event files and installs them for
+    /// [`GameEvent::snapshot`]. `read_file` returns a file's text from the game file
+    /// system, or `None` if it does not exist. Returns the number of known events.
+    pub fn load_schemas<F>(&self, read_file: F) -> Result<usize, String>
+    where
+        F: Fn(&str) -> Option<String>
+    {
+        let schemas = EventSchemas::load(read_file)?;
+        let count = schemas.len();
+        *get_schemas().write().unwrap() = schemas;
+        Ok(count)
+    }
+
+    /// A copy of every loaded event schema.
+    pub fn schemas(&self) -> EventSchemas {
+        get_schemas().read().unwrap().clone()
+    }
+
+    /// The schema of an event, if one was loaded.
+    pub fn schema(&self, event_name: &str) -> Option<EventSchema> {
+        get_schemas().read().unwrap().get(event_name).cloned()
+    }
+
+    /// Removes a specific listener by its ID.
+    pub fn unlisten(&self, id: ListenerId) {
+        let mut registry = get_registry().write().unwrap();
+        for listeners in registry.values_mut() {
+            listeners.retain(|(lid, _)| *lid != id);
+        }
+    }
+
+    /// Removes all Rust listeners for a specific event name.
+    pub fn unlisten_all(&self, event_name: &str) {
+        if let Ok(mut registry) = get_registry().write() {
+            registry.remove(event_name);
+        }
+    }
+
+    /// Completely unregisters the listener from the engine.
+    pub fn shutdown_all_listeners(&self) { // TODO: Use this only when unloading the entire overlay system
+        unsafe {
+            (self.remove_listener)(self.this, self.listener as *mut c_void);
+        }
+        if let Ok(mut registry) = get_registry().write() {
+            registry.clear();
+        }
+    }
+}
+
+#[repr(C)]
+struct IGameEventListener2Vtable {
+    /// One slot on MSVC, the complete/deleting pair on Itanium. The listener is
+    /// never destroyed by the engine, so both can share the same no-op.
+    pub destructor: [vfn!((this: *mut c_void, flags: i32)); DTOR_SLOTS],
+    pub fire_game_event: vfn!((this: *mut c_void, event: *mut IGameEvent)),
+    pub get_event_debug_id: vfn!((this: *mut c_void) -> i32),
+}
+
+pub fn create_master_listener() -> *mut MasterListener {
+    Box::into_raw(Box::new(MasterListener {
+        vtable: &MASTER_LISTENER_VTABLE,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::Abi;
+    use crate::signatures::SignatureDatabase;
+
+    vfn_impl! {
+        fn fake_destructor(_this: *mut c_void, _flags: i32) {}
+        fn fake_name(_this: *mut c_void) -> *const c_char { c"portal_fired".as_ptr() }
+        fn fake_flag(_this: *mut c_void) -> bool { false }
+        fn fake_is_empty(_this: *mut c_void, _key: *const c_char) -> bool { false }
+        fn fake_get_bool(_this: *mut c_void, key: *const c_char, def: bool) -> bool {
+            if unsafe { CStr::from_ptr(key) } == c"leftportal" { true } else { def }
+        }
+        fn fake_get_int(_this: *mut c_void, key: *const c_char, def: i32) -> i32 {
+            if unsafe { CStr::from_ptr(key) } == c"userid" { 7 } else { def }
+        }
+        fn fake_get_uint64(_this: *mut c_void, _key: *const c_char, def: u64) -> u64 { def }
+        fn fake_get_float(_this: *mut c_void, _key: *const c_char, def: f32) -> f32 { def }
+        fn fake_get_string(_this: *mut c_void, _key: *const c_char, _def: *const c_char) -> *const c_char { c"blue".as_ptr() }
+        fn fake_set_bool(_this: *mut c_void, key: *const c_char, val: bool) { record(key, val) }
+        fn fake_set_int(_this: *mut c_void, key: *const c_char, val: i32) { record(key, val) }
+        fn fake_set_uint64(_this: *mut c_void, key: *const c_char, val: u64) { record(key, val) }
+        fn fake_set_float(_this: *mut c_void, key: *const c_char, val: f32) { record(key, val) }
+        fn fake_set_string(_this: *mut c_void, key: *const c_char, val: *const c_char) {
+            record(key, unsafe { CStr::from_ptr(val) }.to_str().unwrap())
+        }
+
+        fn fake_create_event(_this: *mut c_void, _name: *const c_char, _force: bool, _cookie: *mut c_int) -> *mut IGameEvent {
+            Box::into_raw(Box::new(IGameEvent { vtable: &FAKE_EVENT_VTABLE }))
+        }
+        fn fake_fire_event(_this: *mut c_void, event: *mut IGameEvent, dont_broadcast: bool) -> bool {
+            CALLS.with_borrow_mut(|calls| calls.push(format!("fire broadcast={}", !dont_broadcast)));
+            free(event);
+            true
+        }
+        fn fake_fire_event_client_side(_this: *mut c_void, event: *mut IGameEvent) -> bool {
+            CALLS.with_borrow_mut(|calls| calls.push("fire client".to_string()));
+            free(event);
+            true
+        }
+        fn fake_free_event(_this: *mut c_void, event: *mut IGameEvent) { free(event) }
+    }
+
+    fn free(event: *mut IGameEvent) {
+        drop(unsafe { Box::from_raw(event) });
+        CALLS.with_borrow_mut(|calls| calls.push("free".to_string()));
+    }
+
+    thread_local! {
+        /// What the fake event and manager were asked to do, in order.
+        static CALLS: std::cell::RefCell<Vec<String>> = const { std::cell::RefCell::new(Vec::new()) };
+    }
+
+    fn record(key: *const c_char, value: impl std::fmt::Display) {
+        let key = unsafe { CStr::from_ptr(key) }.to_str().unwrap();
+        CALLS.with_borrow_mut(|calls| calls.push(format!("{}={}", key, value)));
+    }
+
+    static FAKE_EVENT_VTABLE: IGameEventVtable = IGameEventVtable {
+        destructor: [fake_destructor; DTOR_SLOTS],
+        get_name: fake_name,
+        is_reliable: fake_flag,
+        is_local: fake_flag,
+        is_empty: fake_is_empty,
+        get_bool: fake_get_bool,
+        get_int: fake_get_int,
+        get_uint64: fake_get_uint64,
+        get_float: fake_get_float,
+        get_string: fake_get_string,
+        set_bool: fake_set_bool,
+        set_int: fake_set_int,
+        set_uint64: fake_set_uint64,
+        set_float: fake_set_float,
+        set_string: fake_set_string,
+    };
+
+    #[test]
+    fn snapshots_fields_by_schema() {
+        let raw = IGameEvent { vtable: &FAKE_EVENT_VTABLE };
+        let event = GameEvent { raw: &raw };
+
+        let mut schemas = EventSchemas::new();
+        schemas.add_file(r#""gameevents" { "portal_fired" { "userid" "short" "leftportal" "bool" "color" "string" } }"#).unwrap();
+        let data = event.snapshot_with(&schemas);
+        assert_eq!(data.name, "portal_fired");
+        assert_eq!(data.get_int("userid"), Some(7));
+        assert_eq!(data.get_bool("leftportal"), Some(true));
+        assert_eq!(data.get_str("color"), Some("blue"));
+
+        // Unknown events keep only their name.
+        assert!(event.snapshot_with(&EventSchemas::new()).fields.is_empty());
+    }
+
+    #[test]
+    fn builds_fires_and_frees_events() {
+        let db = SignatureDatabase::builtin();
+        let mut vtable = [std::ptr::null::<c_void>(); 12];
+        let fakes = [
+            ("create_event", fake_create_event as *const c_void),
+            ("fire_event", fake_fire_event as *const c_void),
+            ("fire_event_client_side", fake_fire_event_client_side as *const c_void),
+            ("free_event", fake_free_event as *const c_void),
+        ];
+        for method in IGameEventManager2::METHODS {
+            let slot = method.index(Abi::CURRENT).unwrap();
+            vtable[slot] = fakes.iter().find(|(name, _)| *name == method.name).map_or(fake_flag as *const c_void, |(_, f)| *f);
+        }
+        let mut object = vtable.as_ptr();
+        let manager = unsafe { IGameEventManager2::resolve(&mut object as *mut _ as *mut c_void, &db) }.unwrap();
+
+        let fired = manager.create_event("puzzle_solved").unwrap()
+            .set_bool("force", true)
+            .set_int("chamber", 19)
+            .set_string("by", "overlay")
+            .fire();
+        assert!(fired);
+        manager.create_event("hud_hint").unwrap().set_float("time", 1.5).fire_client_side();
+        drop(manager.create_event("unused").unwrap());
+
+        let mut data = GameEventData::new("replayed");
+        data.fields.insert("cookie".to_string(), EventValue::Uint64(7));
+        manager.create_event_from(&data).unwrap().fire_server_only();
+
+        assert_eq!(CALLS.take(), [
+            "force=true", "chamber=19", "by=overlay", "fire broadcast=true", "free",
+            "time=1.5", "fire client", "free",
+            "free",
+            "cookie=7", "fire broadcast=false", "free",
+        ]);
+    }
+}
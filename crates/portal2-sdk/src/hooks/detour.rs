@@ -0,0 +1,313 @@
+//! Inline detours, for engine functions that are called directly rather than
+//! through a vtable - most of what the signature database resolves.
+//!
+//! The first instructions of the target are moved into a trampoline (see
+//! [`x86::relocate`]) and replaced with a `jmp` to the detour. Calling the
+//! trampoline runs the moved instructions and continues in the original function,
+//! which is what [`InlineHook::original`] hands out.
+
+use std::ffi::c_void;
+use std::marker::PhantomData;
+use std::sync::Mutex;
+
+use super::x86::{self, JMP_LEN};
+use crate::platform;
+use crate::signatures::SignatureDatabase;
+
+/// How much of the target is read to pick the instructions to move. The longest
+/// x86 instruction is 15 bytes, so this always covers the ones overlapping the jump.
+const MAX_STOLEN: usize = JMP_LEN + 15;
+
+/// Room for the moved instructions, every short branch among them widened, and the
+/// jump back.
+const TRAMPOLINE_SIZE: usize = 64;
+
+/// The bookkeeping for one detoured function.
+struct Record {
+    /// Tells this detour apart from a later one of the same function.
+    id: usize,
+    name: &'static str,
+    target: usize,
+    /// What the function started with before we touched it.
+    original: Vec<u8>,
+    /// What it starts with while the detour is enabled.
+    patch: Vec<u8>,
+    enabled: bool,
+}
+
+static DETOURS: Mutex<Vec<Record>> = Mutex::new(Vec::new());
+
+/// Executable memory trampolines are carved from, as `(next, end)`.
+static POOL: Mutex<(usize, usize)> = Mutex::new((0, 0));
+
+/// Hands out `TRAMPOLINE_SIZE` bytes of executable memory. Trampolines are never
+/// freed: a thread may still be running one after its hook is gone.
+fn alloc_trampoline() -> Result<*mut u8, String> {
+    const CHUNK: usize = 64 * 1024;
+
+    let mut pool = POOL.lock().unwrap();
+    if pool.1 - pool.0 < TRAMPOLINE_SIZE {
+        let chunk = platform::alloc_executable(CHUNK)? as usize;
+        *pool = (chunk, chunk + CHUNK);
+    }
+    let trampoline = pool.0;
+    pool.0 += TRAMPOLINE_SIZE;
+    Ok(trampoline as *mut u8)
+}
+
+/// A detoured function, holding a callable original as an `F`.
+///
+/// `F` is the function's pointer type. The hook is created disabled; dropping the
+/// handle removes it.
+pub struct InlineHook<F> {
+    id: usize,
+    name: &'static str,
+    trampoline: usize,
+    _fn: PhantomData<F>,
+}
+
+unsafe impl<F> Send for InlineHook<F> {}
+unsafe impl<F> Sync for InlineHook<F> {}
+
+impl<F: Copy> InlineHook<F> {
+    /// Prepares a detour of the function at `target` to `detour`. `name` is only used
+    /// in messages. Call [`enable`](Self::enable) to put it in place.
+    ///
+    /// Fails if the function is already detoured through this module, or if its
+    /// first instructions cannot be moved.
+    ///
+    /// # Safety
+    ///
+    /// `target` must be the start of a function of type `F`, and nothing may branch
+    /// into its first five bytes from elsewhere.
+    pub unsafe fn new(name: &'static str, target: *const c_void, detour: F) -> Result<Self, String> {
+        const { assert!(size_of::<F>() == size_of::<usize>(), "F must be a function pointer") };
+        if target.is_null() {
+            return Err(format!("{}: the target is null", name));
+        }
+
+        let target = target as usize;
+        let detour = unsafe { std::mem::transmute_copy::<F, usize>(&detour) };
+
+        let mut detours = DETOURS.lock().unwrap();
+        if let Some(other) = detours.iter().find(|r| r.target == target) {
+            return Err(format!("{}: the function is already detoured as {}", name, other.name));
+        }
+
+        let code = unsafe { std::slice::from_raw_parts(target as *const u8, MAX_STOLEN) };
+        let trampoline = alloc_trampoline()? as usize;
+        let (moved, stolen) = x86::relocate(code, target, trampoline, JMP_LEN).map_err(|e| format!("{}: {}", name, e))?;
+        if moved.len() > TRAMPOLINE_SIZE {
+            return Err(format!("{}: the trampoline needs {} bytes", name, moved.len()));
+        }
+        unsafe { std::ptr::copy_nonoverlapping(moved.as_ptr(), trampoline as *mut u8, moved.len()) };
+
+        // Whatever is left of the last moved instruction becomes padding.
+        let mut patch = x86::jmp(target, detour).to_vec();
+        patch.resize(stolen, 0x90);
+
+        let id = super::next_id();
+        detours.push(Record { id, name, target, original: code[..stolen].to_vec(), patch, enabled: false });
+        log::debug!("Prepared detour {} ({:#x} -> {:#x}, {} bytes moved)", name, target, detour, stolen);
+
+        Ok(Self { id, name, trampoline, _fn: PhantomData })
+    }
+
+    /// Like [`new`](Self::new), for the function behind the signature database entry
+    /// `entry`, which also names the hook.
+    ///
+    /// # Safety
+    ///
+    /// As [`new`](Self::new).
+    pub unsafe fn from_signature(signatures: &SignatureDatabase, entry: &'static str, detour: F) -> Result<Self, String> {
+        let target = signatures.resolve(entry)?;
+        unsafe { Self::new(entry, target as *const c_void, detour) }
+    }
+
+    /// Calls into the original function, detoured or not.
+    pub fn original(&self) -> F {
+        unsafe { std::mem::transmute_copy::<usize, F>(&self.trampoline) }
+    }
+}
+
+impl<F> InlineHook<F> {
+    /// The name the hook was created with.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Puts the jump to the detour in place.
+    ///
+    /// Fails if the function no longer starts the way it did when the hook was
+    /// created, i.e. someone else has patched it since.
+    pub fn enable(&self) -> Result<(), String> {
+        self.set_enabled(true)
+    }
+
+    /// Restores the function's original first bytes, keeping the hook around to be
+    /// enabled again.
+    pub fn disable(&self) -> Result<(), String> {
+        self.set_enabled(false)
+    }
+
+    /// Whether the jump to the detour is currently in place.
+    pub fn is_enabled(&self) -> bool {
+        let detours = DETOURS.lock().unwrap();
+        detours.iter().any(|r| r.id == self.id && r.enabled)
+    }
+
+    /// Disables the hook for good. Equivalent to dropping the handle, but reports
+    /// what happened.
+    pub fn unhook(self) -> Result<(), String> {
+        let result = remove(self.id);
+        std::mem::forget(self);
+        result
+    }
+
+    fn set_enabled(&self, enabled: bool) -> Result<(), String> {
+        let mut detours = DETOURS.lock().unwrap();
+        let record = detours
+            .iter_mut()
+            .find(|r| r.id == self.id)
+            .ok_or_else(|| format!("{}: the hook has been removed", self.name))?;
+        unsafe { set(record, enabled) }
+    }
+}
+
+impl<F> Drop for InlineHook<F> {
+    fn drop(&mut self) {
+        if let Err(e) = remove(self.id) {
+            log::warn!("{}", e);
+        }
+    }
+}
+
+/// Patches or restores the function `record` describes.
+///
+/// # Safety
+///
+/// `record` must come from [`InlineHook::new`].
+unsafe fn set(record: &mut Record, enabled: bool) -> Result<(), String> {
+    if record.enabled == enabled {
+        return Ok(());
+    }
+
+    let (expected, wanted) = match enabled {
+        true => (&record.original, &record.patch),
+        false => (&record.patch, &record.original),
+    };
+    let current = unsafe { std::slice::from_raw_parts(record.target as *const u8, expected.len()) };
+    if current != expected.as_slice() {
+        return Err(format!("{}: the function was patched by someone else, leaving it in place", record.name));
+    }
+
+    unsafe { platform::write_code(record.target as *mut u8, wanted) }.map_err(|e| format!("{}: {}", record.name, e))?;
+    record.enabled = enabled;
+    log::debug!("{} {}", if enabled { "Enabled" } else { "Disabled" }, record.name);
+    Ok(())
+}
+
+/// Disables and forgets the detour `id`.
+fn remove(id: usize) -> Result<(), String> {
+    let mut detours = DETOURS.lock().unwrap();
+    let Some(position) = detours.iter().position(|r| r.id == id) else {
+        return Ok(()); // Already undone by `uninstall_all`.
+    };
+    let mut record = detours.swap_remove(position);
+    unsafe { set(&mut record, false) }
+}
+
+/// Disables and forgets every detour. Returns the number of functions restored.
+pub(super) fn remove_all() -> usize {
+    let records = std::mem::take(&mut *DETOURS.lock().unwrap());
+    records
+        .into_iter()
+        .filter(|r| r.enabled)
+        .filter_map(|mut record| match unsafe { set(&mut record, false) } {
+            Ok(()) => Some(()),
+            Err(e) => {
+                log::warn!("{}", e);
+                None
+            }
+        })
+        .count()
+}
+
+#[cfg(all(test, any(target_arch = "x86", target_arch = "x86_64")))]
+mod tests {
+    use super::*;
+
+    type FnValue = unsafe extern "C" fn() -> i32;
+
+    /// `mov eax, value; ret`, which encodes the same in 32- and 64-bit mode, placed
+    /// in the trampoline pool so that every jump between the pieces stays in range.
+    fn returning(value: i32) -> FnValue {
+        let code = alloc_trampoline().unwrap();
+        let mut bytes = vec![0xB8];
+        bytes.extend_from_slice(&value.to_le_bytes());
+        bytes.push(0xC3);
+        bytes.resize(MAX_STOLEN, 0xCC);
+        unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), code, bytes.len()) };
+        unsafe { std::mem::transmute::<*mut u8, FnValue>(code) }
+    }
+
+    // Records are global; `uninstall_all` must not interleave with the other tests.
+    static SERIAL: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn detours_and_calls_through() {
+        let _guard = SERIAL.lock().unwrap();
+        let (target, detour) = (returning(1), returning(100));
+
+        let hook = unsafe { InlineHook::new("target", target as *const c_void, detour) }.unwrap();
+        assert!(!hook.is_enabled());
+        assert_eq!(unsafe { target() }, 1);
+
+        hook.enable().unwrap();
+        assert!(hook.is_enabled());
+        assert_eq!(unsafe { target() }, 100);
+        assert_eq!(unsafe { (hook.original())() }, 1);
+
+        hook.disable().unwrap();
+        assert_eq!(unsafe { target() }, 1);
+        hook.enable().unwrap();
+        hook.unhook().unwrap();
+        assert_eq!(unsafe { target() }, 1);
+    }
+
+    #[test]
+    fn refuses_a_second_detour_and_foreign_patches() {
+        let _guard = SERIAL.lock().unwrap();
+        let (target, detour) = (returning(1), returning(100));
+
+        let hook = unsafe { InlineHook::new("target", target as *const c_void, detour) }.unwrap();
+        let err = unsafe { InlineHook::new("again", target as *const c_void, detour) }.err().unwrap();
+        assert!(err.contains("already detoured as target"), "{}", err);
+
+        // Someone else rewrites the function's first byte.
+        unsafe { (target as *mut u8).write(0x90) };
+        assert!(hook.enable().unwrap_err().contains("someone else"));
+    }
+
+    #[test]
+    fn uninstall_all_disables_every_detour() {
+        let _guard = SERIAL.lock().unwrap();
+        let (target, detour) = (returning(1), returning(100));
+
+        let hook = unsafe { InlineHook::new("target", target as *const c_void, detour) }.unwrap();
+        hook.enable().unwrap();
+        assert_eq!(remove_all(), 1);
+        assert_eq!(unsafe { target() }, 1);
+        assert!(!hook.is_enabled());
+        assert!(hook.enable().unwrap_err().contains("removed"));
+
+        // A new detour of the same function is not the old handle's to touch.
+        let fresh = unsafe { InlineHook::new("target again", target as *const c_void, detour) }.unwrap();
+        fresh.enable().unwrap();
+        assert!(!hook.is_enabled() && fresh.is_enabled());
+        hook.unhook().unwrap();
+        assert_eq!(unsafe { target() }, 100);
+        drop(fresh);
+        assert_eq!(unsafe { target() }, 1);
+    }
+}
@@ -0,0 +1,345 @@
+//! Hooks on engine functions: vtable hooks for interface methods, and inline
+//! detours ([`InlineHook`]) for functions that are called directly.
+//!
+//! A [`VtableHook`] swaps one slot of an object's vtable for a detour and keeps the
+//! original function, typed, so the detour can call through to it:
+//!
+//! ```ignore
+//! use std::ffi::{c_char, c_void};
+//! use std::sync::OnceLock;
+//! use portal2_sdk::hooks::VtableHook;
+//!
+//! type FnClientCmd = unsafe extern "thiscall" fn(this: *mut c_void, cmd: *const c_char);
+//! static CLIENT_CMD: OnceLock<VtableHook<FnClientCmd>> = OnceLock::new();
+//!
+//! unsafe extern "thiscall" fn client_cmd(this: *mut c_void, cmd: *const c_char) {
+//!     log::info!("ClientCmd: {:?}", unsafe { std::ffi::CStr::from_ptr(cmd) });
+//!     unsafe { (CLIENT_CMD.get().unwrap().original())(this, cmd) }
+//! }
+//!
+//! # let engine_client: *mut c_void = std::ptr::null_mut();
+//! let hook = unsafe { VtableHook::install("IVEngineClient::ClientCmd", engine_client, 7, client_cmd as FnClientCmd) };
+//! let _ = CLIENT_CMD.set(hook.expect("failed to hook ClientCmd"));
+//! ```
+//!
+//! Every installed hook is also recorded in a process-wide registry, so that
+//! [`uninstall_all`] can put every vtable and function back when the overlay unloads, even for
+//! hooks whose handles live in statics and are never dropped.
+//!
+//! Vtables are shared by every object of a class, so hooking one object hooks them
+//! all. Another mod may hook the same slot after us; in that case the slot is left
+//! alone on uninstall, since writing the original back would silently remove their
+//! hook too, and the detour stays reachable through their chain.
+
+use std::ffi::c_void;
+use std::marker::PhantomData;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::platform;
+
+mod detour;
+mod x86;
+
+pub use detour::InlineHook;
+
+/// The bookkeeping for one patched slot.
+#[derive(Debug, Clone, Copy)]
+struct Record {
+    /// Tells this hook apart from a later one on the same slot, once
+    /// [`uninstall_all`] has forgotten it.
+    id: usize,
+    name: &'static str,
+    slot: usize,
+    original: usize,
+    detour: usize,
+}
+
+static HOOKS: Mutex<Vec<Record>> = Mutex::new(Vec::new());
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+
+/// A new id for a vtable hook or a detour.
+fn next_id() -> usize {
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// The state of a hook's vtable slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookStatus {
+    /// The slot points at our detour.
+    Active,
+    /// Something else has been written over the slot since we patched it.
+    Overwritten,
+    /// The hook has been uninstalled.
+    Removed,
+}
+
+/// A hooked vtable slot, holding the original function as an `F`.
+///
+/// `F` is the slot's function pointer type, e.g. `unsafe extern "thiscall" fn(*mut c_void) -> bool`
+/// for the Windows build.
+/// Dropping the handle uninstalls the hook.
+pub struct VtableHook<F> {
+    record: Record,
+    _fn: PhantomData<F>,
+}
+
+// The handle is plain data; the slot it refers to is only ever touched under `HOOKS`.
+unsafe impl<F> Send for VtableHook<F> {}
+unsafe impl<F> Sync for VtableHook<F> {}
+
+impl<F: Copy> VtableHook<F> {
+    /// Points slot `index` of `object`'s vtable at `detour`. `name` is only used in
+    /// log messages and [`installed`].
+    ///
+    /// Fails if the slot is already hooked through this module.
+    ///
+    /// # Safety
+    ///
+    /// `object` must point to a live object whose vtable has at least `index + 1`
+    /// slots, and `F` must be the type of that slot's function.
+    pub unsafe fn install(name: &'static str, object: *mut c_void, index: usize, detour: F) -> Result<Self, String> {
+        const { assert!(size_of::<F>() == size_of::<usize>(), "F must be a function pointer") };
+        if object.is_null() {
+            return Err(format!("{}: the object pointer is null", name));
+        }
+
+        let slot = unsafe { (*(object as *const *mut usize)).add(index) };
+        let detour = unsafe { std::mem::transmute_copy::<F, usize>(&detour) };
+
+        let mut hooks = HOOKS.lock().unwrap();
+        if let Some(other) = hooks.iter().find(|r| r.slot == slot as usize) {
+            return Err(format!("{}: the slot is already hooked as {}", name, other.name));
+        }
+
+        let original = unsafe { slot.read() };
+        unsafe { platform::write_protected(slot, detour) }.map_err(|e| format!("{}: {}", name, e))?;
+
+        let record = Record { id: next_id(), name, slot: slot as usize, original, detour };
+        hooks.push(record);
+        log::debug!("Hooked {} ({:#x} -> {:#x})", name, original, detour);
+
+        Ok(Self { record, _fn: PhantomData })
+    }
+
+    /// The function the slot held before it was hooked.
+    pub fn original(&self) -> F {
+        unsafe { std::mem::transmute_copy::<usize, F>(&self.record.original) }
+    }
+}
+
+impl<F> VtableHook<F> {
+    /// The name the hook was installed with.
+    pub fn name(&self) -> &'static str {
+        self.record.name
+    }
+
+    /// The current state of the hook's slot.
+    pub fn status(&self) -> HookStatus {
+        status_of(&HOOKS.lock().unwrap(), &self.record)
+    }
+
+    /// Restores the original function. Equivalent to dropping the handle, but
+    /// reports what happened.
+    pub fn uninstall(self) -> Result<(), String> {
+        let result = remove(&self.record);
+        std::mem::forget(self);
+        result
+    }
+}
+
+impl<F> Drop for VtableHook<F> {
+    fn drop(&mut self) {
+        if let Err(e) = remove(&self.record) {
+            log::warn!("{}", e);
+        }
+    }
+}
+
+fn status_of(hooks: &[Record], record: &Record) -> HookStatus {
+    if !hooks.iter().any(|r| r.id == record.id) {
+        return HookStatus::Removed;
+    }
+    match unsafe { (record.slot as *const usize).read() } == record.detour {
+        true => HookStatus::Active,
+        false => HookStatus::Overwritten,
+    }
+}
+
+/// Takes `record` out of the registry and restores its slot.
+fn remove(record: &Record) -> Result<(), String> {
+    let mut hooks = HOOKS.lock().unwrap();
+    let Some(position) = hooks.iter().position(|r| r.id == record.id) else {
+        return Ok(()); // Already undone by `uninstall_all`.
+    };
+    hooks.swap_remove(position);
+    unsafe { restore(record) }
+}
+
+/// Writes the original function back, unless someone else has patched the slot since.
+///
+/// # Safety
+///
+/// `record` must describe a slot hooked by [`VtableHook::install`] and not yet restored.
+unsafe fn restore(record: &Record) -> Result<(), String> {
+    let current = unsafe { (record.slot as *const usize).read() };
+    if current != record.detour {
+        return Err(format!(
+            "{}: the slot was re-patched to {:#x} by someone else, leaving it in place",
+            record.name, current
+        ));
+    }
+
+    unsafe { platform::write_protected(record.slot as *mut usize, record.original) }
+        .map_err(|e| format!("{}: {}", record.name, e))?;
+    log::debug!("Unhooked {}", record.name);
+    Ok(())
+}
+
+/// Removes every installed hook, vtable hooks and inline detours alike, restoring
+/// the original functions. Handles that are still alive become inert. Returns the
+/// number of slots and functions restored.
+///
+/// Called when the overlay unloads; afterwards the detours must not be reachable
+/// from the engine any more.
+pub fn uninstall_all() -> usize {
+    let records = std::mem::take(&mut *HOOKS.lock().unwrap());
+    let restored = records
+        .iter()
+        .filter(|record| match unsafe { restore(record) } {
+            Ok(()) => true,
+            Err(e) => {
+                log::warn!("{}", e);
+                false
+            }
+        })
+        .count();
+    restored + detour::remove_all()
+}
+
+/// Every vtable hook currently installed, with the state of its slot.
+pub fn installed() -> Vec<(&'static str, HookStatus)> {
+    let hooks = HOOKS.lock().unwrap();
+    hooks.iter().map(|r| (r.name, status_of(&hooks, r))).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::c_int;
+
+    use super::*;
+    use crate::platform::abi::{vfn, vfn_impl};
+
+    type FnValue = vfn!((this: *mut c_void) -> c_int);
+
+    vfn_impl! {
+        fn one(_this: *mut c_void) -> c_int { 1 }
+        fn two(_this: *mut c_void) -> c_int { 2 }
+        fn detour(_this: *mut c_void) -> c_int { 100 }
+        fn intruder(_this: *mut c_void) -> c_int { 666 }
+    }
+
+    /// An object whose vtable is `[one, two]`.
+    struct Fake {
+        vtable: Box<[FnValue; 2]>,
+        object: Box<*const FnValue>,
+    }
+
+    impl Fake {
+        fn new() -> Self {
+            let vtable = Box::new([one as FnValue, two as FnValue]);
+            let object = Box::new(vtable.as_ptr());
+            Self { vtable, object }
+        }
+
+        fn this(&mut self) -> *mut c_void {
+            &mut *self.object as *mut *const FnValue as *mut c_void
+        }
+
+        fn call(&mut self, index: usize) -> c_int {
+            let this = self.this();
+            unsafe { (self.vtable[index])(this) }
+        }
+    }
+
+    // The registry is global; tests touching `uninstall_all` must not interleave with others.
+    static SERIAL: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn hooks_and_restores_a_slot() {
+        let _guard = SERIAL.lock().unwrap();
+        let mut fake = Fake::new();
+
+        let hook = unsafe { VtableHook::install("Fake::two", fake.this(), 1, detour as FnValue) }.unwrap();
+        assert_eq!(fake.call(1), 100);
+        assert_eq!(fake.call(0), 1);
+        assert_eq!(unsafe { (hook.original())(fake.this()) }, 2);
+        assert_eq!(hook.status(), HookStatus::Active);
+
+        drop(hook);
+        assert_eq!(fake.call(1), 2);
+        assert!(installed().iter().all(|(name, _)| *name != "Fake::two"));
+    }
+
+    #[test]
+    fn refuses_to_hook_a_slot_twice() {
+        let _guard = SERIAL.lock().unwrap();
+        let mut fake = Fake::new();
+
+        let _hook = unsafe { VtableHook::install("Fake::one", fake.this(), 0, detour as FnValue) }.unwrap();
+        let err = unsafe { VtableHook::install("Fake::one again", fake.this(), 0, detour as FnValue) }.err().unwrap();
+        assert!(err.contains("already hooked as Fake::one"), "{}", err);
+    }
+
+    #[test]
+    fn leaves_a_re_patched_slot_alone() {
+        let _guard = SERIAL.lock().unwrap();
+        let mut fake = Fake::new();
+
+        let hook = unsafe { VtableHook::install("Fake::two", fake.this(), 1, detour as FnValue) }.unwrap();
+        fake.vtable[1] = intruder;
+        assert_eq!(hook.status(), HookStatus::Overwritten);
+
+        let err = hook.uninstall().unwrap_err();
+        assert!(err.contains("re-patched"), "{}", err);
+        assert_eq!(fake.call(1), 666);
+    }
+
+    #[test]
+    fn uninstall_all_restores_every_slot() {
+        let _guard = SERIAL.lock().unwrap();
+        let mut fake = Fake::new();
+
+        let first = unsafe { VtableHook::install("Fake::one", fake.this(), 0, detour as FnValue) }.unwrap();
+        let second = unsafe { VtableHook::install("Fake::two", fake.this(), 1, detour as FnValue) }.unwrap();
+        assert_eq!((fake.call(0), fake.call(1)), (100, 100));
+
+        assert_eq!(uninstall_all(), 2);
+        assert_eq!((fake.call(0), fake.call(1)), (1, 2));
+        assert_eq!(first.status(), HookStatus::Removed);
+
+        // The handles are inert now: dropping them writes nothing.
+        fake.vtable[1] = intruder;
+        drop((first, second));
+        assert_eq!(fake.call(1), 666);
+    }
+
+    #[test]
+    fn stale_handles_leave_a_new_hook_on_the_slot_alone() {
+        let _guard = SERIAL.lock().unwrap();
+        let mut fake = Fake::new();
+
+        let stale = unsafe { VtableHook::install("Fake::one", fake.this(), 0, detour as FnValue) }.unwrap();
+        uninstall_all();
+        let fresh = unsafe { VtableHook::install("Fake::one again", fake.this(), 0, intruder as FnValue) }.unwrap();
+
+        assert_eq!(stale.status(), HookStatus::Removed);
+        stale.uninstall().unwrap();
+        assert_eq!(fresh.status(), HookStatus::Active);
+        assert_eq!(fake.call(0), 666);
+
+        drop(fresh);
+        assert_eq!(fake.call(0), 1);
+    }
+}
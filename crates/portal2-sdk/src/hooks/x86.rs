@@ -0,0 +1,419 @@
+//! A length disassembler for 32-bit x86, and the relocation of the instructions an
+//! inline detour displaces.
+//!
+//! Detouring a function overwrites its first five bytes with a `jmp`. Whatever
+//! instructions those bytes belonged to must still run when the original is called,
+//! so they are copied into a trampoline, followed by a jump back into the function.
+//! Doing that needs two things: where each instruction ends, and which of them
+//! encode a target relative to their own address, since those break when moved.
+//!
+//! Only lengths are decoded, not meaning. The tables cover the general-purpose,
+//! x87 and SSE instructions a 32-bit compiler emits; anything else is reported as
+//! undecodable rather than guessed at.
+
+/// What an instruction does to control flow, as far as relocation is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    /// Falls through to the next instruction, with nothing position-dependent.
+    Plain,
+    /// `jmp`, `call` or `jcc` to a target relative to the end of the instruction.
+    /// `offset` is where the displacement starts, `size` its width in bytes.
+    Relative { offset: usize, size: usize },
+    /// A relative branch that has no 32-bit form (`loop`, `jecxz`).
+    ShortOnly,
+    /// `ret`, `jmp` through a register or memory, or a trap: control never falls through.
+    Exit,
+}
+
+/// One decoded instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction {
+    pub len: usize,
+    pub flow: Flow,
+}
+
+/// How many immediate bytes follow an opcode.
+#[derive(Clone, Copy)]
+enum Imm {
+    None,
+    Byte,
+    Word,
+    /// 4 bytes, or 2 under an operand-size prefix.
+    Full,
+    /// `enter`: a word and a byte.
+    Enter,
+    /// A memory offset the size of an address (`mov al, [moffs]`).
+    Address,
+    /// `call far` / `jmp far`: an offset plus a selector.
+    Far,
+}
+
+/// Decodes the instruction at the start of `code`.
+///
+/// Returns `None` if the bytes are not a complete instruction this decoder knows.
+pub fn decode(code: &[u8]) -> Option<Instruction> {
+    let mut pos = 0;
+    let mut operand_16 = false;
+    let mut address_16 = false;
+
+    // Legacy prefixes, in any order and at most a handful.
+    loop {
+        match *code.get(pos)? {
+            0x66 => operand_16 = true,
+            0x67 => address_16 = true,
+            0xF0 | 0xF2 | 0xF3 | 0x2E | 0x36 | 0x3E | 0x26 | 0x64 | 0x65 => {}
+            _ => break,
+        }
+        pos += 1;
+        if pos > 4 {
+            return None;
+        }
+    }
+
+    let opcode = *code.get(pos)?;
+    pos += 1;
+
+    let (has_modrm, mut imm, mut flow) = if opcode == 0x0F {
+        let second = *code.get(pos)?;
+        pos += 1;
+        two_byte(second, code.get(pos).copied())?
+    } else {
+        one_byte(opcode)?
+    };
+
+    // A three-byte opcode's last byte comes before its ModRM.
+    if opcode == 0x0F && matches!(code[pos - 1], 0x38 | 0x3A) {
+        pos += 1;
+    }
+
+    if has_modrm {
+        let modrm = *code.get(pos)?;
+        pos += 1;
+        pos += modrm_extra(modrm, code.get(pos).copied(), address_16)?;
+
+        // `test r/m, imm` hides in the groups that otherwise take no immediate.
+        let reg = (modrm >> 3) & 7;
+        match opcode {
+            0xF6 if reg < 2 => imm = Imm::Byte,
+            0xF7 if reg < 2 => imm = Imm::Full,
+            // `jmp` through memory or a register. `call` (/2, /3) comes back.
+            0xFF if reg == 4 || reg == 5 => flow = Flow::Exit,
+            _ => {}
+        }
+    }
+
+    let imm_len = match imm {
+        Imm::None => 0,
+        Imm::Byte => 1,
+        Imm::Word => 2,
+        Imm::Full if operand_16 => 2,
+        Imm::Full => 4,
+        Imm::Enter => 3,
+        Imm::Address if address_16 => 2,
+        Imm::Address => 4,
+        Imm::Far if operand_16 => 4,
+        Imm::Far => 6,
+    };
+
+    // Relative branches carry their displacement as the immediate.
+    if let Flow::Relative { ref mut offset, ref mut size } = flow {
+        *offset = pos;
+        *size = imm_len;
+    }
+
+    let len = pos + imm_len;
+    (len <= code.len()).then_some(Instruction { len, flow })
+}
+
+/// `(has ModRM, immediate, flow)` of a one-byte opcode.
+fn one_byte(opcode: u8) -> Option<(bool, Imm, Flow)> {
+    let relative = Flow::Relative { offset: 0, size: 0 };
+    Some(match opcode {
+        // add/or/adc/sbb/and/sub/xor/cmp in their r/m, al and eax forms.
+        0x00..=0x3F => match opcode & 7 {
+            0..=3 => (true, Imm::None, Flow::Plain),
+            4 => (false, Imm::Byte, Flow::Plain),
+            5 => (false, Imm::Full, Flow::Plain),
+            // push/pop of segment registers, daa/das/aaa/aas. 0x0F is handled by the
+            // caller, the segment-override prefixes never get here.
+            _ => (false, Imm::None, Flow::Plain),
+        },
+        0x40..=0x61 => (false, Imm::None, Flow::Plain), // inc/dec/push/pop reg, pusha, popa
+        0x62 | 0x63 => (true, Imm::None, Flow::Plain),
+        0x68 => (false, Imm::Full, Flow::Plain),
+        0x69 => (true, Imm::Full, Flow::Plain),
+        0x6A => (false, Imm::Byte, Flow::Plain),
+        0x6B => (true, Imm::Byte, Flow::Plain),
+        0x6C..=0x6F => (false, Imm::None, Flow::Plain), // ins/outs
+        0x70..=0x7F => (false, Imm::Byte, relative),    // jcc rel8
+        0x80 | 0x82 | 0x83 => (true, Imm::Byte, Flow::Plain),
+        0x81 => (true, Imm::Full, Flow::Plain),
+        0x84..=0x8F => (true, Imm::None, Flow::Plain),  // test/xchg/mov/lea/pop r/m
+        0x90..=0x99 => (false, Imm::None, Flow::Plain), // nop, xchg eax, cwde, cdq
+        0x9A => (false, Imm::Far, Flow::Plain),         // call far
+        0x9B..=0x9F => (false, Imm::None, Flow::Plain),
+        0xA0..=0xA3 => (false, Imm::Address, Flow::Plain),
+        0xA4..=0xA7 | 0xAA..=0xAF => (false, Imm::None, Flow::Plain), // string ops
+        0xA8 => (false, Imm::Byte, Flow::Plain),
+        0xA9 => (false, Imm::Full, Flow::Plain),
+        0xB0..=0xB7 => (false, Imm::Byte, Flow::Plain),
+        0xB8..=0xBF => (false, Imm::Full, Flow::Plain),
+        0xC0 | 0xC1 | 0xC6 => (true, Imm::Byte, Flow::Plain),
+        0xC2 | 0xCA => (false, Imm::Word, Flow::Exit), // ret imm16
+        0xC3 | 0xCB => (false, Imm::None, Flow::Exit),
+        0xC4 | 0xC5 => (true, Imm::None, Flow::Plain), // les/lds
+        0xC7 => (true, Imm::Full, Flow::Plain),
+        0xC8 => (false, Imm::Enter, Flow::Plain),
+        0xC9 => (false, Imm::None, Flow::Plain), // leave
+        0xCC => (false, Imm::None, Flow::Exit),  // int3
+        0xCD => (false, Imm::Byte, Flow::Plain),
+        0xCE | 0xCF => (false, Imm::None, Flow::Plain),
+        0xD0..=0xD3 => (true, Imm::None, Flow::Plain),
+        0xD4 | 0xD5 => (false, Imm::Byte, Flow::Plain),
+        0xD7 => (false, Imm::None, Flow::Plain),
+        0xD8..=0xDF => (true, Imm::None, Flow::Plain), // x87
+        0xE0..=0xE3 => (false, Imm::Byte, Flow::ShortOnly),
+        0xE4..=0xE7 => (false, Imm::Byte, Flow::Plain), // in/out imm8
+        0xE8 | 0xE9 => (false, Imm::Full, relative),    // call/jmp rel32
+        0xEA => (false, Imm::Far, Flow::Exit),          // jmp far
+        0xEB => (false, Imm::Byte, relative),           // jmp rel8
+        0xEC..=0xEF => (false, Imm::None, Flow::Plain),
+        0xF1 | 0xF4 | 0xF5 | 0xF8..=0xFD => (false, Imm::None, Flow::Plain),
+        0xF6 | 0xF7 | 0xFE | 0xFF => (true, Imm::None, Flow::Plain),
+        _ => return None,
+    })
+}
+
+/// `(has ModRM, immediate, flow)` of `0F second`, with `third` the byte after it.
+fn two_byte(second: u8, third: Option<u8>) -> Option<(bool, Imm, Flow)> {
+    Some(match second {
+        0x0B => (false, Imm::None, Flow::Exit), // ud2
+        0x05..=0x09 | 0x30..=0x35 | 0x77 | 0xA0..=0xA2 | 0xA8..=0xAA | 0xC8..=0xCF => {
+            (false, Imm::None, Flow::Plain)
+        }
+        0x38 => {
+            third?;
+            (true, Imm::None, Flow::Plain)
+        }
+        0x3A => {
+            third?;
+            (true, Imm::Byte, Flow::Plain)
+        }
+        0x70..=0x73 | 0xA4 | 0xAC | 0xBA | 0xC2 | 0xC4..=0xC6 => (true, Imm::Byte, Flow::Plain),
+        0x80..=0x8F => (false, Imm::Full, Flow::Relative { offset: 0, size: 0 }), // jcc rel32
+        0x00..=0x03 | 0x0D | 0x10..=0x1F | 0x20..=0x23 | 0x28..=0x2F | 0x40..=0x6F | 0x74..=0x76
+        | 0x7C..=0x7F | 0x90..=0x9F | 0xA3 | 0xA5 | 0xAB | 0xAD..=0xAF | 0xB0..=0xB9 | 0xBB..=0xC1
+        | 0xC3 | 0xC7 | 0xD0..=0xFE => (true, Imm::None, Flow::Plain),
+        _ => return None,
+    })
+}
+
+/// Bytes of SIB and displacement after a ModRM byte; `next` is the byte after it.
+fn modrm_extra(modrm: u8, next: Option<u8>, address_16: bool) -> Option<usize> {
+    let mode = modrm >> 6;
+    let rm = modrm & 7;
+
+    if mode == 3 {
+        return Some(0);
+    }
+
+    if address_16 {
+        return Some(match mode {
+            0 if rm == 6 => 2,
+            0 => 0,
+            1 => 1,
+            _ => 2,
+        });
+    }
+
+    let mut extra = 0;
+    let mut base_is_disp32 = mode == 0 && rm == 5;
+    if rm == 4 {
+        extra += 1; // SIB
+        base_is_disp32 = mode == 0 && next? & 7 == 5;
+    }
+
+    extra += match mode {
+        1 => 1,
+        2 => 4,
+        _ if base_is_disp32 => 4,
+        _ => 0,
+    };
+    Some(extra)
+}
+
+/// Size of the `jmp rel32` a detour is patched in with.
+pub const JMP_LEN: usize = 5;
+
+/// Encodes `jmp rel32` from `from` to `to`.
+pub fn jmp(from: usize, to: usize) -> [u8; JMP_LEN] {
+    let rel = to.wrapping_sub(from + JMP_LEN) as u32;
+    let mut bytes = [0xE9, 0, 0, 0, 0];
+    bytes[1..].copy_from_slice(&rel.to_le_bytes());
+    bytes
+}
+
+/// The instructions covering at least `min_len` bytes of `code`, rewritten to run
+/// from `to` instead of `from`, followed by a jump back to where they ended.
+///
+/// Returns the trampoline and the number of bytes of `code` it replaces. Short
+/// branches are widened to their 32-bit forms. Fails on anything that cannot be
+/// moved: undecodable bytes, `loop`-style branches, branches back into the moved
+/// range, or a function that ends before `min_len` bytes.
+pub fn relocate(code: &[u8], from: usize, to: usize, min_len: usize) -> Result<(Vec<u8>, usize), String> {
+    let mut out = Vec::with_capacity(min_len * 2 + JMP_LEN);
+    let mut pos = 0;
+
+    while pos < min_len {
+        let insn = decode(&code[pos..]).ok_or_else(|| {
+            format!("cannot decode the instruction at +{:#x} ({:02X?})", pos, &code[pos..code.len().min(pos + 8)])
+        })?;
+        let bytes = &code[pos..pos + insn.len];
+        let end = from + pos + insn.len;
+
+        match insn.flow {
+            Flow::Plain => out.extend_from_slice(bytes),
+            Flow::Exit if pos + insn.len < min_len => {
+                return Err(format!("the function ends after {} bytes, too short to detour", pos + insn.len));
+            }
+            Flow::Exit => out.extend_from_slice(bytes),
+            Flow::ShortOnly => return Err(format!("cannot relocate the short branch at +{:#x}", pos)),
+            Flow::Relative { offset, size } => {
+                let rel = match size {
+                    1 => bytes[offset] as i8 as isize,
+                    4 => i32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap()) as isize,
+                    _ => return Err(format!("cannot relocate the 16-bit branch at +{:#x}", pos)),
+                };
+                let target = end.wrapping_add_signed(rel);
+                if (from..from + min_len.max(pos + insn.len)).contains(&target) && target != from {
+                    return Err(format!("the branch at +{:#x} jumps into the detoured bytes", pos));
+                }
+
+                // Re-encoded with a 32-bit displacement: E8/E9 keep their opcode,
+                // EB becomes E9, 7x becomes 0F 8x, 0F 8x stays.
+                let opcode: &[u8] = match bytes[offset - 1] {
+                    0xEB => &[0xE9],
+                    op @ 0x70..=0x7F => &[0x0F, op + 0x10],
+                    _ => &bytes[..offset],
+                };
+                out.extend_from_slice(opcode);
+                let new_end = to + out.len() + 4;
+                out.extend_from_slice(&(target.wrapping_sub(new_end) as u32).to_le_bytes());
+            }
+        }
+        pos += insn.len;
+    }
+
+    let back = to + out.len();
+    out.extend_from_slice(&jmp(back, from + pos));
+    Ok((out, pos))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn len(code: &[u8]) -> Option<usize> {
+        decode(code).map(|i| i.len)
+    }
+
+    #[test]
+    fn decodes_common_prologues() {
+        assert_eq!(len(&[0x55]), Some(1)); // push ebp
+        assert_eq!(len(&[0x8B, 0xEC]), Some(2)); // mov ebp, esp
+        assert_eq!(len(&[0x83, 0xEC, 0x20]), Some(3)); // sub esp, 0x20
+        assert_eq!(len(&[0x81, 0xEC, 0x00, 0x01, 0x00, 0x00]), Some(6)); // sub esp, 0x100
+        assert_eq!(len(&[0x8B, 0x45, 0x08]), Some(3)); // mov eax, [ebp+8]
+        assert_eq!(len(&[0x8B, 0x44, 0x24, 0x04]), Some(4)); // mov eax, [esp+4]
+        assert_eq!(len(&[0x8B, 0x0D, 0x40, 0x30, 0x20, 0x10]), Some(6)); // mov ecx, [0x10203040]
+        assert_eq!(len(&[0x8B, 0x04, 0x85, 0x00, 0x10, 0x00, 0x00]), Some(7)); // mov eax, [eax*4+0x1000]
+        assert_eq!(len(&[0xA1, 0x00, 0x10, 0x00, 0x00]), Some(5)); // mov eax, [0x1000]
+        assert_eq!(len(&[0xB8, 0x01, 0x00, 0x00, 0x00]), Some(5)); // mov eax, 1
+        assert_eq!(len(&[0xC7, 0x45, 0xFC, 0x00, 0x00, 0x00, 0x00]), Some(7)); // mov dword [ebp-4], 0
+        assert_eq!(len(&[0x6A, 0xFF]), Some(2)); // push -1
+        assert_eq!(len(&[0x68, 0x00, 0x10, 0x00, 0x00]), Some(5)); // push 0x1000
+    }
+
+    #[test]
+    fn decodes_prefixes_and_two_byte_opcodes() {
+        assert_eq!(len(&[0x66, 0xB8, 0x01, 0x00]), Some(4)); // mov ax, 1
+        assert_eq!(len(&[0x64, 0xA1, 0x00, 0x00, 0x00, 0x00]), Some(6)); // mov eax, fs:[0]
+        assert_eq!(len(&[0x0F, 0xB6, 0x45, 0x08]), Some(4)); // movzx eax, byte [ebp+8]
+        assert_eq!(len(&[0xF3, 0x0F, 0x10, 0x45, 0x08]), Some(5)); // movss xmm0, [ebp+8]
+        assert_eq!(len(&[0x0F, 0x3A, 0x0F, 0xC1, 0x08]), Some(5)); // palignr xmm0, xmm1, 8
+        assert_eq!(len(&[0xD9, 0x45, 0x08]), Some(3)); // fld dword [ebp+8]
+        assert_eq!(len(&[0xF6, 0xC1, 0x01]), Some(3)); // test cl, 1
+        assert_eq!(len(&[0xF7, 0xD8]), Some(2)); // neg eax
+    }
+
+    #[test]
+    fn classifies_control_flow() {
+        assert_eq!(decode(&[0xC3]).unwrap().flow, Flow::Exit);
+        assert_eq!(decode(&[0xC2, 0x08, 0x00]).unwrap(), Instruction { len: 3, flow: Flow::Exit });
+        assert_eq!(decode(&[0xFF, 0xE0]).unwrap().flow, Flow::Exit); // jmp eax
+        assert_eq!(decode(&[0xFF, 0xD0]).unwrap().flow, Flow::Plain); // call eax
+        assert_eq!(decode(&[0x74, 0x05]).unwrap().flow, Flow::Relative { offset: 1, size: 1 });
+        assert_eq!(decode(&[0x0F, 0x84, 0, 0, 0, 0]).unwrap().flow, Flow::Relative { offset: 2, size: 4 });
+        assert_eq!(decode(&[0xE2, 0xFE]).unwrap().flow, Flow::ShortOnly);
+    }
+
+    #[test]
+    fn rejects_truncated_and_unknown_bytes() {
+        assert_eq!(len(&[0x8B]), None);
+        assert_eq!(len(&[0xB8, 0x01, 0x00]), None);
+        assert_eq!(len(&[0xD6]), None);
+        assert_eq!(len(&[]), None);
+    }
+
+    #[test]
+    fn copies_plain_instructions() {
+        // push ebp; mov ebp, esp; sub esp, 0x20; ...
+        let code = [0x55, 0x8B, 0xEC, 0x83, 0xEC, 0x20, 0x90];
+        let (trampoline, stolen) = relocate(&code, 0x1000, 0x8000, JMP_LEN).unwrap();
+        assert_eq!(stolen, 6);
+        assert_eq!(&trampoline[..6], &code[..6]);
+        // jmp 0x1006 from 0x8006
+        assert_eq!(&trampoline[6..], &jmp(0x8006, 0x1006));
+    }
+
+    #[test]
+    fn rebases_relative_calls() {
+        // call 0x2000 (from 0x1000: rel = 0x2000 - 0x1005)
+        let code = [0xE8, 0xFB, 0x0F, 0x00, 0x00, 0x90];
+        let (trampoline, stolen) = relocate(&code, 0x1000, 0x8000, JMP_LEN).unwrap();
+        assert_eq!(stolen, 5);
+        let rel = i32::from_le_bytes(trampoline[1..5].try_into().unwrap());
+        assert_eq!(trampoline[0], 0xE8);
+        assert_eq!(0x8005usize.wrapping_add_signed(rel as isize), 0x2000);
+    }
+
+    #[test]
+    fn widens_short_branches() {
+        // test eax, eax; jz +0x10; nop
+        let code = [0x85, 0xC0, 0x74, 0x10, 0x90, 0x90];
+        let (trampoline, stolen) = relocate(&code, 0x1000, 0x8000, JMP_LEN).unwrap();
+        assert_eq!(stolen, 5);
+        // test eax, eax; jz rel32 to 0x1014; nop; jmp back to 0x1005
+        assert_eq!(&trampoline[..4], &[0x85, 0xC0, 0x0F, 0x84]);
+        let rel = i32::from_le_bytes(trampoline[4..8].try_into().unwrap());
+        assert_eq!(0x8008usize.wrapping_add_signed(rel as isize), 0x1014);
+        assert_eq!(trampoline[8], 0x90);
+        assert_eq!(&trampoline[9..], &jmp(0x8009, 0x1005));
+
+        // jmp short +2 becomes jmp rel32.
+        let (trampoline, _) = relocate(&[0xEB, 0x02, 0x90, 0x90, 0x90, 0x90], 0x1000, 0x8000, 2).unwrap();
+        assert_eq!(trampoline[0], 0xE9);
+    }
+
+    #[test]
+    fn refuses_what_cannot_move() {
+        // ret after one byte.
+        assert!(relocate(&[0x90, 0xC3, 0xCC, 0xCC, 0xCC], 0, 0x100, JMP_LEN).unwrap_err().contains("too short"));
+        // loop to itself.
+        assert!(relocate(&[0xE2, 0xFE, 0x90, 0x90, 0x90], 0, 0x100, JMP_LEN).is_err());
+        // jz into the middle of the stolen bytes.
+        assert!(relocate(&[0x74, 0x01, 0x90, 0x90, 0x90, 0x90], 0, 0x100, JMP_LEN).unwrap_err().contains("into"));
+        // Undecodable.
+        assert!(relocate(&[0xD6, 0x90, 0x90, 0x90, 0x90], 0, 0x100, JMP_LEN).unwrap_err().contains("decode"));
+    }
+}
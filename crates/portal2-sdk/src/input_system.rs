@@ -1,36 +1,22 @@
 
-use crate::platform::abi::vfn;
+use crate::interface::interface;
 
 #[repr(C)]
 pub struct InputContextT { _private: [u8; 0] }
 
-// Opaque type for the `this` pointer.
-#[repr(C)] pub(crate) struct RawIInputStackSystem { _private: [u8; 0] }
-
-// --- Function Pointer Type Definitions ---
-type FnPushInputContext = vfn!((this: *mut RawIInputStackSystem) -> *mut InputContextT);
-type FnEnableInputContext = vfn!((this: *mut RawIInputStackSystem, context: *mut InputContextT, enable: bool));
-type FnSetCursorVisible = vfn!((this: *mut RawIInputStackSystem, context: *mut InputContextT, enable: bool));
-type FnSetMouseCapture = vfn!((this: *mut RawIInputStackSystem, context: *mut InputContextT, enable: bool));
-type FnSetCursorPosition = vfn!((this: *mut RawIInputStackSystem, context: *mut InputContextT, x: i32, y: i32));
-type FnIsTopmostEnabledContext = vfn!((this: *mut RawIInputStackSystem, context: *mut InputContextT) -> bool);
-
-/// Represents an instance of the IInputStackSystem interface.
-pub struct IInputStackSystem {
-    pub(crate) this: *mut RawIInputStackSystem,
-
-    pub(crate) push_input_context: FnPushInputContext,
-    pub(crate) enable_input_context: FnEnableInputContext,
-    pub(crate) set_cursor_visible: FnSetCursorVisible,
-    pub(crate) set_mouse_capture: FnSetMouseCapture,
-    pub(crate) set_cursor_position: FnSetCursorPosition,
-    pub(crate) is_topmost_enabled_context: FnIsTopmostEnabledContext,
+interface! {
+    /// Represents an instance of the IInputStackSystem interface.
+    pub struct IInputStackSystem: "IInputStackSystem" {
+        [safe] fn push_input_context() -> *mut InputContextT = sig "IInputStackSystem::PushInputContext";
+        fn enable_input_context(context: *mut InputContextT, enable: bool) = sig "IInputStackSystem::EnableInputContext";
+        fn set_cursor_visible(context: *mut InputContextT, enable: bool) = sig "IInputStackSystem::SetCursorVisible";
+        fn set_mouse_capture(context: *mut InputContextT, enable: bool) = sig "IInputStackSystem::SetMouseCapture";
+        fn set_cursor_position(context: *mut InputContextT, x: i32, y: i32) = sig "IInputStackSystem::SetCursorPosition";
+        fn is_topmost_enabled_context(context: *mut InputContextT) -> bool = sig "IInputStackSystem::IsTopmostEnabledContext";
+    }
 }
 
 impl IInputStackSystem {
-    pub fn push_input_context(&self) -> *mut InputContextT {
-        unsafe { (self.push_input_context)(self.this) }
-    }
     pub fn enable_input_context(&self, context: *mut InputContextT, enable: bool) {
         unsafe { (self.enable_input_context)(self.this, context, enable) }
     }
@@ -0,0 +1,300 @@
+//! Declarative engine interface definitions.
+//!
+//! Every engine interface the SDK talks to is declared once, with `interface!`, as
+//! the ordered list of the methods it uses and where each of them lives:
+//!
+//! ```ignore
+//! interface! {
+//!     /// Interface from the engine to tools for manipulating entities.
+//!     pub struct IServerTools: "IServerTools" {
+//!         destructor = 0;
+//!
+//!         fn first_entity() -> *mut CBaseEntity = 7;
+//!         /// Whether the game is paused.
+//!         [safe] fn is_paused() -> bool = 81;
+//!         fn overloaded(x: c_int) = 12, itanium 14;
+//!         fn server_cmd(cmd: *const c_char, reliable: bool) = sig "IVEngineClient::ServerCmd";
+//!         fn con_nprintf(pos: c_int, fmt: *const c_char, ...) = 47;
+//!     }
+//! }
+//! ```
+//!
+//! From that it generates:
+//!
+//! - the struct itself: the object's `this` pointer plus one typed function
+//!   pointer per method, declared with the platform's member-function ABI (`vfn!`),
+//!   or `cdecl` for variadic methods;
+//! - `METHODS`, the index table, holding every method's slot under both the MSVC
+//!   and the Itanium vtable layout;
+//! - `resolve`, which fills the struct in from a live object, honouring
+//!   overrides from the [`SignatureDatabase`](crate::SignatureDatabase);
+//! - a forwarding `pub fn` for every method marked `[safe]`. Only mark methods whose
+//!   arguments are plain values the engine cannot misuse.
+//!
+//! Indices are written in MSVC terms. With `destructor = N` (the MSVC slot of the
+//! virtual destructor), the Itanium slots of the methods after it are derived
+//! automatically; anything else the two ABIs disagree on, such as overloads,
+//! which MSVC groups and reverses, takes an explicit `itanium` index. Methods that
+//! cannot be reached by index take `sig` and the name of a signature database
+//! entry instead. Besides methods, a struct can carry extra fields, declared as
+//! `let name: Type = initializer;` before the first method.
+
+use std::ffi::c_void;
+
+use crate::platform::{vtable_index, Abi};
+use crate::signatures::SignatureDatabase;
+
+/// Where the code of an interface method is found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+    /// A slot in the object's vtable, under either ABI.
+    Vtable { msvc: usize, itanium: usize },
+    /// The signature database entry with this name.
+    Signature(&'static str),
+}
+
+/// One entry of an interface's index table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Method {
+    /// The method's name, which is also its key for vtable overrides.
+    pub name: &'static str,
+    pub location: Location,
+}
+
+impl Method {
+    /// A vtable method at `msvc`. The Itanium slot is `itanium` if given, otherwise
+    /// derived from the position of the interface's virtual `destructor`.
+    pub const fn vtable(name: &'static str, msvc: usize, itanium: Option<usize>, destructor: Option<usize>) -> Self {
+        let itanium = match itanium {
+            Some(index) => index,
+            None => vtable_index(Abi::Itanium, msvc, destructor),
+        };
+        Self { name, location: Location::Vtable { msvc, itanium } }
+    }
+
+    /// A method found through the signature database entry `entry`.
+    pub const fn signature(name: &'static str, entry: &'static str) -> Self {
+        Self { name, location: Location::Signature(entry) }
+    }
+
+    /// The method's vtable slot under `abi`, or `None` if it is found by signature.
+    pub const fn index(&self, abi: Abi) -> Option<usize> {
+        match self.location {
+            Location::Vtable { msvc, .. } if matches!(abi, Abi::Msvc) => Some(msvc),
+            Location::Vtable { itanium, .. } => Some(itanium),
+            Location::Signature(_) => None,
+        }
+    }
+}
+
+/// Finds the code of `method` for the object `this` of `interface`.
+///
+/// # Safety
+/// `this` must point to a live object implementing `interface`.
+pub(crate) unsafe fn locate(
+    this: *mut c_void,
+    interface: &str,
+    method: &Method,
+    signatures: &SignatureDatabase,
+) -> Result<*const c_void, String> {
+    if this.is_null() {
+        return Err(format!("{}::{}: the interface pointer is null", interface, method.name));
+    }
+
+    match method.index(Abi::CURRENT) {
+        Some(default) => {
+            let index = signatures.vtable_index(interface, method.name, default);
+            unsafe {
+                let vtable = *(this as *const *const *const c_void);
+                Ok(vtable.add(index).read())
+            }
+        }
+        None => {
+            let Location::Signature(entry) = method.location else { unreachable!() };
+            signatures.resolve(entry).map(|addr| addr as *const c_void)
+        }
+    }
+}
+
+/// Declares an engine interface. See the [module documentation](self).
+macro_rules! interface {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident: $interface:literal {
+            $(destructor = $destructor:literal;)?
+            $(let $field:ident: $field_ty:ty = $field_init:expr;)*
+            $(
+                $(#[$method_meta:meta])*
+                $([$safe:ident])? fn $method:ident($($params:tt)*) $(-> $ret:ty)?
+                    = $($msvc:literal $(, itanium $itanium:literal)?)? $(sig $entry:literal)?;
+            )*
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            pub(crate) this: *mut ::std::ffi::c_void,
+            $(pub(crate) $field: $field_ty,)*
+            $(
+                $(#[$method_meta])*
+                pub(crate) $method: $crate::interface::interface!(@type ($($params)*) $(-> $ret)?),
+            )*
+        }
+
+        impl $name {
+            /// The interface's name in the signature database.
+            pub const NAME: &'static str = $interface;
+
+            /// MSVC vtable slot of the virtual destructor, if the interface has one.
+            pub const DESTRUCTOR: Option<usize> = $crate::interface::interface!(@option $($destructor)?);
+
+            /// Every method the SDK uses, in declaration order, with its location under both ABIs.
+            pub const METHODS: &'static [$crate::interface::Method] = &[
+                $($crate::interface::interface!(@method $method [$($msvc $($itanium)?)?] [$($entry)?]),)*
+            ];
+
+            /// Builds the interface around the object `this`, looking every method
+            /// up through `signatures`.
+            ///
+            /// # Safety
+            /// `this` must point to a live object implementing the interface.
+            pub(crate) unsafe fn resolve(
+                this: *mut ::std::ffi::c_void,
+                signatures: &$crate::signatures::SignatureDatabase,
+            ) -> Result<Self, String> {
+                let mut methods = Self::METHODS.iter();
+                Ok(Self {
+                    this,
+                    $($field: $field_init,)*
+                    $($method: {
+                        let method = methods.next().expect("one table entry per method");
+                        let addr = unsafe { $crate::interface::locate(this, Self::NAME, method, signatures)? };
+                        unsafe {
+                            ::std::mem::transmute::<
+                                *const ::std::ffi::c_void,
+                                $crate::interface::interface!(@type ($($params)*) $(-> $ret)?),
+                            >(addr)
+                        }
+                    },)*
+                })
+            }
+
+            $(
+                $crate::interface::interface!(
+                    @accessor [$($safe)?] [$(#[$method_meta])*] $method ($($params)*) $(-> $ret)?
+                );
+            )*
+        }
+    };
+
+    (@type ($($arg:ident: $arg_ty:ty),*) $(-> $ret:ty)?) => {
+        $crate::platform::abi::vfn!((this: *mut ::std::ffi::c_void, $($arg: $arg_ty),*) $(-> $ret)?)
+    };
+    // C++ variadics are always `cdecl`, `this` included.
+    (@type ($($arg:ident: $arg_ty:ty,)* ...) $(-> $ret:ty)?) => {
+        unsafe extern "C" fn(this: *mut ::std::ffi::c_void, $($arg: $arg_ty,)* ...) $(-> $ret)?
+    };
+
+    (@option) => { None };
+    (@option $value:literal) => { Some($value) };
+
+    (@method $method:ident [$msvc:literal] []) => {
+        $crate::interface::Method::vtable(stringify!($method), $msvc, None, Self::DESTRUCTOR)
+    };
+    (@method $method:ident [$msvc:literal $itanium:literal] []) => {
+        $crate::interface::Method::vtable(stringify!($method), $msvc, Some($itanium), Self::DESTRUCTOR)
+    };
+    (@method $method:ident [] [$entry:literal]) => {
+        $crate::interface::Method::signature(stringify!($method), $entry)
+    };
+
+    (@accessor [] [$(#[$meta:meta])*] $($rest:tt)*) => {};
+    (@accessor [safe] [$(#[$meta:meta])*] $method:ident ($($arg:ident: $arg_ty:ty),*) $(-> $ret:ty)?) => {
+        $(#[$meta])*
+        pub fn $method(&self, $($arg: $arg_ty),*) $(-> $ret)? {
+            unsafe { (self.$method)(self.this, $($arg),*) }
+        }
+    };
+}
+
+pub(crate) use interface;
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::{c_char, c_int};
+    use std::path::Path;
+
+    use super::*;
+
+    interface! {
+        #[allow(dead_code)]
+        struct ITest: "ITest" {
+            destructor = 0;
+            let calls: c_int = 7;
+
+            fn first() -> c_int = 1;
+            fn add(a: c_int, b: c_int) -> c_int = 2;
+            fn overloaded(x: f32) = 4, itanium 3;
+            fn printf(fmt: *const c_char, ...) = 5;
+            fn scanned() = sig "ITest::Scanned";
+        }
+    }
+
+    #[test]
+    fn index_table_covers_both_abis() {
+        assert_eq!(ITest::NAME, "ITest");
+        assert_eq!(ITest::DESTRUCTOR, Some(0));
+        assert_eq!(ITest::METHODS.iter().map(|m| m.name).collect::<Vec<_>>(), ["first", "add", "overloaded", "printf", "scanned"]);
+
+        let slots = |abi| ITest::METHODS.iter().map(|m| m.index(abi)).collect::<Vec<_>>();
+        assert_eq!(slots(Abi::Msvc), [Some(1), Some(2), Some(4), Some(5), None]);
+        assert_eq!(slots(Abi::Itanium), [Some(2), Some(3), Some(3), Some(6), None]);
+        assert_eq!(ITest::METHODS[4].location, Location::Signature("ITest::Scanned"));
+    }
+
+    interface! {
+        struct IPlain: "IPlain" {
+            fn first() -> c_int = 0;
+            [safe] fn add(a: c_int, b: c_int) -> c_int = 2;
+        }
+    }
+
+    crate::platform::abi::vfn_impl! {
+        fn first(_this: *mut c_void) -> c_int { 1 }
+        fn add(_this: *mut c_void, a: c_int, b: c_int) -> c_int { a + b }
+    }
+
+    #[test]
+    fn resolves_methods_from_a_vtable() {
+        let vtable: [*const c_void; 3] = [first as *const c_void, std::ptr::null(), add as *const c_void];
+        let mut object = vtable.as_ptr();
+        let this = &mut object as *mut _ as *mut c_void;
+
+        let plain = unsafe { IPlain::resolve(this, &SignatureDatabase::builtin()) }.unwrap();
+        assert_eq!(unsafe { (plain.first)(plain.this) }, 1);
+        assert_eq!(plain.add(2, 3), 5);
+
+        // A vtable override from the database moves the slot.
+        let mut db = SignatureDatabase::builtin();
+        db.apply_str("version = 1\n[[build]]\n[build.vtables.IPlain]\nfirst = 2\n", Path::new("test.toml"), None).unwrap();
+        let plain = unsafe { IPlain::resolve(this, &db) }.unwrap();
+        assert_eq!(plain.first as *const c_void, add as *const c_void);
+    }
+
+    #[test]
+    fn signature_methods_go_through_the_database() {
+        let vtable = [std::ptr::null::<c_void>(); 8];
+        let mut object = vtable.as_ptr();
+        let this = &mut object as *mut _ as *mut c_void;
+
+        // No engine is loaded here, so the entry cannot resolve, and the error says which one.
+        let err = unsafe { ITest::resolve(this, &SignatureDatabase::builtin()) }.err().unwrap();
+        assert!(err.contains("ITest::Scanned"), "{}", err);
+    }
+
+    #[test]
+    fn rejects_a_null_object() {
+        let db = SignatureDatabase::builtin();
+        let err = unsafe { locate(std::ptr::null_mut(), "ITest", &ITest::METHODS[0], &db) }.unwrap_err();
+        assert!(err.contains("ITest::first"), "{}", err);
+    }
+}
@@ -0,0 +1,54 @@
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use crate::interface::interface;
+
+interface! {
+    /// The allocator and key-name symbol table shared by every `KeyValues` in the game,
+    /// exported by `vstdlib` as `KeyValuesSystem()`.
+    pub struct IKeyValuesSystem: "IKeyValuesSystem" {
+        fn alloc_key_values_memory(size: c_int) -> *mut c_void = 1;
+        fn free_key_values_memory(memory: *mut c_void) = 2;
+        fn get_symbol_for_string(name: *const c_char, create: bool) -> c_int = 3;
+        fn get_string_for_symbol(symbol: c_int) -> *const c_char = 4;
+        fn get_symbol_for_string_case_sensitive(case_insensitive: *mut c_int, name: *const c_char, create: bool) -> c_int = 9;
+    }
+}
+
+impl IKeyValuesSystem {
+    /// Allocates memory for a `KeyValues` node. Nodes must come from here for the
+    /// engine to be able to delete them.
+    pub(crate) fn alloc_key_values_memory(&self, size: usize) -> *mut c_void {
+        unsafe { (self.alloc_key_values_memory)(self.this, size as c_int) }
+    }
+
+    /// Frees a node allocated with [`alloc_key_values_memory`](Self::alloc_key_values_memory).
+    pub(crate) unsafe fn free_key_values_memory(&self, memory: *mut c_void) {
+        unsafe { (self.free_key_values_memory)(self.this, memory) }
+    }
+
+    /// The symbol for a key name, added to the table if it is new. Symbols are
+    /// case-insensitive. Returns `None` for names holding a null byte.
+    pub fn get_symbol_for_string(&self, name: &str) -> Option<i32> {
+        let name = CString::new(name).ok()?;
+        Some(unsafe { (self.get_symbol_for_string)(self.this, name.as_ptr(), true) })
+    }
+
+    /// Both symbols for a key name, case-insensitive first, added to the table if
+    /// they are new. Returns `None` for names holding a null byte.
+    pub fn get_symbols_for_string(&self, name: &str) -> Option<(i32, i32)> {
+        let name = CString::new(name).ok()?;
+        let mut case_insensitive: c_int = 0;
+        let case_sensitive = unsafe {
+            (self.get_symbol_for_string_case_sensitive)(self.this, &mut case_insensitive, name.as_ptr(), true)
+        };
+        Some((case_insensitive, case_sensitive))
+    }
+
+    /// The key name a `KeyValues` symbol stands for, or `None` for an unknown symbol.
+    pub fn get_string_for_symbol(&self, symbol: i32) -> Option<String> {
+        let name = unsafe { (self.get_string_for_symbol)(self.this, symbol as c_int) };
+        if name.is_null() {
+            return None;
+        }
+        Some(unsafe { CStr::from_ptr(name) }.to_string_lossy().into_owned())
+    }
+}
@@ -0,0 +1,22 @@
+use std::ffi::c_void;
+use crate::interface::interface;
+
+interface! {
+    /// tier0's global allocator, `g_pMemAlloc`, behind every `new` and `delete` in
+    /// the engine. Memory the engine will free itself has to come from here.
+    pub(crate) struct IMemAlloc: "IMemAlloc" {
+        // MSVC groups each overload pair, the debug variant taking a file and line first.
+        fn alloc(size: usize) -> *mut c_void = 1, itanium 0;
+        fn free(memory: *mut c_void) = 5, itanium 2;
+    }
+}
+
+impl IMemAlloc {
+    pub(crate) fn alloc(&self, size: usize) -> *mut c_void {
+        unsafe { (self.alloc)(self.this, size) }
+    }
+
+    pub(crate) unsafe fn free(&self, memory: *mut c_void) {
+        unsafe { (self.free)(self.this, memory) }
+    }
+}
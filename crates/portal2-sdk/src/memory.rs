@@ -1,3 +1,158 @@
+//! Signature scanning over a module's mapped code.
+//!
+//! Patterns are usually written the way disassemblers print them,
+//! `"55 8B EC ?? ?? 8B 45"`, and parsed with [`Pattern::parse`]. The older
+//! byte-string-plus-mask form is still accepted through [`Pattern::from_mask`]
+//! and [`find_pattern`].
+
+use std::fmt;
+
+/// A byte pattern where some positions match any byte.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    /// `None` is a wildcard.
+    bytes: Vec<Option<u8>>,
+}
+
+/// Why [`Pattern::find_unique`] could not settle on a single address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanError {
+    NotFound,
+    /// Offsets of every match. A signature that matches twice is a signature
+    /// that will silently resolve to the wrong function after the next update.
+    Ambiguous(Vec<usize>),
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound => write!(f, "not found"),
+            Self::Ambiguous(matches) => {
+                write!(f, "ambiguous, {} matches at", matches.len())?;
+                for offset in matches.iter().take(4) {
+                    write!(f, " {:#x}", offset)?;
+                }
+                if matches.len() > 4 {
+                    write!(f, " ...")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Pattern {
+    /// Parses IDA/x64dbg notation: hex bytes separated by whitespace, with `?` or
+    /// `??` for a wildcard byte.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let bytes = text
+            .split_whitespace()
+            .map(|token| match token {
+                "?" | "??" => Ok(None),
+                _ if token.len() == 2 => u8::from_str_radix(token, 16)
+                    .map(Some)
+                    .map_err(|_| format!("invalid pattern byte '{}'", token)),
+                _ => Err(format!("invalid pattern byte '{}'", token)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::from_bytes(bytes).ok_or_else(|| "pattern has no fixed bytes".to_string())
+    }
+
+    /// Builds a pattern from a byte string and an `x`/`?` mask of the same length.
+    pub fn from_mask(pattern: &[u8], mask: &str) -> Option<Self> {
+        if pattern.len() != mask.len() {
+            return None;
+        }
+
+        let bytes = pattern
+            .iter()
+            .zip(mask.chars())
+            .map(|(&byte, m)| match m {
+                'x' => Some(Some(byte)),
+                '?' => Some(None),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Self::from_bytes(bytes)
+    }
+
+    fn from_bytes(bytes: Vec<Option<u8>>) -> Option<Self> {
+        // All-wildcard patterns match everywhere, which is never what anyone meant.
+        bytes.iter().any(Option::is_some).then_some(Self { bytes })
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Always `false`: a pattern has at least one fixed byte.
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// The pattern in `x`/`?` mask form, as stored by the compiled-in signatures.
+    pub fn mask(&self) -> String {
+        self.bytes.iter().map(|b| if b.is_some() { 'x' } else { '?' }).collect()
+    }
+
+    /// The pattern bytes, with wildcards as zero.
+    pub fn bytes(&self) -> Vec<u8> {
+        self.bytes.iter().map(|b| b.unwrap_or(0)).collect()
+    }
+
+    fn matches_at(&self, window: &[u8]) -> bool {
+        window.iter().zip(&self.bytes).all(|(&mem, pat)| pat.is_none_or(|p| p == mem))
+    }
+
+    /// Iterates over the offsets of every match, in ascending order.
+    ///
+    /// The scan is anchored on the first fixed byte of the pattern: candidates are
+    /// found by searching for that byte alone, and only those get compared in full.
+    pub fn matches<'a>(&'a self, memory: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+        let (anchor_at, anchor) = self.bytes.iter().enumerate()
+            .find_map(|(i, b)| b.map(|b| (i, b)))
+            .expect("patterns always have a fixed byte");
+        let last_start = memory.len().checked_sub(self.len());
+
+        let mut next = anchor_at;
+        std::iter::from_fn(move || {
+            let last_start = last_start?;
+            while next <= last_start + anchor_at {
+                let hit = next + memory[next..=last_start + anchor_at].iter().position(|&b| b == anchor)?;
+                next = hit + 1;
+
+                let start = hit - anchor_at;
+                if self.matches_at(&memory[start..start + self.len()]) {
+                    return Some(start);
+                }
+            }
+            None
+        })
+    }
+
+    /// Offset of the first match.
+    pub fn find(&self, memory: &[u8]) -> Option<usize> {
+        self.matches(memory).next()
+    }
+
+    /// Offsets of every match.
+    pub fn find_all(&self, memory: &[u8]) -> Vec<usize> {
+        self.matches(memory).collect()
+    }
+
+    /// Offset of the only match, or an error if there is none or more than one.
+    pub fn find_unique(&self, memory: &[u8]) -> Result<usize, ScanError> {
+        let all = self.find_all(memory);
+        match all.as_slice() {
+            [] => Err(ScanError::NotFound),
+            [single] => Ok(*single),
+            _ => Err(ScanError::Ambiguous(all)),
+        }
+    }
+}
+
 /// Searches for a byte pattern in a memory slice using a mask.
 /// `?` in the mask means "any byte".
 /// `x` in the mask means "the byte must match".
@@ -5,37 +160,78 @@
 /// Returns `None` if `pattern` and `mask` disagree in length, so a typo in a
 /// signature surfaces as "not found" rather than a bogus match.
 pub fn find_pattern(memory: &[u8], pattern: &[u8], mask: &str) -> Option<usize> {
-    // The length of the pattern and mask must be the same
-    if pattern.len() != mask.len() {
+    Pattern::from_mask(pattern, mask)?.find(memory)
+}
+
+/// Follows the `E8` (call) or `E9` (jmp) rel32 instruction at `offset`, returning
+/// the offset of its target.
+///
+/// Returns `None` if there is no such instruction there or the target falls
+/// outside of `memory`.
+pub fn follow_relative(memory: &[u8], offset: usize) -> Option<usize> {
+    let instr = memory.get(offset..offset.checked_add(5)?)?;
+    if instr[0] != 0xE8 && instr[0] != 0xE9 {
         return None;
     }
 
-    let pattern_len = pattern.len();
+    let rel = i32::from_le_bytes(instr[1..5].try_into().unwrap());
+    let target = (offset + 5).checked_add_signed(rel as isize)?;
+    (target < memory.len()).then_some(target)
+}
 
-    // Iterate over "windows" in memory, the size of each window is equal to the length of the pattern
-    for (i, window) in memory.windows(pattern_len).enumerate() {
-        // Check if the current window matches the pattern
-        let is_match = window.iter()
-            .zip(pattern.iter())
-            .zip(mask.chars())
-            .all(|((&mem_byte, &pat_byte), mask_char)| {
-                // If the mask is 'x', the bytes must match. If '?', skip the check.
-                mask_char == '?' || mem_byte == pat_byte
-            });
-
-        if is_match {
-            // If a match is found, return the offset from the beginning of the memory slice
-            return Some(i);
-        }
+/// Reads the 32-bit absolute operand at `offset`, e.g. the address in
+/// `mov eax, [0x1234abcd]`.
+pub fn read_absolute(memory: &[u8], offset: usize) -> Option<u32> {
+    let bytes = memory.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+/// [`follow_relative`] on live memory.
+///
+/// # Safety
+///
+/// `instr` must point to at least 5 readable bytes.
+pub unsafe fn follow_relative_ptr(instr: *const u8) -> Option<*const u8> {
+    let opcode = unsafe { instr.read() };
+    if opcode != 0xE8 && opcode != 0xE9 {
+        return None;
     }
 
-    // If nothing is found
-    None
+    let rel = unsafe { (instr.add(1) as *const i32).read_unaligned() };
+    // The target lives in another function, so it is outside whatever allocation
+    // `instr` belongs to; `offset` would be UB there.
+    Some(instr.wrapping_add(5).wrapping_offset(rel as isize))
+}
+
+/// Dereferences the 32-bit absolute operand `offset` bytes into the instruction at
+/// `instr`, returning the address it names.
+///
+/// # Safety
+///
+/// `instr + offset` must point to 4 readable bytes.
+pub unsafe fn deref_absolute(instr: *const u8, offset: usize) -> *const u8 {
+    unsafe { (instr.add(offset) as *const u32).read_unaligned() as usize as *const u8 }
+}
+
+/// Whether `len` bytes at `ptr` lie inside the `size` bytes at `base`, e.g. inside
+/// a module's image as [`module_range`](crate::platform::module_range) reports it.
+pub fn contains_range(base: *const u8, size: usize, ptr: *const u8, len: usize) -> bool {
+    let (base, ptr) = (base as usize, ptr as usize);
+    ptr >= base && ptr.checked_add(len).is_some_and(|end| end <= base + size)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::find_pattern;
+    use super::*;
+
+    #[test]
+    fn checks_ranges() {
+        let base = 0x1000 as *const u8;
+        assert!(contains_range(base, 0x100, 0x1010 as *const u8, 0xF0));
+        assert!(!contains_range(base, 0x100, 0x1010 as *const u8, 0xF1));
+        assert!(!contains_range(base, 0x100, 0x0FFF as *const u8, 1));
+        assert!(!contains_range(base, 0x100, usize::MAX as *const u8, 2));
+    }
 
     #[test]
     fn matches_exact_pattern() {
@@ -72,4 +268,83 @@ mod tests {
     fn pattern_longer_than_memory() {
         assert_eq!(find_pattern(b"\x55", b"\x55\x8B", "xx"), None);
     }
+
+    #[test]
+    fn parses_ida_notation() {
+        let pattern = Pattern::parse("55 8B EC ?? ? 8b").unwrap();
+        assert_eq!(pattern.len(), 6);
+        assert_eq!(pattern.mask(), "xxx??x");
+        assert_eq!(pattern.bytes(), vec![0x55, 0x8B, 0xEC, 0, 0, 0x8B]);
+    }
+
+    #[test]
+    fn rejects_malformed_patterns() {
+        assert!(Pattern::parse("55 8G").is_err());
+        assert!(Pattern::parse("558B").is_err());
+        assert!(Pattern::parse("?? ??").is_err());
+        assert!(Pattern::parse("").is_err());
+    }
+
+    #[test]
+    fn finds_every_match_with_leading_wildcards() {
+        let memory = b"\x01\x55\x8B\x02\x55\x8B\x55";
+        let pattern = Pattern::parse("?? 55 8B").unwrap();
+        assert_eq!(pattern.find_all(memory), vec![0, 3]);
+        assert_eq!(pattern.find(memory), Some(0));
+    }
+
+    #[test]
+    fn overlapping_matches_are_all_reported() {
+        let pattern = Pattern::parse("90 90").unwrap();
+        assert_eq!(pattern.find_all(b"\x90\x90\x90"), vec![0, 1]);
+    }
+
+    #[test]
+    fn unique_match_is_enforced() {
+        let pattern = Pattern::parse("55 ?? EC").unwrap();
+        assert_eq!(pattern.find_unique(b"\x00\x55\x00\xEC"), Ok(1));
+        assert_eq!(pattern.find_unique(b"\x00"), Err(ScanError::NotFound));
+        assert_eq!(
+            pattern.find_unique(b"\x55\x01\xEC\x55\x02\xEC"),
+            Err(ScanError::Ambiguous(vec![0, 3]))
+        );
+    }
+
+    #[test]
+    fn match_at_the_very_end() {
+        let pattern = Pattern::parse("8B ??").unwrap();
+        assert_eq!(pattern.find_all(b"\x00\x8B\x01"), vec![1]);
+        assert_eq!(pattern.find_all(b"\x00\x00\x8B"), vec![]);
+    }
+
+    #[test]
+    fn follows_relative_calls_and_jumps() {
+        // call +3 at 0, landing on the byte after the padding.
+        let memory = b"\xE8\x03\x00\x00\x00\x90\x90\x90\xC3";
+        assert_eq!(follow_relative(memory, 0), Some(8));
+
+        // jmp -7 at 2, landing back on 0.
+        let memory = b"\xC3\x90\xE9\xF9\xFF\xFF\xFF";
+        assert_eq!(follow_relative(memory, 2), Some(0));
+
+        assert_eq!(follow_relative(b"\x90\x00\x00\x00\x00", 0), None);
+        assert_eq!(follow_relative(b"\xE8\x00\x10\x00\x00", 0), None);
+        assert_eq!(follow_relative(b"\xE8\x00", 0), None);
+    }
+
+    #[test]
+    fn follows_relative_pointers() {
+        let memory = b"\xE9\x01\x00\x00\x00\x90\xC3";
+        let target = unsafe { follow_relative_ptr(memory.as_ptr()) }.unwrap();
+        assert_eq!(target, unsafe { memory.as_ptr().add(6) });
+    }
+
+    #[test]
+    fn reads_absolute_operands() {
+        // mov esi, [0x0B7B0254]
+        let memory = b"\x8B\x35\x54\x02\x7B\x0B";
+        assert_eq!(read_absolute(memory, 2), Some(0x0B7B_0254));
+        assert_eq!(unsafe { deref_absolute(memory.as_ptr(), 2) } as usize, 0x0B7B_0254);
+        assert_eq!(read_absolute(memory, 3), None);
+    }
 }
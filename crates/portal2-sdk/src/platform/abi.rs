@@ -18,6 +18,14 @@
 //! returns small PODs in `EAX`/`EDX`, while the i386 System V ABI always goes
 //! through a hidden return-slot pointer. Such functions need per-platform
 //! handling and cannot just be wrapped in `vfn!`.
+//!
+//! # Vtable layout
+//!
+//! The two toolchains also disagree on vtable layout, in one way that matters
+//! here: a virtual destructor takes **one** slot under MSVC (the scalar deleting
+//! destructor) but **two** under the Itanium ABI (complete-object and deleting
+//! destructors). Every index after it is shifted by one. The SDK's indices are
+//! written down in MSVC terms and translated with [`vtable_index`].
 
 /// Declares a vtable function-pointer *type* using the platform's member-function ABI.
 ///
@@ -68,3 +76,77 @@ macro_rules! vfn_impl {
 }
 
 pub(crate) use {vfn, vfn_impl};
+
+/// The C++ ABI a build of the game was compiled with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Abi {
+    /// MSVC, the Windows build.
+    Msvc,
+    /// GCC's Itanium C++ ABI, the native Linux build.
+    Itanium,
+}
+
+impl Abi {
+    /// The ABI of the game this crate is compiled for.
+    #[cfg(target_os = "windows")]
+    pub const CURRENT: Abi = Abi::Msvc;
+    /// The ABI of the game this crate is compiled for.
+    #[cfg(not(target_os = "windows"))]
+    pub const CURRENT: Abi = Abi::Itanium;
+
+    /// How many vtable slots a virtual destructor occupies.
+    pub const fn destructor_slots(self) -> usize {
+        match self {
+            Abi::Msvc => 1,
+            Abi::Itanium => 2,
+        }
+    }
+}
+
+/// Vtable slots a virtual destructor occupies on the running platform.
+pub const DTOR_SLOTS: usize = Abi::CURRENT.destructor_slots();
+
+/// Translates an MSVC vtable index to `abi`.
+///
+/// `destructor` is the MSVC index of the class's virtual destructor, if it has
+/// one; everything past it moves down a slot under the Itanium ABI. Overloaded
+/// virtuals, which MSVC groups and reverses, are not accounted for - those need
+/// an explicit index.
+pub const fn vtable_index(abi: Abi, msvc_index: usize, destructor: Option<usize>) -> usize {
+    match destructor {
+        Some(dtor) if msvc_index > dtor => msvc_index + abi.destructor_slots() - 1,
+        _ => msvc_index,
+    }
+}
+
+/// [`vtable_index`] for the running platform.
+pub const fn vslot(msvc_index: usize, destructor: Option<usize>) -> usize {
+    vtable_index(Abi::CURRENT, msvc_index, destructor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn msvc_indices_are_unchanged() {
+        assert_eq!(vtable_index(Abi::Msvc, 7, Some(0)), 7);
+        assert_eq!(vtable_index(Abi::Msvc, 3, None), 3);
+    }
+
+    #[test]
+    fn itanium_shifts_past_the_destructor_pair() {
+        // IServerTools: ~IBaseInterface at 0, GetIServerEntity at 1.
+        assert_eq!(vtable_index(Abi::Itanium, 1, Some(0)), 2);
+        // IServerNetworkable: destructor last, at 9; earlier slots stay put.
+        assert_eq!(vtable_index(Abi::Itanium, 1, Some(9)), 1);
+        assert_eq!(vtable_index(Abi::Itanium, 9, Some(9)), 9);
+        assert_eq!(vtable_index(Abi::Itanium, 20, None), 20);
+    }
+
+    #[test]
+    fn destructor_slot_count() {
+        assert_eq!(Abi::Msvc.destructor_slots(), 1);
+        assert_eq!(Abi::Itanium.destructor_slots(), 2);
+    }
+}
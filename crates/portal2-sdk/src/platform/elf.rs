@@ -0,0 +1,202 @@
+//! Symbol lookup in 32-bit ELF files, read straight from disk.
+//!
+//! `dlsym` only sees the dynamic symbol table. The native build's modules carry a
+//! full `.symtab` as well, with the mangled names of functions that were never
+//! exported; this reads both. Pure byte parsing with no loader involved, so it
+//! also works on a file that is not loaded at all.
+
+use std::collections::HashMap;
+
+const ELFCLASS32: u8 = 1;
+const ELFDATA2LSB: u8 = 1;
+const SHT_SYMTAB: u32 = 2;
+const SHT_DYNSYM: u32 = 11;
+const SHN_UNDEF: u16 = 0;
+const SYM_SIZE: usize = 16;
+
+fn u16_at(file: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(file.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+fn u32_at(file: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(file.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+struct SectionHeader {
+    kind: u32,
+    offset: usize,
+    size: usize,
+    link: usize,
+}
+
+fn section_headers(file: &[u8]) -> Option<Vec<SectionHeader>> {
+    if file.get(..4) != Some(b"\x7FELF") || file.get(4) != Some(&ELFCLASS32) || file.get(5) != Some(&ELFDATA2LSB) {
+        return None;
+    }
+
+    let table = u32_at(file, 0x20)? as usize;
+    let entry_size = u16_at(file, 0x2E)? as usize;
+    let count = u16_at(file, 0x30)? as usize;
+    if entry_size < 40 {
+        return None;
+    }
+
+    (0..count)
+        .map(|i| {
+            let h = table.checked_add(i * entry_size)?;
+            Some(SectionHeader {
+                kind: u32_at(file, h + 4)?,
+                offset: u32_at(file, h + 16)? as usize,
+                size: u32_at(file, h + 20)? as usize,
+                link: u32_at(file, h + 24)? as usize,
+            })
+        })
+        .collect()
+}
+
+/// Every defined symbol of one file, parsed once to answer any number of lookups.
+pub struct SymbolTable {
+    symbols: HashMap<Box<[u8]>, u32>,
+}
+
+impl SymbolTable {
+    /// Reads `.dynsym`, then `.symtab`; a name in both keeps its `.dynsym` value.
+    /// Returns `None` if the file is not a 32-bit little-endian ELF file.
+    pub fn parse(file: &[u8]) -> Option<Self> {
+        let sections = section_headers(file)?;
+
+        let tables = sections.iter().filter(|s| s.kind == SHT_DYNSYM)
+            .chain(sections.iter().filter(|s| s.kind == SHT_SYMTAB));
+
+        let mut symbols = HashMap::new();
+        for table in tables {
+            let Some(strings) = sections.get(table.link) else { continue };
+            let Some(strings) = file.get(strings.offset..strings.offset.saturating_add(strings.size)) else { continue };
+            let Some(entries) = file.get(table.offset..table.offset.saturating_add(table.size)) else { continue };
+
+            for sym in entries.chunks_exact(SYM_SIZE) {
+                let name_at = u32_at(sym, 0)? as usize;
+                let section = u16_at(sym, 14)?;
+                if section == SHN_UNDEF {
+                    continue;
+                }
+
+                let Some(name) = strings.get(name_at..).and_then(|s| s.split(|&b| b == 0).next()) else { continue };
+                if !name.is_empty() {
+                    symbols.entry(name.into()).or_insert(u32_at(sym, 4)?);
+                }
+            }
+        }
+
+        Some(Self { symbols })
+    }
+
+    /// The value of the defined symbol `name`, i.e. its address relative to the
+    /// object's load base.
+    pub fn get(&self, name: &str) -> Option<u32> {
+        self.symbols.get(name.as_bytes()).copied()
+    }
+}
+
+/// Finds the defined symbol `name` and returns its value, i.e. its address
+/// relative to the object's load base.
+///
+/// `.dynsym` is searched before `.symtab`. Returns `None` if the file is not a
+/// 32-bit little-endian ELF file or does not define the symbol. Parses the whole
+/// file; use [`SymbolTable`] for repeated lookups.
+pub fn find_symbol(file: &[u8], name: &str) -> Option<u32> {
+    SymbolTable::parse(file)?.get(name)
+}
+
+/// Builds minimal shared objects for tests: just section headers, symbol tables
+/// and their string tables.
+#[cfg(test)]
+pub(crate) mod fixtures {
+    /// A 32-bit ELF file with `.dynsym` holding `dynamic` and `.symtab` holding
+    /// `local`, each a list of `(name, value)`. Every symbol is defined, except
+    /// for names starting with `!`, which are emitted as undefined.
+    pub fn shared_object(dynamic: &[(&str, u32)], local: &[(&str, u32)]) -> Vec<u8> {
+        fn tables(symbols: &[(&str, u32)]) -> (Vec<u8>, Vec<u8>) {
+            let mut strings = vec![0u8];
+            let mut table = vec![0u8; 16]; // STN_UNDEF
+            for &(name, value) in symbols {
+                let (name, section) = match name.strip_prefix('!') {
+                    Some(name) => (name, 0u16),
+                    None => (name, 1u16),
+                };
+                table.extend_from_slice(&(strings.len() as u32).to_le_bytes());
+                table.extend_from_slice(&value.to_le_bytes());
+                table.extend_from_slice(&0u32.to_le_bytes());
+                table.extend_from_slice(&[0x12, 0]); // STB_GLOBAL | STT_FUNC
+                table.extend_from_slice(&section.to_le_bytes());
+                strings.extend_from_slice(name.as_bytes());
+                strings.push(0);
+            }
+            (table, strings)
+        }
+
+        let (dynsym, dynstr) = tables(dynamic);
+        let (symtab, strtab) = tables(local);
+
+        let mut file = vec![0u8; 0x34];
+        file[0..4].copy_from_slice(b"\x7FELF");
+        file[4] = 1;
+        file[5] = 1;
+
+        let place = |bytes: &[u8], file: &mut Vec<u8>| {
+            let at = file.len();
+            file.extend_from_slice(bytes);
+            (at as u32, bytes.len() as u32)
+        };
+        let dynsym_at = place(&dynsym, &mut file);
+        let dynstr_at = place(&dynstr, &mut file);
+        let symtab_at = place(&symtab, &mut file);
+        let strtab_at = place(&strtab, &mut file);
+        let table = file.len();
+
+        // type, (offset, size), link
+        let headers = [(0, (0, 0), 0), (11, dynsym_at, 2), (3, dynstr_at, 0), (2, symtab_at, 4), (3, strtab_at, 0)];
+        for (kind, (offset, size), link) in headers {
+            let fields: [u32; 10] = [0, kind, 0, 0, offset, size, link, 0, 0, 0];
+            for field in fields {
+                file.extend_from_slice(&field.to_le_bytes());
+            }
+        }
+
+        file[0x20..0x24].copy_from_slice(&(table as u32).to_le_bytes());
+        file[0x2E..0x30].copy_from_slice(&40u16.to_le_bytes());
+        file[0x30..0x32].copy_from_slice(&(headers.len() as u16).to_le_bytes());
+        file
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_exported_and_local_symbols() {
+        let file = fixtures::shared_object(
+            &[("CreateInterface", 0x1000)],
+            &[("_ZN4CCvar7FindVarEPKc", 0x2340), ("CreateInterface", 0x9999)],
+        );
+        assert_eq!(find_symbol(&file, "CreateInterface"), Some(0x1000), ".dynsym wins");
+        assert_eq!(find_symbol(&file, "_ZN4CCvar7FindVarEPKc"), Some(0x2340));
+        assert_eq!(find_symbol(&file, "_ZN4CCvar7FindVar"), None, "no prefix matches");
+    }
+
+    #[test]
+    fn skips_undefined_symbols() {
+        let file = fixtures::shared_object(&[("!malloc", 0)], &[("malloc", 0x40)]);
+        assert_eq!(find_symbol(&file, "malloc"), Some(0x40));
+    }
+
+    #[test]
+    fn rejects_non_elf32() {
+        let mut file = fixtures::shared_object(&[("a", 1)], &[]);
+        assert_eq!(find_symbol(&file, "a"), Some(1));
+        file[4] = 2; // ELFCLASS64
+        assert_eq!(find_symbol(&file, "a"), None);
+        assert_eq!(find_symbol(b"MZ\x90\x00", "a"), None);
+    }
+}
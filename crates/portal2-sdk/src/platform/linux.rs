@@ -7,10 +7,13 @@
 //! The module file names in [`Module`] follow the usual Source-on-Linux convention
 //! and still need to be confirmed against an actual native install.
 
+use std::collections::HashMap;
 use std::ffi::{c_char, c_int, c_void, CStr, CString, OsStr};
 use std::os::unix::ffi::OsStrExt;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
 
+use super::elf::SymbolTable;
 use super::Module;
 
 /// The `CreateInterface` export every Source module carries.
@@ -21,6 +24,8 @@ type CreateInterfaceFn =
 struct LoadedObject {
     /// Full path the object was loaded from, needed to re-open it via `dlopen`.
     path: PathBuf,
+    /// Difference between the object's link-time and run-time addresses.
+    load_bias: usize,
     /// Start of the executable mapping.
     text_base: *const u8,
     /// Length of the executable mapping.
@@ -70,6 +75,7 @@ unsafe extern "C" fn visit_object(
 
     search.found = Some(LoadedObject {
         path: path.to_path_buf(),
+        load_bias: info.dlpi_addr as usize,
         text_base: (info.dlpi_addr as usize + text.p_vaddr as usize) as *const u8,
         text_size: text.p_memsz as usize,
     });
@@ -96,12 +102,12 @@ pub(super) fn module_range(module: Module) -> Option<(*const u8, usize)> {
     find_object(module).map(|obj| (obj.text_base, obj.text_size))
 }
 
-pub(super) unsafe fn find_interface<T>(module: Module, interface_name: &CStr) -> *mut T {
-    let Some(object) = find_object(module) else {
-        log::warn!("Module is not loaded: {:?}", module);
-        return std::ptr::null_mut();
-    };
+pub(super) fn module_path(module: Module) -> Option<PathBuf> {
+    find_object(module).map(|obj| obj.path)
+}
 
+/// Looks up `name` through the dynamic loader, like `GetProcAddress` on Windows.
+fn dlsym_in(object: &LoadedObject, name: &CStr) -> *mut c_void {
     let Ok(path) = CString::new(object.path.as_os_str().as_bytes()) else {
         return std::ptr::null_mut();
     };
@@ -110,15 +116,65 @@ pub(super) unsafe fn find_interface<T>(module: Module, interface_name: &CStr) ->
     // we get null instead of quietly loading a second copy of it.
     let handle = unsafe { libc::dlopen(path.as_ptr(), libc::RTLD_NOW | libc::RTLD_NOLOAD) };
     if handle.is_null() {
-        log::error!("Failed to open an already-loaded {:?}", module);
+        log::error!("Failed to open an already-loaded {}", object.path.display());
         return std::ptr::null_mut();
     }
 
-    let factory = unsafe { libc::dlsym(handle, c"CreateInterface".as_ptr()) };
+    let symbol = unsafe { libc::dlsym(handle, name.as_ptr()) };
     // Drops the reference `dlopen` just took. The symbol address stays valid:
     // the engine holds its own reference to the module.
     unsafe { libc::dlclose(handle) };
+    symbol
+}
+
+pub(super) fn find_symbol(module: Module, name: &CStr) -> *const c_void {
+    let Some(object) = find_object(module) else {
+        return std::ptr::null();
+    };
+    find_symbol_in(&object, name)
+}
+
+fn find_symbol_in(object: &LoadedObject, name: &CStr) -> *const c_void {
+    let symbol = dlsym_in(object, name);
+    if !symbol.is_null() {
+        return symbol;
+    }
+
+    // Not exported; the full symbol table on disk may still have it.
+    let Ok(name) = name.to_str() else {
+        return std::ptr::null();
+    };
+    match symbol_table(&object.path).and_then(|table| table.get(name)) {
+        Some(value) => (object.load_bias + value as usize) as *const c_void,
+        None => std::ptr::null(),
+    }
+}
+
+/// Symbol tables read from disk, by the path of their object. `None` if the file
+/// could not be read or parsed, so that is not retried either.
+type SymbolTables = Mutex<HashMap<PathBuf, Option<Arc<SymbolTable>>>>;
+
+static SYMBOL_TABLES: OnceLock<SymbolTables> = OnceLock::new();
+
+/// The full symbol table of the object at `path`, read and parsed on first use.
+fn symbol_table(path: &Path) -> Option<Arc<SymbolTable>> {
+    let mut tables = SYMBOL_TABLES.get_or_init(|| Mutex::new(HashMap::new())).lock().unwrap_or_else(|e| e.into_inner());
+    tables
+        .entry(path.to_path_buf())
+        .or_insert_with(|| {
+            let file = std::fs::read(path).ok()?;
+            SymbolTable::parse(&file).map(Arc::new)
+        })
+        .clone()
+}
+
+pub(super) unsafe fn find_interface<T>(module: Module, interface_name: &CStr) -> *mut T {
+    let Some(object) = find_object(module) else {
+        log::warn!("Module is not loaded: {:?}", module);
+        return std::ptr::null_mut();
+    };
 
+    let factory = dlsym_in(&object, c"CreateInterface");
     if factory.is_null() {
         log::error!("'CreateInterface' not found in {:?}", module);
         return std::ptr::null_mut();
@@ -128,6 +184,66 @@ pub(super) unsafe fn find_interface<T>(module: Module, interface_name: &CStr) ->
     unsafe { factory(interface_name.as_ptr(), std::ptr::null_mut()) as *mut T }
 }
 
+/// The protection of the mapping containing `addr`, from `/proc/self/maps`.
+fn page_protection(addr: usize) -> Option<c_int> {
+    let maps = std::fs::read_to_string("/proc/self/maps").ok()?;
+    maps.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        let (start, end) = fields.next()?.split_once('-')?;
+        let (start, end) = (usize::from_str_radix(start, 16).ok()?, usize::from_str_radix(end, 16).ok()?);
+        if !(start..end).contains(&addr) {
+            return None;
+        }
+
+        let perms = fields.next()?.as_bytes();
+        let mut prot = libc::PROT_NONE;
+        if perms.first() == Some(&b'r') { prot |= libc::PROT_READ; }
+        if perms.get(1) == Some(&b'w') { prot |= libc::PROT_WRITE; }
+        if perms.get(2) == Some(&b'x') { prot |= libc::PROT_EXEC; }
+        Some(prot)
+    })
+}
+
+pub(super) unsafe fn write_protected(addr: *mut u8, bytes: &[u8]) -> Result<(), String> {
+    let prot = page_protection(addr as usize).ok_or_else(|| format!("{:p} is not mapped", addr))?;
+    if prot & libc::PROT_WRITE != 0 {
+        unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), addr, bytes.len()) };
+        return Ok(());
+    }
+
+    // The range may straddle a page boundary; it is assumed to sit in a single mapping.
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+    let start = addr as usize & !(page_size - 1);
+    let len = (addr as usize + bytes.len() - start).next_multiple_of(page_size);
+    if unsafe { libc::mprotect(start as *mut c_void, len, prot | libc::PROT_WRITE) } != 0 {
+        return Err(format!("mprotect failed at {:p}: {}", addr, std::io::Error::last_os_error()));
+    }
+
+    unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), addr, bytes.len()) };
+
+    if unsafe { libc::mprotect(start as *mut c_void, len, prot) } != 0 {
+        return Err(format!("mprotect restore failed at {:p}: {}", addr, std::io::Error::last_os_error()));
+    }
+    Ok(())
+}
+
+pub(super) fn alloc_executable(len: usize) -> Result<*mut u8, String> {
+    let memory = unsafe {
+        libc::mmap(
+            std::ptr::null_mut(),
+            len,
+            libc::PROT_READ | libc::PROT_WRITE | libc::PROT_EXEC,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        )
+    };
+    if memory == libc::MAP_FAILED {
+        return Err(format!("mmap failed: {}", std::io::Error::last_os_error()));
+    }
+    Ok(memory as *mut u8)
+}
+
 pub(super) fn module_dir_of_self() -> Option<PathBuf> {
     let mut info: libc::Dl_info = unsafe { std::mem::zeroed() };
 
@@ -161,6 +277,27 @@ mod tests {
         assert_eq!(libc.path.file_name().unwrap(), "libc.so.6");
     }
 
+    #[test]
+    fn finds_an_exported_symbol() {
+        let libc = find_object_named(b"libc.so.6").unwrap();
+        let found = find_symbol_in(&libc, c"malloc");
+        assert_eq!(found, libc::malloc as *const c_void);
+        assert!(find_symbol_in(&libc, c"definitely_not_a_libc_symbol").is_null());
+    }
+
+    #[test]
+    fn parses_each_symbol_table_once() {
+        let path = std::env::temp_dir().join(format!("portal2-sdk-symbols-{}.so", std::process::id()));
+        std::fs::write(&path, super::super::elf::fixtures::shared_object(&[], &[("_ZN4CCvar7FindVarEPKc", 0x2340)])).unwrap();
+
+        let first = symbol_table(&path).unwrap();
+        // Served from the cache from now on, even though the file is gone.
+        std::fs::remove_file(&path).unwrap();
+        let second = symbol_table(&path).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(second.get("_ZN4CCvar7FindVarEPKc"), Some(0x2340));
+    }
+
     #[test]
     fn missing_object_is_none() {
         assert!(find_object_named(b"definitely-not-loaded.so").is_none());
@@ -173,6 +310,24 @@ mod tests {
         assert!(module_range(Module::Engine).is_none());
     }
 
+    #[test]
+    fn writes_through_read_only_pages() {
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+        let page = unsafe {
+            libc::mmap(std::ptr::null_mut(), page_size, libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS, -1, 0)
+        };
+        assert_ne!(page, libc::MAP_FAILED);
+        unsafe { libc::mprotect(page, page_size, libc::PROT_READ) };
+
+        let slot = unsafe { (page as *mut usize).add(3) };
+        unsafe { write_protected(slot as *mut u8, &0x1234usize.to_ne_bytes()) }.unwrap();
+        assert_eq!(unsafe { slot.read() }, 0x1234);
+        assert_eq!(page_protection(slot as usize), Some(libc::PROT_READ));
+
+        unsafe { libc::munmap(page, page_size) };
+    }
+
     #[test]
     fn resolves_own_directory() {
         let dir = module_dir_of_self().expect("the test binary has a path");
@@ -5,15 +5,20 @@
 //! where our own binary sits on disk. The rest of the SDK talks to the engine
 //! through plain pointers and is platform-agnostic.
 //!
-//! The Windows build (the one everyone actually plays, natively or through Proton)
-//! is fully supported. The native Linux build is a work in progress - this layer
-//! already works there, but [`crate::Engine::initialize`] does not: it still
-//! resolves functions through Windows-only byte signatures and MSVC vtable layouts.
+//! Both the Windows build (natively or through Proton) and the native Linux build
+//! are supported. Where the Windows build needs byte signatures, the Linux build
+//! resolves the same functions by their mangled names through [`find_symbol`];
+//! vtable indices are translated between the two C++ ABIs by [`vtable_index`].
 //!
 //! Both builds of Portal 2 are 32-bit; the structure layouts in [`crate::types`]
 //! assume `target_pointer_width = "32"`.
 
 pub(crate) mod abi;
+pub mod elf;
+mod registry;
+
+pub use abi::{vtable_index, Abi};
+pub use registry::{closest_version, find_interface_or_closest, registered_interfaces, RegisteredInterface};
 
 #[cfg_attr(target_os = "windows", path = "windows.rs")]
 #[cfg_attr(target_os = "linux", path = "linux.rs")]
@@ -22,13 +27,14 @@ mod imp;
 #[cfg(not(any(target_os = "windows", target_os = "linux")))]
 compile_error!("portal2-sdk supports the Windows and native Linux builds of Portal 2 only");
 
-use std::ffi::CStr;
+use std::ffi::{c_void, CStr};
 use std::path::PathBuf;
 
 /// An engine module the SDK resolves interfaces from.
 ///
 /// The Windows build ships these as DLLs, the native Linux build as shared objects
-/// with the same base name (`engine.dll` / `engine.so`).
+/// with the same base name (`engine.dll` / `engine.so`) - except for the tier
+/// libraries, which get a `lib` prefix there (`libvstdlib.so`, `libtier0.so`).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Module {
     /// `engine` - the core engine: client state, events, tracing, debug overlay.
@@ -37,32 +43,70 @@ pub enum Module {
     InputSystem,
     /// `vstdlib` - Valve's runtime library; hosts the cvar system.
     VStdLib,
+    /// `tier0` - the lowest platform layer; hosts the global memory allocator.
+    Tier0,
     /// `server` - the server-side game DLL, loaded only once a map is running.
     Server,
+    /// `client` - the client-side game DLL: rendering, prediction, HUD.
+    Client,
 }
 
 impl Module {
-    /// The module's file name as the dynamic loader knows it.
-    #[cfg(target_os = "windows")]
-    pub const fn file_name(self) -> &'static CStr {
+    /// Every module, in declaration order.
+    pub const ALL: [Module; 6] = [Self::Engine, Self::InputSystem, Self::VStdLib, Self::Tier0, Self::Server, Self::Client];
+
+    /// The module's base name, without the platform-specific extension.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Engine => "engine",
+            Self::InputSystem => "inputsystem",
+            Self::VStdLib => "vstdlib",
+            Self::Tier0 => "tier0",
+            Self::Server => "server",
+            Self::Client => "client",
+        }
+    }
+
+    /// The inverse of [`Module::name`], case-insensitive.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|m| m.name().eq_ignore_ascii_case(name))
+    }
+
+    /// The module's file name in the Windows build.
+    pub const fn dll_name(self) -> &'static CStr {
         match self {
             Self::Engine => c"engine.dll",
             Self::InputSystem => c"inputsystem.dll",
             Self::VStdLib => c"vstdlib.dll",
+            Self::Tier0 => c"tier0.dll",
             Self::Server => c"server.dll",
+            Self::Client => c"client.dll",
         }
     }
 
-    /// The module's file name as the dynamic loader knows it.
-    #[cfg(target_os = "linux")]
-    pub const fn file_name(self) -> &'static CStr {
+    /// The module's file name in the native Linux build.
+    pub const fn so_name(self) -> &'static CStr {
         match self {
             Self::Engine => c"engine.so",
             Self::InputSystem => c"inputsystem.so",
-            Self::VStdLib => c"vstdlib.so",
+            Self::VStdLib => c"libvstdlib.so",
+            Self::Tier0 => c"libtier0.so",
             Self::Server => c"server.so",
+            Self::Client => c"client.so",
         }
     }
+
+    /// The module's file name as the dynamic loader knows it.
+    #[cfg(target_os = "windows")]
+    pub const fn file_name(self) -> &'static CStr {
+        self.dll_name()
+    }
+
+    /// The module's file name as the dynamic loader knows it.
+    #[cfg(target_os = "linux")]
+    pub const fn file_name(self) -> &'static CStr {
+        self.so_name()
+    }
 }
 
 /// The address range a module's code is mapped at, as `(base, size)`.
@@ -76,11 +120,30 @@ pub fn module_range(module: Module) -> Option<(*const u8, usize)> {
     imp::module_range(module)
 }
 
+/// Full path of the file a loaded module was mapped from.
+///
+/// Returns `None` if the module is not currently loaded.
+pub fn module_path(module: Module) -> Option<PathBuf> {
+    imp::module_path(module)
+}
+
+/// Looks up a symbol by name in a loaded module, returning its address.
+///
+/// On Windows this only sees the module's exports. On Linux it sees the dynamic
+/// symbol table and, failing that, the full `.symtab` of the file on disk, which
+/// is where the mangled names of unexported engine functions live.
+///
+/// Returns a null pointer if the module is not loaded or has no such symbol.
+pub fn find_symbol(module: Module, name: &CStr) -> *const c_void {
+    imp::find_symbol(module, name)
+}
+
 /// Requests an interface from a module's `CreateInterface` factory.
 ///
 /// This is how every engine interface is obtained: the module exports a single
 /// `CreateInterface` symbol that maps a versioned name to a pointer to the
-/// singleton implementing it.
+/// singleton implementing it. To list the names a module knows, see
+/// [`registered_interfaces`].
 ///
 /// Returns a null pointer if the module is not loaded, exports no factory, or does
 /// not know the requested interface name.
@@ -93,6 +156,34 @@ pub unsafe fn find_interface<T>(module: Module, interface_name: &CStr) -> *mut T
     unsafe { imp::find_interface(module, interface_name) }
 }
 
+/// Overwrites the pointer-sized value at `addr`, such as a vtable slot, even if the
+/// page it lives on is read-only. The page's protection is restored afterwards.
+///
+/// # Safety
+///
+/// `addr` must be aligned and point into mapped memory, and nothing may be relying
+/// on the value being overwritten.
+pub unsafe fn write_protected(addr: *mut usize, value: usize) -> Result<(), String> {
+    unsafe { imp::write_protected(addr as *mut u8, &value.to_ne_bytes()) }
+}
+
+/// Overwrites the code at `addr` with `bytes`, like [`write_protected`] does for a
+/// single pointer.
+///
+/// # Safety
+///
+/// `addr..addr + bytes.len()` must be mapped, and no thread may be executing the
+/// instructions being replaced.
+pub unsafe fn write_code(addr: *mut u8, bytes: &[u8]) -> Result<(), String> {
+    unsafe { imp::write_protected(addr, bytes) }
+}
+
+/// Allocates `len` bytes of readable, writable and executable memory, for code
+/// generated at run time. It is never freed.
+pub fn alloc_executable(len: usize) -> Result<*mut u8, String> {
+    imp::alloc_executable(len)
+}
+
 /// The directory containing the binary this code is running from.
 ///
 /// Exposed to users through [`crate::utils::get_dll_directory`].
@@ -0,0 +1,324 @@
+//! Enumeration of the interfaces a module registers with its `CreateInterface` factory.
+//!
+//! Every Source module links the same `tier1` registry: each `EXPOSE_INTERFACE`
+//! adds an `InterfaceReg` node to the singly linked list headed by the static
+//! `InterfaceReg::s_pInterfaceRegs`, and `CreateInterface` does nothing but walk
+//! that list comparing names. Walking it ourselves gives us every interface a
+//! module knows about, not just the ones we can name up front.
+//!
+//! The list head is found by its mangled name where the module has a symbol table
+//! (the native Linux build), and otherwise by decoding the first instructions of
+//! `CreateInterface`, which loads it into a register straight away. A decoded head
+//! is only a guess, so it and every node reached from it must lie inside the
+//! module's image before they are read.
+
+use std::ffi::{c_char, c_void, CStr};
+
+use super::Module;
+use crate::memory::contains_range;
+
+/// Mangled name of `InterfaceReg::s_pInterfaceRegs`.
+const LIST_HEAD_SYMBOL: &CStr = c"_ZN12InterfaceReg15s_pInterfaceRegsE";
+
+/// Upper bound on the nodes walked, in case the list is not what we think it is.
+const MAX_INTERFACES: usize = 4096;
+
+/// `InstantiateInterfaceFn`: a plain `cdecl` function without arguments.
+type InstantiateInterfaceFn = unsafe extern "C" fn() -> *mut c_void;
+
+/// The engine's `InterfaceReg` node.
+#[repr(C)]
+struct InterfaceReg {
+    create_fn: InstantiateInterfaceFn,
+    name: *const c_char,
+    next: *const InterfaceReg,
+}
+
+/// An interface a module has registered, as found by [`registered_interfaces`].
+#[derive(Debug, Clone)]
+pub struct RegisteredInterface {
+    /// The module that registered it.
+    pub module: Module,
+    /// Its versioned name, e.g. `VEngineClient015`.
+    pub name: String,
+    factory: InstantiateInterfaceFn,
+}
+
+impl RegisteredInterface {
+    /// Address of the function that instantiates the interface.
+    pub fn factory(&self) -> *const c_void {
+        self.factory as *const c_void
+    }
+
+    /// Gets a pointer to the interface, exactly as `CreateInterface` would.
+    ///
+    /// Most interfaces are singletons and this returns the same pointer every time,
+    /// but those exposed with `EXPOSE_INTERFACE` allocate a new object per call.
+    ///
+    /// # Safety
+    ///
+    /// See [`find_interface`](super::find_interface): the engine performs no type
+    /// checking, the pointer is only meaningful as the interface named.
+    pub unsafe fn instantiate<T>(&self) -> *mut T {
+        unsafe { (self.factory)() as *mut T }
+    }
+}
+
+/// Lists every interface `module` has registered, in the order `CreateInterface`
+/// would consider them.
+///
+/// Returns an empty list if the module is not loaded or its registry could not be
+/// located.
+pub fn registered_interfaces(module: Module) -> Vec<RegisteredInterface> {
+    let Some((head, bounds)) = list_head(module) else {
+        log::warn!("Could not locate the interface registry of {:?}", module);
+        return Vec::new();
+    };
+    unsafe { walk(head, module, bounds) }
+}
+
+/// Like [`find_interface`](super::find_interface), but if `module` does not know
+/// `interface_name`, falls back to the registered version of the same interface
+/// closest to it (see [`closest_version`]).
+///
+/// # Safety
+///
+/// As [`find_interface`](super::find_interface). A fallback implements a different
+/// version of the interface, so its layout may differ from the one asked for.
+pub unsafe fn find_interface_or_closest<T>(module: Module, interface_name: &CStr) -> *mut T {
+    let exact = unsafe { super::find_interface::<T>(module, interface_name) };
+    if !exact.is_null() {
+        return exact;
+    }
+
+    let Ok(wanted) = interface_name.to_str() else {
+        return exact;
+    };
+    let available = registered_interfaces(module);
+    let Some(closest) = closest_version(wanted, available.iter().map(|i| i.name.as_str())) else {
+        return exact;
+    };
+
+    log::warn!("{:?} does not provide {}, falling back to {}", module, wanted, closest);
+    let entry = available.iter().find(|i| i.name == closest).unwrap();
+    unsafe { entry.instantiate() }
+}
+
+/// Picks the version of `wanted` closest to it among `available`.
+///
+/// Interface names end in a version number (`VEngineClient015`); candidates must
+/// share everything before it. Of two equally close versions the newer one wins.
+/// Returns `None` if `wanted` carries no version or nothing matches.
+pub fn closest_version<'a>(wanted: &str, available: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let (stem, version) = split_version(wanted)?;
+
+    available
+        .into_iter()
+        .filter_map(|name| {
+            let (other_stem, other_version) = split_version(name)?;
+            (other_stem == stem).then_some((name, other_version))
+        })
+        .min_by_key(|&(_, other)| (other.abs_diff(version), std::cmp::Reverse(other)))
+        .map(|(name, _)| name)
+}
+
+/// Splits `VEngineClient015` into `("VEngineClient", 15)`.
+fn split_version(name: &str) -> Option<(&str, u32)> {
+    let stem = name.trim_end_matches(|c: char| c.is_ascii_digit());
+    let version = name[stem.len()..].parse().ok()?;
+    Some((stem, version))
+}
+
+/// The memory a list's nodes must lie in, as a base and a size; `None` if the list
+/// is trusted as it is.
+type Bounds = Option<(*const u8, usize)>;
+
+/// Finds the address of `s_pInterfaceRegs` in `module`, and the bounds its nodes are
+/// checked against: none for a head the module's symbol table names, the module's
+/// image for one decoded from code.
+fn list_head(module: Module) -> Option<(*const *const InterfaceReg, Bounds)> {
+    let symbol = super::find_symbol(module, LIST_HEAD_SYMBOL);
+    if !symbol.is_null() {
+        return Some((symbol as *const *const InterfaceReg, None));
+    }
+
+    let entry = super::find_symbol(module, c"CreateInterface");
+    let (base, size) = super::module_range(module)?;
+    if entry.is_null() {
+        return None;
+    }
+
+    let image = unsafe { std::slice::from_raw_parts(base, size) };
+    let head = list_head_from_code(image, base as usize, entry as usize)?;
+    Some((head as *const *const InterfaceReg, Some((base, size))))
+}
+
+/// Decodes `CreateInterface` at `entry` to find the address of the list head.
+///
+/// `image` is the module's memory, mapped at `base`. The MSVC build compiles
+/// `CreateInterface` to a jump into `CreateInterfaceInternal`, possibly behind a
+/// frame-pointer prologue, and that function starts by loading the list head with
+/// `mov reg, [s_pInterfaceRegs]` after saving a register or two. The address it
+/// loads from must lie inside `image`.
+fn list_head_from_code(image: &[u8], base: usize, entry: usize) -> Option<usize> {
+    let read = |addr: usize, len: usize| -> Option<&[u8]> {
+        let start = addr.checked_sub(base)?;
+        image.get(start..start.checked_add(len)?)
+    };
+    let read_u32 = |addr: usize| read(addr, 4).map(|b| u32::from_le_bytes(b.try_into().unwrap()) as usize);
+
+    let mut pc = entry;
+    // A few jumps at most; anything longer is not the code we are looking for.
+    for _ in 0..4 {
+        if read(pc, 3)? == [0x55, 0x8B, 0xEC] {
+            pc += 3; // push ebp; mov ebp, esp
+        }
+        if read(pc, 1)? == [0x5D] {
+            pc += 1; // pop ebp
+        }
+        if read(pc, 1)? == [0xE9] {
+            let rel = read_u32(pc + 1)? as i32;
+            pc = (pc + 5).wrapping_add_signed(rel as isize);
+            continue;
+        }
+
+        while matches!(read(pc, 1)?[0], 0x50..=0x57) {
+            pc += 1; // push reg
+        }
+        let head = match read(pc, 2)? {
+            // mov eax, [disp32]
+            [0xA1, _] => read_u32(pc + 1)?,
+            // mov reg, [disp32]
+            [0x8B, modrm] if modrm & 0xC7 == 0x05 => read_u32(pc + 2)?,
+            _ => return None,
+        };
+        return read(head, size_of::<*const InterfaceReg>()).map(|_| head);
+    }
+    None
+}
+
+/// Collects the list starting at `*head`, stopping at the first node or name
+/// outside `bounds`.
+///
+/// # Safety
+///
+/// `head` must be readable, and point to a well-formed `InterfaceReg` list head
+/// unless `bounds` is given.
+unsafe fn walk(head: *const *const InterfaceReg, module: Module, bounds: Bounds) -> Vec<RegisteredInterface> {
+    let in_bounds = |ptr: *const u8, len: usize| bounds.is_none_or(|(base, size)| contains_range(base, size, ptr, len));
+
+    let mut interfaces = Vec::new();
+    let mut node = unsafe { *head };
+
+    while !node.is_null() && interfaces.len() < MAX_INTERFACES {
+        if !in_bounds(node as *const u8, size_of::<InterfaceReg>()) {
+            log::warn!("Interface registry of {:?} leaves the module at {:p}; stopping there", module, node);
+            break;
+        }
+        let reg = unsafe { &*node };
+        if !reg.name.is_null() && !in_bounds(reg.name as *const u8, 1) {
+            log::warn!("Interface registry of {:?} names an interface outside the module; stopping there", module);
+            break;
+        }
+        if !reg.name.is_null() {
+            interfaces.push(RegisteredInterface {
+                module,
+                name: unsafe { CStr::from_ptr(reg.name) }.to_string_lossy().into_owned(),
+                factory: reg.create_fn,
+            });
+        }
+        node = reg.next;
+    }
+
+    interfaces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_the_closest_version() {
+        let available = ["VEngineClient013", "VEngineClient014", "VEngineClient017", "VEngineServer022"];
+        assert_eq!(closest_version("VEngineClient015", available), Some("VEngineClient014"));
+        assert_eq!(closest_version("VEngineClient016", available), Some("VEngineClient017"));
+        assert_eq!(closest_version("VEngineServer021", available), Some("VEngineServer022"));
+        assert_eq!(closest_version("VEngineCvar007", available), None);
+        assert_eq!(closest_version("VEngineClient", available), None);
+    }
+
+    #[test]
+    fn ties_prefer_the_newer_version() {
+        assert_eq!(closest_version("ICvar005", ["ICvar004", "ICvar006"]), Some("ICvar006"));
+    }
+
+    /// `CreateInterface` as the MSVC build has it: a prologue and a jump into the
+    /// function that actually walks the list.
+    #[test]
+    fn decodes_create_interface() {
+        let base = 0x1000_0000;
+        let mut image = vec![0xCC; 0x40];
+        // CreateInterface: push ebp; mov ebp, esp; pop ebp; jmp +0x10
+        image[..9].copy_from_slice(&[0x55, 0x8B, 0xEC, 0x5D, 0xE9, 0x10, 0x00, 0x00, 0x00]);
+        // CreateInterfaceInternal: push ebp; mov ebp, esp; push esi; mov esi, [0x10000030]
+        image[0x19..0x23].copy_from_slice(&[0x55, 0x8B, 0xEC, 0x56, 0x8B, 0x35, 0x30, 0x00, 0x00, 0x10]);
+
+        assert_eq!(list_head_from_code(&image, base, base), Some(0x1000_0030));
+
+        // The same load from an address outside the image.
+        image[0x1F..0x23].copy_from_slice(&[0x40, 0x30, 0x20, 0x10]);
+        assert_eq!(list_head_from_code(&image, base, base), None);
+    }
+
+    #[test]
+    fn rejects_unknown_code() {
+        let image = [0x31, 0xC0, 0xC3, 0xCC, 0xCC, 0xCC]; // xor eax, eax; ret
+        assert_eq!(list_head_from_code(&image, 0, 0), None);
+        // A jump out of the image.
+        let image = [0xE9, 0x00, 0x10, 0x00, 0x00, 0xCC];
+        assert_eq!(list_head_from_code(&image, 0, 0), None);
+    }
+
+    static SECOND: u8 = 0;
+
+    extern "C" fn first() -> *mut c_void { std::ptr::null_mut() }
+    extern "C" fn second() -> *mut c_void { &SECOND as *const u8 as *mut c_void }
+
+    #[test]
+    fn walks_the_list() {
+        let tail = InterfaceReg { create_fn: second, name: c"Second002".as_ptr(), next: std::ptr::null() };
+        let node = InterfaceReg { create_fn: first, name: c"First001".as_ptr(), next: &tail };
+        let head: *const InterfaceReg = &node;
+
+        let found = unsafe { walk(&head, Module::Engine, None) };
+        assert_eq!(found.iter().map(|i| i.name.as_str()).collect::<Vec<_>>(), ["First001", "Second002"]);
+        assert_eq!(unsafe { found[1].instantiate::<u8>() } as *const u8, &SECOND as *const u8);
+        assert_eq!(found[0].factory(), first as *const c_void);
+    }
+
+    #[test]
+    fn stops_at_nodes_outside_the_module() {
+        #[repr(C)]
+        struct Image {
+            first: InterfaceReg,
+            name: [u8; 9],
+            second: InterfaceReg,
+        }
+        let mut image = Box::new(Image {
+            first: InterfaceReg { create_fn: first, name: std::ptr::null(), next: std::ptr::null() },
+            name: *b"First001\0",
+            second: InterfaceReg { create_fn: second, name: c"Second002".as_ptr(), next: std::ptr::null() },
+        });
+        image.first.name = image.name.as_ptr() as *const c_char;
+        image.first.next = &image.second;
+        let head: *const InterfaceReg = &image.first;
+        let base = head as *const u8;
+
+        // The "module" ends before the second node.
+        let found = unsafe { walk(&head, Module::Engine, Some((base, std::mem::offset_of!(Image, second)))) };
+        assert_eq!(found.iter().map(|i| i.name.as_str()).collect::<Vec<_>>(), ["First001"]);
+
+        // Now it ends before the first node's name, too.
+        assert!(unsafe { walk(&head, Module::Engine, Some((base, size_of::<InterfaceReg>()))) }.is_empty());
+    }
+}
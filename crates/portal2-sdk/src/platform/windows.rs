@@ -5,10 +5,14 @@ use std::path::PathBuf;
 
 use windows::core::PCSTR;
 use windows::Win32::Foundation::HMODULE;
+use windows::Win32::System::Diagnostics::Debug::FlushInstructionCache;
 use windows::Win32::System::LibraryLoader::{
     GetModuleFileNameA, GetModuleHandleA, GetModuleHandleExA, GetProcAddress,
     GET_MODULE_HANDLE_EX_FLAG_FROM_ADDRESS, GET_MODULE_HANDLE_EX_FLAG_UNCHANGED_REFCOUNT,
 };
+use windows::Win32::System::Memory::{
+    VirtualAlloc, VirtualProtect, MEM_COMMIT, MEM_RESERVE, PAGE_EXECUTE_READWRITE, PAGE_PROTECTION_FLAGS,
+};
 use windows::Win32::System::ProcessStatus::{GetModuleInformation, MODULEINFO};
 use windows::Win32::System::Threading::GetCurrentProcess;
 
@@ -53,6 +57,29 @@ pub(super) fn module_range(module: Module) -> Option<(*const u8, usize)> {
     Some((info.lpBaseOfDll as *const u8, info.SizeOfImage as usize))
 }
 
+pub(super) fn module_path(module: Module) -> Option<PathBuf> {
+    let handle = module_handle(module)?;
+
+    let mut path = vec![0u8; 512];
+    let len = unsafe { GetModuleFileNameA(Some(handle), &mut path) } as usize;
+    if len == 0 {
+        return None;
+    }
+
+    std::str::from_utf8(&path[..len]).ok().map(PathBuf::from)
+}
+
+pub(super) fn find_symbol(module: Module, name: &CStr) -> *const c_void {
+    let Some(handle) = module_handle(module) else {
+        return std::ptr::null();
+    };
+
+    match unsafe { GetProcAddress(handle, PCSTR(name.as_ptr() as *const u8)) } {
+        Some(symbol) => symbol as *const c_void,
+        None => std::ptr::null(),
+    }
+}
+
 pub(super) unsafe fn find_interface<T>(module: Module, interface_name: &CStr) -> *mut T {
     let Some(handle) = module_handle(module) else {
         log::warn!("Module is not loaded: {:?}", module);
@@ -69,6 +96,30 @@ pub(super) unsafe fn find_interface<T>(module: Module, interface_name: &CStr) ->
     unsafe { factory(PCSTR(interface_name.as_ptr() as *const u8), std::ptr::null_mut()) as *mut T }
 }
 
+pub(super) unsafe fn write_protected(addr: *mut u8, bytes: &[u8]) -> Result<(), String> {
+    let mut old_protect = PAGE_PROTECTION_FLAGS(0);
+    unsafe { VirtualProtect(addr as _, bytes.len(), PAGE_EXECUTE_READWRITE, &mut old_protect) }
+        .map_err(|e| format!("VirtualProtect failed at {:p}: {}", addr, e))?;
+
+    unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), addr, bytes.len()) };
+    // The patched bytes may be code another thread is about to run. The protection
+    // is restored whether or not this succeeds.
+    let flushed = unsafe { FlushInstructionCache(GetCurrentProcess(), Some(addr as *const c_void), bytes.len()) }
+        .map_err(|e| format!("FlushInstructionCache failed at {:p}: {}", addr, e));
+
+    unsafe { VirtualProtect(addr as _, bytes.len(), old_protect, &mut old_protect) }
+        .map_err(|e| format!("VirtualProtect restore failed at {:p}: {}", addr, e))?;
+    flushed
+}
+
+pub(super) fn alloc_executable(len: usize) -> Result<*mut u8, String> {
+    let memory = unsafe { VirtualAlloc(None, len, MEM_COMMIT | MEM_RESERVE, PAGE_EXECUTE_READWRITE) };
+    if memory.is_null() {
+        return Err(format!("VirtualAlloc failed: {}", windows::core::Error::from_win32()));
+    }
+    Ok(memory as *mut u8)
+}
+
 pub(super) fn module_dir_of_self() -> Option<PathBuf> {
     unsafe {
         let mut handle = HMODULE::default();
@@ -0,0 +1,773 @@
+//! Reflection over entity members through the engine's own tables.
+//!
+//! Every networked class has a [`SendTable`] tree, reached through its
+//! [`ServerClass`](crate::types::ServerClass), and every entity class a
+//! [`DataMap`] chain. Walking both gives the name, type and offset of nearly every
+//! interesting member of an entity. The result is cached per class, so lookups
+//! after the first one are a hash map access.
+//!
+//! ```no_run
+//! use portal2_sdk::types::Vector;
+//!
+//! let engine = portal2_sdk::get_engine();
+//! if let Some(player) = engine.entities().find_by_classname(None, "player") {
+//!     let velocity: Option<Vector> = player.prop("m_vecVelocity");
+//!     player.set_prop("m_iHealth", 50i32);
+//!     if let Some(props) = player.class_props() {
+//!         for info in props.iter() {
+//!             println!("{} = {:?}", info.name(), player.prop_value(info, 0));
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::ffi::{c_char, CStr};
+use std::fmt;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use crate::types::{
+    CBaseEntity, CBaseHandle, DataMap, FieldType, QAngle, SendPropType, SendTable, Vector,
+    SPROP_EXCLUDE, SPROP_INSIDEARRAY,
+};
+
+/// How deep nested tables are followed. Real trees are a handful of levels deep;
+/// this only guards against cycles in a corrupted table.
+const MAX_DEPTH: usize = 32;
+
+/// Longest inline string read when the size of the buffer is not known.
+const MAX_INLINE_STRING: usize = 256;
+
+/// The in-memory type of a member, merged from the send and datamap types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropKind {
+    /// An integer whose width is [`PropInfo::size`]. If that is unknown, it is read
+    /// as 4 bytes and not written at all.
+    Int,
+    Int64,
+    Bool,
+    Short,
+    Char,
+    Float,
+    /// A `Vector` or `QAngle`.
+    Vector,
+    Vector2D,
+    Quaternion,
+    Color32,
+    /// A `char` buffer stored in the entity.
+    String,
+    /// A `string_t`: a pointer to a string stored elsewhere.
+    StringT,
+    EHandle,
+    Pointer,
+    Matrix,
+    /// Anything else, e.g. custom save/restore types and outputs.
+    Other,
+}
+
+impl PropKind {
+    /// The element size of the kind, when it does not depend on the member.
+    fn natural_size(self) -> Option<usize> {
+        match self {
+            PropKind::Int | PropKind::Float | PropKind::Color32 | PropKind::EHandle => Some(4),
+            PropKind::Int64 => Some(8),
+            PropKind::Bool | PropKind::Char => Some(1),
+            PropKind::Short => Some(2),
+            PropKind::Vector => Some(12),
+            PropKind::Vector2D => Some(8),
+            PropKind::Quaternion => Some(16),
+            PropKind::StringT | PropKind::Pointer => Some(size_of::<usize>()),
+            PropKind::String | PropKind::Matrix | PropKind::Other => None,
+        }
+    }
+
+    fn from_send(ty: SendPropType) -> Self {
+        match ty {
+            SendPropType::Int => PropKind::Int,
+            SendPropType::Float => PropKind::Float,
+            SendPropType::Vector | SendPropType::VectorXY => PropKind::Vector,
+            SendPropType::String => PropKind::String,
+            SendPropType::Int64 => PropKind::Int64,
+            _ => PropKind::Other,
+        }
+    }
+
+    fn from_field(ty: FieldType) -> Self {
+        match ty {
+            FieldType::Float | FieldType::Time => PropKind::Float,
+            FieldType::String | FieldType::ModelName | FieldType::SoundName => PropKind::StringT,
+            FieldType::Vector | FieldType::PositionVector => PropKind::Vector,
+            FieldType::Quaternion => PropKind::Quaternion,
+            FieldType::Integer | FieldType::Tick | FieldType::ModelIndex | FieldType::MaterialIndex => PropKind::Int,
+            FieldType::Boolean => PropKind::Bool,
+            FieldType::Short => PropKind::Short,
+            FieldType::Character => PropKind::Char,
+            FieldType::Color32 => PropKind::Color32,
+            FieldType::EHandle => PropKind::EHandle,
+            FieldType::ClassPtr | FieldType::Edict => PropKind::Pointer,
+            FieldType::VMatrix | FieldType::VMatrixWorldSpace | FieldType::Matrix3x4WorldSpace => PropKind::Matrix,
+            FieldType::Vector2D => PropKind::Vector2D,
+            _ => PropKind::Other,
+        }
+    }
+}
+
+/// Which table a [`PropInfo`] came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropSource {
+    /// A networked property. Writing it marks the entity for a network update.
+    SendTable,
+    DataMap,
+}
+
+/// A member of an entity class.
+///
+/// Only built from the engine's own tables, through [`ClassProps`]: the offset and
+/// size are what reads and writes trust, so they cannot be set from outside.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropInfo {
+    name: String,
+    kind: PropKind,
+    /// Offset from the start of the entity.
+    offset: usize,
+    /// Number of elements; 1 for anything but arrays.
+    count: usize,
+    /// Size of one element in bytes, if known.
+    size: Option<usize>,
+    source: PropSource,
+}
+
+impl PropInfo {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn kind(&self) -> PropKind {
+        self.kind
+    }
+
+    /// Offset from the start of the entity.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Number of elements; 1 for anything but arrays.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Size of one element in bytes, if known.
+    pub fn size(&self) -> Option<usize> {
+        self.size
+    }
+
+    pub fn source(&self) -> PropSource {
+        self.source
+    }
+
+    /// Distance between two elements of an array.
+    fn stride(&self) -> usize {
+        self.size.or(self.kind.natural_size()).unwrap_or(0)
+    }
+}
+
+/// Every member of one entity class, from its send table and its datamap.
+#[derive(Debug, Clone, Default)]
+pub struct ClassProps {
+    props: Vec<PropInfo>,
+    /// Name to index into `props`; networked props win over datamap ones.
+    by_name: HashMap<String, usize>,
+}
+
+impl ClassProps {
+    /// Walks a class's tables. Networked props come first, in table order with base
+    /// classes first, then the datamap fields from the most derived class down.
+    ///
+    /// # Safety
+    /// Both tables must be valid engine tables, or null.
+    pub unsafe fn from_tables(send_table: *const SendTable, datamap: *const DataMap) -> Self {
+        let mut send = Vec::new();
+        if let Some(table) = unsafe { send_table.as_ref() } {
+            walk_send_table(table, 0, 0, &mut send);
+        }
+        let mut data = Vec::new();
+        if let Some(map) = unsafe { datamap.as_ref() } {
+            walk_datamap(map, 0, 0, &mut data);
+        }
+
+        // The datamap knows the exact C++ type of networked members, which the
+        // send table only describes by how they are encoded.
+        let by_offset: HashMap<usize, &PropInfo> = data.iter().rev().map(|p| (p.offset, p)).collect();
+        for prop in &mut send {
+            if let Some(field) = by_offset.get(&prop.offset) {
+                if prop.size.is_none() && prop.count == field.count {
+                    prop.size = field.size;
+                }
+                if compatible(prop.kind, field.kind) {
+                    prop.kind = field.kind;
+                }
+            }
+        }
+
+        let mut props = ClassProps::default();
+        for prop in send.into_iter().chain(data) {
+            props.by_name.entry(prop.name.clone()).or_insert(props.props.len());
+            props.props.push(prop);
+        }
+        props
+    }
+
+    /// The prop called `name`, preferring the networked one.
+    pub fn get(&self, name: &str) -> Option<&PropInfo> {
+        self.by_name.get(name).map(|&i| &self.props[i])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &PropInfo> {
+        self.props.iter()
+    }
+
+    /// Whether `info` is one of this class's props, including the datamap entries
+    /// that [`get`](Self::get) hides behind a networked prop of the same name.
+    pub fn contains(&self, info: &PropInfo) -> bool {
+        self.props.iter().any(|prop| prop == info)
+    }
+
+    pub fn len(&self) -> usize {
+        self.props.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.props.is_empty()
+    }
+}
+
+/// Whether the datamap `field` kind refines the send table `send` kind.
+fn compatible(send: PropKind, field: PropKind) -> bool {
+    match send {
+        PropKind::Int => matches!(field, PropKind::Int | PropKind::Bool | PropKind::Short | PropKind::Char | PropKind::EHandle | PropKind::Color32),
+        PropKind::String => matches!(field, PropKind::String | PropKind::StringT),
+        _ => false,
+    }
+}
+
+fn walk_send_table(table: &SendTable, base: usize, depth: usize, out: &mut Vec<PropInfo>) {
+    if depth > MAX_DEPTH {
+        return;
+    }
+
+    for prop in table.props() {
+        if prop.flags & (SPROP_EXCLUDE | SPROP_INSIDEARRAY) != 0 {
+            continue;
+        }
+        let name = prop.get_name();
+        let offset = base + prop.offset.max(0) as usize;
+
+        match prop.get_type() {
+            SendPropType::DataTable => {
+                let Some(inner) = (unsafe { prop.data_table.as_ref() }) else { continue };
+                match array_table(inner) {
+                    // Arrays of networked vars are sent as tables of props named "000", "001", ...
+                    Some((first, stride)) => out.push(PropInfo {
+                        name,
+                        kind: PropKind::from_send(first.get_type()),
+                        offset: offset + first.offset.max(0) as usize,
+                        count: inner.props().len(),
+                        size: stride,
+                        source: PropSource::SendTable,
+                    }),
+                    None => walk_send_table(inner, offset, depth + 1, out),
+                }
+            }
+            SendPropType::Array => {
+                let Some(element) = (unsafe { prop.array_prop.as_ref() }) else { continue };
+                out.push(PropInfo {
+                    name,
+                    kind: PropKind::from_send(element.get_type()),
+                    offset: base + element.offset.max(0) as usize,
+                    count: prop.elements.max(1) as usize,
+                    size: (prop.element_stride > 0).then_some(prop.element_stride as usize),
+                    source: PropSource::SendTable,
+                });
+            }
+            ty => out.push(PropInfo {
+                name,
+                kind: PropKind::from_send(ty),
+                offset,
+                count: 1,
+                size: None,
+                source: PropSource::SendTable,
+            }),
+        }
+    }
+}
+
+/// The first element and the stride of a table that is really an array.
+fn array_table(table: &SendTable) -> Option<(&crate::types::SendProp, Option<usize>)> {
+    let props = table.props();
+    let first = props.first()?;
+    let is_array = props.iter().all(|p| {
+        let name = unsafe { raw_name(p.var_name) };
+        !name.is_empty() && name.bytes().all(|b| b.is_ascii_digit()) && p.get_type() != SendPropType::DataTable
+    });
+    if !is_array {
+        return None;
+    }
+    let stride = props.get(1).and_then(|second| usize::try_from(second.offset - first.offset).ok());
+    Some((first, stride))
+}
+
+fn walk_datamap(map: &DataMap, base: usize, depth: usize, out: &mut Vec<PropInfo>) {
+    let mut current = Some(map);
+    let mut level = depth;
+    while let Some(map) = current {
+        if level > MAX_DEPTH {
+            return;
+        }
+
+        for field in map.fields() {
+            if field.field_name.is_null() {
+                continue;
+            }
+            let offset = base + field.field_offset.max(0) as usize;
+            let ty = field.get_type();
+            match ty {
+                FieldType::Void | FieldType::Input | FieldType::Function => continue,
+                FieldType::Embedded => {
+                    if let Some(inner) = unsafe { field.td.as_ref() } {
+                        walk_datamap(inner, offset, level + 1, out);
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+
+            let count = field.field_size.max(1) as usize;
+            let total = usize::try_from(field.field_size_in_bytes).ok().filter(|&s| s > 0);
+            let mut info = PropInfo {
+                name: field.get_name(),
+                kind: PropKind::from_field(ty),
+                offset,
+                count,
+                size: total.map(|s| s / count),
+                source: PropSource::DataMap,
+            };
+            // `char` arrays are string buffers.
+            if info.kind == PropKind::Char && count > 1 {
+                info.kind = PropKind::String;
+                info.count = 1;
+                info.size = total;
+            }
+            out.push(info);
+        }
+
+        current = map.base();
+        level += 1;
+    }
+}
+
+unsafe fn raw_name<'a>(ptr: *const c_char) -> &'a str {
+    if ptr.is_null() {
+        return "";
+    }
+    unsafe { CStr::from_ptr(ptr) }.to_str().unwrap_or("")
+}
+
+// --- CACHE ---
+
+/// Keyed by the addresses of the class's send table and datamap, which live for as
+/// long as the server module is loaded. Classes sharing a `ServerClass` can still
+/// differ in their datamap, so both are part of the key.
+type ClassCache = RwLock<HashMap<(usize, usize), Arc<ClassProps>>>;
+
+static CLASS_CACHE: OnceLock<ClassCache> = OnceLock::new();
+
+fn class_cache() -> &'static ClassCache {
+    CLASS_CACHE.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Forgets every cached class, e.g. after the server module was reloaded.
+pub fn clear_cache() {
+    if let Ok(mut cache) = class_cache().write() {
+        cache.clear();
+    }
+}
+
+// --- TYPED ACCESS ---
+
+/// A Rust type a prop can be read as and written from.
+///
+/// # Safety
+/// `accepts` must only allow props whose memory is a valid `Self`.
+pub unsafe trait PropType: Copy {
+    /// Whether a prop of `kind`, with elements of `size` bytes if known, holds a `Self`.
+    fn accepts(kind: PropKind, size: Option<usize>) -> bool;
+}
+
+macro_rules! prop_type {
+    ($($ty:ty => $($kind:ident)|+;)*) => {$(
+        unsafe impl PropType for $ty {
+            fn accepts(kind: PropKind, size: Option<usize>) -> bool {
+                matches!(kind, $(PropKind::$kind)|+) && size.is_none_or(|s| s == size_of::<$ty>())
+            }
+        }
+    )*};
+}
+
+prop_type! {
+    i32 => Int | EHandle | Color32;
+    u32 => Int | EHandle | Color32;
+    i16 => Int | Short;
+    u16 => Int | Short;
+    i8 => Int | Char | Bool;
+    u8 => Int | Char | Bool;
+    i64 => Int64;
+    u64 => Int64;
+    f32 => Float;
+    Vector => Vector;
+    QAngle => Vector;
+    CBaseHandle => EHandle | Int;
+    [u8; 4] => Color32 | Int;
+}
+
+// A `bool` may only be read where the engine stores one, as other bytes are not valid `bool`s.
+unsafe impl PropType for bool {
+    fn accepts(kind: PropKind, size: Option<usize>) -> bool {
+        kind == PropKind::Bool && size.is_none_or(|s| s == 1)
+    }
+}
+
+/// A prop's value read without knowing its type up front, for display.
+#[derive(Debug, Clone)]
+pub enum PropValue {
+    Int(i64),
+    Bool(bool),
+    Float(f32),
+    Vector(Vector),
+    Vector2D([f32; 2]),
+    Quaternion([f32; 4]),
+    Color([u8; 4]),
+    String(String),
+    Handle(CBaseHandle),
+    Pointer(usize),
+    /// A member whose type cannot be shown, with its size if known.
+    Opaque(Option<usize>),
+}
+
+impl fmt::Display for PropValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PropValue::Int(v) => write!(f, "{}", v),
+            PropValue::Bool(v) => write!(f, "{}", v),
+            PropValue::Float(v) => write!(f, "{}", v),
+            PropValue::Vector(v) => write!(f, "{} {} {}", v.x, v.y, v.z),
+            PropValue::Vector2D([x, y]) => write!(f, "{} {}", x, y),
+            PropValue::Quaternion([x, y, z, w]) => write!(f, "{} {} {} {}", x, y, z, w),
+            PropValue::Color([r, g, b, a]) => write!(f, "{} {} {} {}", r, g, b, a),
+            PropValue::String(v) => f.write_str(v),
+            PropValue::Handle(h) => write!(f, "{:#010x}", h.0),
+            PropValue::Pointer(p) => write!(f, "{:#x}", p),
+            PropValue::Opaque(Some(size)) => write!(f, "<{} bytes>", size),
+            PropValue::Opaque(None) => f.write_str("<?>"),
+        }
+    }
+}
+
+/// Address of element `index` of `info` in the object at `base`.
+fn element(base: *const u8, info: &PropInfo, index: usize) -> Option<*const u8> {
+    (index < info.count).then(|| unsafe { base.add(info.offset + index * info.stride()) })
+}
+
+unsafe fn read<T: PropType>(base: *const u8, info: &PropInfo, index: usize) -> Option<T> {
+    if !T::accepts(info.kind, info.size) {
+        return None;
+    }
+    let ptr = element(base, info, index)?;
+    Some(unsafe { (ptr as *const T).read_unaligned() })
+}
+
+unsafe fn write<T: PropType>(base: *mut u8, info: &PropInfo, index: usize, value: T) -> bool {
+    // A networked int with no datamap field to size it may be as narrow as one bit
+    // on the wire and one byte in the entity, so writing a guessed width could
+    // clobber its neighbours.
+    if info.kind == PropKind::Int && info.size.is_none() {
+        return false;
+    }
+    if !T::accepts(info.kind, info.size) {
+        return false;
+    }
+    let Some(ptr) = element(base, info, index) else { return false };
+    unsafe { (ptr as *mut T).write_unaligned(value) };
+    true
+}
+
+unsafe fn read_string(base: *const u8, info: &PropInfo) -> Option<String> {
+    let ptr = element(base, info, 0)?;
+    let text = match info.kind {
+        PropKind::StringT => {
+            let text = unsafe { (ptr as *const *const c_char).read_unaligned() };
+            if text.is_null() {
+                return Some(String::new());
+            }
+            unsafe { CStr::from_ptr(text) }.to_bytes()
+        }
+        PropKind::String => {
+            // Scanned a byte at a time: with no known size, the bytes past the
+            // terminator may well lie outside the entity.
+            let max = info.size.unwrap_or(MAX_INLINE_STRING);
+            let len = (0..max).take_while(|&i| unsafe { ptr.add(i).read() } != 0).count();
+            unsafe { std::slice::from_raw_parts(ptr, len) }
+        }
+        _ => return None,
+    };
+    Some(String::from_utf8_lossy(text).into_owned())
+}
+
+unsafe fn read_value(base: *const u8, info: &PropInfo, index: usize) -> Option<PropValue> {
+    let ptr = element(base, info, index)?;
+    let value = unsafe {
+        match (info.kind, info.size.or(info.kind.natural_size())) {
+            (PropKind::String | PropKind::StringT, _) => PropValue::String(read_string(base, info)?),
+            (PropKind::Int | PropKind::Short | PropKind::Char, Some(1)) => PropValue::Int((ptr as *const i8).read() as i64),
+            (PropKind::Int | PropKind::Short | PropKind::Char, Some(2)) => PropValue::Int((ptr as *const i16).read_unaligned() as i64),
+            (PropKind::Int, Some(4)) => PropValue::Int((ptr as *const i32).read_unaligned() as i64),
+            (PropKind::Int64, _) => PropValue::Int((ptr as *const i64).read_unaligned()),
+            (PropKind::Bool, _) => PropValue::Bool(ptr.read() != 0),
+            (PropKind::Float, Some(4)) => PropValue::Float((ptr as *const f32).read_unaligned()),
+            (PropKind::Vector, Some(12)) => PropValue::Vector((ptr as *const Vector).read_unaligned()),
+            (PropKind::Vector2D, _) => PropValue::Vector2D((ptr as *const [f32; 2]).read_unaligned()),
+            (PropKind::Quaternion, _) => PropValue::Quaternion((ptr as *const [f32; 4]).read_unaligned()),
+            (PropKind::Color32, _) => PropValue::Color((ptr as *const [u8; 4]).read_unaligned()),
+            (PropKind::EHandle, _) => PropValue::Handle((ptr as *const CBaseHandle).read_unaligned()),
+            (PropKind::Pointer, _) => PropValue::Pointer((ptr as *const usize).read_unaligned()),
+            (_, size) => PropValue::Opaque(size),
+        }
+    };
+    Some(value)
+}
+
+impl CBaseEntity {
+    /// The members of this entity's class, built on first use and cached.
+    pub fn class_props(&self) -> Option<Arc<ClassProps>> {
+        let send_table = self.get_server_class().map_or(std::ptr::null(), |class| class.table as *const SendTable);
+        let datamap = self.get_data_desc_map().map_or(std::ptr::null(), |map| map as *const DataMap);
+        if send_table.is_null() && datamap.is_null() {
+            return None;
+        }
+
+        let key = (send_table as usize, datamap as usize);
+        if let Some(props) = class_cache().read().ok()?.get(&key) {
+            return Some(props.clone());
+        }
+        let props = Arc::new(unsafe { ClassProps::from_tables(send_table, datamap) });
+        class_cache().write().ok()?.insert(key, props.clone());
+        Some(props)
+    }
+
+    /// Reads the prop `name` as a `T`, or the first element of an array prop.
+    /// `None` if there is no such prop or it does not hold a `T`.
+    pub fn prop<T: PropType>(&self, name: &str) -> Option<T> {
+        self.prop_at(name, 0)
+    }
+
+    /// Reads element `index` of the array prop `name`.
+    pub fn prop_at<T: PropType>(&self, name: &str, index: usize) -> Option<T> {
+        let props = self.class_props()?;
+        unsafe { read(self as *const _ as *const u8, props.get(name)?, index) }
+    }
+
+    /// Writes the prop `name`. Returns `false` if there is no such prop, it does not
+    /// hold a `T`, or it is an integer of unknown width. Networked props are sent to
+    /// clients with the next update.
+    pub fn set_prop<T: PropType>(&mut self, name: &str, value: T) -> bool {
+        self.set_prop_at(name, 0, value)
+    }
+
+    /// Writes element `index` of the array prop `name`.
+    pub fn set_prop_at<T: PropType>(&mut self, name: &str, index: usize, value: T) -> bool {
+        let Some(props) = self.class_props() else { return false };
+        let Some(info) = props.get(name) else { return false };
+        if !unsafe { write(self as *mut _ as *mut u8, info, index, value) } {
+            return false;
+        }
+        if info.source == PropSource::SendTable && let Some(edict) = self.get_edict() {
+            edict.state_changed();
+        }
+        true
+    }
+
+    /// Reads a string prop, stored either in the entity or as a `string_t`.
+    pub fn prop_string(&self, name: &str) -> Option<String> {
+        let props = self.class_props()?;
+        unsafe { read_string(self as *const _ as *const u8, props.get(name)?) }
+    }
+
+    /// Reads element `index` of any prop of this entity's class, whatever its type.
+    /// `None` if `info` does not describe a prop of this entity's class.
+    pub fn prop_value(&self, info: &PropInfo, index: usize) -> Option<PropValue> {
+        let props = self.class_props()?;
+        if !props.contains(info) {
+            return None;
+        }
+        unsafe { read_value(self as *const _ as *const u8, info, index) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::c_int;
+    use crate::types::{SendProp, TypeDescription};
+
+    fn send_prop(name: &'static CStr, ty: c_int, offset: c_int) -> SendProp {
+        let mut prop: SendProp = unsafe { std::mem::zeroed() };
+        prop.var_name = name.as_ptr();
+        prop.prop_type = ty;
+        prop.offset = offset;
+        prop
+    }
+
+    fn table(props: &mut [SendProp]) -> SendTable {
+        let mut table: SendTable = unsafe { std::mem::zeroed() };
+        table.props = props.as_mut_ptr();
+        table.prop_count = props.len() as c_int;
+        table
+    }
+
+    fn field(name: &'static CStr, ty: FieldType, offset: c_int, count: u16, bytes: c_int) -> TypeDescription {
+        let mut field: TypeDescription = unsafe { std::mem::zeroed() };
+        field.field_name = name.as_ptr();
+        field.field_type = match ty {
+            FieldType::Float => 1,
+            FieldType::String => 2,
+            FieldType::Vector => 3,
+            FieldType::Integer => 5,
+            FieldType::Boolean => 6,
+            FieldType::Character => 8,
+            FieldType::Embedded => 10,
+            _ => unreachable!(),
+        };
+        field.field_offset = offset;
+        field.field_size = count;
+        field.field_size_in_bytes = bytes;
+        field
+    }
+
+    fn datamap(fields: &mut [TypeDescription], base: *mut DataMap) -> DataMap {
+        let mut map: DataMap = unsafe { std::mem::zeroed() };
+        map.data_desc = fields.as_mut_ptr();
+        map.data_num_fields = fields.len() as c_int;
+        map.base_map = base;
+        map
+    }
+
+    /// The layout the fake tables below describe.
+    #[repr(C)]
+    #[derive(Default)]
+    struct FakeEntity {
+        _vtable: [u8; 8],
+        health: i32,            // 8, networked through "baseclass"
+        origin: Vector,         // 12, networked
+        on_ground: bool,        // 24, networked as an int, a bool in the datamap
+        ammo: [i32; 3],         // 28, networked as an array table
+        name: [u8; 16],         // 40, a char buffer in the datamap
+        collision_mins: Vector, // 56, in an embedded struct
+    }
+
+    #[test]
+    fn walks_tables_and_reads_typed_props() {
+        let mut ammo_props = [send_prop(c"000", 0, 0), send_prop(c"001", 0, 4), send_prop(c"002", 0, 8)];
+        let mut ammo_table = table(&mut ammo_props);
+        let mut base_props = [send_prop(c"m_iHealth", 0, 8)];
+        let mut base_table = table(&mut base_props);
+
+        let mut excluded = send_prop(c"m_vecMins", 2, 0);
+        excluded.flags = SPROP_EXCLUDE;
+        let mut baseclass = send_prop(c"baseclass", 6, 0);
+        baseclass.data_table = &mut base_table;
+        let mut ammo = send_prop(c"m_iAmmo", 6, 28);
+        ammo.data_table = &mut ammo_table;
+        let mut props = [baseclass, excluded, send_prop(c"m_vecOrigin", 2, 12), send_prop(c"m_bOnGround", 0, 24), ammo];
+        let send = table(&mut props);
+
+        let mut embedded_fields = [field(c"m_vecMins", FieldType::Vector, 0, 1, 12)];
+        let mut embedded = datamap(&mut embedded_fields, std::ptr::null_mut());
+        let mut base_fields = [field(c"m_iHealth", FieldType::Integer, 8, 1, 4)];
+        let mut base_map = datamap(&mut base_fields, std::ptr::null_mut());
+        let mut collision = field(c"m_Collision", FieldType::Embedded, 56, 1, 12);
+        collision.td = &mut embedded;
+        let mut fields = [
+            field(c"m_bOnGround", FieldType::Boolean, 24, 1, 1),
+            field(c"m_iName", FieldType::Character, 40, 16, 16),
+            collision,
+        ];
+        let map = datamap(&mut fields, &mut base_map);
+
+        let props = unsafe { ClassProps::from_tables(&send, &map) };
+        let names: Vec<(&str, PropSource)> = props.iter().map(|p| (p.name.as_str(), p.source)).collect();
+        assert_eq!(names, [
+            ("m_iHealth", PropSource::SendTable),
+            ("m_vecOrigin", PropSource::SendTable),
+            ("m_bOnGround", PropSource::SendTable),
+            ("m_iAmmo", PropSource::SendTable),
+            ("m_bOnGround", PropSource::DataMap),
+            ("m_iName", PropSource::DataMap),
+            ("m_vecMins", PropSource::DataMap),
+            ("m_iHealth", PropSource::DataMap),
+        ]);
+        // The datamap tells the networked int apart as a bool.
+        let on_ground = props.get("m_bOnGround").unwrap();
+        assert_eq!((on_ground.kind, on_ground.size, on_ground.source), (PropKind::Bool, Some(1), PropSource::SendTable));
+        let ammo = props.get("m_iAmmo").unwrap();
+        assert_eq!((ammo.offset, ammo.count, ammo.size), (28, 3, Some(4)));
+        assert_eq!(props.get("m_vecMins").unwrap().offset, 56);
+        // `get` hides the datamap copy of a networked name, but it is still one of the class's props.
+        let health_field = props.iter().find(|p| p.name == "m_iHealth" && p.source == PropSource::DataMap).unwrap();
+        assert!(props.contains(health_field) && props.contains(on_ground));
+        assert!(!props.contains(&PropInfo { offset: 12, ..health_field.clone() }));
+
+        let mut entity = FakeEntity { health: 80, on_ground: true, ammo: [1, 2, 3], ..Default::default() };
+        entity.name[..5].copy_from_slice(b"chell");
+        let base = &mut entity as *mut FakeEntity as *mut u8;
+        unsafe {
+            assert_eq!(read::<i32>(base, props.get("m_iHealth").unwrap(), 0), Some(80));
+            assert_eq!(read::<bool>(base, on_ground, 0), Some(true));
+            assert_eq!(read::<i32>(base, on_ground, 0), None, "a bool is not an i32");
+            assert_eq!(read::<i32>(base, ammo, 2), Some(3));
+            assert_eq!(read::<i32>(base, ammo, 3), None);
+            assert!(write(base, props.get("m_vecOrigin").unwrap(), 0, Vector { x: 1.0, y: 2.0, z: 3.0 }));
+            assert!(!write(base, props.get("m_vecOrigin").unwrap(), 0, 1.0f32));
+            assert_eq!(read_string(base, props.get("m_iName").unwrap()).as_deref(), Some("chell"));
+            assert!(matches!(read_value(base, ammo, 1), Some(PropValue::Int(2))));
+            assert!(matches!(read_value(base, health_field, 0), Some(PropValue::Int(80))));
+        }
+        assert_eq!(entity.origin.y, 2.0);
+    }
+
+    #[test]
+    fn reads_unsized_inline_strings_up_to_the_terminator() {
+        let info = PropInfo { name: "m_szName".into(), kind: PropKind::String, offset: 0, count: 1, size: None, source: PropSource::DataMap };
+        // Nothing may be read past the null byte, which ends the allocation here.
+        let text = Box::new(*b"hi\0");
+        assert_eq!(unsafe { read_string(text.as_ptr(), &info) }.as_deref(), Some("hi"));
+
+        let sized = PropInfo { size: Some(4), ..info };
+        assert_eq!(unsafe { read_string(b"full".as_ptr(), &sized) }.as_deref(), Some("full"));
+    }
+
+    #[test]
+    fn writes_ints_only_when_their_width_is_known() {
+        let info = PropInfo { name: "m_bFlag".into(), kind: PropKind::Int, offset: 0, count: 1, size: None, source: PropSource::SendTable };
+        let mut memory = [7u8, 1, 2, 3];
+        let base = memory.as_mut_ptr();
+        unsafe {
+            assert_eq!(read::<i32>(base, &info, 0), Some(i32::from_le_bytes([7, 1, 2, 3])), "reads stay lenient");
+            assert!(!write(base, &info, 0, 0i32));
+            assert!(!write(base, &info, 0, 0u8));
+            assert_eq!(memory, [7, 1, 2, 3]);
+
+            let sized = PropInfo { size: Some(1), ..info };
+            assert!(write(base, &sized, 0, 0u8));
+            assert_eq!(memory, [0, 1, 2, 3]);
+        }
+    }
+}
@@ -4,155 +4,183 @@ use std::ffi::{c_char, c_int, c_void, CStr, CString};
 use std::ptr;
 
 use crate::types::*;
-use crate::platform::abi::vfn;
-
-pub struct IVEngineServer {
-    pub(crate) this: *mut c_void,
-
-    pub(crate) change_level: vfn!((this: *mut c_void, s1: *const c_char, s2: *const c_char)),
-    pub(crate) is_map_valid: vfn!((this: *mut c_void, filename: *const c_char) -> c_int),
-    pub(crate) is_dedicated_server: vfn!((this: *mut c_void) -> bool),
-    pub(crate) is_in_edit_mode: vfn!((this: *mut c_void) -> c_int),
-    pub(crate) get_launch_options: vfn!((this: *mut c_void) -> *mut KeyValues),
-    pub(crate) precache_model: vfn!((this: *mut c_void, s: *const c_char, preload: bool) -> c_int),
-    pub(crate) precache_sentence_file: vfn!((this: *mut c_void, s: *const c_char, preload: bool) -> c_int),
-    pub(crate) precache_decal: vfn!((this: *mut c_void, name: *const c_char, preload: bool) -> c_int),
-    pub(crate) precache_generic: vfn!((this: *mut c_void, s: *const c_char, preload: bool) -> c_int),
-    pub(crate) is_model_precached: vfn!((this: *mut c_void, s: *const c_char) -> bool),
-    pub(crate) is_decal_precached: vfn!((this: *mut c_void, s: *const c_char) -> bool),
-    pub(crate) is_generic_precached: vfn!((this: *mut c_void, s: *const c_char) -> bool),
-    pub(crate) get_cluster_for_origin: vfn!((this: *mut c_void, org: *const Vector) -> c_int),
-    pub(crate) get_pvs_for_cluster: vfn!((this: *mut c_void, cluster: c_int, outputpvslength: c_int, outputpvs: *mut u8) -> c_int),
-    pub(crate) check_origin_in_pvs: vfn!((this: *mut c_void, org: *const Vector, checkpvs: *const u8, checkpvssize: c_int) -> bool),
-    pub(crate) check_box_in_pvs: vfn!((this: *mut c_void, mins: *const Vector, maxs: *const Vector, checkpvs: *const u8, checkpvssize: c_int) -> bool),
-    pub(crate) get_player_user_id: vfn!((this: *mut c_void, e: *const Edict) -> c_int),
-    pub(crate) get_player_network_id_string: vfn!((this: *mut c_void, e: *const Edict) -> *const c_char),
-    pub(crate) is_user_id_in_use: vfn!((this: *mut c_void, user_id: c_int) -> bool),
-    pub(crate) get_loading_progress_for_user_id: vfn!((this: *mut c_void, user_id: c_int) -> c_int),
-    pub(crate) get_entity_count: vfn!((this: *mut c_void) -> c_int),
-    pub(crate) get_player_net_info: vfn!((this: *mut c_void, player_index: c_int) -> *mut INetChannelInfo),
-    pub(crate) create_edict: vfn!((this: *mut c_void, force_edict_index: c_int) -> *mut Edict),
-    pub(crate) remove_edict: vfn!((this: *mut c_void, e: *mut Edict)),
-    pub(crate) pv_alloc_ent_private_data: vfn!((this: *mut c_void, cb: c_int) -> *mut c_void),
-    pub(crate) free_ent_private_data: vfn!((this: *mut c_void, entity: *mut c_void)),
-    pub(crate) save_alloc_memory: vfn!((this: *mut c_void, num: usize, size: usize) -> *mut c_void),
-    pub(crate) save_free_memory: vfn!((this: *mut c_void, save_mem: *mut c_void)),
-    pub(crate) emit_ambient_sound: vfn!((this: *mut c_void, entindex: c_int, pos: *const Vector, samp: *const c_char, vol: f32, soundlevel: SoundLevelT, flags: c_int, pitch: c_int, delay: f32)),
-    pub(crate) fade_client_volume: vfn!((this: *mut c_void, edict: *const Edict, fade_percent: f32, fade_out_seconds: f32, hold_time: f32, fade_in_seconds: f32)),
-    pub(crate) sentence_group_pick: vfn!((this: *mut c_void, group_index: c_int, name: *mut c_char, name_buf_len: c_int) -> c_int),
-    pub(crate) sentence_group_pick_sequential: vfn!((this: *mut c_void, group_index: c_int, name: *mut c_char, name_buf_len: c_int, sentence_index: c_int, reset: c_int) -> c_int),
-    pub(crate) sentence_index_from_name: vfn!((this: *mut c_void, sentence_name: *const c_char) -> c_int),
-    pub(crate) sentence_name_from_index: vfn!((this: *mut c_void, sentence_index: c_int) -> *const c_char),
-    pub(crate) sentence_group_index_from_name: vfn!((this: *mut c_void, group_name: *const c_char) -> c_int),
-    pub(crate) sentence_group_name_from_index: vfn!((this: *mut c_void, group_index: c_int) -> *const c_char),
-    pub(crate) sentence_length: vfn!((this: *mut c_void, sentence_index: c_int) -> f32),
-    pub(crate) server_command: vfn!((this: *mut c_void, str: *const c_char)),
-    pub(crate) server_execute: vfn!((this: *mut c_void)),
-    pub(crate) client_command: unsafe extern "C" fn(this: *mut c_void, edict: *mut Edict, fmt: *const c_char, ...), // Variadic (cdecl)
-    pub(crate) light_style: vfn!((this: *mut c_void, style: c_int, val: *const c_char)),
-    pub(crate) static_decal: vfn!((this: *mut c_void, origin: *const Vector, decal_index: c_int, entity_index: c_int, model_index: c_int, lowpriority: bool)),
-    pub(crate) message_determine_multicast_recipients: vfn!((this: *mut c_void, usepas: bool, origin: *const Vector, playerbits: *mut CPlayerBitVec)),
-    pub(crate) entity_message_begin: vfn!((this: *mut c_void, ent_index: c_int, ent_class: *mut ServerClass, reliable: bool) -> *mut BfWrite),
-    pub(crate) user_message_begin: vfn!((this: *mut c_void, filter: *mut IRecipientFilter, msg_type: c_int, msg_name: *const c_char) -> *mut BfWrite),
-    pub(crate) message_end: vfn!((this: *mut c_void)),
-    pub(crate) client_printf: vfn!((this: *mut c_void, edict: *mut Edict, msg: *const c_char)),
-    pub(crate) con_nprintf: unsafe extern "C" fn(this: *mut c_void, pos: c_int, fmt: *const c_char, ...), // Variadic (cdecl)
-    // pub(crate) con_nxprintf: unsafe extern "C" fn(this: *mut c_void, info: *const ConNPrintS, fmt: *const c_char, ...), // Variadic (cdecl)
-    pub(crate) set_view: vfn!((this: *mut c_void, client: *const Edict, viewent: *const Edict)),
-    pub(crate) crosshair_angle: vfn!((this: *mut c_void, client: *const Edict, pitch: f32, yaw: f32)),
-    pub(crate) get_game_dir: vfn!((this: *mut c_void, buf: *mut c_char, maxlen: c_int)),
-    pub(crate) compare_file_time: vfn!((this: *mut c_void, filename1: *const c_char, filename2: *const c_char, compare: *mut c_int) -> c_int),
-    pub(crate) lock_network_string_tables: vfn!((this: *mut c_void, lock: bool) -> bool),
-    pub(crate) create_fake_client: vfn!((this: *mut c_void, netname: *const c_char) -> *mut Edict),
-    pub(crate) get_client_con_var_value: vfn!((this: *mut c_void, client_index: c_int, name: *const c_char) -> *const c_char),
-    pub(crate) parse_file: vfn!((this: *mut c_void, data: *const c_char, token: *mut c_char, maxlen: c_int) -> *const c_char),
-    pub(crate) copy_file: vfn!((this: *mut c_void, source: *const c_char, dest: *const c_char) -> bool),
-    pub(crate) reset_pvs: vfn!((this: *mut c_void, pvs: *mut u8, pvssize: c_int)),
-    pub(crate) add_origin_to_pvs: vfn!((this: *mut c_void, origin: *const Vector)),
-    pub(crate) set_area_portal_state: vfn!((this: *mut c_void, portal_number: c_int, is_open: c_int)),
-    pub(crate) playback_temp_entity: vfn!((this: *mut c_void, filter: *mut IRecipientFilter, delay: f32, sender: *const c_void, st: *const SendTable, class_id: c_int)),
-    pub(crate) check_headnode_visible: vfn!((this: *mut c_void, nodenum: c_int, pvs: *const u8, vissize: c_int) -> c_int),
-    pub(crate) check_areas_connected: vfn!((this: *mut c_void, area1: c_int, area2: c_int) -> c_int),
-    pub(crate) get_area: vfn!((this: *mut c_void, origin: *const Vector) -> c_int),
-    pub(crate) get_area_bits: vfn!((this: *mut c_void, area: c_int, bits: *mut u8, buflen: c_int)),
-    pub(crate) get_area_portal_plane: vfn!((this: *mut c_void, view_origin: *const Vector, portal_key: c_int, plane: *mut VPlane) -> bool),
-    pub(crate) load_game_state: vfn!((this: *mut c_void, map_name: *const c_char, create_players: bool) -> bool),
-    pub(crate) load_adjacent_ents: vfn!((this: *mut c_void, old_level: *const c_char, landmark_name: *const c_char)),
-    pub(crate) clear_save_dir: vfn!((this: *mut c_void)),
-    pub(crate) get_map_entities_string: vfn!((this: *mut c_void) -> *const c_char),
-    pub(crate) text_message_get: vfn!((this: *mut c_void, name: *const c_char) -> *mut ClientTextMessage),
-    pub(crate) log_print: vfn!((this: *mut c_void, msg: *const c_char)),
-    pub(crate) is_log_enabled: vfn!((this: *mut c_void) -> bool),
-    pub(crate) build_entity_cluster_list: vfn!((this: *mut c_void, edict: *mut Edict, pvs_info: *mut PVSInfoT)),
-    pub(crate) solid_moved: vfn!((this: *mut c_void, solid_ent: *mut Edict, solid_collide: *mut ICollideable, prev_abs_origin: *const Vector, bounds_only: bool)),
-    pub(crate) trigger_moved: vfn!((this: *mut c_void, trigger_ent: *mut Edict, bounds_only: bool)),
-    pub(crate) create_spatial_partition: vfn!((this: *mut c_void, worldmin: *const Vector, worldmax: *const Vector) -> *mut ISpatialPartition),
-    pub(crate) destroy_spatial_partition: vfn!((this: *mut c_void, partition: *mut ISpatialPartition)),
-    pub(crate) draw_map_to_scratch_pad: vfn!((this: *mut c_void, pad: *mut IScratchPad3D, flags: u32)),
-    pub(crate) get_entity_transmit_bits_for_client: vfn!((this: *mut c_void, client_index: c_int) -> *const CBitVec),
-    pub(crate) is_paused: vfn!((this: *mut c_void) -> bool),
-    pub(crate) get_timescale: vfn!((this: *mut c_void) -> f32),
-    pub(crate) force_exact_file: vfn!((this: *mut c_void, s: *const c_char)),
-    pub(crate) force_model_bounds: vfn!((this: *mut c_void, s: *const c_char, mins: *const Vector, maxs: *const Vector)),
-    pub(crate) clear_save_dir_after_client_load: vfn!((this: *mut c_void)),
-    pub(crate) set_fake_client_con_var_value: vfn!((this: *mut c_void, entity: *mut Edict, cvar: *const c_char, value: *const c_char)),
-    pub(crate) force_simple_material: vfn!((this: *mut c_void, s: *const c_char)),
-    pub(crate) is_in_commentary_mode: vfn!((this: *mut c_void) -> c_int),
-    pub(crate) is_level_main_menu_background: vfn!((this: *mut c_void) -> bool),
-    pub(crate) set_area_portal_states: vfn!((this: *mut c_void, portal_numbers: *const c_int, is_open: *const c_int, n_portals: c_int)),
-    pub(crate) notify_edict_flags_change: vfn!((this: *mut c_void, edict_index: c_int)),
-    pub(crate) get_prev_check_transmit_info: vfn!((this: *mut c_void, player_edict: *mut Edict) -> *const CCheckTransmitInfo),
-    pub(crate) get_shared_edict_change_info: vfn!((this: *mut c_void) -> *mut CSharedEdictChangeInfo),
-    pub(crate) allow_immediate_edict_reuse: vfn!((this: *mut c_void)),
-    pub(crate) is_internal_build: vfn!((this: *mut c_void) -> bool),
-    pub(crate) get_change_accessor: vfn!((this: *mut c_void, edict: *const Edict) -> *mut IChangeInfoAccessor),
-    pub(crate) get_most_recently_loaded_file_name: vfn!((this: *mut c_void) -> *const c_char),
-    pub(crate) get_save_file_name: vfn!((this: *mut c_void) -> *const c_char),
-    pub(crate) clean_up_entity_cluster_list: vfn!((this: *mut c_void, pvs_info: *mut PVSInfoT)),
-    pub(crate) get_app_id: vfn!((this: *mut c_void) -> c_int),
-    pub(crate) is_low_violence: vfn!((this: *mut c_void) -> bool),
-    pub(crate) is_any_client_low_violence: vfn!((this: *mut c_void) -> bool),
-    pub(crate) start_query_cvar_value: vfn!((this: *mut c_void, player_entity: *mut Edict, name: *const c_char) -> QueryCvarCookieT),
-    pub(crate) insert_server_command: vfn!((this: *mut c_void, str: *const c_char)),
-    pub(crate) get_player_info: vfn!((this: *mut c_void, ent_num: c_int, pinfo: *mut PlayerInfo) -> bool),
-    pub(crate) is_client_fully_authenticated: vfn!((this: *mut c_void, edict: *mut Edict) -> bool),
-    pub(crate) set_dedicated_server_benchmark_mode: vfn!((this: *mut c_void, benchmark_mode: bool)),
-    pub(crate) is_split_screen_player: vfn!((this: *mut c_void, ent_num: c_int) -> bool),
-    pub(crate) get_split_screen_player_attach_to_edict: vfn!((this: *mut c_void, ent_num: c_int) -> *mut Edict),
-    pub(crate) get_num_split_screen_users_attached_to_edict: vfn!((this: *mut c_void, ent_num: c_int) -> c_int),
-    pub(crate) get_split_screen_player_for_edict: vfn!((this: *mut c_void, ent_num: c_int, slot: c_int) -> *mut Edict),
-    pub(crate) is_override_load_game_ents_on: vfn!((this: *mut c_void) -> bool),
-    pub(crate) force_flush_entity: vfn!((this: *mut c_void, entity_index: c_int)),
-    pub(crate) get_single_player_shared_memory_space: vfn!((this: *mut c_void, name: *const c_char, ent_num: c_int) -> *mut ISPSharedMemory),
-    pub(crate) alloc_level_static_data: vfn!((this: *mut c_void, bytes: usize) -> *mut c_void),
-    pub(crate) get_cluster_count: vfn!((this: *mut c_void) -> c_int),
-    pub(crate) get_all_cluster_bounds: vfn!((this: *mut c_void, bbox_list: *mut BBoxT, max_bbox: c_int) -> c_int),
-    pub(crate) is_creating_reslist: vfn!((this: *mut c_void) -> bool),
-    pub(crate) is_creating_xbox_reslist: vfn!((this: *mut c_void) -> bool),
-    pub(crate) is_dedicated_server_for_xbox: vfn!((this: *mut c_void) -> bool),
-    pub(crate) pause: vfn!((this: *mut c_void, pause: bool, force: bool)),
-    pub(crate) set_timescale: vfn!((this: *mut c_void, timescale: f32)),
-    pub(crate) set_gamestats_data: vfn!((this: *mut c_void, gamestats_data: *mut CGamestatsData)),
-    pub(crate) get_gamestats_data: vfn!((this: *mut c_void) -> *mut CGamestatsData),
-    pub(crate) get_client_steam_id: vfn!((this: *mut c_void, player_edict: *const Edict) -> *const CSteamID),
-    pub(crate) get_game_server_steam_id: vfn!((this: *mut c_void) -> *const CSteamID),
-    pub(crate) host_validate_session: vfn!((this: *mut c_void)),
-    pub(crate) refresh_screen_if_necessary: vfn!((this: *mut c_void)),
-    pub(crate) has_paintmap: vfn!((this: *mut c_void) -> bool),
-    pub(crate) sphere_paint_surface: vfn!((this: *mut c_void, model: *const ModelT, pos: *const Vector, paint_type: u8, radius: f32, alpha: f32) -> bool),
-    pub(crate) sphere_trace_paint_surface: vfn!((this: *mut c_void, model: *const ModelT, pos: *const Vector, dir: *const Vector, radius: f32, paint_types: *mut CUtlVector)),
-    pub(crate) remove_all_paint: vfn!((this: *mut c_void)),
-    pub(crate) paint_all_surfaces: vfn!((this: *mut c_void, paint_type: u8)),
-    pub(crate) remove_paint: vfn!((this: *mut c_void, model: *const ModelT)),
-    pub(crate) client_command_key_values: vfn!((this: *mut c_void, edict: *mut Edict, command: *mut KeyValues)),
-    pub(crate) get_client_xuid: vfn!((this: *mut c_void, player_edict: *const Edict) -> u64),
-    pub(crate) is_active_app: vfn!((this: *mut c_void) -> bool),
-    pub(crate) set_no_clip_enabled: vfn!((this: *mut c_void, enabled: bool)),
-    pub(crate) get_paintmap_data_rle: vfn!((this: *mut c_void, mapdata: *mut CUtlVector)),
-    pub(crate) load_paintmap_data_rle: vfn!((this: *mut c_void, mapdata: *mut CUtlVector)),
-    pub(crate) send_paintmap_data_to_client: vfn!((this: *mut c_void, edict: *mut Edict)),
-    pub(crate) get_latency_for_choreo_sounds: vfn!((this: *mut c_void) -> f32),
-    pub(crate) get_client_cross_play_platform: vfn!((this: *mut c_void, client_index: c_int) -> c_int),
+use crate::interface::interface;
+
+interface! {
+    pub struct IVEngineServer: "IVEngineServer" {
+        fn change_level(s1: *const c_char, s2: *const c_char) = 0;
+        fn is_map_valid(filename: *const c_char) -> c_int = 1;
+        /// Is this a dedicated server?
+        [safe] fn is_dedicated_server() -> bool = 2;
+        fn is_in_edit_mode() -> c_int = 3;
+        fn get_launch_options() -> *mut KeyValues = 4;
+        fn precache_model(s: *const c_char, preload: bool) -> c_int = 5;
+        fn precache_sentence_file(s: *const c_char, preload: bool) -> c_int = 6;
+        fn precache_decal(name: *const c_char, preload: bool) -> c_int = 7;
+        fn precache_generic(s: *const c_char, preload: bool) -> c_int = 8;
+        fn is_model_precached(s: *const c_char) -> bool = 9;
+        fn is_decal_precached(s: *const c_char) -> bool = 10;
+        fn is_generic_precached(s: *const c_char) -> bool = 11;
+        fn get_cluster_for_origin(org: *const Vector) -> c_int = 12;
+        fn get_pvs_for_cluster(cluster: c_int, outputpvslength: c_int, outputpvs: *mut u8) -> c_int = 13;
+        fn check_origin_in_pvs(org: *const Vector, checkpvs: *const u8, checkpvssize: c_int) -> bool = 14;
+        fn check_box_in_pvs(mins: *const Vector, maxs: *const Vector, checkpvs: *const u8, checkpvssize: c_int) -> bool = 15;
+        fn get_player_user_id(e: *const Edict) -> c_int = 16;
+        fn get_player_network_id_string(e: *const Edict) -> *const c_char = 17;
+        fn is_user_id_in_use(user_id: c_int) -> bool = 18;
+        fn get_loading_progress_for_user_id(user_id: c_int) -> c_int = 19;
+        fn get_entity_count() -> c_int = 20;
+        fn get_player_net_info(player_index: c_int) -> *mut INetChannelInfo = 21;
+        fn create_edict(force_edict_index: c_int) -> *mut Edict = 22;
+        fn remove_edict(e: *mut Edict) = 23; // todo: invalid index?
+        fn pv_alloc_ent_private_data(cb: c_int) -> *mut c_void = 24;
+        fn free_ent_private_data(entity: *mut c_void) = 25;
+        /// Save/restore memory allocator.
+        [safe] fn save_alloc_memory(num: usize, size: usize) -> *mut c_void = 26;
+        fn save_free_memory(save_mem: *mut c_void) = 27;
+        fn emit_ambient_sound(entindex: c_int, pos: *const Vector, samp: *const c_char, vol: f32, soundlevel: SoundLevelT, flags: c_int, pitch: c_int, delay: f32) = 28;
+        fn fade_client_volume(edict: *const Edict, fade_percent: f32, fade_out_seconds: f32, hold_time: f32, fade_in_seconds: f32) = 29;
+        fn sentence_group_pick(group_index: c_int, name: *mut c_char, name_buf_len: c_int) -> c_int = 30;
+        fn sentence_group_pick_sequential(group_index: c_int, name: *mut c_char, name_buf_len: c_int, sentence_index: c_int, reset: c_int) -> c_int = 31;
+        fn sentence_index_from_name(sentence_name: *const c_char) -> c_int = 32;
+        fn sentence_name_from_index(sentence_index: c_int) -> *const c_char = 33;
+        fn sentence_group_index_from_name(group_name: *const c_char) -> c_int = 34;
+        fn sentence_group_name_from_index(group_index: c_int) -> *const c_char = 35;
+        fn sentence_length(sentence_index: c_int) -> f32 = 36;
+        fn server_command(str: *const c_char) = 37;
+        /// Execute any commands currently in the command parser immediately.
+        [safe] fn server_execute() = 38;
+        fn client_command(edict: *mut Edict, fmt: *const c_char, ...) = 39;
+        fn light_style(style: c_int, val: *const c_char) = 40;
+        fn static_decal(origin: *const Vector, decal_index: c_int, entity_index: c_int, model_index: c_int, lowpriority: bool) = 41;
+        fn message_determine_multicast_recipients(usepas: bool, origin: *const Vector, playerbits: *mut CPlayerBitVec) = 42;
+        fn entity_message_begin(ent_index: c_int, ent_class: *mut ServerClass, reliable: bool) -> *mut BfWrite = 43;
+        fn user_message_begin(filter: *mut IRecipientFilter, msg_type: c_int, msg_name: *const c_char) -> *mut BfWrite = 44;
+        /// Finish the Entity or UserMessage and dispatch to network layer.
+        [safe] fn message_end() = 45;
+        fn client_printf(edict: *mut Edict, msg: *const c_char) = 46;
+        fn con_nprintf(pos: c_int, fmt: *const c_char, ...) = 47;
+        // fn con_nxprintf(info: *const ConNPrintS, fmt: *const c_char, ...) = 48;
+        fn set_view(client: *const Edict, viewent: *const Edict) = 49;
+        fn crosshair_angle(client: *const Edict, pitch: f32, yaw: f32) = 50;
+        fn get_game_dir(buf: *mut c_char, maxlen: c_int) = 51;
+        fn compare_file_time(filename1: *const c_char, filename2: *const c_char, compare: *mut c_int) -> c_int = 52;
+        /// Locks/unlocks the network string tables.
+        [safe] fn lock_network_string_tables(lock: bool) -> bool = 53;
+        fn create_fake_client(netname: *const c_char) -> *mut Edict = 54;
+        fn get_client_con_var_value(client_index: c_int, name: *const c_char) -> *const c_char = 55;
+        fn parse_file(data: *const c_char, token: *mut c_char, maxlen: c_int) -> *const c_char = 56;
+        fn copy_file(source: *const c_char, dest: *const c_char) -> bool = 57;
+        fn reset_pvs(pvs: *mut u8, pvssize: c_int) = 58;
+        fn add_origin_to_pvs(origin: *const Vector) = 59;
+        fn set_area_portal_state(portal_number: c_int, is_open: c_int) = 60;
+        fn playback_temp_entity(filter: *mut IRecipientFilter, delay: f32, sender: *const c_void, st: *const SendTable, class_id: c_int) = 61;
+        fn check_headnode_visible(nodenum: c_int, pvs: *const u8, vissize: c_int) -> c_int = 62;
+        fn check_areas_connected(area1: c_int, area2: c_int) -> c_int = 63;
+        fn get_area(origin: *const Vector) -> c_int = 64;
+        fn get_area_bits(area: c_int, bits: *mut u8, buflen: c_int) = 65;
+        fn get_area_portal_plane(view_origin: *const Vector, portal_key: c_int, plane: *mut VPlane) -> bool = 66;
+        fn load_game_state(map_name: *const c_char, create_players: bool) -> bool = 67;
+        fn load_adjacent_ents(old_level: *const c_char, landmark_name: *const c_char) = 68;
+        /// Clears the save directory.
+        [safe] fn clear_save_dir() = 69;
+        fn get_map_entities_string() -> *const c_char = 70;
+        fn text_message_get(name: *const c_char) -> *mut ClientTextMessage = 71;
+        fn log_print(msg: *const c_char) = 72;
+        /// Is logging enabled?
+        [safe] fn is_log_enabled() -> bool = 73;
+        fn build_entity_cluster_list(edict: *mut Edict, pvs_info: *mut PVSInfoT) = 74;
+        fn solid_moved(solid_ent: *mut Edict, solid_collide: *mut ICollideable, prev_abs_origin: *const Vector, bounds_only: bool) = 75;
+        fn trigger_moved(trigger_ent: *mut Edict, bounds_only: bool) = 76;
+        fn create_spatial_partition(worldmin: *const Vector, worldmax: *const Vector) -> *mut ISpatialPartition = 77;
+        fn destroy_spatial_partition(partition: *mut ISpatialPartition) = 78;
+        fn draw_map_to_scratch_pad(pad: *mut IScratchPad3D, flags: u32) = 79;
+        fn get_entity_transmit_bits_for_client(client_index: c_int) -> *const CBitVec = 80;
+        /// Is the game paused?
+        [safe] fn is_paused() -> bool = 81;
+        /// Returns the game timescale multiplied by the host timescale.
+        [safe] fn get_timescale() -> f32 = 82;
+        fn force_exact_file(s: *const c_char) = 83;
+        fn force_model_bounds(s: *const c_char, mins: *const Vector, maxs: *const Vector) = 84;
+        /// Clear save dir after client loads.
+        [safe] fn clear_save_dir_after_client_load() = 85;
+        fn set_fake_client_con_var_value(entity: *mut Edict, cvar: *const c_char, value: *const c_char) = 86;
+        fn force_simple_material(s: *const c_char) = 87;
+        fn is_in_commentary_mode() -> c_int = 88;
+        /// Is the engine running a background map?
+        [safe] fn is_level_main_menu_background() -> bool = 89;
+        fn set_area_portal_states(portal_numbers: *const c_int, is_open: *const c_int, n_portals: c_int) = 90;
+        fn notify_edict_flags_change(edict_index: c_int) = 91;
+        fn get_prev_check_transmit_info(player_edict: *mut Edict) -> *const CCheckTransmitInfo = 92;
+        fn get_shared_edict_change_info() -> *mut CSharedEdictChangeInfo = 93;
+        /// Allow immediate edict reuse.
+        [safe] fn allow_immediate_edict_reuse() = 94;
+        /// Is the engine an internal build?
+        [safe] fn is_internal_build() -> bool = 95;
+        fn get_change_accessor(edict: *const Edict) -> *mut IChangeInfoAccessor = 96;
+        fn get_most_recently_loaded_file_name() -> *const c_char = 97;
+        fn get_save_file_name() -> *const c_char = 98;
+        fn clean_up_entity_cluster_list(pvs_info: *mut PVSInfoT) = 99;
+        fn get_app_id() -> c_int = 100;
+        /// Returns true if the game is running in low violence mode.
+        [safe] fn is_low_violence() -> bool = 101;
+        /// Returns true if any connected client is running in low violence mode.
+        [safe] fn is_any_client_low_violence() -> bool = 102;
+        fn start_query_cvar_value(player_entity: *mut Edict, name: *const c_char) -> QueryCvarCookieT = 103;
+        fn insert_server_command(str: *const c_char) = 104;
+        fn get_player_info(ent_num: c_int, pinfo: *mut PlayerInfo) -> bool = 105;
+        fn is_client_fully_authenticated(edict: *mut Edict) -> bool = 106;
+        /// Sets whether the server runs in benchmark mode (1 tick per frame).
+        [safe] fn set_dedicated_server_benchmark_mode(benchmark_mode: bool) = 107;
+        fn is_split_screen_player(ent_num: c_int) -> bool = 108;
+        fn get_split_screen_player_attach_to_edict(ent_num: c_int) -> *mut Edict = 109;
+        fn get_num_split_screen_users_attached_to_edict(ent_num: c_int) -> c_int = 110;
+        fn get_split_screen_player_for_edict(ent_num: c_int, slot: c_int) -> *mut Edict = 111;
+        /// Is Foundry override load game entities active?
+        [safe] fn is_override_load_game_ents_on() -> bool = 112;
+        fn force_flush_entity(entity_index: c_int) = 113;
+        fn get_single_player_shared_memory_space(name: *const c_char, ent_num: c_int) -> *mut ISPSharedMemory = 114;
+        /// Allocate hunk memory.
+        [safe] fn alloc_level_static_data(bytes: usize) -> *mut c_void = 115;
+        fn get_cluster_count() -> c_int = 116;
+        fn get_all_cluster_bounds(bbox_list: *mut BBoxT, max_bbox: c_int) -> c_int = 117;
+        fn is_creating_reslist() -> bool = 118;
+        fn is_creating_xbox_reslist() -> bool = 119;
+        fn is_dedicated_server_for_xbox() -> bool = 120;
+        /// Pauses or unpauses the game.
+        [safe] fn pause(pause: bool, force: bool) = 121;
+        /// Sets the timescale.
+        [safe] fn set_timescale(timescale: f32) = 122;
+        fn set_gamestats_data(gamestats_data: *mut CGamestatsData) = 123;
+        fn get_gamestats_data() -> *mut CGamestatsData = 124;
+        fn get_client_steam_id(player_edict: *const Edict) -> *const CSteamID = 125;
+        fn get_game_server_steam_id() -> *const CSteamID = 126;
+        /// Validates the current matchmaking session.
+        [safe] fn host_validate_session() = 127;
+        /// Refreshes the screen if necessary (primarily for Xbox loading screens).
+        [safe] fn refresh_screen_if_necessary() = 128;
+        /// Checks if the map has a paintmap (used in Portal 2).
+        [safe] fn has_paintmap() -> bool = 129;
+        fn sphere_paint_surface(model: *const ModelT, pos: *const Vector, paint_type: u8, radius: f32, alpha: f32) -> bool = 130;
+        fn sphere_trace_paint_surface(model: *const ModelT, pos: *const Vector, dir: *const Vector, radius: f32, paint_types: *mut CUtlVector) = 131;
+        /// Removes all paint from the level.
+        [safe] fn remove_all_paint() = 132;
+        /// Paints all surfaces in the level with a specific paint type.
+        [safe] fn paint_all_surfaces(paint_type: u8) = 133;
+        fn remove_paint(model: *const ModelT) = 134;
+        fn client_command_key_values(edict: *mut Edict, command: *mut KeyValues) = 135;
+        fn get_client_xuid(player_edict: *const Edict) -> u64 = 136;
+        /// Is this the active application?
+        [safe] fn is_active_app() -> bool = 137;
+        /// Toggles the noclip mode globally.
+        [safe] fn set_no_clip_enabled(enabled: bool) = 138;
+        fn get_paintmap_data_rle(mapdata: *mut CUtlVector) = 139;
+        fn load_paintmap_data_rle(mapdata: *mut CUtlVector) = 140;
+        fn send_paintmap_data_to_client(edict: *mut Edict) = 141;
+        /// Gets latency specifically for choreo sound sync.
+        [safe] fn get_latency_for_choreo_sounds() -> f32 = 142;
+        fn get_client_cross_play_platform(client_index: c_int) -> c_int = 143;
+    }
 }
 
 // ==========================================================================
@@ -174,11 +202,6 @@ impl IVEngineServer {
         } else { false }
     }
 
-    /// Is this a dedicated server?
-    pub fn is_dedicated_server(&self) -> bool {
-        unsafe { (self.is_dedicated_server)(self.this) }
-    }
-
     /// Is in Hammer editing mode?
     pub fn is_in_edit_mode(&self) -> bool {
         unsafe { (self.is_in_edit_mode)(self.this) != 0 }
@@ -314,11 +337,6 @@ impl IVEngineServer {
         unsafe { (self.free_ent_private_data)(self.this, entity) }
     }
 
-    /// Save/restore memory allocator.
-    pub fn save_alloc_memory(&self, num: usize, size: usize) -> *mut c_void {
-        unsafe { (self.save_alloc_memory)(self.this, num, size) }
-    }
-
     /// Save/restore memory freer.
     pub fn save_free_memory(&self, save_mem: *mut c_void) {
         unsafe { (self.save_free_memory)(self.this, save_mem) }
@@ -385,11 +403,6 @@ impl IVEngineServer {
         }
     }
 
-    /// Execute any commands currently in the command parser immediately.
-    pub fn server_execute(&self) {
-        unsafe { (self.server_execute)(self.this) }
-    }
-
     /// Issue the specified command to the specified client (mimics client typing).
     /// Safely formats the command and avoids format string vulnerabilities.
     pub fn client_command(&self, edict: &mut Edict, cmd: &str) {
@@ -431,11 +444,6 @@ impl IVEngineServer {
         } else { None }
     }
 
-    /// Finish the Entity or UserMessage and dispatch to network layer.
-    pub fn message_end(&self) {
-        unsafe { (self.message_end)(self.this) }
-    }
-
     /// Print message to the client console.
     pub fn client_printf(&self, edict: &mut Edict, msg: &str) {
         if let Ok(c_msg) = CString::new(msg) {
@@ -488,11 +496,6 @@ impl IVEngineServer {
         } else { None }
     }
 
-    /// Locks/unlocks the network string tables.
-    pub fn lock_network_string_tables(&self, lock: bool) -> bool {
-        unsafe { (self.lock_network_string_tables)(self.this, lock) }
-    }
-
     /// Create a bot with the given name.
     pub fn create_fake_client<'a>(&self, netname: &str) -> Option<&'a mut Edict> {
         if let Ok(c_name) = CString::new(netname) {
@@ -583,17 +586,18 @@ impl IVEngineServer {
         }
     }
 
-    /// Clears the save directory.
-    pub fn clear_save_dir(&self) {
-        unsafe { (self.clear_save_dir)(self.this) }
-    }
-
     /// Get the pristine map entity lump string.
     pub fn get_map_entities_string(&self) -> Option<String> {
         let ptr = unsafe { (self.get_map_entities_string)(self.this) };
         if ptr.is_null() { None } else { Some(unsafe { CStr::from_ptr(ptr).to_string_lossy().into_owned() }) }
     }
 
+    /// Parses the map's entity lump, i.e. the entities as the map was compiled.
+    pub fn get_map_entities(&self) -> Result<MapEntities, String> {
+        let text = self.get_map_entities_string().ok_or("no map is loaded")?;
+        MapEntities::parse(&text)
+    }
+
     /// Lookup a text message by name.
     pub fn text_message_get<'a>(&self, name: &str) -> Option<&'a mut ClientTextMessage> {
         if let Ok(c_name) = CString::new(name) {
@@ -609,11 +613,6 @@ impl IVEngineServer {
         }
     }
 
-    /// Is logging enabled?
-    pub fn is_log_enabled(&self) -> bool {
-        unsafe { (self.is_log_enabled)(self.this) }
-    }
-
     /// Build PVS cluster list for an entity.
     pub fn build_entity_cluster_list(&self, edict: &mut Edict, pvs_info: &mut PVSInfoT) {
         unsafe { (self.build_entity_cluster_list)(self.this, edict as *mut _, pvs_info as *mut _) }
@@ -651,16 +650,6 @@ impl IVEngineServer {
         if ptr.is_null() { None } else { Some(unsafe { &*ptr }) }
     }
 
-    /// Is the game paused?
-    pub fn is_paused(&self) -> bool {
-        unsafe { (self.is_paused)(self.this) }
-    }
-
-    /// Returns the game timescale multiplied by the host timescale.
-    pub fn get_timescale(&self) -> f32 {
-        unsafe { (self.get_timescale)(self.this) }
-    }
-
     /// Force exact file consistency checking.
     pub fn force_exact_file(&self, filename: &str) {
         if let Ok(c_str) = CString::new(filename) {
@@ -675,11 +664,6 @@ impl IVEngineServer {
         }
     }
 
-    /// Clear save dir after client loads.
-    pub fn clear_save_dir_after_client_load(&self) {
-        unsafe { (self.clear_save_dir_after_client_load)(self.this) }
-    }
-
     /// Sets a USERINFO client ConVar for a fake client.
     pub fn set_fake_client_con_var_value(&self, entity: &mut Edict, cvar: &str, value: &str) {
         if let (Ok(c_cvar), Ok(c_val)) = (CString::new(cvar), CString::new(value)) {
@@ -699,11 +683,6 @@ impl IVEngineServer {
         unsafe { (self.is_in_commentary_mode)(self.this) != 0 }
     }
 
-    /// Is the engine running a background map?
-    pub fn is_level_main_menu_background(&self) -> bool {
-        unsafe { (self.is_level_main_menu_background)(self.this) }
-    }
-
     /// Mark an array of area portals as open/closed.
     pub fn set_area_portal_states(&self, portal_numbers: &[i32], is_open: &[i32]) {
         let count = std::cmp::min(portal_numbers.len(), is_open.len()) as c_int;
@@ -727,16 +706,6 @@ impl IVEngineServer {
         if ptr.is_null() { None } else { Some(unsafe { &mut *ptr }) }
     }
 
-    /// Allow immediate edict reuse.
-    pub fn allow_immediate_edict_reuse(&self) {
-        unsafe { (self.allow_immediate_edict_reuse)(self.this) }
-    }
-
-    /// Is the engine an internal build?
-    pub fn is_internal_build(&self) -> bool {
-        unsafe { (self.is_internal_build)(self.this) }
-    }
-
     /// Get change accessor for an edict.
     pub fn get_change_accessor<'a>(&self, edict: &Edict) -> Option<&'a mut IChangeInfoAccessor> {
         let ptr = unsafe { (self.get_change_accessor)(self.this, edict as *const _) };
@@ -765,16 +734,6 @@ impl IVEngineServer {
         unsafe { (self.get_app_id)(self.this) }
     }
 
-    /// Returns true if the game is running in low violence mode.
-    pub fn is_low_violence(&self) -> bool {
-        unsafe { (self.is_low_violence)(self.this) }
-    }
-
-    /// Returns true if any connected client is running in low violence mode.
-    pub fn is_any_client_low_violence(&self) -> bool {
-        unsafe { (self.is_any_client_low_violence)(self.this) }
-    }
-
     /// Starts an async query to get a client's cvar value.
     pub fn start_query_cvar_value(&self, player_entity: &mut Edict, name: &str) -> QueryCvarCookieT {
         if let Ok(c_name) = CString::new(name) {
@@ -800,11 +759,6 @@ impl IVEngineServer {
         unsafe { (self.is_client_fully_authenticated)(self.this, edict as *mut _) }
     }
 
-    /// Sets whether the server runs in benchmark mode (1 tick per frame).
-    pub fn set_dedicated_server_benchmark_mode(&self, benchmark_mode: bool) {
-        unsafe { (self.set_dedicated_server_benchmark_mode)(self.this, benchmark_mode) }
-    }
-
     /// Returns true if the player is a split screen player.
     pub fn is_split_screen_player(&self, ent_num: i32) -> bool {
         unsafe { (self.is_split_screen_player)(self.this, ent_num) }
@@ -827,11 +781,6 @@ impl IVEngineServer {
         if ptr.is_null() { None } else { Some(unsafe { &mut *ptr }) }
     }
 
-    /// Is Foundry override load game entities active?
-    pub fn is_override_load_game_ents_on(&self) -> bool {
-        unsafe { (self.is_override_load_game_ents_on)(self.this) }
-    }
-
     /// Force flush an entity for Foundry.
     pub fn force_flush_entity(&self, entity_index: i32) {
         unsafe { (self.force_flush_entity)(self.this, entity_index) }
@@ -845,11 +794,6 @@ impl IVEngineServer {
         } else { None }
     }
 
-    /// Allocate hunk memory.
-    pub fn alloc_level_static_data(&self, bytes: usize) -> *mut c_void {
-        unsafe { (self.alloc_level_static_data)(self.this, bytes) }
-    }
-
     /// Gets the total number of clusters.
     pub fn get_cluster_count(&self) -> i32 {
         unsafe { (self.get_cluster_count)(self.this) }
@@ -865,16 +809,6 @@ impl IVEngineServer {
     pub fn is_creating_xbox_reslist(&self) -> bool { unsafe { (self.is_creating_xbox_reslist)(self.this) } }
     pub fn is_dedicated_server_for_xbox(&self) -> bool { unsafe { (self.is_dedicated_server_for_xbox)(self.this) } }
 
-    /// Pauses or unpauses the game.
-    pub fn pause(&self, pause: bool, force: bool) {
-        unsafe { (self.pause)(self.this, pause, force) }
-    }
-
-    /// Sets the timescale.
-    pub fn set_timescale(&self, timescale: f32) {
-        unsafe { (self.set_timescale)(self.this, timescale) }
-    }
-
     /// Sets the gamestats data.
     pub fn set_gamestats_data(&self, gamestats_data: &mut CGamestatsData) {
         unsafe { (self.set_gamestats_data)(self.this, gamestats_data as *mut _) }
@@ -898,21 +832,6 @@ impl IVEngineServer {
         if ptr.is_null() { None } else { Some(unsafe { *ptr }) }
     }
 
-    /// Validates the current matchmaking session.
-    pub fn host_validate_session(&self) {
-        unsafe { (self.host_validate_session)(self.this) }
-    }
-
-    /// Refreshes the screen if necessary (primarily for Xbox loading screens).
-    pub fn refresh_screen_if_necessary(&self) {
-        unsafe { (self.refresh_screen_if_necessary)(self.this) }
-    }
-
-    /// Checks if the map has a paintmap (used in Portal 2).
-    pub fn has_paintmap(&self) -> bool {
-        unsafe { (self.has_paintmap)(self.this) }
-    }
-
     /// Shoots a paint sphere onto surfaces.
     pub fn sphere_paint_surface(&self, model: &ModelT, pos: &Vector, paint_type: u8, radius: f32, alpha: f32) -> bool {
         unsafe { (self.sphere_paint_surface)(self.this, model as *const _, pos, paint_type, radius, alpha) }
@@ -923,24 +842,28 @@ impl IVEngineServer {
         unsafe { (self.sphere_trace_paint_surface)(self.this, model as *const _, pos, dir, radius, paint_types as *mut _) }
     }
 
-    /// Removes all paint from the level.
-    pub fn remove_all_paint(&self) {
-        unsafe { (self.remove_all_paint)(self.this) }
-    }
-
-    /// Paints all surfaces in the level with a specific paint type.
-    pub fn paint_all_surfaces(&self, paint_type: u8) {
-        unsafe { (self.paint_all_surfaces)(self.this, paint_type) }
-    }
-
     /// Removes paint from a specific model.
     pub fn remove_paint(&self, model: &ModelT) {
         unsafe { (self.remove_paint)(self.this, model as *const _) }
     }
 
-    /// Sends a client command using KeyValues.
-    pub fn client_command_key_values(&self, edict: &mut Edict, command: &mut KeyValues) {
-        unsafe { (self.client_command_key_values)(self.this, edict as *mut _, command as *mut _) }
+    /// Sends a client command using KeyValues. The engine takes ownership of `command`
+    /// and frees it.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use portal2_sdk::types::{Edict, KeyValuesTree};
+    ///
+    /// fn ping(edict: &mut Edict) {
+    ///     let mut command = KeyValuesTree::new("ClientCommand");
+    ///     command.set("Command", "ping");
+    ///     let engine = portal2_sdk::get_engine();
+    ///     engine.engine_server().client_command_key_values(edict, command.to_engine().unwrap());
+    /// }
+    /// ```
+    pub fn client_command_key_values(&self, edict: &mut Edict, command: EngineKeyValues) {
+        unsafe { (self.client_command_key_values)(self.this, edict as *mut _, command.into_raw()) }
     }
 
     /// Gets the Xbox User ID (XUID) for a player.
@@ -948,16 +871,6 @@ impl IVEngineServer {
         unsafe { (self.get_client_xuid)(self.this, player_edict as *const _) }
     }
 
-    /// Is this the active application?
-    pub fn is_active_app(&self) -> bool {
-        unsafe { (self.is_active_app)(self.this) }
-    }
-
-    /// Toggles the noclip mode globally.
-    pub fn set_no_clip_enabled(&self, enabled: bool) {
-        unsafe { (self.set_no_clip_enabled)(self.this, enabled) }
-    }
-
     /// Retrieves compressed RLE data for the paintmap.
     pub fn get_paintmap_data_rle(&self, mapdata: &mut CUtlVector) {
         unsafe { (self.get_paintmap_data_rle)(self.this, mapdata as *mut _) }
@@ -973,11 +886,6 @@ impl IVEngineServer {
         unsafe { (self.send_paintmap_data_to_client)(self.this, edict as *mut _) }
     }
 
-    /// Gets latency specifically for choreo sound sync.
-    pub fn get_latency_for_choreo_sounds(&self) -> f32 {
-        unsafe { (self.get_latency_for_choreo_sounds)(self.this) }
-    }
-
     /// Gets the crossplay platform indicator for the client.
     pub fn get_client_cross_play_platform(&self, client_index: i32) -> i32 {
         unsafe { (self.get_client_cross_play_platform)(self.this, client_index) }
@@ -4,77 +4,40 @@ use std::ffi::{c_char, c_int, c_void, CStr, CString};
 use std::ptr;
 
 use crate::types::{CBaseEntity, CEntityRespawnInfo, IClientEntity, IServerEntity, QAngle, Vector};
-use crate::platform::abi::vfn;
-
-// ==========================================================================
-// Raw FFI Function Pointer Types
-// ==========================================================================
-
-type FnGetIServerEntity = vfn!((this: *mut c_void, p_client_entity: *const IClientEntity) -> *mut IServerEntity);
-type FnSnapPlayerToPosition = vfn!((this: *mut c_void, org: *const Vector, ang: *const QAngle, p_client_player: *const IClientEntity) -> bool);
-type FnGetPlayerPosition = vfn!((this: *mut c_void, org: *mut Vector, ang: *mut QAngle, p_client_player: *const IClientEntity) -> bool);
-type FnSetPlayerFOV = vfn!((this: *mut c_void, fov: c_int, p_client_player: *const IClientEntity) -> bool);
-type FnGetPlayerFOV = vfn!((this: *mut c_void, p_client_player: *const IClientEntity) -> c_int);
-type FnIsInNoClipMode = vfn!((this: *mut c_void, p_client_player: *const IClientEntity) -> bool);
-
-type FnFirstEntity = vfn!((this: *mut c_void) -> *mut CBaseEntity);
-type FnNextEntity = vfn!((this: *mut c_void, p_entity: *const CBaseEntity) -> *mut CBaseEntity);
-type FnFindEntityByHammerID = vfn!((this: *mut c_void, i_hammer_id: c_int) -> *mut CBaseEntity);
-
-type FnGetKeyValue = vfn!((this: *mut c_void, p_entity: *const CBaseEntity, sz_field: *const c_char, sz_value: *mut c_char, i_max_len: c_int) -> bool);
-type FnSetKeyValueStr = vfn!((this: *mut c_void, p_entity: *mut CBaseEntity, sz_field: *const c_char, sz_value: *const c_char) -> bool);
-type FnSetKeyValueFlt = vfn!((this: *mut c_void, p_entity: *mut CBaseEntity, sz_field: *const c_char, fl_value: f32) -> bool);
-type FnSetKeyValueVec = vfn!((this: *mut c_void, p_entity: *mut CBaseEntity, sz_field: *const c_char, vec_value: *const Vector) -> bool);
-
-type FnCreateEntityByName = vfn!((this: *mut c_void, sz_class_name: *const c_char) -> *mut CBaseEntity);
-type FnDispatchSpawn = vfn!((this: *mut c_void, p_entity: *mut CBaseEntity));
-type FnDestroyEntityByHammerId = vfn!((this: *mut c_void, i_hammer_id: c_int) -> bool);
-type FnRespawnEntitiesWithEdits = vfn!((this: *mut c_void, p_infos: *mut CEntityRespawnInfo, n_infos: c_int) -> bool);
-
-type FnReloadParticleDefintions = vfn!((this: *mut c_void, p_file_name: *const c_char, p_buf_data: *const c_void, n_len: c_int));
-type FnAddOriginToPVS = vfn!((this: *mut c_void, org: *const Vector));
-type FnMoveEngineViewTo = vfn!((this: *mut c_void, v_pos: *const Vector, v_angles: *const QAngle));
-type FnRemoveEntity = vfn!((this: *mut c_void, n_hammer_id: c_int));
+use crate::interface::interface;
 
 // ==========================================================================
 // Interface Structure
 // ==========================================================================
 
-/// Interface from the engine to tools for manipulating entities.
-/// Provides safe abstractions to access underlying engine features.
-pub struct IServerTools {
-    pub(crate) this: *mut c_void,
-
-    // VTable index 0-5
-    pub(crate) get_iserver_entity: FnGetIServerEntity,
-    pub(crate) snap_player_to_position: FnSnapPlayerToPosition,
-    pub(crate) get_player_position: FnGetPlayerPosition,
-    pub(crate) set_player_fov: FnSetPlayerFOV,
-    pub(crate) get_player_fov: FnGetPlayerFOV,
-    pub(crate) is_in_no_clip_mode: FnIsInNoClipMode,
-
-    // VTable index 6-8
-    pub(crate) first_entity: FnFirstEntity,
-    pub(crate) next_entity: FnNextEntity,
-    pub(crate) find_entity_by_hammer_id: FnFindEntityByHammerID,
-
-    // VTable index 9-12
-    pub(crate) get_key_value: FnGetKeyValue,
-    pub(crate) set_key_value_str: FnSetKeyValueStr,
-    pub(crate) set_key_value_flt: FnSetKeyValueFlt,
-    pub(crate) set_key_value_vec: FnSetKeyValueVec,
-
-    // VTable index 13-16
-    pub(crate) create_entity_by_name: FnCreateEntityByName,
-    pub(crate) dispatch_spawn: FnDispatchSpawn,
-    pub(crate) destroy_entity_by_hammer_id: FnDestroyEntityByHammerId,
-    pub(crate) respawn_entities_with_edits: FnRespawnEntitiesWithEdits,
-
-    // VTable index 17-20
-    pub(crate) reload_particle_defintions: FnReloadParticleDefintions,
-    pub(crate) add_origin_to_pvs: FnAddOriginToPVS,
-    pub(crate) move_engine_view_to: FnMoveEngineViewTo,
-    pub(crate) remove_entity: FnRemoveEntity,
+interface! {
+    /// Interface from the engine to tools for manipulating entities.
+    /// Provides safe abstractions to access underlying engine features.
+    pub struct IServerTools: "IServerTools" {
+        destructor = 0;
+
+        fn get_iserver_entity(p_client_entity: *const IClientEntity) -> *mut IServerEntity = 1;
+        fn snap_player_to_position(org: *const Vector, ang: *const QAngle, p_client_player: *const IClientEntity) -> bool = 2;
+        fn get_player_position(org: *mut Vector, ang: *mut QAngle, p_client_player: *const IClientEntity) -> bool = 3;
+        fn set_player_fov(fov: c_int, p_client_player: *const IClientEntity) -> bool = 4;
+        fn get_player_fov(p_client_player: *const IClientEntity) -> c_int = 5;
+        fn is_in_no_clip_mode(p_client_player: *const IClientEntity) -> bool = 6;
+        fn first_entity() -> *mut CBaseEntity = 7;
+        fn next_entity(p_entity: *const CBaseEntity) -> *mut CBaseEntity = 8;
+        fn find_entity_by_hammer_id(i_hammer_id: c_int) -> *mut CBaseEntity = 9;
+        fn get_key_value(p_entity: *const CBaseEntity, sz_field: *const c_char, sz_value: *mut c_char, i_max_len: c_int) -> bool = 10;
+        fn set_key_value_vec(p_entity: *mut CBaseEntity, sz_field: *const c_char, vec_value: *const Vector) -> bool = 11;
+        fn set_key_value_flt(p_entity: *mut CBaseEntity, sz_field: *const c_char, fl_value: f32) -> bool = 12;
+        fn set_key_value_str(p_entity: *mut CBaseEntity, sz_field: *const c_char, sz_value: *const c_char) -> bool = 13;
+        fn create_entity_by_name(sz_class_name: *const c_char) -> *mut CBaseEntity = 14;
+        fn dispatch_spawn(p_entity: *mut CBaseEntity) = 15;
+        fn destroy_entity_by_hammer_id(i_hammer_id: c_int) -> bool = 16;
+        fn respawn_entities_with_edits(p_infos: *mut CEntityRespawnInfo, n_infos: c_int) -> bool = 17;
+        fn reload_particle_defintions(p_file_name: *const c_char, p_buf_data: *const c_void, n_len: c_int) = 18;
+        fn add_origin_to_pvs(org: *const Vector) = 19;
+        fn move_engine_view_to(v_pos: *const Vector, v_angles: *const QAngle) = 20;
+        fn remove_entity(n_hammer_id: c_int) = 21;
+    }
 }
 
 // ==========================================================================
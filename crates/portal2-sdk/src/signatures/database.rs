@@ -0,0 +1,597 @@
+//! Signature database: compiled-in defaults plus per-build overrides from disk.
+//!
+//! A game update that moves a function used to mean a new plugin build. Instead,
+//! [`SignatureDatabase::load_default`] looks for a `signatures.toml` next to the
+//! plugin binary and layers whatever it finds there on top of the compiled-in set.
+//! Entries the file does not mention keep their compiled-in values.
+//!
+//! The file is versioned and split into `[[build]]` sections. A section without a
+//! `patch_version` applies to every build; a section with one applies only when it
+//! equals the `PatchVersion` from the game's `steam.inf`, and wins over the generic
+//! sections regardless of their order in the file.
+//!
+//! ```toml
+//! version = 1
+//!
+//! [[build]]
+//! patch_version = "2.0.0.1"
+//!
+//! # On the native Linux build, functions are looked up by mangled name instead.
+//! [build.signatures."ICvar::FindVar"]
+//! module = "vstdlib"
+//! symbol = "_ZN4CCvar7FindVarEPKc"
+//!
+//! # Byte patterns use IDA/x64dbg notation, `??` being a wildcard. A separate x/?
+//! # `mask` is also accepted. `offset` is added to the match address.
+//! [build.signatures."IVEngineClient::ServerCmd"]
+//! module = "engine"
+//! pattern = "55 8B EC 8B 45 ?? 81 EC"
+//! offset = 0
+//!
+//! # Vtable indices, keyed by interface and then by the SDK's field name.
+//! [build.vtables.IVEngineClient]
+//! con_is_visible = 11
+//! world_to_screen_matrix = 37
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::CString;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::memory::Pattern;
+use crate::platform::{self, Abi, Module};
+
+/// The only database format this SDK understands. Bumped on breaking changes.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// File name [`SignatureDatabase::load_default`] looks for next to the plugin.
+pub const DEFAULT_FILE_NAME: &str = "signatures.toml";
+
+/// How a [`Signature`] finds its function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Locator {
+    /// A byte pattern scanned for in the module's code.
+    Pattern {
+        bytes: Vec<u8>,
+        /// `x` for "must match", `?` for "any byte"; same length as `bytes`.
+        mask: String,
+    },
+    /// A (mangled) symbol name, looked up with [`platform::find_symbol`].
+    Symbol(String),
+}
+
+/// How to find a single function the engine does not expose through a vtable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    /// Module the function lives in.
+    pub module: Module,
+    pub locator: Locator,
+    /// Added to the found address, for patterns anchored inside the function.
+    pub offset: isize,
+}
+
+impl Signature {
+    /// A byte-pattern signature.
+    pub fn new(module: Module, pattern: Vec<u8>, mask: String, offset: isize) -> Self {
+        Self { module, locator: Locator::Pattern { bytes: pattern, mask }, offset }
+    }
+
+    /// A signature resolved by symbol name.
+    pub fn symbol(module: Module, name: &str) -> Self {
+        Self { module, locator: Locator::Symbol(name.to_string()), offset: 0 }
+    }
+
+    /// The pattern in scannable form, or `None` for a symbol, or if the pattern
+    /// and mask disagree.
+    pub fn pattern(&self) -> Option<Pattern> {
+        match &self.locator {
+            Locator::Pattern { bytes, mask } => Pattern::from_mask(bytes, mask),
+            Locator::Symbol(_) => None,
+        }
+    }
+}
+
+/// Where a database entry came from, for error messages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureOrigin {
+    /// Compiled into the SDK (`signatures/mod.rs`).
+    Builtin,
+    /// Loaded from a database file.
+    File {
+        path: PathBuf,
+        /// `patch_version` of the section the entry came from, `None` for a generic one.
+        build: Option<String>,
+    },
+}
+
+impl fmt::Display for SignatureOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Builtin => write!(f, "builtin"),
+            Self::File { path, build: Some(build) } => write!(f, "{} [build {}]", path.display(), build),
+            Self::File { path, build: None } => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// The game build the plugin is running against, as reported by `steam.inf`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameBuild {
+    pub patch_version: String,
+}
+
+impl GameBuild {
+    /// Parses the contents of a `steam.inf` file.
+    pub fn from_steam_inf(text: &str) -> Option<Self> {
+        text.lines()
+            .filter_map(|line| line.split_once('='))
+            .find(|(key, _)| key.trim().eq_ignore_ascii_case("PatchVersion"))
+            .map(|(_, value)| value.trim())
+            .filter(|value| !value.is_empty())
+            .map(|value| Self { patch_version: value.to_string() })
+    }
+
+    /// Reads the build of the running game.
+    ///
+    /// `steam.inf` lives in the game directory (`portal2/`), which is a sibling of
+    /// the `bin/` directory the engine module is loaded from.
+    pub fn detect() -> Option<Self> {
+        let engine = platform::module_path(Module::Engine)?;
+        let root = engine.parent()?.parent()?;
+        let text = std::fs::read_to_string(root.join("portal2").join("steam.inf")).ok()?;
+        Self::from_steam_inf(&text)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    signature: Signature,
+    origin: SignatureOrigin,
+}
+
+#[derive(Debug, Clone)]
+struct VtableEntry {
+    index: usize,
+    origin: SignatureOrigin,
+}
+
+/// The set of signatures and vtable indices [`crate::Engine::initialize`] resolves through.
+#[derive(Debug, Clone)]
+pub struct SignatureDatabase {
+    signatures: HashMap<String, Entry>,
+    /// Overrides only: indices not listed here use the default compiled into `initialize`.
+    vtables: HashMap<(String, String), VtableEntry>,
+}
+
+impl Default for SignatureDatabase {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl SignatureDatabase {
+    /// The compiled-in signature set for the running platform, with no overrides.
+    pub fn builtin() -> Self {
+        Self::builtin_for(Abi::CURRENT)
+    }
+
+    /// The compiled-in signature set for the build of the game compiled with `abi`:
+    /// byte patterns for the Windows build, symbol names for the Linux one.
+    pub fn builtin_for(abi: Abi) -> Self {
+        let set = match abi {
+            Abi::Msvc => super::builtin_msvc(),
+            Abi::Itanium => super::builtin_itanium(),
+        };
+        let signatures = set
+            .into_iter()
+            .map(|(name, signature)| (name.to_string(), Entry { signature, origin: SignatureOrigin::Builtin }))
+            .collect();
+
+        Self { signatures, vtables: HashMap::new() }
+    }
+
+    /// The compiled-in set, overridden by `signatures.toml` next to the plugin if there is one.
+    ///
+    /// Never fails: a missing file is the common case, and a broken one is logged
+    /// and ignored so that the compiled-in set still gets a chance to work.
+    pub fn load_default() -> Self {
+        let mut db = Self::builtin();
+
+        let Some(path) = crate::utils::get_dll_directory().map(|dir| dir.join(DEFAULT_FILE_NAME)) else {
+            return db;
+        };
+        if !path.is_file() {
+            return db;
+        }
+
+        let build = GameBuild::detect();
+        match build {
+            Some(ref build) => log::info!("Detected game build {}", build.patch_version),
+            None => log::warn!("Could not read the game build from steam.inf; only generic sections apply"),
+        }
+
+        if let Err(e) = db.apply_file(&path, build.as_ref()) {
+            log::error!("Ignoring signature database: {}", e);
+        }
+        db
+    }
+
+    /// Layers the sections of a database file that apply to `build` on top of this set.
+    pub fn apply_file(&mut self, path: &Path, build: Option<&GameBuild>) -> Result<(), String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        self.apply_str(&text, path, build)
+    }
+
+    /// Same as [`Self::apply_file`], for text that has already been read. `path` is
+    /// only used to label the entries.
+    pub fn apply_str(&mut self, text: &str, path: &Path, build: Option<&GameBuild>) -> Result<(), String> {
+        let file: RawFile = toml::from_str(text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        if file.version != FORMAT_VERSION {
+            return Err(format!(
+                "{}: unsupported database version {} (expected {})",
+                path.display(), file.version, FORMAT_VERSION
+            ));
+        }
+
+        // Generic sections first, so that the build-specific ones overwrite them.
+        let wanted = build.map(|b| b.patch_version.as_str());
+        let generic = file.builds.iter().filter(|b| b.patch_version.is_none());
+        let specific = file.builds.iter().filter(|b| b.patch_version.is_some() && b.patch_version.as_deref() == wanted);
+
+        // Staged so that a bad entry halfway through leaves `self` untouched.
+        let mut staged = self.clone();
+        for section in generic.chain(specific) {
+            let origin = SignatureOrigin::File { path: path.to_path_buf(), build: section.patch_version.clone() };
+
+            for (name, raw) in &section.signatures {
+                let signature = raw.parse().map_err(|e| format!("entry '{}' in {}: {}", name, origin, e))?;
+                staged.signatures.insert(name.clone(), Entry { signature, origin: origin.clone() });
+            }
+
+            for (interface, indices) in &section.vtables {
+                for (method, &index) in indices {
+                    staged.vtables.insert(
+                        (interface.clone(), method.clone()),
+                        VtableEntry { index, origin: origin.clone() },
+                    );
+                }
+            }
+        }
+
+        *self = staged;
+        Ok(())
+    }
+
+    /// Looks up a signature by entry name.
+    pub fn get(&self, name: &str) -> Option<(&Signature, &SignatureOrigin)> {
+        self.signatures.get(name).map(|e| (&e.signature, &e.origin))
+    }
+
+    /// All signature entries, sorted by name.
+    pub fn signatures(&self) -> Vec<(&str, &Signature, &SignatureOrigin)> {
+        let mut all: Vec<_> = self.signatures.iter()
+            .map(|(name, e)| (name.as_str(), &e.signature, &e.origin))
+            .collect();
+        all.sort_by_key(|(name, ..)| *name);
+        all
+    }
+
+    /// The vtable index of `interface`'s `method`, or `default` if no file overrides it.
+    ///
+    /// Overrides are taken as-is, in the running platform's vtable layout.
+    pub fn vtable_index(&self, interface: &str, method: &str, default: usize) -> usize {
+        match self.vtables.get(&(interface.to_string(), method.to_string())) {
+            Some(entry) => {
+                if entry.index != default {
+                    log::debug!("{}::{} -> vtable index {} ({})", interface, method, entry.index, entry.origin);
+                }
+                entry.index
+            }
+            None => default,
+        }
+    }
+
+    /// Vtable index lookups scoped to a single interface.
+    pub fn vtable<'a>(&'a self, interface: &'a str) -> VtableIndices<'a> {
+        VtableIndices { db: self, interface, destructor: None }
+    }
+
+    /// Finds the entry `name` in `memory`, returning the offset of the function from
+    /// the start of the slice.
+    pub fn find_in(&self, name: &str, memory: &[u8]) -> Result<usize, String> {
+        let entry = self.signatures.get(name)
+            .ok_or_else(|| format!("no signature database entry named '{}'", name))?;
+        let sig = &entry.signature;
+
+        if let Locator::Symbol(symbol) = &sig.locator {
+            return Err(format!("signature '{}' ({}) is the symbol {}, not a pattern", name, entry.origin, symbol));
+        }
+        let pattern = sig.pattern()
+            .ok_or_else(|| format!("signature '{}' ({}) has a malformed pattern or mask", name, entry.origin))?;
+        let found = pattern.find_unique(memory).map_err(|e| {
+            format!("signature '{}' ({}) {} in {}", name, entry.origin, e, sig.module.name())
+        })?;
+
+        found.checked_add_signed(sig.offset).ok_or_else(|| {
+            format!("signature '{}' ({}): offset {} points outside the module", name, entry.origin, sig.offset)
+        })
+    }
+
+    /// Resolves the entry `name` to an address inside the loaded module.
+    pub fn resolve(&self, name: &str) -> Result<*const u8, String> {
+        let (sig, origin) = self.get(name)
+            .ok_or_else(|| format!("no signature database entry named '{}'", name))?;
+
+        if let Locator::Symbol(symbol) = &sig.locator {
+            let c_symbol = CString::new(symbol.as_str())
+                .map_err(|_| format!("signature '{}' ({}): symbol contains a NUL byte", name, origin))?;
+            let addr = platform::find_symbol(sig.module, &c_symbol) as *const u8;
+            if addr.is_null() {
+                return Err(format!(
+                    "signature '{}' ({}): symbol {} not found in {}",
+                    name, origin, symbol, sig.module.name()
+                ));
+            }
+            return Ok(addr.wrapping_offset(sig.offset));
+        }
+
+        let (base, size) = platform::module_range(sig.module)
+            .ok_or_else(|| format!("signature '{}': module {} is not loaded", name, sig.module.name()))?;
+
+        // SAFETY: `module_range` only reports readable, mapped memory.
+        let memory = unsafe { std::slice::from_raw_parts(base, size) };
+        let offset = self.find_in(name, memory)?;
+        Ok(unsafe { base.add(offset) })
+    }
+}
+
+/// See [`SignatureDatabase::vtable`].
+#[derive(Clone, Copy)]
+pub struct VtableIndices<'a> {
+    db: &'a SignatureDatabase,
+    interface: &'a str,
+    destructor: Option<usize>,
+}
+
+impl VtableIndices<'_> {
+    /// Declares where the interface's virtual destructor sits, as an MSVC index,
+    /// so that defaults can be translated to the Itanium layout.
+    pub fn virtual_destructor_at(mut self, msvc_index: usize) -> Self {
+        self.destructor = Some(msvc_index);
+        self
+    }
+
+    /// The index of `method`: a database override if there is one, otherwise
+    /// `msvc_default` translated to the running platform's ABI.
+    pub fn index(&self, method: &str, msvc_default: usize) -> usize {
+        let default = platform::vtable_index(Abi::CURRENT, msvc_default, self.destructor);
+        self.db.vtable_index(self.interface, method, default)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawFile {
+    version: u32,
+    #[serde(default, rename = "build")]
+    builds: Vec<RawBuild>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawBuild {
+    patch_version: Option<String>,
+    #[serde(default)]
+    signatures: BTreeMap<String, RawSignature>,
+    #[serde(default)]
+    vtables: BTreeMap<String, BTreeMap<String, usize>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawSignature {
+    module: String,
+    pattern: Option<String>,
+    mask: Option<String>,
+    symbol: Option<String>,
+    #[serde(default)]
+    offset: isize,
+}
+
+impl RawSignature {
+    fn parse(&self) -> Result<Signature, String> {
+        let module = Module::from_name(&self.module)
+            .ok_or_else(|| format!("unknown module '{}'", self.module))?;
+
+        let pattern = match (&self.pattern, &self.symbol) {
+            (Some(pattern), None) => Pattern::parse(pattern)?,
+            (None, Some(symbol)) if self.mask.is_none() => {
+                let mut sig = Signature::symbol(module, symbol);
+                sig.offset = self.offset;
+                return Ok(sig);
+            }
+            _ => return Err("needs either a `pattern` (with an optional `mask`) or a `symbol`".to_string()),
+        };
+
+        // An explicit mask can only add wildcards on top of the pattern's own `??`s.
+        let mask = match &self.mask {
+            None => pattern.mask(),
+            Some(mask) if mask.len() == pattern.len() && mask.chars().all(|c| c == 'x' || c == '?') => {
+                mask.chars().zip(pattern.mask().chars())
+                    .map(|(a, b)| if a == '?' || b == '?' { '?' } else { 'x' })
+                    .collect()
+            }
+            Some(mask) => return Err(format!("mask '{}' does not fit a {}-byte pattern", mask, pattern.len())),
+        };
+        let pattern = pattern.bytes();
+
+        Ok(Signature::new(module, pattern, mask, self.offset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = r#"
+        version = 1
+
+        [[build]]
+        patch_version = "2.0.0.1"
+        [build.signatures."IVEngineClient::ServerCmd"]
+        module = "engine"
+        pattern = "AA BB CC"
+        offset = 1
+        [build.vtables.IVEngineClient]
+        con_is_visible = 12
+
+        [[build]]
+        [build.signatures."IVEngineClient::ServerCmd"]
+        module = "engine"
+        pattern = "11 22"
+        [build.signatures."ICvar::FindVar"]
+        module = "vstdlib"
+        pattern = "55 ?? EC"
+    "#;
+
+    fn build(v: &str) -> GameBuild {
+        GameBuild { patch_version: v.to_string() }
+    }
+
+    fn load(build: Option<&GameBuild>) -> SignatureDatabase {
+        let mut db = SignatureDatabase::builtin();
+        db.apply_str(FILE, Path::new("signatures.toml"), build).unwrap();
+        db
+    }
+
+    #[test]
+    fn builtin_covers_every_entry_once() {
+        let db = SignatureDatabase::builtin_for(Abi::Msvc);
+        assert_eq!(db.signatures().len(), super::super::builtin_msvc().len());
+        assert!(db.signatures().iter().all(|(_, sig, _)| sig.pattern().is_some()));
+
+        let linux = SignatureDatabase::builtin_for(Abi::Itanium);
+        let names = |db: &SignatureDatabase| db.signatures().iter().map(|(n, ..)| n.to_string()).collect::<Vec<_>>();
+        assert_eq!(names(&linux), names(&db), "both ABIs must resolve the same functions");
+        assert!(linux.signatures().iter().all(|(_, sig, _)| matches!(sig.locator, Locator::Symbol(_))));
+    }
+
+    #[test]
+    fn build_specific_section_wins_over_generic() {
+        let db = load(Some(&build("2.0.0.1")));
+        let (sig, origin) = db.get("IVEngineClient::ServerCmd").unwrap();
+        assert_eq!(sig.locator, Locator::Pattern { bytes: vec![0xAA, 0xBB, 0xCC], mask: "xxx".into() });
+        assert_eq!(sig.offset, 1);
+        assert_eq!(origin, &SignatureOrigin::File { path: "signatures.toml".into(), build: Some("2.0.0.1".into()) });
+        assert_eq!(db.vtable_index("IVEngineClient", "con_is_visible", 11), 12);
+    }
+
+    #[test]
+    fn other_builds_only_get_generic_sections() {
+        let db = load(Some(&build("2.0.0.0")));
+        assert_eq!(db.get("IVEngineClient::ServerCmd").unwrap().0.pattern().unwrap().bytes(), vec![0x11, 0x22]);
+        assert_eq!(db.get("ICvar::FindVar").unwrap().0.pattern().unwrap().mask(), "x?x");
+        assert_eq!(db.vtable_index("IVEngineClient", "con_is_visible", 11), 11);
+    }
+
+    #[test]
+    fn untouched_entries_stay_builtin() {
+        let db = load(None);
+        assert_eq!(db.get("IVEngineClient::ClientCmd").unwrap().1, &SignatureOrigin::Builtin);
+    }
+
+    #[test]
+    fn explicit_mask_adds_wildcards() {
+        let text = r#"
+            version = 1
+            [[build]]
+            [build.signatures."ICvar::FindVar"]
+            module = "vstdlib"
+            pattern = "55 ?? EC 01"
+            mask = "xxx?"
+        "#;
+        let mut db = SignatureDatabase::builtin();
+        db.apply_str(text, Path::new("s.toml"), None).unwrap();
+        assert_eq!(db.get("ICvar::FindVar").unwrap().0.pattern().unwrap().mask(), "x?x?");
+    }
+
+    #[test]
+    fn symbol_entries() {
+        let text = r#"
+            version = 1
+            [[build]]
+            [build.signatures."ICvar::FindVar"]
+            module = "vstdlib"
+            symbol = "_ZN4CCvar7FindVarEPKc"
+        "#;
+        let mut db = SignatureDatabase::builtin_for(Abi::Msvc);
+        db.apply_str(text, Path::new("s.toml"), None).unwrap();
+        let (sig, _) = db.get("ICvar::FindVar").unwrap();
+        assert_eq!(sig.locator, Locator::Symbol("_ZN4CCvar7FindVarEPKc".into()));
+        assert!(db.find_in("ICvar::FindVar", b"").unwrap_err().contains("is the symbol"));
+
+        let both = text.replace("symbol =", "pattern = \"55\"\nsymbol =");
+        assert!(db.apply_str(&both, Path::new("s.toml"), None).is_err());
+    }
+
+    #[test]
+    fn vtable_defaults_follow_the_abi() {
+        let db = SignatureDatabase::builtin();
+        let tools = db.vtable("IServerTools").virtual_destructor_at(0);
+        let expected = if Abi::CURRENT == Abi::Itanium { 2 } else { 1 };
+        assert_eq!(tools.index("get_iserver_entity", 1), expected);
+        assert_eq!(db.vtable("IVEngineServer").index("change_level", 0), 0);
+
+        // Overrides are already in the platform's layout and are not translated.
+        let db = load(Some(&build("2.0.0.1")));
+        assert_eq!(db.vtable("IVEngineClient").virtual_destructor_at(0).index("con_is_visible", 11), 12);
+    }
+
+    #[test]
+    fn rejects_unknown_version() {
+        let mut db = SignatureDatabase::builtin();
+        let err = db.apply_str("version = 2", Path::new("s.toml"), None).unwrap_err();
+        assert!(err.contains("unsupported database version 2"));
+    }
+
+    #[test]
+    fn bad_entry_is_named_in_error() {
+        let text = r#"
+            version = 1
+            [[build]]
+            [build.signatures."ICvar::FindVar"]
+            module = "vstdlib"
+            pattern = "55 8B"
+            mask = "x"
+        "#;
+        let err = SignatureDatabase::builtin().apply_str(text, Path::new("s.toml"), None).unwrap_err();
+        assert!(err.contains("ICvar::FindVar"), "{}", err);
+    }
+
+    #[test]
+    fn find_in_applies_offset_and_names_failures() {
+        let db = load(None);
+        assert_eq!(db.find_in("ICvar::FindVar", b"\x00\x55\x42\xEC"), Ok(1));
+
+        let db = load(Some(&build("2.0.0.1")));
+        assert_eq!(db.find_in("IVEngineClient::ServerCmd", b"\x00\xAA\xBB\xCC"), Ok(2));
+
+        let err = db.find_in("IVEngineClient::ServerCmd", b"\x00").unwrap_err();
+        assert!(err.contains("'IVEngineClient::ServerCmd'") && err.contains("[build 2.0.0.1]"), "{}", err);
+
+        let err = db.find_in("ICvar::FindVar", b"\x55\x00\xEC\x55\x01\xEC").unwrap_err();
+        assert!(err.contains("ambiguous, 2 matches at 0x0 0x3"), "{}", err);
+        assert!(db.find_in("Nope::Nothing", b"").unwrap_err().contains("no signature database entry"));
+    }
+
+    #[test]
+    fn reads_patch_version_from_steam_inf() {
+        let inf = "ClientVersion=2\r\nPatchVersion=2.0.0.1\r\nProductName=portal2\r\n";
+        assert_eq!(GameBuild::from_steam_inf(inf), Some(build("2.0.0.1")));
+        assert_eq!(GameBuild::from_steam_inf("ProductName=portal2"), None);
+    }
+}
@@ -0,0 +1,171 @@
+//! Signatures for functions the engine does not expose through a vtable.
+//!
+//! The byte patterns are scraped from the **Windows** build of Portal 2 and only
+//! valid there - the native Linux build is a different compiler's output
+//! entirely. Its equivalent is not a second set of patterns, though: Valve's Linux
+//! modules keep their mangled symbols, so [`builtin_itanium`] resolves the same
+//! functions by name through [`crate::platform::find_symbol`] instead.
+//!
+//! The patterns here are only the compiled-in defaults. [`Engine::initialize`]
+//! goes through a [`SignatureDatabase`], which lets a `signatures.toml` shipped next
+//! to the plugin override any of them - or any vtable index - for a specific game
+//! build, without rebuilding the plugin. See the [`database`] module for the format.
+//!
+//! [`Engine::initialize`]: crate::Engine::initialize
+
+pub mod database;
+
+pub use database::{GameBuild, Locator, Signature, SignatureDatabase, SignatureOrigin};
+
+// Signatures for IVEngineClient
+pub mod iv_engine_client {
+    pub const SERVER_CMD_PATTERN: &[u8] = b"\x55\x8B\xEC\x8B\x45\x08\x81\xEC\x08\x06\x00\x00\x50\x68\x2C\x57\x47\x0B\x8D\x8D\x00\xFF\xFF\xFF\x68\xFF\x00\x00\x00\x51\xE8\x4D\x10\x20\x00\x83\xC4\x10\x8D\x8D\xF8\xF9\xFF\xFF\xE8\x4F\xF1\x1F\x00\x6A\x00\x8D\x95\x00\xFF\xFF\xFF\x52\x8D\x8D\xF8\xF9\xFF\xFF";
+    pub const SERVER_CMD_MASK: &str = "xxxxx?xx????xx????xx????x????xx????xx?xx????x????x?xx????xxx????";
+
+    pub const CLIENT_CMD_PATTERN: &[u8] = b"\x55\x8B\xEC\xE8\xF8\x4E\x02\x00\x80\xB8\xAD\x49\x00\x00\x00\x74\x10\x6A\x63\xE8\x28\x11\x00\x00\x50\xE8\xF2\xF2\x0D\x00\x83\xC4\x08\x8B\x45\x08\x6A\x00\x50\xE8\x14\x11\x00\x00\x50\xE8\x1E\xEE\x0D\x00\x83\xC4\x0C\x6A\x00\x68\x0C\x6A\x46\x0B\xE8\xFF\x10\x00\x00";
+    pub const CLIENT_CMD_MASK: &str = "xxxx????xx?????x?x?x????xx????xx?xx?x?xx????xx????xx?x?x????x????";
+
+    pub const GET_PLAYER_INFO_PATTERN: &[u8] = b"\x55\x8B\xEC\x56\x8B\x75\x08\x4E\xE8\x23\x2B\x02\x00\x3B\xB0\x10\x01\x00\x00\x0F\x8D\x8B\x00\x00\x00\x85\xF6\x0F\x88\x83\x00\x00\x00\xE8\x0A\x2B\x02\x00\x83\xB8\x2C\x4F\x00\x00\x00\x75\x1A\x8B\x45\x0C\x68\x90\x00\x00\x00\x6A\x00\x50\xE8\xA1\xE8\x1F\x00\x83\xC4\x0C";
+    pub const GET_PLAYER_INFO_MASK: &str = "xxxxxx?xx????xx????xx????xxxx????x????xx?????x?xx?x????x?xx????xx?";
+
+    pub const GET_LAST_TIME_STAMP_PATTERN: &[u8] = b"\xE8\x3B\x4D\x02\x00\xD9\x80\xE8\x49\x00\x00";
+    pub const GET_LAST_TIME_STAMP_MASK: &str = "x????xx????";
+
+    pub const GET_VIEW_ANGLES_PATTERN: &[u8] = b"\x55\x8B\xEC\x6A\xFF\xE8\x16\x4C\x02\x00\xD9\x80\x60\x4A\x00\x00\x8B\x4D\x08\xD9\x19\x83\xC4\x04\xD9\x80\x64\x4A\x00\x00\xD9\x59\x04\xD9\x80\x68\x4A\x00\x00\xD9\x59\x08\x5D";
+    pub const GET_VIEW_ANGLES_MASK: &str = "xxxx?x????xx????xx?xxxx?xx????xx?xx????xx?x";
+
+    pub const SET_VIEW_ANGLES_PATTERN: &[u8] = b"\x55\x8B\xEC\x51\x56\x8B\x75\x08\xF3\x0F\x10\x06\xF3\x0F\x11\x45\xFC\x8B\x45\xFC\x25\x00\x00\x80\x7F\xF3\x0F\x11\x45\x08\x3D\x00\x00\x80\x7F\x74\x36\xF3\x0F\x10\x46\x04\xF3\x0F\x11\x45\xFC\x8B\x4D\xFC\x81\xE1\x00\x00\x80\x7F\x81\xF9\x00\x00\x80\x7F\x74\x1B";
+    pub const SET_VIEW_ANGLES_MASK: &str = "xxxxxxx?xxxxxxxx?xx?x????xxxx?x????x?xxxx?xxxx?xx?xx????xx????x?";
+
+    pub const IS_IN_GAME_PATTERN: &[u8] = b"\xE8\x9B\x2F\x02\x00\x33\xC9\x83\x78\x68\x06\x0F\x94\xC0";
+    pub const IS_IN_GAME_MASK: &str = "x????xxxx??xxx";
+
+    pub const IS_CONNECTED_PATTERN: &[u8] = b"\xE8\x9B\xED\xE8\xFF\x33\xC9\x83\x78\x68\x02\x0F\x9D\xC0";
+    pub const IS_CONNECTED_MASK: &str = "x????xxxx??xxx";
+
+    pub const EXECUTE_CLIENT_CMD_UNRESTRICTED_PATTERN: &[u8] = b"\x55\x8B\xEC\xA1\x54\x02\x7B\x0B\x8B\x40\x30\x8B\x0D\xC4\x3F\x55\x0B\x8B\x11\x83\xEC\x10\x56\x50\x8B\x42\x1C\x33\xF6\xFF\xD0\x84\xC0\x74\x09\x8B\x0D\x54\x02\x7B\x0B\x8B\x71\x30\x56\x68\x30\x03\x00\x00\x68\x34\x57\x47\x0B\x8D\x4D\xF0\xE8\x01\xF0\xFE\xFF\x8B\x55\x08";
+    pub const EXECUTE_CLIENT_CMD_UNRESTRICTED_MASK: &str = "xxxx????xx?xx????xxxx?xxxx?xxxxxxx?xx????xx?xx????x????xx?x????xx?";
+
+    pub const IS_SINGLPLAYER_PATTERN: &[u8] = b"\x33\xC0\x39\x05\xE8\xAB\x7C\x0B\x0F\x95\xC0";
+    pub const IS_SINGLPLAYER_MASK: &str = "xxxx????xxx";
+
+    pub const GET_SCREEN_SIZE_PATTERN: &[u8] = b"\x55\x8B\xEC\x8B\x0D\x14\x46\x7F\x0B\x8B\x01\x8B\x90\xC4\x01\x00\x00\x56\xFF\xD2\x8B\xF0\x85\xF6\x74\x09\x8B\x06\x8B\x50\x08\x8B\xCE\xFF\xD2\x8B\x4D\x0C\x8B\x06\x8B\x55\x08\x8B\x80\xA0\x01\x00\x00\x51\x52\x8B\xCE\xFF\xD0\x8B\x16\x8B\x42\x0C\x8B\xCE\xFF\xD0";
+    pub const GET_SCREEN_SIZE_MASK: &str = "xxxxx????xxxx????xxxxxxxx?xxxx?xxxxxx?xxxx?xx????xxxxxxxxxx?xxxx";
+
+    pub const GET_PLAYER_FOR_USER_ID_PATTERN: &[u8] = b"\x55\x8B\xEC\xE8\x38\x43\x02\x00\x83\xB8\x2C\x4F\x00\x00\x00\x75\x06\x33\xC0\x5D";
+    pub const GET_PLAYER_FOR_USER_ID_MASK: &str = "xxxx????xx?????x?xxx";
+
+    pub const GET_LOCAL_PLAYER_PATTERN: &[u8] = b"\x6A\xFF\xE8\x29\x4D\x02\x00\x8B\x80\xD8\x00\x00\x00\x83\xC4\x04\x40";
+    pub const GET_LOCAL_PLAYER_MASK: &str = "x?x????xx????xx?x";
+
+    pub const LOAD_MODEL_PATTERN: &[u8] = b"\x55\x8B\xEC\x0F\xB6\x55\x0C\x8B\x0D\x08\x5B\x55\x0B\x8B\x01\x8B\x40\x1C\xF7\xDA\x1B\xD2\x83\xE2\x18\x83\xC2\x08\x52\x8B\x55\x08\x52\xFF\xD0\x5D";
+    pub const LOAD_MODEL_MASK: &str = "xxxxxx?xx????xxxx?xxxxxx?xx?xxx?xxxx";
+
+    pub const KEY_LOOKUP_BINDING_PATTERN: &[u8] = b"\x55\x8B\xEC\x8B\x45\x08\x6A\xFF\x6A\x00\x6A\xFF\x50\xE8\x3E\xE9\x12\x00\x83\xC4\x10\x5D";
+    pub const KEY_LOOKUP_BINDING_MASK: &str = "xxxxx?x?x?x?xx????xx?x";
+}
+
+// Signatures for ICvar
+pub mod icvar {
+    pub const FIND_VAR_PATTERN: &[u8] = b"\x55\x8B\xEC\x8B\x01\x8B\x55\x08\x8B\x40\x34\x56\x52\xFF\xD0\x8B\xF0\x85\xF6\x75\x05\x5E\x5D";
+    pub const FIND_VAR_MASK: &str = "xxxxxxx?xx?xxxxxxxxx?xx";
+
+}
+
+// Signatures for IInputStackSystem
+pub mod iinput_stack_system {
+    pub const PUSH_INPUT_CONTEXT_PATTERN: &[u8] = b"\x53\x56\x57\x6A\x08\x8B\xD9\xE8\x14\x3A\x00\x00\x8B\xF8\x66\xC7\x47\x04\x01\x01\xC6\x47\x06\x00\x8B\x0D\x68\x79\xC5\x0B\x8B\x01\x8B\x90\xA8\x00\x00\x00\x83\xC4\x04\x6A\x01\xFF\xD2\x89\x07\x8B\x43\x10\x3B\x43\x08\x7C\x0A\x6A\x01\x8D\x4B\x04\xE8\xEF\x08\x00\x00\xFF\x43\x10\x8B\x43\x04\x8B\x4B\x10\x89\x43\x14\x8D\x44\x88\xFC\x85\xC0\x74\x02\x89\x38\x8B\xCB\xE8\x22\xFE\xFF\xFF\x8B\xC7";
+    pub const PUSH_INPUT_CONTEXT_MASK: &str = "xxxx?xxx????xxxxx???xx??xx????xxxx????xx?x?xxxxxx?xx?x?x?xx?x????xx?xx?xx?xx?xxx?xxx?xxxxx????xx";
+
+    pub const ENABLE_INPUT_CONTEXT_PATTERN: &[u8] = b"\x55\x8B\xEC\x56\x57\x8B\x7D\x08\x8B\xF1\x85\xFF\x74\x22\x53\x8A\x5D\x0C\x38\x5F\x04\x74\x18\x84\xDB\x75\x0A\x8B\x06\x8B\x50\x34\x6A\x00\x57\xFF\xD2\x8B\xCE\x88\x5F\x04\xE8\xF1\xFE\xFF\xFF\x5B\x5F\x5E\x5D";
+    pub const ENABLE_INPUT_CONTEXT_MASK: &str = "xxxxxxx?xxxxx?xxx?xx?x?xxx?xxxx?x?xxxxxxx?x????xxxx";
+
+    pub const SET_CURSOR_VISIBLE_PATTERN: &[u8] = b"\x55\x8B\xEC\x8B\x45\x08\x85\xC0\x74\x10\x8A\x55\x0C\x38\x50\x05\x74\x08\x88\x50\x05\xE8\xC6\xFE\xFF\xFF\x5D";
+    pub const SET_CURSOR_VISIBLE_MASK: &str = "xxxxx?xxx?xx?xx?x?xx?x????x";
+
+    pub const SET_MOUSE_CAPTURE_PATTERN: &[u8] = b"\x55\x8B\xEC\x8B\x45\x08\x85\xC0\x74\x10\x8A\x55\x0C\x38\x50\x06\x74\x08\x88\x50\x06\xE8\x86\xFE\xFF\xFF\x5D";
+    pub const SET_MOUSE_CAPTURE_MASK: &str = "xxxxx?xxx?xx?xx?x?xx?x????x";
+
+    pub const SET_CURSOR_POSITION_PATTERN: &[u8] = b"\x55\x8B\xEC\x8B\x01\x8B\x55\x08\x8B\x40\x3C\x52\xFF\xD0\x84\xC0\x74\x18\x8B\x45\x10\x8B\x0D\x68\x79\xC5\x0B\x8B\x11\x8B\x92\x98\x00\x00\x00\x50\x8B\x45\x0C\x50\xFF\xD2\x5D";
+    pub const SET_CURSOR_POSITION_MASK: &str = "xxxxxxx?xx?xxxxxx?xx?xx????xxxx????xxx?xxxx";
+
+    pub const IS_TOPMOST_ENABLED_CONTEXT_PATTERN: &[u8] = b"\x55\x8B\xEC\x56\x8B\x75\x08\x85\xF6\x74\x20\x8B\x51\x10\x8B\xFF\x4A\x78\x18\x8B\x41\x04\x8B\x04\x90\x80\x78\x04\x00\x74\xF1\x33\xC9\x3B\xC6\x0F\x94\xC0\x5E\x5D";
+    pub const IS_TOPMOST_ENABLED_CONTEXT_MASK: &str = "xxxxxx?xxx?xx?xxxx?xx?xxxxx??x?xxxxxxxxx";
+
+}
+
+/// The compiled-in signature set for the Windows build, under the names database
+/// entries use.
+///
+/// Entry names are `Interface::Function`, matching the engine's own naming, so
+/// an error message can be searched for in both this file and a disassembler.
+pub(crate) fn builtin_msvc() -> Vec<(&'static str, Signature)> {
+    use crate::platform::Module;
+    use iinput_stack_system::*;
+    use icvar::*;
+    use iv_engine_client::*;
+
+    let sig = |module, pattern: &[u8], mask: &str| Signature::new(module, pattern.to_vec(), mask.to_string(), 0);
+
+    vec![
+        ("IVEngineClient::ServerCmd", sig(Module::Engine, SERVER_CMD_PATTERN, SERVER_CMD_MASK)),
+        ("IVEngineClient::ClientCmd", sig(Module::Engine, CLIENT_CMD_PATTERN, CLIENT_CMD_MASK)),
+        ("IVEngineClient::GetPlayerInfo", sig(Module::Engine, GET_PLAYER_INFO_PATTERN, GET_PLAYER_INFO_MASK)),
+        ("IVEngineClient::GetLastTimeStamp", sig(Module::Engine, GET_LAST_TIME_STAMP_PATTERN, GET_LAST_TIME_STAMP_MASK)),
+        ("IVEngineClient::GetViewAngles", sig(Module::Engine, GET_VIEW_ANGLES_PATTERN, GET_VIEW_ANGLES_MASK)),
+        ("IVEngineClient::SetViewAngles", sig(Module::Engine, SET_VIEW_ANGLES_PATTERN, SET_VIEW_ANGLES_MASK)),
+        ("IVEngineClient::IsInGame", sig(Module::Engine, IS_IN_GAME_PATTERN, IS_IN_GAME_MASK)),
+        ("IVEngineClient::IsConnected", sig(Module::Engine, IS_CONNECTED_PATTERN, IS_CONNECTED_MASK)),
+        ("IVEngineClient::IsSingleplayer", sig(Module::Engine, IS_SINGLPLAYER_PATTERN, IS_SINGLPLAYER_MASK)),
+        ("IVEngineClient::GetScreenSize", sig(Module::Engine, GET_SCREEN_SIZE_PATTERN, GET_SCREEN_SIZE_MASK)),
+        ("IVEngineClient::GetPlayerForUserId", sig(Module::Engine, GET_PLAYER_FOR_USER_ID_PATTERN, GET_PLAYER_FOR_USER_ID_MASK)),
+        ("IVEngineClient::GetLocalPlayer", sig(Module::Engine, GET_LOCAL_PLAYER_PATTERN, GET_LOCAL_PLAYER_MASK)),
+        ("IVEngineClient::LoadModel", sig(Module::Engine, LOAD_MODEL_PATTERN, LOAD_MODEL_MASK)),
+        ("IVEngineClient::KeyLookupBinding", sig(Module::Engine, KEY_LOOKUP_BINDING_PATTERN, KEY_LOOKUP_BINDING_MASK)),
+        ("IVEngineClient::ExecuteClientCmdUnrestricted", sig(Module::Engine, EXECUTE_CLIENT_CMD_UNRESTRICTED_PATTERN, EXECUTE_CLIENT_CMD_UNRESTRICTED_MASK)),
+        ("ICvar::FindVar", sig(Module::VStdLib, FIND_VAR_PATTERN, FIND_VAR_MASK)),
+        ("IInputStackSystem::PushInputContext", sig(Module::InputSystem, PUSH_INPUT_CONTEXT_PATTERN, PUSH_INPUT_CONTEXT_MASK)),
+        ("IInputStackSystem::EnableInputContext", sig(Module::InputSystem, ENABLE_INPUT_CONTEXT_PATTERN, ENABLE_INPUT_CONTEXT_MASK)),
+        ("IInputStackSystem::SetCursorVisible", sig(Module::InputSystem, SET_CURSOR_VISIBLE_PATTERN, SET_CURSOR_VISIBLE_MASK)),
+        ("IInputStackSystem::SetMouseCapture", sig(Module::InputSystem, SET_MOUSE_CAPTURE_PATTERN, SET_MOUSE_CAPTURE_MASK)),
+        ("IInputStackSystem::SetCursorPosition", sig(Module::InputSystem, SET_CURSOR_POSITION_PATTERN, SET_CURSOR_POSITION_MASK)),
+        ("IInputStackSystem::IsTopmostEnabledContext", sig(Module::InputSystem, IS_TOPMOST_ENABLED_CONTEXT_PATTERN, IS_TOPMOST_ENABLED_CONTEXT_MASK)),
+    ]
+}
+
+/// The compiled-in signature set for the native Linux build: the same entries as
+/// [`builtin_msvc`], resolved by the mangled names of the engine's implementation
+/// classes.
+///
+/// These follow the class names in Valve's public SDK sources. Should a build
+/// turn out to name one differently, it can be corrected from `signatures.toml`
+/// without a rebuild, same as a stale pattern on Windows.
+pub(crate) fn builtin_itanium() -> Vec<(&'static str, Signature)> {
+    use crate::platform::Module;
+
+    let sym = Signature::symbol;
+    vec![
+        ("IVEngineClient::ServerCmd", sym(Module::Engine, "_ZN13CEngineClient9ServerCmdEPKcb")),
+        ("IVEngineClient::ClientCmd", sym(Module::Engine, "_ZN13CEngineClient9ClientCmdEPKc")),
+        ("IVEngineClient::GetPlayerInfo", sym(Module::Engine, "_ZN13CEngineClient13GetPlayerInfoEiP13player_info_s")),
+        ("IVEngineClient::GetLastTimeStamp", sym(Module::Engine, "_ZN13CEngineClient16GetLastTimeStampEv")),
+        ("IVEngineClient::GetViewAngles", sym(Module::Engine, "_ZN13CEngineClient13GetViewAnglesER6QAngle")),
+        ("IVEngineClient::SetViewAngles", sym(Module::Engine, "_ZN13CEngineClient13SetViewAnglesER6QAngle")),
+        ("IVEngineClient::IsInGame", sym(Module::Engine, "_ZN13CEngineClient8IsInGameEv")),
+        ("IVEngineClient::IsConnected", sym(Module::Engine, "_ZN13CEngineClient11IsConnectedEv")),
+        ("IVEngineClient::IsSingleplayer", sym(Module::Engine, "_ZN13CEngineClient14IsSinglePlayerEv")),
+        ("IVEngineClient::GetScreenSize", sym(Module::Engine, "_ZN13CEngineClient13GetScreenSizeERiS0_")),
+        ("IVEngineClient::GetPlayerForUserId", sym(Module::Engine, "_ZN13CEngineClient18GetPlayerForUserIDEi")),
+        ("IVEngineClient::GetLocalPlayer", sym(Module::Engine, "_ZN13CEngineClient14GetLocalPlayerEv")),
+        ("IVEngineClient::LoadModel", sym(Module::Engine, "_ZN13CEngineClient9LoadModelEPKcb")),
+        ("IVEngineClient::KeyLookupBinding", sym(Module::Engine, "_ZN13CEngineClient17Key_LookupBindingEPKc")),
+        ("IVEngineClient::ExecuteClientCmdUnrestricted", sym(Module::Engine, "_ZN13CEngineClient28ExecuteClientCmdUnrestrictedEPKc")),
+        ("ICvar::FindVar", sym(Module::VStdLib, "_ZN4CCvar7FindVarEPKc")),
+        ("IInputStackSystem::PushInputContext", sym(Module::InputSystem, "_ZN17CInputStackSystem16PushInputContextEv")),
+        ("IInputStackSystem::EnableInputContext", sym(Module::InputSystem, "_ZN17CInputStackSystem18EnableInputContextEP22InputContextHandle_t__b")),
+        ("IInputStackSystem::SetCursorVisible", sym(Module::InputSystem, "_ZN17CInputStackSystem16SetCursorVisibleEP22InputContextHandle_t__b")),
+        ("IInputStackSystem::SetMouseCapture", sym(Module::InputSystem, "_ZN17CInputStackSystem15SetMouseCaptureEP22InputContextHandle_t__b")),
+        ("IInputStackSystem::SetCursorPosition", sym(Module::InputSystem, "_ZN17CInputStackSystem17SetCursorPositionEP22InputContextHandle_t__ii")),
+        ("IInputStackSystem::IsTopmostEnabledContext", sym(Module::InputSystem, "_ZN17CInputStackSystem23IsTopmostEnabledContextEP22InputContextHandle_t__")),
+    ]
+}
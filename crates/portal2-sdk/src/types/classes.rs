@@ -0,0 +1,559 @@
+//! Typed views of the Portal 2 entity classes that puzzle tools care about.
+//!
+//! Each wrapper is a [`CBaseEntity`] that was checked to be of the right class, and
+//! dereferences to it. The class-specific accessors read members through
+//! [`crate::props`], so they return `None` when a member is missing from the class
+//! tables rather than reading the wrong memory.
+//!
+//! ```no_run
+//! use portal2_sdk::types::{Handle, PropPortal};
+//!
+//! let engine = portal2_sdk::get_engine();
+//! let entities = engine.entities();
+//! for portal in entities.iter().filter_map(|ent| ent.downcast::<PropPortal>()) {
+//!     let linked = portal.linked_portal().and_then(|h| h.resolve(&entities));
+//!     println!("{:?} linked to {:?}", portal.get_handle(), linked.map(|p| p.get_handle()));
+//! }
+//! ```
+
+use std::ffi::c_int;
+use std::ops::{Deref, DerefMut};
+
+use super::{CBaseEntity, CBaseHandle, FromEntity, Handle, QAngle, Vector};
+use crate::entities::Entities;
+use crate::props::PropValue;
+
+macro_rules! entity_class {
+    ($(
+        $(#[$meta:meta])*
+        $name:ident: server [$($server:literal),*], classnames [$($classname:literal),*];
+    )*) => {$(
+        $(#[$meta])*
+        #[repr(transparent)]
+        pub struct $name(CBaseEntity);
+
+        impl $name {
+            /// The network class names of the entities this type accepts.
+            pub const SERVER_CLASSES: &'static [&'static str] = &[$($server),*];
+            /// The entity class names this type accepts, for classes that share
+            /// their server class with others.
+            pub const CLASSNAMES: &'static [&'static str] = &[$($classname),*];
+        }
+
+        impl FromEntity for $name {
+            fn from_entity(entity: &CBaseEntity) -> Option<&Self> {
+                is_class(entity, Self::SERVER_CLASSES, Self::CLASSNAMES)
+                    // SAFETY: `$name` is a transparent wrapper around `CBaseEntity`.
+                    .then(|| unsafe { &*(entity as *const CBaseEntity as *const $name) })
+            }
+
+            fn from_entity_mut(entity: &mut CBaseEntity) -> Option<&mut Self> {
+                is_class(entity, Self::SERVER_CLASSES, Self::CLASSNAMES)
+                    .then(|| unsafe { &mut *(entity as *mut CBaseEntity as *mut $name) })
+            }
+        }
+
+        impl Deref for $name {
+            type Target = CBaseEntity;
+
+            fn deref(&self) -> &CBaseEntity {
+                &self.0
+            }
+        }
+
+        impl DerefMut for $name {
+            fn deref_mut(&mut self) -> &mut CBaseEntity {
+                &mut self.0
+            }
+        }
+    )*};
+}
+
+entity_class! {
+    /// `player`, the Portal 2 player.
+    Player: server ["CPortal_Player"], classnames ["player"];
+    /// `prop_portal`, a placed portal.
+    PropPortal: server ["CProp_Portal"], classnames ["prop_portal"];
+    /// `prop_weighted_cube`, any kind of cube.
+    WeightedCube: server ["CPropWeightedCube"], classnames ["prop_weighted_cube"];
+    /// Any of the floor buttons.
+    FloorButton: server [], classnames ["prop_floor_button", "prop_floor_cube_button", "prop_floor_ball_button", "prop_under_floor_button"];
+    /// `env_portal_laser`, a Thermal Discouragement Beam emitter.
+    LaserEmitter: server ["CPortalLaser"], classnames ["env_portal_laser"];
+    /// `point_laser_target`, what a laser catcher or relay is powered through.
+    LaserTarget: server ["CPortalLaserTarget"], classnames ["point_laser_target"];
+    /// `trigger_catapult`, the trigger that launches things off an Aerial Faith Plate.
+    FaithPlate: server [], classnames ["trigger_catapult"];
+    /// `paint_stream`, the blobs one paint sprayer has in flight.
+    PaintStream: server ["CPaintStream"], classnames ["paint_stream"];
+}
+
+fn is_class(entity: &CBaseEntity, server_classes: &[&str], classnames: &[&str]) -> bool {
+    if !server_classes.is_empty()
+        && let Some(class) = entity.get_server_class()
+        && server_classes.contains(&class.get_name().as_str())
+    {
+        return true;
+    }
+    !classnames.is_empty() && classnames.contains(&entity.get_classname().as_str())
+}
+
+impl CBaseEntity {
+    /// Views this entity as the typed class `T`, if it is one.
+    pub fn downcast<T: FromEntity>(&mut self) -> Option<&mut T> {
+        T::from_entity_mut(self)
+    }
+
+    /// Same as [`downcast`](Self::downcast), for a shared entity.
+    pub fn downcast_ref<T: FromEntity>(&self) -> Option<&T> {
+        T::from_entity(self)
+    }
+}
+
+/// Reads an integer or boolean prop whatever its width.
+fn int_prop(entity: &CBaseEntity, name: &str) -> Option<i64> {
+    let props = entity.class_props()?;
+    match entity.prop_value(props.get(name)?, 0)? {
+        PropValue::Int(value) => Some(value),
+        PropValue::Bool(value) => Some(value as i64),
+        _ => None,
+    }
+}
+
+fn bool_prop(entity: &CBaseEntity, name: &str) -> Option<bool> {
+    int_prop(entity, name).map(|value| value != 0)
+}
+
+fn handle_prop<T>(entity: &CBaseEntity, name: &str) -> Option<Handle<T>> {
+    entity.prop::<CBaseHandle>(name).map(Handle::from_raw).filter(|handle| handle.is_valid())
+}
+
+/// The player is standing on something.
+const FL_ONGROUND: i64 = 1 << 0;
+
+impl Player {
+    pub fn health(&self) -> Option<i32> {
+        self.prop("m_iHealth")
+    }
+
+    pub fn velocity(&self) -> Option<Vector> {
+        self.prop("m_vecVelocity")
+    }
+
+    pub fn is_on_ground(&self) -> Option<bool> {
+        int_prop(self, "m_fFlags").map(|flags| flags & FL_ONGROUND != 0)
+    }
+
+    /// The object the player is carrying.
+    pub fn held_object(&self) -> Option<Handle> {
+        handle_prop(self, "m_hAttachedObject")
+    }
+
+    /// The portal the player is currently passing through.
+    pub fn portal_environment(&self) -> Option<Handle<PropPortal>> {
+        handle_prop(self, "m_hPortalEnvironment")
+    }
+}
+
+impl PropPortal {
+    /// The portal this one leads to.
+    pub fn linked_portal(&self) -> Option<Handle<PropPortal>> {
+        handle_prop(self, "m_hLinkedPortal")
+    }
+
+    /// Whether the portal is placed, as opposed to fizzled or not yet fired.
+    pub fn is_activated(&self) -> Option<bool> {
+        bool_prop(self, "m_bActivated")
+    }
+
+    /// Whether this is the second (orange) portal of its pair.
+    pub fn is_portal2(&self) -> Option<bool> {
+        bool_prop(self, "m_bIsPortal2")
+    }
+
+    /// The portal gun pair this portal belongs to; 0 is the player's.
+    pub fn linkage_group(&self) -> Option<u8> {
+        int_prop(self, "m_iLinkageGroupID").map(|id| id as u8)
+    }
+
+    /// Whether the portal is placed and has a partner to lead to.
+    pub fn is_open(&self) -> bool {
+        self.is_activated() == Some(true) && self.linked_portal().is_some()
+    }
+}
+
+/// The `CubeType` keyvalue of a [`WeightedCube`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CubeType {
+    Standard,
+    Companion,
+    /// Redirects lasers.
+    Reflection,
+    Sphere,
+    Antique,
+    Unknown(i32),
+}
+
+impl From<i32> for CubeType {
+    fn from(value: i32) -> Self {
+        match value {
+            0 => CubeType::Standard,
+            1 => CubeType::Companion,
+            2 => CubeType::Reflection,
+            3 => CubeType::Sphere,
+            4 => CubeType::Antique,
+            other => CubeType::Unknown(other),
+        }
+    }
+}
+
+impl WeightedCube {
+    pub fn cube_type(&self) -> Option<CubeType> {
+        int_prop(self, "m_nCubeType").map(|ty| CubeType::from(ty as i32))
+    }
+
+    /// The beam a reflection cube sends on while a laser goes through it.
+    pub fn laser(&self) -> Option<Handle<LaserEmitter>> {
+        handle_prop(self, "m_hLaser")
+    }
+}
+
+impl FloorButton {
+    pub fn is_pressed(&self) -> Option<bool> {
+        bool_prop(self, "m_bButtonState")
+    }
+}
+
+impl LaserEmitter {
+    pub fn is_on(&self) -> Option<bool> {
+        bool_prop(self, "m_bLaserOn")
+    }
+
+    pub fn is_lethal(&self) -> Option<bool> {
+        bool_prop(self, "m_bIsLethal")
+    }
+
+    pub fn start_point(&self) -> Option<Vector> {
+        self.prop("m_vStartPoint")
+    }
+
+    /// Where this emitter's beam stops, after any portals. A reflection cube sends the
+    /// beam on with a laser of its own; see [`last_segment`](Self::last_segment).
+    pub fn end_point(&self) -> Option<Vector> {
+        self.prop("m_vEndPoint")
+    }
+
+    /// The reflection cube the beam goes through first.
+    pub fn reflector(&self) -> Option<Handle<WeightedCube>> {
+        handle_prop(self, "m_hReflector")
+    }
+
+    /// The laser the beam ends with, following it through every reflection cube.
+    pub fn last_segment<'e>(&'e self, entities: &'e Entities<'_>) -> &'e LaserEmitter {
+        let mut segment = self;
+        // Cubes can reflect into each other in a loop; the engine stops the beam there too.
+        for _ in 0..MAX_LASER_SEGMENTS {
+            let next = segment
+                .reflector()
+                .and_then(|cube| cube.resolve(entities))
+                .and_then(|cube| cube.laser())
+                .and_then(|laser| laser.resolve(entities));
+            match next {
+                Some(next) if !std::ptr::eq(next, self) => segment = next,
+                _ => break,
+            }
+        }
+        segment
+    }
+
+    /// The laser target the beam powers, if it ends on one.
+    ///
+    /// Targets do not record which beam powers them. This is the powered target
+    /// closest to where the [`last_segment`](Self::last_segment) ends, within the
+    /// size of a catcher or relay.
+    pub fn target<'e>(&self, entities: &'e Entities<'_>) -> Option<&'e LaserTarget> {
+        let end = self.last_segment(entities).end_point()?;
+        let mut found: Option<(&LaserTarget, f32)> = None;
+        let mut current = entities.find_in_sphere(None, &end, LASER_TARGET_RADIUS);
+        while let Some(entity) = current {
+            let entity: &'e CBaseEntity = entity;
+            if let Some(target) = entity.downcast_ref::<LaserTarget>()
+                && target.is_powered() == Some(true)
+            {
+                let distance = target.get_origin().distance(&end);
+                if found.is_none_or(|(_, closest)| distance < closest) {
+                    found = Some((target, distance));
+                }
+            }
+            current = entities.find_in_sphere(Some(entity), &end, LASER_TARGET_RADIUS);
+        }
+        found.map(|(target, _)| target)
+    }
+}
+
+/// Reflection cubes followed before a beam is taken to loop.
+const MAX_LASER_SEGMENTS: usize = 32;
+/// How far from where a beam ends its target can be: a catcher's target sits in the
+/// middle of the model the beam hits.
+const LASER_TARGET_RADIUS: f32 = 64.0;
+
+impl LaserTarget {
+    /// Whether a laser is hitting the target.
+    pub fn is_powered(&self) -> Option<bool> {
+        bool_prop(self, "m_bPowered")
+    }
+
+    /// Whether the target only counts a beam that ends on it, as catchers do, as
+    /// opposed to one passing through it, as relays do.
+    pub fn is_terminal_point(&self) -> Option<bool> {
+        bool_prop(self, "m_bTerminalPoint")
+    }
+}
+
+impl FaithPlate {
+    /// Launch speed for players.
+    pub fn player_speed(&self) -> Option<f32> {
+        self.prop("m_flPlayerVelocity")
+    }
+
+    /// Launch speed for physics objects.
+    pub fn physics_speed(&self) -> Option<f32> {
+        self.prop("m_flPhysicsVelocity")
+    }
+
+    pub fn launch_angles(&self) -> Option<QAngle> {
+        self.prop("m_vecLaunchAngles")
+    }
+
+    /// The entity the plate aims at. Without one, it launches along [`launch_angles`](Self::launch_angles).
+    pub fn launch_target(&self) -> Option<Handle> {
+        handle_prop(self, "m_hLaunchTarget")
+    }
+
+    /// The velocity a player is launched with when the plate has no launch target.
+    /// With a target, the engine solves for an arc that lands on it instead.
+    pub fn launch_velocity(&self) -> Option<Vector> {
+        if self.launch_target().is_some() {
+            return None;
+        }
+        Some(self.launch_angles()?.to_forward_vector() * self.player_speed()?)
+    }
+}
+
+/// The paint a blob or surface carries, numbered as `PaintPowerType`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaintType {
+    /// Repulsion Gel.
+    Bounce,
+    /// Unused in the shipped game.
+    Reflect,
+    /// Propulsion Gel.
+    Speed,
+    /// Conversion Gel.
+    Portal,
+    /// Cleansing water, which removes paint.
+    Erase,
+    Unknown(i32),
+}
+
+impl From<i32> for PaintType {
+    fn from(value: i32) -> Self {
+        match value {
+            0 => PaintType::Bounce,
+            1 => PaintType::Reflect,
+            2 => PaintType::Speed,
+            3 => PaintType::Portal,
+            4 => PaintType::Erase,
+            other => PaintType::Unknown(other),
+        }
+    }
+}
+
+/// One blob of paint in flight. Blobs are not entities: the paint blob manager
+/// creates a [`PaintStream`] for each sprayer, and the stream owns its blobs.
+///
+/// Laid out as the start of `CBasePaintBlob`.
+#[repr(C)]
+pub struct PaintBlob {
+    _vtable: *const std::ffi::c_void,
+    _temp_end_position: Vector,
+    _temp_end_velocity: Vector,
+    position: Vector,
+    prev_position: Vector,
+    velocity: Vector,
+    paint_type: c_int,
+}
+
+impl PaintBlob {
+    pub fn position(&self) -> Vector {
+        self.position
+    }
+
+    /// Where the blob was the update before.
+    pub fn previous_position(&self) -> Vector {
+        self.prev_position
+    }
+
+    pub fn velocity(&self) -> Vector {
+        self.velocity
+    }
+
+    pub fn paint_type(&self) -> PaintType {
+        PaintType::from(self.paint_type)
+    }
+}
+
+/// `PaintBlobVector_t`, a `CUtlVector<CPaintBlob*>`.
+#[repr(C)]
+struct BlobVector {
+    memory: *const *const PaintBlob,
+    alloc_count: c_int,
+    _grow_size: c_int,
+    size: c_int,
+    _elements: *const *const PaintBlob,
+}
+
+impl BlobVector {
+    /// The blobs, or `None` if the header does not look like a vector.
+    fn blobs(&self) -> Option<Vec<&PaintBlob>> {
+        if self.size < 0 || self.size > self.alloc_count.max(0) || (self.size > 0 && self.memory.is_null()) {
+            return None;
+        }
+        let pointers = unsafe { std::slice::from_raw_parts(self.memory, self.size as usize) };
+        pointers.iter().map(|&blob| unsafe { blob.as_ref() }).collect()
+    }
+}
+
+impl PaintStream {
+    pub fn paint_type(&self) -> Option<PaintType> {
+        int_prop(self, "m_nPaintType").map(|ty| PaintType::from(ty as i32))
+    }
+
+    /// The stream's blobs in flight. `None` if the class tables do not list the blob
+    /// vector, or it does not look like one.
+    pub fn blobs(&self) -> Option<Vec<&PaintBlob>> {
+        let offset = self.class_props()?.get("m_blobs")?.offset();
+        let vector = unsafe { &*((&self.0 as *const CBaseEntity as *const u8).add(offset) as *const BlobVector) };
+        vector.blobs()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::{c_char, c_void, CStr};
+    use crate::platform::abi::{vfn_impl, vslot};
+    use crate::types::ServerClass;
+
+    /// An entity whose networkable reports `class` and `classname`, which is all
+    /// the class checks look at.
+    #[repr(C)]
+    struct FakeEntity {
+        vtable: *const *const c_void,
+        networkable: *mut FakeNetworkable,
+    }
+
+    #[repr(C)]
+    struct FakeNetworkable {
+        vtable: *const *const c_void,
+        class: *mut ServerClass,
+        classname: *const c_char,
+    }
+
+    vfn_impl! {
+        fn fake_get_networkable(this: *const FakeEntity) -> *mut FakeNetworkable { unsafe { (*this).networkable } }
+        fn fake_get_server_class(this: *const FakeNetworkable) -> *mut ServerClass { unsafe { (*this).class } }
+        fn fake_get_class_name(this: *const FakeNetworkable) -> *const c_char { unsafe { (*this).classname } }
+    }
+
+    fn check(server_class: Option<&'static CStr>, classname: &'static CStr, test: impl FnOnce(&mut CBaseEntity)) {
+        let mut entity_vtable = [std::ptr::null(); 8];
+        entity_vtable[vslot(4, Some(0))] = fake_get_networkable as *const c_void;
+        let networkable_vtable = [std::ptr::null(), fake_get_server_class as *const c_void, std::ptr::null(), fake_get_class_name as *const c_void];
+
+        let mut class = server_class.map(|name| ServerClass {
+            name: name.as_ptr(),
+            table: std::ptr::null_mut(),
+            next: std::ptr::null_mut(),
+            class_id: 0,
+            instance_baseline_index: 0,
+        });
+        let mut networkable = FakeNetworkable {
+            vtable: networkable_vtable.as_ptr(),
+            class: class.as_mut().map_or(std::ptr::null_mut(), |class| class as *mut ServerClass),
+            classname: classname.as_ptr(),
+        };
+        let mut entity = FakeEntity { vtable: entity_vtable.as_ptr(), networkable: &mut networkable };
+        test(unsafe { &mut *(&mut entity as *mut FakeEntity as *mut CBaseEntity) });
+    }
+
+    #[test]
+    fn downcasts_by_server_class_or_classname() {
+        check(Some(c"CProp_Portal"), c"prop_portal", |entity| {
+            assert!(entity.downcast::<PropPortal>().is_some());
+            assert!(entity.downcast_ref::<WeightedCube>().is_none());
+            let address = entity as *mut CBaseEntity as usize;
+            assert_eq!(entity.downcast::<PropPortal>().map(|portal| portal as *mut PropPortal as usize), Some(address));
+        });
+
+        // Floor buttons share their server class with other props; only the classname tells.
+        check(Some(c"CPortal_Base2D"), c"prop_floor_cube_button", |entity| {
+            assert!(entity.downcast_ref::<FloorButton>().is_some());
+            assert!(entity.downcast_ref::<PropPortal>().is_none());
+        });
+
+        // A classname that merely starts like an accepted one does not match.
+        check(None, c"prop_weighted_cube_dropper", |entity| {
+            assert!(entity.downcast_ref::<WeightedCube>().is_none());
+            assert!(entity.downcast_ref::<CBaseEntity>().is_some());
+        });
+    }
+
+    #[test]
+    fn converts_keyvalue_enums() {
+        assert_eq!(CubeType::from(1), CubeType::Companion);
+        assert_eq!(CubeType::from(3), CubeType::Sphere);
+        assert_eq!(CubeType::from(9), CubeType::Unknown(9));
+        assert_eq!(PaintType::from(0), PaintType::Bounce);
+        assert_eq!(PaintType::from(3), PaintType::Portal);
+        assert_eq!(PaintType::from(7), PaintType::Unknown(7));
+    }
+
+    #[test]
+    fn reads_blob_vectors() {
+        let blob = |x: f32, paint_type: c_int| PaintBlob {
+            _vtable: std::ptr::null(),
+            _temp_end_position: Vector::default(),
+            _temp_end_velocity: Vector::default(),
+            position: Vector::new(x, 0.0, 0.0),
+            prev_position: Vector::default(),
+            velocity: Vector::default(),
+            paint_type,
+        };
+        let blobs = [blob(1.0, 0), blob(2.0, 2)];
+        let pointers: Vec<*const PaintBlob> = blobs.iter().map(|blob| blob as *const PaintBlob).collect();
+        let vector = |size: c_int, alloc_count: c_int| BlobVector {
+            memory: pointers.as_ptr(),
+            alloc_count,
+            _grow_size: 0,
+            size,
+            _elements: pointers.as_ptr(),
+        };
+
+        let valid = vector(2, 4);
+        let read = valid.blobs().unwrap();
+        assert_eq!(read.len(), 2);
+        assert_eq!(read[1].position().x, 2.0);
+        assert_eq!(read[1].paint_type(), PaintType::Speed);
+        assert!(vector(3, 2).blobs().is_none(), "more elements than allocated");
+        assert!(vector(-1, 2).blobs().is_none());
+    }
+
+    #[cfg(target_pointer_width = "32")]
+    #[test]
+    fn blobs_match_the_engine_layout() {
+        assert_eq!(std::mem::offset_of!(PaintBlob, position), 0x1C);
+        assert_eq!(std::mem::offset_of!(PaintBlob, paint_type), 0x40);
+        assert_eq!(size_of::<BlobVector>(), 20);
+    }
+}
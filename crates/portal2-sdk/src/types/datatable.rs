@@ -0,0 +1,296 @@
+//! The engine's reflection tables: the network `SendTable`s every `ServerClass` points
+//! to, and the save/restore `datamap_t` every entity class declares. Layouts follow
+//! the Portal 2 branch of the engine.
+//!
+//! These are read-only views; [`crate::props`] walks them into something usable.
+
+use std::ffi::{c_char, c_int, c_void, CStr};
+
+/// `SendPropType`: how a [`SendProp`] is encoded on the wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendPropType {
+    Int,
+    Float,
+    Vector,
+    /// A `Vector` of which only `x` and `y` are networked.
+    VectorXY,
+    String,
+    /// A fixed-size array; the element is described by [`SendProp::array_prop`].
+    Array,
+    /// A nested table, see [`SendProp::data_table`].
+    DataTable,
+    Int64,
+    Unknown(c_int),
+}
+
+impl From<c_int> for SendPropType {
+    fn from(value: c_int) -> Self {
+        match value {
+            0 => SendPropType::Int,
+            1 => SendPropType::Float,
+            2 => SendPropType::Vector,
+            3 => SendPropType::VectorXY,
+            4 => SendPropType::String,
+            5 => SendPropType::Array,
+            6 => SendPropType::DataTable,
+            7 => SendPropType::Int64,
+            other => SendPropType::Unknown(other),
+        }
+    }
+}
+
+/// The prop is one of the exclusions listed by a table, and holds no data.
+pub const SPROP_EXCLUDE: c_int = 1 << 6;
+/// The prop is the element template of an array prop that follows it.
+pub const SPROP_INSIDEARRAY: c_int = 1 << 8;
+
+/// A networked table of properties.
+#[repr(C)]
+pub struct SendTable {
+    pub props: *mut SendProp,
+    pub prop_count: c_int,
+    pub net_table_name: *const c_char,
+    precalc: *mut c_void,
+    /// `m_bInitialized`, `m_bHasBeenWritten` and `m_bHasPropsEncodedAgainstCurrentTickCount`.
+    state_bits: u8,
+}
+
+impl SendTable {
+    /// The table's name, e.g. `DT_BaseEntity`.
+    pub fn get_name(&self) -> String {
+        unsafe { cstr(self.net_table_name) }
+    }
+
+    pub fn props(&self) -> &[SendProp] {
+        if self.props.is_null() || self.prop_count <= 0 {
+            return &[];
+        }
+        unsafe { std::slice::from_raw_parts(self.props, self.prop_count as usize) }
+    }
+}
+
+/// One property of a [`SendTable`].
+#[repr(C)]
+pub struct SendProp {
+    vtable: *const c_void,
+    matching_recv_prop: *mut c_void,
+    pub prop_type: c_int,
+    pub bits: c_int,
+    pub low_value: f32,
+    pub high_value: f32,
+    pub array_prop: *mut SendProp,
+    array_length_proxy: *const c_void,
+    pub elements: c_int,
+    pub element_stride: c_int,
+    pub exclude_dt_name: *const c_char,
+    pub parent_array_prop_name: *const c_char,
+    pub var_name: *const c_char,
+    pub high_low_mul: f32,
+    pub flags: c_int,
+    proxy_fn: *const c_void,
+    data_table_proxy_fn: *const c_void,
+    pub data_table: *mut SendTable,
+    /// Offset of the member from the start of the object the table is sent from.
+    pub offset: c_int,
+    extra_data: *const c_void,
+}
+
+impl SendProp {
+    /// The member's name, e.g. `m_vecOrigin`.
+    pub fn get_name(&self) -> String {
+        unsafe { cstr(self.var_name) }
+    }
+
+    pub fn get_type(&self) -> SendPropType {
+        SendPropType::from(self.prop_type)
+    }
+}
+
+/// `fieldtype_t`: the type of a [`TypeDescription`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldType {
+    Void,
+    Float,
+    /// A `string_t`: a pointer into the engine's string pool.
+    String,
+    Vector,
+    Quaternion,
+    Integer,
+    Boolean,
+    Short,
+    Character,
+    Color32,
+    /// A nested struct, described by [`TypeDescription::td`].
+    Embedded,
+    Custom,
+    ClassPtr,
+    EHandle,
+    Edict,
+    PositionVector,
+    Time,
+    Tick,
+    ModelName,
+    SoundName,
+    /// An input handler; holds no data.
+    Input,
+    Function,
+    VMatrix,
+    VMatrixWorldSpace,
+    Matrix3x4WorldSpace,
+    Interval,
+    ModelIndex,
+    MaterialIndex,
+    Vector2D,
+    Unknown(c_int),
+}
+
+impl From<c_int> for FieldType {
+    fn from(value: c_int) -> Self {
+        use FieldType::*;
+        const TYPES: [FieldType; 29] = [
+            Void, Float, String, Vector, Quaternion, Integer, Boolean, Short, Character, Color32,
+            Embedded, Custom, ClassPtr, EHandle, Edict, PositionVector, Time, Tick, ModelName,
+            SoundName, Input, Function, VMatrix, VMatrixWorldSpace, Matrix3x4WorldSpace, Interval,
+            ModelIndex, MaterialIndex, Vector2D,
+        ];
+        usize::try_from(value).ok().and_then(|i| TYPES.get(i).copied()).unwrap_or(Unknown(value))
+    }
+}
+
+/// The field is set from the map; its external name is the keyvalue.
+pub const FTYPEDESC_KEY: i16 = 0x0004;
+/// The field is an input handler; its external name is the input.
+pub const FTYPEDESC_INPUT: i16 = 0x0008;
+
+/// `datamap_t`: the save/restore and key-value description of an entity class.
+#[repr(C)]
+pub struct DataMap {
+    pub data_desc: *mut TypeDescription,
+    pub data_num_fields: c_int,
+    pub data_class_name: *const c_char,
+    /// The map of the base class, or null.
+    pub base_map: *mut DataMap,
+    chains_validated: bool,
+    packed_offsets_computed: bool,
+    packed_size: c_int,
+}
+
+impl DataMap {
+    /// The C++ class name, e.g. `CBaseEntity`.
+    pub fn get_class_name(&self) -> String {
+        unsafe { cstr(self.data_class_name) }
+    }
+
+    /// The fields this class declares itself, without those of its base classes.
+    pub fn fields(&self) -> &[TypeDescription] {
+        if self.data_desc.is_null() || self.data_num_fields <= 0 {
+            return &[];
+        }
+        unsafe { std::slice::from_raw_parts(self.data_desc, self.data_num_fields as usize) }
+    }
+
+    pub fn base(&self) -> Option<&DataMap> {
+        unsafe { self.base_map.as_ref() }
+    }
+
+    /// The external names of the fields flagged with `flag`, e.g. [`FTYPEDESC_KEY`],
+    /// in this class and its base classes. Sorted, without duplicates.
+    pub fn external_names(&self, flag: i16) -> Vec<String> {
+        let mut names = Vec::new();
+        let mut current = Some(self);
+        while let Some(map) = current {
+            names.extend(map.fields().iter()
+                .filter(|field| field.flags & flag != 0)
+                .filter_map(TypeDescription::get_external_name));
+            current = map.base();
+        }
+        names.sort_unstable_by_key(|name| name.to_ascii_lowercase());
+        names.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+        names
+    }
+}
+
+/// A pointer to a member function: just the code pointer under MSVC, the pointer
+/// and a `this` adjustment under the Itanium ABI.
+#[cfg(target_os = "windows")]
+type MemberFn = *const c_void;
+#[cfg(not(target_os = "windows"))]
+type MemberFn = [*const c_void; 2];
+
+/// `typedescription_t`: one field of a [`DataMap`].
+#[repr(C)]
+pub struct TypeDescription {
+    pub field_type: c_int,
+    pub field_name: *const c_char,
+    /// Offset of the member from the start of the object.
+    pub field_offset: c_int,
+    /// Number of elements; more than one for arrays.
+    pub field_size: u16,
+    pub flags: i16,
+    /// The key-value or input name used by maps, if any.
+    pub external_name: *const c_char,
+    save_restore_ops: *mut c_void,
+    input_func: MemberFn,
+    /// The description of an embedded struct, for [`FieldType::Embedded`].
+    pub td: *mut DataMap,
+    /// Size of the whole member, all elements included.
+    pub field_size_in_bytes: c_int,
+    override_field: *mut TypeDescription,
+    override_count: c_int,
+    field_tolerance: f32,
+    flat_offset: [c_int; 2],
+    flat_group: u16,
+}
+
+impl TypeDescription {
+    pub fn get_name(&self) -> String {
+        unsafe { cstr(self.field_name) }
+    }
+
+    pub fn get_external_name(&self) -> Option<String> {
+        (!self.external_name.is_null()).then(|| unsafe { cstr(self.external_name) })
+    }
+
+    pub fn get_type(&self) -> FieldType {
+        FieldType::from(self.field_type)
+    }
+}
+
+unsafe fn cstr(ptr: *const c_char) -> String {
+    if ptr.is_null() {
+        return String::new();
+    }
+    unsafe { CStr::from_ptr(ptr) }.to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(name: &'static CStr, flags: i16) -> TypeDescription {
+        let mut field: TypeDescription = unsafe { std::mem::zeroed() };
+        field.field_name = name.as_ptr();
+        field.external_name = name.as_ptr();
+        field.flags = flags;
+        field
+    }
+
+    fn map(fields: &mut [TypeDescription], base: *mut DataMap) -> DataMap {
+        let mut map: DataMap = unsafe { std::mem::zeroed() };
+        map.data_desc = fields.as_mut_ptr();
+        map.data_num_fields = fields.len() as c_int;
+        map.base_map = base;
+        map
+    }
+
+    #[test]
+    fn collects_external_names_from_base_classes() {
+        let mut base_fields = [field(c"targetname", FTYPEDESC_KEY), field(c"Kill", FTYPEDESC_INPUT)];
+        let mut base = map(&mut base_fields, std::ptr::null_mut());
+        let mut fields = [field(c"CubeType", FTYPEDESC_KEY), field(c"TargetName", FTYPEDESC_KEY), field(c"m_bPrivate", 0)];
+        let derived = map(&mut fields, &mut base);
+
+        assert_eq!(derived.external_names(FTYPEDESC_KEY), ["CubeType", "TargetName"]);
+        assert_eq!(derived.external_names(FTYPEDESC_INPUT), ["Kill"]);
+    }
+}
@@ -1,6 +1,8 @@
-use std::ffi::{CStr, c_char, c_int};
-use super::{Vector, QAngle, SendTable};
-use crate::platform::abi::vfn;
+use std::collections::HashSet;
+use std::ffi::{CStr, CString, c_char, c_int};
+use std::sync::Mutex;
+use super::{Vector, QAngle, SendTable, DataMap, Handle, FTYPEDESC_INPUT, FTYPEDESC_KEY};
+use crate::platform::abi::{vfn, vslot};
 
 /// A unique identifier for a networkable entity. It combines an entity index
 /// with a serial number to prevent stale handles from referring to new entities
@@ -43,6 +45,7 @@ impl IServerNetworkable {
     // VTable index 7: GetBaseEntity
     // VTable index 8: GetPVSInfo
     // VTable index 9: ~IServerNetworkable
+    // The destructor comes last, so these indices are the same on both ABIs.
 
     /// Returns the ServerClass associated with this entity.
     pub fn get_server_class<'a>(&self) -> Option<&'a mut ServerClass> {
@@ -93,12 +96,13 @@ impl IServerEntity {
     //   6: GetModelIndex
     //   7: GetModelName
     //   8: SetModelIndex
+    // The destructor pair shifts everything by one on the Itanium ABI; see `vslot`.
 
     /// Returns the collision interface for this entity (bounding boxes, raycasts).
     pub fn get_collideable<'a>(&self) -> Option<&'a mut ICollideable> {
         unsafe {
             let vtable = *(self as *const _ as *const *const usize);
-            let get_col: vfn!((*const IServerEntity) -> *mut ICollideable) = std::mem::transmute(vtable.add(3).read());
+            let get_col: vfn!((*const IServerEntity) -> *mut ICollideable) = std::mem::transmute(vtable.add(vslot(3, Some(0))).read());
             let ptr = get_col(self);
             if ptr.is_null() { None } else { Some(&mut *ptr) }
         }
@@ -108,7 +112,7 @@ impl IServerEntity {
     pub fn get_networkable<'a>(&self) -> Option<&'a mut IServerNetworkable> {
         unsafe {
             let vtable = *(self as *const _ as *const *const usize);
-            let get_net: vfn!((*const IServerEntity) -> *mut IServerNetworkable) = std::mem::transmute(vtable.add(4).read());
+            let get_net: vfn!((*const IServerEntity) -> *mut IServerNetworkable) = std::mem::transmute(vtable.add(vslot(4, Some(0))).read());
             let ptr = get_net(self);
             if ptr.is_null() { None } else { Some(&mut *ptr) }
         }
@@ -118,8 +122,10 @@ impl IServerEntity {
     pub fn get_handle(&self) -> CBaseHandle {
         unsafe {
             let vtable = *(self as *const _ as *const *const usize);
-            let get_handle: vfn!((*const IServerEntity) -> CBaseHandle) = std::mem::transmute(vtable.add(2).read());
-            get_handle(self)
+            // `GetRefEHandle` returns a reference to the handle stored in the entity.
+            let get_handle: vfn!((*const IServerEntity) -> *const CBaseHandle) = std::mem::transmute(vtable.add(vslot(2, Some(0))).read());
+            let ptr = get_handle(self);
+            if ptr.is_null() { CBaseHandle::INVALID } else { *ptr }
         }
     }
 
@@ -127,7 +133,7 @@ impl IServerEntity {
     pub fn get_model_index(&self) -> i32 {
         unsafe {
             let vtable = *(self as *const _ as *const *const usize);
-            let get_idx: vfn!((*const IServerEntity) -> c_int) = std::mem::transmute(vtable.add(6).read());
+            let get_idx: vfn!((*const IServerEntity) -> c_int) = std::mem::transmute(vtable.add(vslot(6, Some(0))).read());
             get_idx(self)
         }
     }
@@ -153,7 +159,12 @@ impl CBaseEntity {
 
     /// Returns the entity index (extracted from the handle).
     pub fn get_index(&self) -> i32 {
-        self.as_server_entity().get_handle().0 as i32 & 0xFFF
+        self.as_server_entity().get_handle().index() as i32
+    }
+
+    /// Returns a handle to this entity, which can be kept across frames.
+    pub fn get_handle(&self) -> Handle {
+        Handle::from_raw(self.as_server_entity().get_handle())
     }
 
     /// Wrapper for IServerTools::GetKeyValue.
@@ -181,6 +192,18 @@ impl CBaseEntity {
         self.as_server_entity().get_networkable()?.get_server_class()
     }
 
+    /// Returns the save/restore description of the entity's class (`GetDataDescMap`).
+    /// See [`crate::props`] for reading the members it describes.
+    pub fn get_data_desc_map<'a>(&self) -> Option<&'a DataMap> {
+        // After IServerEntity's slots: GetServerClass, YouForgotToImplementOrDeclareServerClass, GetDataDescMap.
+        let index = crate::get_engine().signatures().vtable("CBaseEntity").virtual_destructor_at(0).index("get_data_desc_map", 11);
+        unsafe {
+            let vtable = *(self as *const _ as *const *const usize);
+            let get_map: vfn!((*const CBaseEntity) -> *mut DataMap) = std::mem::transmute(vtable.add(index).read());
+            get_map(self).as_ref()
+        }
+    }
+
     //
     // High-level entity manipulation methods
     //
@@ -249,6 +272,36 @@ impl CBaseEntity {
         }
     }
 
+    /// The keyvalues the entity's class understands, for [`get_key_value`](Self::get_key_value).
+    pub fn key_names(&self) -> Vec<String> {
+        self.get_data_desc_map().map(|map| map.external_names(FTYPEDESC_KEY)).unwrap_or_default()
+    }
+
+    /// The inputs the entity's class accepts, for [`fire_input`](Self::fire_input).
+    pub fn input_names(&self) -> Vec<String> {
+        self.get_data_desc_map().map(|map| map.external_names(FTYPEDESC_INPUT)).unwrap_or_default()
+    }
+
+    /// Fires `input` at this entity alone with `CBaseEntity::AcceptInput`, passing
+    /// `param` as a string value. Fails if the entity does not accept `input`.
+    pub fn fire_input(&mut self, input: &str, param: &str) -> Result<(), String> {
+        let input_name = CString::new(input).map_err(|_| format!("Input name {input:?} contains a NUL byte"))?;
+        let value = Variant {
+            value: VariantValue { string: pooled_string(param)? },
+            entity: CBaseHandle::INVALID,
+            field_type: FIELD_STRING,
+        };
+        // After IServerEntity's slots and the entity's own think, touch and I/O helpers.
+        let index = crate::get_engine().signatures().vtable("CBaseEntity").virtual_destructor_at(0).index("accept_input", 40);
+        let accepted = unsafe {
+            let vtable = *(self as *const _ as *const *const usize);
+            let accept_input: vfn!((*mut CBaseEntity, *const c_char, *mut CBaseEntity, *mut CBaseEntity, Variant, c_int) -> bool) =
+                std::mem::transmute(vtable.add(index).read());
+            accept_input(self, input_name.as_ptr(), std::ptr::null_mut(), std::ptr::null_mut(), value, 0)
+        };
+        if accepted { Ok(()) } else { Err(format!("{} does not accept the input {input}", self.get_classname())) }
+    }
+
     /// Sets a string key-value field for the entity.
     pub fn set_key_value(&mut self, key: &str, value: &str) -> bool {
         let tools = crate::get_engine().server_tools();
@@ -262,6 +315,41 @@ impl CBaseEntity {
     }
 }
 
+/// `fieldtype_t`'s `FIELD_STRING`.
+const FIELD_STRING: c_int = 2;
+
+/// `variant_t`: the value handed to an input, passed to `AcceptInput` by value.
+#[repr(C)]
+struct Variant {
+    value: VariantValue,
+    /// An entity value; unused unless `field_type` is `FIELD_EHANDLE`.
+    entity: CBaseHandle,
+    field_type: c_int,
+}
+
+#[repr(C)]
+union VariantValue {
+    /// A `string_t`.
+    string: *const c_char,
+    /// The largest member, which sizes the union.
+    _vector: [f32; 3],
+}
+
+/// Input handlers may keep a `string_t` they are given, as `SetTargetName` does, so
+/// parameters live as long as the engine's own pooled strings: forever.
+fn pooled_string(value: &str) -> Result<*const c_char, String> {
+    static POOL: Mutex<Option<HashSet<&'static CStr>>> = Mutex::new(None);
+    let value = CString::new(value).map_err(|_| format!("Parameter {value:?} contains a NUL byte"))?;
+    let mut pool = POOL.lock().unwrap_or_else(|e| e.into_inner());
+    let pool = pool.get_or_insert_with(HashSet::new);
+    if let Some(pooled) = pool.get(value.as_c_str()) {
+        return Ok(pooled.as_ptr());
+    }
+    let pooled: &'static CStr = Box::leak(value.into_boxed_c_str());
+    pool.insert(pooled);
+    Ok(pooled.as_ptr())
+}
+
 /// Holds information about an entity being respawned with edits (used by IServerTools).
 #[repr(C)]
 #[derive(Debug, Clone)]
@@ -281,6 +369,20 @@ impl CEntityRespawnInfo {
 }
 
 #[repr(C)] pub struct Edict { _private: [u8; 0] }
+
+/// The entity's networked state changed since the last update.
+const FL_EDICT_CHANGED: c_int = 1 << 0;
+/// Compare every networked prop, not only the ones reported as changed.
+const FL_FULL_EDICT_CHANGED: c_int = 1 << 8;
+
+impl Edict {
+    /// Marks the entity's networked state as changed, so that it is sent to clients
+    /// with the next update. Needed after writing networked members directly.
+    pub fn state_changed(&mut self) {
+        // `m_fStateFlags` is the first member of `CBaseEdict`.
+        unsafe { *(self as *mut _ as *mut c_int) |= FL_EDICT_CHANGED | FL_FULL_EDICT_CHANGED };
+    }
+}
 #[repr(C)] pub struct IClientEntity { _private: [u8; 0] }
 #[repr(C)] pub struct ICollideable { _private: [u8; 0] }
 #[repr(C)] pub struct IChangeInfoAccessor { _private: [u8; 0] }
@@ -288,3 +390,23 @@ impl CEntityRespawnInfo {
 #[repr(C)] pub struct IScratchPad3D { _private: [u8; 0] }
 #[repr(C)] pub struct CCheckTransmitInfo { _private: [u8; 0] }
 #[repr(C)] pub struct CSharedEdictChangeInfo { _private: [u8; 0] }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pools_input_parameters() {
+        let first = pooled_string("0 255 0").unwrap();
+        assert_eq!(pooled_string("0 255 0").unwrap(), first);
+        assert_ne!(pooled_string("255 0 0").unwrap(), first);
+        assert_eq!(unsafe { CStr::from_ptr(first) }, c"0 255 0");
+        assert!(pooled_string("a\0b").is_err());
+    }
+
+    #[cfg(target_pointer_width = "32")]
+    #[test]
+    fn variant_matches_the_engine_layout() {
+        assert_eq!(size_of::<Variant>(), 20);
+    }
+}
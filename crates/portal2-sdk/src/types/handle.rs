@@ -0,0 +1,194 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+use super::{CBaseEntity, CBaseHandle};
+use crate::entities::Entities;
+
+// The layout of the engine branch Portal 2 shares with Alien Swarm and CS:GO, as in
+// that branch's `public/const.h`: `NUM_ENT_ENTRY_BITS` is `MAX_EDICT_BITS + 2`, and the
+// serial number takes the upper 16 bits (`NUM_SERIAL_NUM_SHIFT_BITS`). The 2013 SDK's
+// 12 index bits do not apply.
+const MAX_EDICT_BITS: u32 = 11;
+/// Bits needed to number every slot of the server entity list.
+pub const NUM_ENT_ENTRY_BITS: u32 = MAX_EDICT_BITS + 2;
+/// Number of slots in the server entity list.
+pub const NUM_ENT_ENTRIES: usize = 1 << NUM_ENT_ENTRY_BITS;
+/// Where the serial number starts in a [`CBaseHandle`]; the slot is below it.
+pub const NUM_SERIAL_NUM_SHIFT_BITS: u32 = 16;
+const ENT_ENTRY_MASK: u32 = (1 << NUM_SERIAL_NUM_SHIFT_BITS) - 1;
+/// The value of a handle that refers to nothing.
+pub const INVALID_EHANDLE_INDEX: u32 = 0xFFFF_FFFF;
+
+impl CBaseHandle {
+    pub const INVALID: CBaseHandle = CBaseHandle(INVALID_EHANDLE_INDEX);
+
+    /// The slot in the entity list, which is also the entity index.
+    pub fn index(self) -> usize {
+        (self.0 & ENT_ENTRY_MASK) as usize
+    }
+
+    /// Bumped every time the slot is reused, so that old handles stop matching.
+    pub fn serial(self) -> u32 {
+        self.0 >> NUM_SERIAL_NUM_SHIFT_BITS
+    }
+
+    pub fn is_valid(self) -> bool {
+        self.0 != INVALID_EHANDLE_INDEX
+    }
+}
+
+/// An entity type a [`Handle`] can resolve to.
+pub trait FromEntity {
+    /// Views `entity` as `Self`, or `None` if it is not one.
+    fn from_entity(entity: &CBaseEntity) -> Option<&Self>;
+    /// Same as [`from_entity`](Self::from_entity), for changing the entity.
+    fn from_entity_mut(entity: &mut CBaseEntity) -> Option<&mut Self>;
+}
+
+impl FromEntity for CBaseEntity {
+    fn from_entity(entity: &CBaseEntity) -> Option<&Self> {
+        Some(entity)
+    }
+
+    fn from_entity_mut(entity: &mut CBaseEntity) -> Option<&mut Self> {
+        Some(entity)
+    }
+}
+
+/// A reference to an entity that is safe to keep across frames.
+///
+/// It stores the entity's slot and serial number rather than a pointer, and
+/// resolves to `None` once the entity is removed, even if another entity has
+/// taken the same slot since.
+///
+/// ```no_run
+/// use portal2_sdk::types::Handle;
+///
+/// let engine = portal2_sdk::get_engine();
+/// let cube: Handle = engine.entities()
+///     .find_by_classname(None, "prop_weighted_cube")
+///     .map(|cube| cube.get_handle())
+///     .unwrap_or_default();
+///
+/// // Frames later:
+/// if let Some(cube) = cube.resolve(&engine.entities()) {
+///     println!("The cube is still at {}", cube.get_origin());
+/// }
+/// ```
+#[repr(transparent)]
+pub struct Handle<T = CBaseEntity> {
+    raw: CBaseHandle,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    pub const INVALID: Self = Self::from_raw(CBaseHandle::INVALID);
+
+    pub const fn from_raw(raw: CBaseHandle) -> Self {
+        Self { raw, _marker: PhantomData }
+    }
+
+    pub fn raw(self) -> CBaseHandle {
+        self.raw
+    }
+
+    /// The entity index the handle refers to.
+    pub fn index(self) -> usize {
+        self.raw.index()
+    }
+
+    pub fn serial(self) -> u32 {
+        self.raw.serial()
+    }
+
+    /// Whether the handle was ever set. A valid handle can still fail to resolve.
+    pub fn is_valid(self) -> bool {
+        self.raw.is_valid()
+    }
+
+    /// The same entity, resolved as another type.
+    pub fn cast<U>(self) -> Handle<U> {
+        Handle::from_raw(self.raw)
+    }
+}
+
+impl<T: FromEntity> Handle<T> {
+    /// The entity, if it still exists and is a `T`. See [`Entities::get_by_handle`].
+    pub fn resolve<'e>(&self, entities: &'e Entities<'_>) -> Option<&'e T> {
+        entities.get_by_handle(self.raw).and_then(T::from_entity)
+    }
+
+    /// Same as [`resolve`](Self::resolve), for changing the entity. Borrowing
+    /// `entities` mutably keeps two handles from handing out the same entity twice.
+    pub fn resolve_mut<'e>(&self, entities: &'e mut Entities<'_>) -> Option<&'e mut T> {
+        entities.get_by_handle_mut(self.raw).and_then(T::from_entity_mut)
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.raw == other.raw
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.raw.hash(state);
+    }
+}
+
+impl<T> Default for Handle<T> {
+    fn default() -> Self {
+        Self::INVALID
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.is_valid() {
+            true => write!(f, "Handle({}:{})", self.index(), self.serial()),
+            false => f.write_str("Handle(invalid)"),
+        }
+    }
+}
+
+impl<T> From<CBaseHandle> for Handle<T> {
+    fn from(raw: CBaseHandle) -> Self {
+        Self::from_raw(raw)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_index_and_serial() {
+        let handle: Handle = Handle::from_raw(CBaseHandle((7 << NUM_SERIAL_NUM_SHIFT_BITS) | 42));
+        assert_eq!((handle.index(), handle.serial()), (42, 7));
+        assert!(handle.is_valid());
+        assert_eq!(format!("{:?}", handle), "Handle(42:7)");
+
+        // A reused slot has a new serial number, so the old handle no longer matches.
+        let reused: Handle = Handle::from_raw(CBaseHandle((8 << NUM_SERIAL_NUM_SHIFT_BITS) | 42));
+        assert_eq!(reused.index(), handle.index());
+        assert_ne!(reused, handle);
+
+        assert!(!Handle::<CBaseEntity>::default().is_valid());
+
+        // Slots past 4095 keep their upper bits.
+        let high = CBaseHandle((3 << NUM_SERIAL_NUM_SHIFT_BITS) | 5000);
+        assert_eq!((high.index(), high.serial()), (5000, 3));
+    }
+}
@@ -0,0 +1,327 @@
+//! Engine `KeyValues` trees built from Rust.
+//!
+//! The engine deletes the `KeyValues` some APIs are given, so a tree built here has to
+//! look exactly like one the engine built itself: every node allocated by
+//! `IKeyValuesSystem`, key names interned in its symbol table, and every string
+//! allocated by tier0's `g_pMemAlloc`, which is what the engine's `delete[]` frees
+//! them with. Both are exported by name, so no signatures are involved.
+
+use std::ffi::c_void;
+use std::ops::{Deref, DerefMut};
+use std::ptr::NonNull;
+
+use super::{KeyValues, KeyValuesTree, KeyValuesType, KvValue};
+use crate::key_values_system::IKeyValuesSystem;
+use crate::mem_alloc::IMemAlloc;
+
+/// The two engine allocators a tree is built with.
+struct Allocators<'a> {
+    key_values: &'a IKeyValuesSystem,
+    memory: &'a IMemAlloc,
+}
+
+impl Allocators<'_> {
+    fn from_engine() -> Result<Self, String> {
+        let engine = crate::ENGINE.get().ok_or("Engine not initialized")?;
+        let key_values = engine.key_values_system().ok_or("IKeyValuesSystem is not available")?;
+        let memory = engine.mem_alloc().ok_or("g_pMemAlloc is not available")?;
+        Ok(Self { key_values, memory })
+    }
+
+    /// Copies `bytes` into engine memory, followed by a null byte.
+    fn alloc_string(&self, bytes: &[u8]) -> Result<*mut u8, String> {
+        let memory = self.memory.alloc(bytes.len() + 1) as *mut u8;
+        if memory.is_null() {
+            return Err("Out of engine memory".to_string());
+        }
+        unsafe {
+            std::ptr::copy_nonoverlapping(bytes.as_ptr(), memory, bytes.len());
+            memory.add(bytes.len()).write(0);
+        }
+        Ok(memory)
+    }
+
+    /// Builds `tree` into a new node, freeing what was built so far on failure.
+    unsafe fn build(&self, tree: &KeyValuesTree) -> Result<*mut KeyValues, String> {
+        if tree.name.contains('\0') {
+            return Err(format!("Key name {:?} holds a null byte", tree.name));
+        }
+        let (symbol, case_sensitive) = self.key_values.get_symbols_for_string(&tree.name).ok_or("Invalid key name")?;
+
+        let kv = self.key_values.alloc_key_values_memory(size_of::<KeyValues>()) as *mut KeyValues;
+        if kv.is_null() {
+            return Err("Out of KeyValues memory".to_string());
+        }
+        unsafe {
+            std::ptr::write_bytes(kv, 0, 1);
+            // As `KeyValues::Init` leaves it, with the name set.
+            (*kv).set_key_symbols(symbol, case_sensitive);
+            (*kv).data_type = KeyValuesType::None;
+        }
+
+        if let Err(error) = unsafe { self.fill(kv, &tree.value) } {
+            unsafe { self.free(kv) };
+            return Err(error);
+        }
+        Ok(kv)
+    }
+
+    /// Stores `value` in the zeroed node `kv`. On failure, whatever was attached to
+    /// `kv` is still reachable from it, for [`free`](Self::free).
+    unsafe fn fill(&self, kv: *mut KeyValues, value: &KvValue) -> Result<(), String> {
+        let kv = unsafe { &mut *kv };
+        match value {
+            KvValue::Section(children) => {
+                let mut last: *mut KeyValues = std::ptr::null_mut();
+                for child in children {
+                    let node = unsafe { self.build(child)? };
+                    match unsafe { last.as_mut() } {
+                        Some(last) => last.peer = node,
+                        None => kv.sub = node,
+                    }
+                    last = node;
+                }
+            }
+            KvValue::String(s) => {
+                if s.contains('\0') {
+                    return Err(format!("Value of {:?} holds a null byte", s));
+                }
+                kv.string_value = self.alloc_string(s.as_bytes())? as *mut _;
+                kv.data_type = KeyValuesType::String;
+            }
+            KvValue::Int(v) => {
+                kv.value.int_val = *v;
+                kv.data_type = KeyValuesType::Int;
+            }
+            KvValue::Float(v) => {
+                kv.value.float_val = *v;
+                kv.data_type = KeyValuesType::Float;
+            }
+            KvValue::Uint64(v) => {
+                // Kept behind the string pointer, in memory of its own.
+                kv.string_value = self.alloc_string(&v.to_ne_bytes())? as *mut _;
+                kv.data_type = KeyValuesType::Uint64;
+            }
+            KvValue::Color(c) => {
+                kv.value.color_val = *c;
+                kv.data_type = KeyValuesType::Color;
+            }
+            KvValue::Pointer(p) => {
+                kv.value.ptr_val = *p as *mut c_void;
+                kv.data_type = KeyValuesType::Ptr;
+            }
+        }
+        Ok(())
+    }
+
+    /// Frees `kv`, its strings and everything under it, as `KeyValues::deleteThis` does.
+    unsafe fn free(&self, kv: *mut KeyValues) {
+        let node = unsafe { &mut *kv };
+        let mut child = node.sub;
+        while !child.is_null() {
+            let next = unsafe { (*child).peer };
+            unsafe { self.free(child) };
+            child = next;
+        }
+        unsafe {
+            if !node.string_value.is_null() {
+                self.memory.free(node.string_value as *mut c_void);
+            }
+            if !node.wstring_value.is_null() {
+                self.memory.free(node.wstring_value as *mut c_void);
+            }
+            self.key_values.free_key_values_memory(kv as *mut c_void);
+        }
+    }
+}
+
+/// A `KeyValues` tree in engine memory, built from a [`KeyValuesTree`] and freed when
+/// dropped.
+///
+/// It derefs to [`KeyValues`] for APIs that only borrow the tree. APIs that take
+/// ownership, like [`IVEngineServer::client_command_key_values`](crate::IVEngineServer::client_command_key_values),
+/// take it by value instead, and the engine frees it.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use portal2_sdk::types::keyvalues::{EngineKeyValues, KeyValuesTree};
+///
+/// let mut tree = KeyValuesTree::new("ClientCommand");
+/// tree.set("Command", "ping");
+/// let kv = EngineKeyValues::new(&tree).unwrap();
+/// assert_eq!(kv.to_tree().get_str("Command"), Some("ping"));
+/// ```
+pub struct EngineKeyValues {
+    root: NonNull<KeyValues>,
+}
+
+unsafe impl Send for EngineKeyValues {}
+
+impl EngineKeyValues {
+    /// Builds `tree` in engine memory. Fails if the engine is not initialized or lacks
+    /// either allocator, a key or string holds a null byte, or the engine is out of memory.
+    pub fn new(tree: &KeyValuesTree) -> Result<Self, String> {
+        Self::build(&Allocators::from_engine()?, tree)
+    }
+
+    fn build(allocators: &Allocators, tree: &KeyValuesTree) -> Result<Self, String> {
+        let root = unsafe { allocators.build(tree)? };
+        Ok(Self { root: NonNull::new(root).unwrap() })
+    }
+
+    /// Gives up ownership, for an engine API that frees the tree itself.
+    pub fn into_raw(self) -> *mut KeyValues {
+        let root = self.root.as_ptr();
+        std::mem::forget(self);
+        root
+    }
+
+    /// Takes ownership of a tree the engine allocated, to free it on drop.
+    ///
+    /// # Safety
+    ///
+    /// `kv` must be a live, engine-allocated `KeyValues` that nothing else will free.
+    pub unsafe fn from_raw(kv: NonNull<KeyValues>) -> Self {
+        Self { root: kv }
+    }
+}
+
+impl Deref for EngineKeyValues {
+    type Target = KeyValues;
+
+    fn deref(&self) -> &KeyValues {
+        unsafe { self.root.as_ref() }
+    }
+}
+
+impl DerefMut for EngineKeyValues {
+    fn deref_mut(&mut self) -> &mut KeyValues {
+        unsafe { self.root.as_mut() }
+    }
+}
+
+impl Drop for EngineKeyValues {
+    fn drop(&mut self) {
+        // Without an engine and both allocators nothing could have been built.
+        if let Ok(allocators) = Allocators::from_engine() {
+            unsafe { allocators.free(self.root.as_ptr()) };
+        }
+    }
+}
+
+impl KeyValuesTree {
+    /// Builds the tree in engine memory. See [`EngineKeyValues`].
+    pub fn to_engine(&self) -> Result<EngineKeyValues, String> {
+        EngineKeyValues::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::{c_char, c_int, CStr};
+    use std::sync::Mutex;
+    use crate::signatures::SignatureDatabase;
+
+    /// Live allocations of the fake allocators, by address, and the fake symbol table.
+    static ALLOCATIONS: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+    static SYMBOLS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+    static CASE_SENSITIVE_SYMBOLS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    /// The index of `name` in `table`, added if it is new.
+    fn intern(table: &Mutex<Vec<String>>, name: String) -> c_int {
+        let mut symbols = table.lock().unwrap();
+        match symbols.iter().position(|s| *s == name) {
+            Some(index) => index as c_int,
+            None => {
+                symbols.push(name);
+                symbols.len() as c_int - 1
+            }
+        }
+    }
+
+    fn track_alloc(size: usize) -> *mut c_void {
+        let memory = Box::into_raw(vec![0xCDu8; size.max(1)].into_boxed_slice()) as *mut c_void;
+        ALLOCATIONS.lock().unwrap().push(memory as usize);
+        memory
+    }
+
+    fn track_free(memory: *mut c_void) {
+        let mut live = ALLOCATIONS.lock().unwrap();
+        let index = live.iter().position(|&m| m == memory as usize).expect("freed memory that was not allocated");
+        live.remove(index);
+        // Leaked rather than freed: the fake does not track sizes.
+    }
+
+    crate::platform::abi::vfn_impl! {
+        fn fake_alloc_kv(_this: *mut c_void, size: c_int) -> *mut c_void { track_alloc(size as usize) }
+        fn fake_free(_this: *mut c_void, memory: *mut c_void) { track_free(memory) }
+        fn fake_get_symbol(_this: *mut c_void, name: *const c_char, _create: bool) -> c_int {
+            intern(&SYMBOLS, unsafe { CStr::from_ptr(name) }.to_str().unwrap().to_ascii_lowercase())
+        }
+        fn fake_get_symbol_case_sensitive(_this: *mut c_void, case_insensitive: *mut c_int, name: *const c_char, _create: bool) -> c_int {
+            let name = unsafe { CStr::from_ptr(name) }.to_str().unwrap();
+            unsafe { *case_insensitive = intern(&SYMBOLS, name.to_ascii_lowercase()) };
+            // Offset so the symbol spills into the second part of the field.
+            intern(&CASE_SENSITIVE_SYMBOLS, name.to_string()) + 0x1234
+        }
+        fn fake_get_string(_this: *mut c_void, _symbol: c_int) -> *const c_char { std::ptr::null() }
+        fn fake_alloc(_this: *mut c_void, size: usize) -> *mut c_void { track_alloc(size) }
+    }
+
+    /// A fake interface object around `vtable`, which must stay alive while it is used.
+    fn object(vtable: &[*const c_void]) -> Box<*const *const c_void> {
+        Box::new(vtable.as_ptr())
+    }
+
+    #[test]
+    fn builds_and_frees_engine_compatible_trees() {
+        let mut kv_vtable = [std::ptr::null(); 10];
+        kv_vtable[1..5].copy_from_slice(&[fake_alloc_kv as *const c_void, fake_free as *const c_void, fake_get_symbol as *const c_void, fake_get_string as *const c_void]);
+        kv_vtable[9] = fake_get_symbol_case_sensitive as *const c_void;
+        // Both the MSVC and the Itanium slots are filled, so the test runs under either ABI.
+        let mem_vtable = [fake_alloc as *const c_void, fake_alloc as *const c_void, fake_free as *const c_void, std::ptr::null(), std::ptr::null(), fake_free as *const c_void];
+        let (mut kv_object, mut mem_object) = (object(&kv_vtable), object(&mem_vtable));
+        let db = SignatureDatabase::builtin();
+        let key_values = unsafe { IKeyValuesSystem::resolve(&mut *kv_object as *mut _ as *mut c_void, &db) }.unwrap();
+        let memory = unsafe { IMemAlloc::resolve(&mut *mem_object as *mut _ as *mut c_void, &db) }.unwrap();
+        let allocators = Allocators { key_values: &key_values, memory: &memory };
+
+        let mut tree = KeyValuesTree::new("Root");
+        tree.set("Command", "ping");
+        tree.set("Sub/Count", 3);
+        tree.set("Sub/Scale", 0.5);
+        tree.set("Sub/Id", 76561197960287930u64);
+        tree.set("Sub/Tint", [1u8, 2, 3, 4]);
+        tree.ensure("Empty");
+
+        let built = EngineKeyValues::build(&allocators, &tree).unwrap();
+        let names = |kv: &KeyValues| SYMBOLS.lock().unwrap()[kv.key_symbol() as usize].clone();
+        let copy = KeyValuesTree::convert(&built, &names);
+        let mut expected = tree.clone();
+        rename_lowercase(&mut expected);
+        assert_eq!(copy, expected);
+        let case_sensitive = CASE_SENSITIVE_SYMBOLS.lock().unwrap()[(built.case_sensitive_symbol() - 0x1234) as usize].clone();
+        assert_eq!(case_sensitive, "Root");
+        let count = built.first_sub_key().unwrap().next().unwrap().first_sub_key().unwrap();
+        assert_eq!(CASE_SENSITIVE_SYMBOLS.lock().unwrap()[(count.case_sensitive_symbol() - 0x1234) as usize], "Count");
+
+        unsafe { allocators.free(built.into_raw()) };
+        assert!(ALLOCATIONS.lock().unwrap().is_empty(), "every node and string is freed");
+
+        let mut bad = KeyValuesTree::new("Root");
+        bad.set("Good", "1");
+        bad.set("Bad", "a\0b");
+        assert!(EngineKeyValues::build(&allocators, &bad).err().unwrap().contains("null byte"));
+        assert!(ALLOCATIONS.lock().unwrap().is_empty(), "a failed build frees what it built");
+    }
+
+    /// The fake symbol table is case-insensitive and keeps lower-case names.
+    fn rename_lowercase(tree: &mut KeyValuesTree) {
+        tree.name = tree.name.to_ascii_lowercase();
+        if let KvValue::Section(children) = &mut tree.value {
+            children.iter_mut().for_each(rename_lowercase);
+        }
+    }
+}
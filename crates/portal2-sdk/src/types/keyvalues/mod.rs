@@ -0,0 +1,183 @@
+use std::ffi::{CStr, c_char, c_int, c_void};
+
+mod tree;
+mod vdf;
+mod engine;
+pub use tree::{KeyValuesTree, KvValue};
+pub use engine::EngineKeyValues;
+pub use vdf::{VdfReader, VdfWriter};
+
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StringT(pub u32);
+
+/// Defines the underlying data type of a KeyValues node.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyValuesType {
+    None = 0,
+    String = 1,
+    Int = 2,
+    Float = 3,
+    Ptr = 4,
+    WString = 5,
+    Color = 6,
+    Uint64 = 7,
+    CompiledIntByte = 8,
+}
+
+/// Union holding the raw value of a KeyValues node.
+/// In a 32-bit Source Engine, this union occupies exactly 4 bytes.
+#[repr(C)]
+pub union KeyValuesValue {
+    pub int_val: c_int,
+    pub float_val: f32,
+    pub ptr_val: *mut c_void,
+    pub color_val: [u8; 4],
+}
+
+/// Represents the exact memory layout of the `KeyValues` C++ class in a 32-bit Source Engine.
+/// This allows safe reading of parsed VDF (Valve Data Format) trees.
+///
+/// Portal 2 keeps two symbols per key: the case-insensitive one in the low 24 bits of
+/// `key_name`, and a case-sensitive one split between the top byte of `key_name` and
+/// `key_name_case_sensitive2`.
+#[repr(C)]
+pub struct KeyValues {
+    pub key_name: u32,                  // 0x00 m_iKeyName : 24, m_iKeyNameCaseSensitive1 : 8
+    pub string_value: *mut c_char,      // 0x04 Pointer to the string (if type is String)
+    pub wstring_value: *mut u16,        // 0x08 Pointer to the wide string (if type is WString)
+    pub value: KeyValuesValue,          // 0x0C The raw numeric/pointer value
+    pub data_type: KeyValuesType,       // 0x10 The type of data stored in `value` or `string_value`
+    pub has_escape_sequences: bool,     // 0x11
+    pub key_name_case_sensitive2: u16,  // 0x12 m_iKeyNameCaseSensitive2
+    pub peer: *mut KeyValues,           // 0x14 Next element at the same hierarchy level
+    pub sub: *mut KeyValues,            // 0x18 First child element (sub-key)
+    pub chain: *mut KeyValues,          // 0x1C Chain element
+}
+
+/// `sizeof(KeyValues)` in the engine. Nodes built from Rust are allocated with
+/// `size_of::<KeyValues>()`, so the two must agree on the game's 32-bit targets.
+#[cfg(target_pointer_width = "32")]
+const _: () = assert!(size_of::<KeyValues>() == 0x20);
+
+/// The low 24 bits of the name field are the key's symbol; the rest is part of its
+/// case-sensitive variant.
+const KEY_NAME_SYMBOL_MASK: u32 = 0x00FF_FFFF;
+
+impl KeyValues {
+    /// The key's case-insensitive symbol, as `GetSymbolForString` returns it.
+    pub fn key_symbol(&self) -> i32 {
+        (self.key_name & KEY_NAME_SYMBOL_MASK) as i32
+    }
+
+    /// The key's case-sensitive symbol, reassembled from its two parts.
+    pub fn case_sensitive_symbol(&self) -> i32 {
+        ((self.key_name >> 24) | (self.key_name_case_sensitive2 as u32) << 8) as i32
+    }
+
+    /// Stores both of the key's symbols.
+    pub fn set_key_symbols(&mut self, symbol: i32, case_sensitive: i32) {
+        self.key_name = (symbol as u32 & KEY_NAME_SYMBOL_MASK) | (case_sensitive as u32) << 24;
+        self.key_name_case_sensitive2 = (case_sensitive as u32 >> 8) as u16;
+    }
+
+    /// The key's name, looked up in the engine's symbol table. Empty if the engine is
+    /// not initialized, has no `IKeyValuesSystem`, or does not know the symbol.
+    pub fn get_name(&self) -> String {
+        crate::ENGINE
+            .get()
+            .and_then(|engine| engine.key_values_system()?.get_string_for_symbol(self.key_symbol()))
+            .unwrap_or_default()
+    }
+
+    /// Copies the node and everything under it into an owned tree.
+    pub fn to_tree(&self) -> KeyValuesTree {
+        KeyValuesTree::from_engine(self)
+    }
+
+    /// Returns an Option referencing the next KeyValues node at the same level.
+    pub fn next(&self) -> Option<&KeyValues> {
+        unsafe { self.peer.as_ref() }
+    }
+
+    /// Returns an Option referencing the first child KeyValues node.
+    pub fn first_sub_key(&self) -> Option<&KeyValues> {
+        unsafe { self.sub.as_ref() }
+    }
+
+    /// Safely reads the value as a Rust String.
+    /// If the node holds a number, it will be formatted as a string.
+    pub fn get_string(&self) -> String {
+        match self.data_type {
+            KeyValuesType::String => unsafe {
+                if self.string_value.is_null() {
+                    String::new()
+                } else {
+                    CStr::from_ptr(self.string_value).to_string_lossy().into_owned()
+                }
+            },
+            KeyValuesType::WString => read_wide(self.wstring_value),
+            KeyValuesType::Int => unsafe { format!("{}", self.value.int_val) },
+            KeyValuesType::Float => unsafe { format!("{:.4}", self.value.float_val) },
+            _ => String::new(),
+        }
+    }
+
+    /// Safely reads the value as an integer.
+    /// If the node holds a float or a parsable string, it attempts to convert it.
+    pub fn get_int(&self) -> i32 {
+        match self.data_type {
+            KeyValuesType::Int | KeyValuesType::CompiledIntByte => unsafe { self.value.int_val },
+            KeyValuesType::Float => unsafe { self.value.float_val as i32 },
+            KeyValuesType::String => self.get_string().parse().unwrap_or(0),
+            _ => 0,
+        }
+    }
+
+    /// Reads a `TYPE_UINT64` value, which the engine keeps behind the string pointer.
+    pub fn get_uint64(&self) -> u64 {
+        match self.data_type {
+            KeyValuesType::Uint64 if !self.string_value.is_null() => unsafe { (self.string_value as *const u64).read_unaligned() },
+            KeyValuesType::Int => self.get_int() as u64,
+            _ => 0,
+        }
+    }
+
+    /// Safely reads the value as a float.
+    /// If the node holds an integer or a parsable string, it attempts to convert it.
+    pub fn get_float(&self) -> f32 {
+        match self.data_type {
+            KeyValuesType::Float => unsafe { self.value.float_val },
+            KeyValuesType::Int => unsafe { self.value.int_val as f32 },
+            KeyValuesType::String => self.get_string().parse().unwrap_or(0.0),
+            _ => 0.0,
+        }
+    }
+}
+
+/// Reads a null-terminated `wchar_t` string, two bytes wide on Windows and four elsewhere.
+fn read_wide(text: *const u16) -> String {
+    if text.is_null() {
+        return String::new();
+    }
+    #[cfg(target_os = "windows")]
+    type WChar = u16;
+    #[cfg(not(target_os = "windows"))]
+    type WChar = u32;
+
+    let text = text as *const WChar;
+    let len = (0..).take_while(|&i| unsafe { *text.add(i) } != 0).count();
+    let units = unsafe { std::slice::from_raw_parts(text, len) };
+    #[cfg(target_os = "windows")]
+    return String::from_utf16_lossy(units);
+    #[cfg(not(target_os = "windows"))]
+    return units.iter().map(|&c| char::from_u32(c).unwrap_or(char::REPLACEMENT_CHARACTER)).collect();
+}
+
+#[repr(C)] pub struct CUtlVector { _private: [u8; 0] }
+#[repr(C)] pub struct CBitVec { _private: [u8; 0] }
+#[repr(C)] pub struct BfWrite { _private: [u8; 0] }
+#[repr(C)] pub struct BfRead { _private: [u8; 0] }
+#[repr(C)] pub struct CGamestatsData { _private: [u8; 0] }
+#[repr(C)] pub struct CSaveRestoreData { _private: [u8; 0] }
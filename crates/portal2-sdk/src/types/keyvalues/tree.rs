@@ -0,0 +1,353 @@
+use std::fmt;
+
+use super::{KeyValues, KeyValuesType, VdfReader, VdfWriter};
+
+/// The value of a [`KeyValuesTree`] node.
+///
+/// Text VDF only ever produces `String` and `Section`; the other types come from
+/// trees copied out of the engine, and are written as text when serialized.
+#[derive(Debug, Clone, PartialEq)]
+pub enum KvValue {
+    /// A node holding other nodes, in order. Keys may repeat.
+    Section(Vec<KeyValuesTree>),
+    String(String),
+    Int(i32),
+    Float(f32),
+    Uint64(u64),
+    /// `r g b a`.
+    Color([u8; 4]),
+    /// An engine pointer, kept as an address.
+    Pointer(usize),
+}
+
+impl fmt::Display for KvValue {
+    /// The value as it is written in text; empty for sections.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KvValue::Section(_) => Ok(()),
+            KvValue::String(s) => f.write_str(s),
+            KvValue::Int(v) => write!(f, "{}", v),
+            KvValue::Float(v) => write!(f, "{}", v),
+            KvValue::Uint64(v) => write!(f, "{}", v),
+            KvValue::Color([r, g, b, a]) => write!(f, "{} {} {} {}", r, g, b, a),
+            KvValue::Pointer(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+impl From<&str> for KvValue {
+    fn from(value: &str) -> Self { KvValue::String(value.to_string()) }
+}
+
+impl From<String> for KvValue {
+    fn from(value: String) -> Self { KvValue::String(value) }
+}
+
+impl From<i32> for KvValue {
+    fn from(value: i32) -> Self { KvValue::Int(value) }
+}
+
+impl From<f32> for KvValue {
+    fn from(value: f32) -> Self { KvValue::Float(value) }
+}
+
+impl From<u64> for KvValue {
+    fn from(value: u64) -> Self { KvValue::Uint64(value) }
+}
+
+impl From<[u8; 4]> for KvValue {
+    fn from(value: [u8; 4]) -> Self { KvValue::Color(value) }
+}
+
+/// An owned `KeyValues` tree: a named node holding either a value or more nodes.
+///
+/// Unlike [`KeyValues`], which is a view into engine memory, this can be built, edited,
+/// parsed from and written to text VDF freely. Keys are matched case-insensitively,
+/// as the engine does, and `/` separates the keys of a path.
+///
+/// # Example
+///
+/// ```rust
+/// use portal2_sdk::types::keyvalues::KeyValuesTree;
+///
+/// let material = KeyValuesTree::parse(r#"
+///     "LightmappedGeneric"
+///     {
+///         "$basetexture" "metal/black_wall_metal_002a"
+///         "Proxies"
+///         {
+///             "AnimatedTexture" { "animatedtexturevar" "$basetexture" }
+///         }
+///     }
+/// "#).unwrap();
+///
+/// assert_eq!(material.get_str("Proxies/AnimatedTexture/animatedtexturevar"), Some("$basetexture"));
+/// assert_eq!(material.get_str("$BaseTexture"), Some("metal/black_wall_metal_002a"));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyValuesTree {
+    pub name: String,
+    pub value: KvValue,
+}
+
+impl KeyValuesTree {
+    /// An empty section named `name`.
+    pub fn new(name: &str) -> Self {
+        Self { name: name.to_string(), value: KvValue::Section(Vec::new()) }
+    }
+
+    /// A leaf named `name` holding `value`.
+    pub fn with_value(name: &str, value: impl Into<KvValue>) -> Self {
+        Self { name: name.to_string(), value: value.into() }
+    }
+
+    /// Parses text VDF holding a single root, with the platform's default conditionals
+    /// and no `#include` support. See [`VdfReader`] for more control.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        VdfReader::new().parse(text)
+    }
+
+    /// Writes the tree as text VDF. See [`VdfWriter`] for more control.
+    pub fn to_vdf(&self) -> String {
+        VdfWriter::new().write(self)
+    }
+
+    /// Copies an engine tree: `kv` and everything under it, but not its peers.
+    pub fn from_engine(kv: &KeyValues) -> Self {
+        Self::convert(kv, &|kv| kv.get_name())
+    }
+
+    /// [`from_engine`](Self::from_engine), with key names looked up by `name`.
+    pub(super) fn convert(kv: &KeyValues, name: &dyn Fn(&KeyValues) -> String) -> Self {
+        let value = match kv.data_type {
+            KeyValuesType::None => {
+                let mut children = Vec::new();
+                let mut child = kv.first_sub_key();
+                while let Some(node) = child {
+                    children.push(Self::convert(node, name));
+                    child = node.next();
+                }
+                KvValue::Section(children)
+            }
+            KeyValuesType::String | KeyValuesType::WString => KvValue::String(kv.get_string()),
+            KeyValuesType::Int | KeyValuesType::CompiledIntByte => KvValue::Int(kv.get_int()),
+            KeyValuesType::Float => KvValue::Float(kv.get_float()),
+            KeyValuesType::Uint64 => KvValue::Uint64(kv.get_uint64()),
+            KeyValuesType::Color => KvValue::Color(unsafe { kv.value.color_val }),
+            KeyValuesType::Ptr => KvValue::Pointer(unsafe { kv.value.ptr_val } as usize),
+        };
+        Self { name: name(kv), value }
+    }
+
+    /// Whether the node holds other nodes rather than a value.
+    pub fn is_section(&self) -> bool {
+        matches!(self.value, KvValue::Section(_))
+    }
+
+    /// The nodes under this one; empty for a leaf.
+    pub fn children(&self) -> &[KeyValuesTree] {
+        match &self.value {
+            KvValue::Section(children) => children,
+            _ => &[],
+        }
+    }
+
+    /// The nodes under this one, turning a leaf into an empty section first.
+    pub fn children_mut(&mut self) -> &mut Vec<KeyValuesTree> {
+        if !self.is_section() {
+            self.value = KvValue::Section(Vec::new());
+        }
+        match &mut self.value {
+            KvValue::Section(children) => children,
+            _ => unreachable!(),
+        }
+    }
+
+    /// The first child named `name`.
+    pub fn child(&self, name: &str) -> Option<&KeyValuesTree> {
+        self.children().iter().find(|c| c.name.eq_ignore_ascii_case(name))
+    }
+
+    /// The first child named `name`, mutably.
+    pub fn child_mut(&mut self, name: &str) -> Option<&mut KeyValuesTree> {
+        match &mut self.value {
+            KvValue::Section(children) => children.iter_mut().find(|c| c.name.eq_ignore_ascii_case(name)),
+            _ => None,
+        }
+    }
+
+    /// Every child named `name`, for keys that repeat, like `SearchPaths/Game`.
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a KeyValuesTree> + 'a {
+        self.children().iter().filter(move |c| c.name.eq_ignore_ascii_case(name))
+    }
+
+    /// The node at `path`, below this one. An empty path is this node.
+    pub fn get(&self, path: &str) -> Option<&KeyValuesTree> {
+        path.split('/').filter(|key| !key.is_empty()).try_fold(self, |node, key| node.child(key))
+    }
+
+    /// The node at `path`, mutably.
+    pub fn get_mut(&mut self, path: &str) -> Option<&mut KeyValuesTree> {
+        path.split('/').filter(|key| !key.is_empty()).try_fold(self, |node, key| node.child_mut(key))
+    }
+
+    /// The string at `path`, if there is a string there.
+    pub fn get_str(&self, path: &str) -> Option<&str> {
+        self.get(path)?.as_str()
+    }
+
+    /// The integer at `path`. Strings are parsed, and floats truncated.
+    pub fn get_int(&self, path: &str) -> Option<i32> {
+        self.get(path)?.as_int()
+    }
+
+    /// The float at `path`. Strings are parsed.
+    pub fn get_float(&self, path: &str) -> Option<f32> {
+        self.get(path)?.as_float()
+    }
+
+    /// The value at `path` as a flag: any non-zero number is `true`.
+    pub fn get_bool(&self, path: &str) -> Option<bool> {
+        self.get_int(path).map(|v| v != 0)
+    }
+
+    /// The value, if it is a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.value {
+            KvValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// The value as an integer, like [`get_int`](Self::get_int).
+    pub fn as_int(&self) -> Option<i32> {
+        match &self.value {
+            KvValue::Int(v) => Some(*v),
+            KvValue::Float(v) => Some(*v as i32),
+            KvValue::Uint64(v) => Some(*v as i32),
+            KvValue::String(s) => s.trim().parse().ok().or_else(|| s.trim().parse::<f32>().ok().map(|v| v as i32)),
+            _ => None,
+        }
+    }
+
+    /// The value as a float, like [`get_float`](Self::get_float).
+    pub fn as_float(&self) -> Option<f32> {
+        match &self.value {
+            KvValue::Float(v) => Some(*v),
+            KvValue::Int(v) => Some(*v as f32),
+            KvValue::Uint64(v) => Some(*v as f32),
+            KvValue::String(s) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Sets the value at `path`, creating the missing nodes on the way as sections.
+    /// Returns the node that was set.
+    pub fn set(&mut self, path: &str, value: impl Into<KvValue>) -> &mut KeyValuesTree {
+        let node = self.ensure(path);
+        node.value = value.into();
+        node
+    }
+
+    /// The node at `path`, created as an empty section if it does not exist.
+    pub fn ensure(&mut self, path: &str) -> &mut KeyValuesTree {
+        path.split('/').filter(|key| !key.is_empty()).fold(self, |node, key| {
+            let children = node.children_mut();
+            let index = match children.iter().position(|c| c.name.eq_ignore_ascii_case(key)) {
+                Some(index) => index,
+                None => {
+                    children.push(KeyValuesTree::new(key));
+                    children.len() - 1
+                }
+            };
+            &mut children[index]
+        })
+    }
+
+    /// Removes the first node at `path`, returning it.
+    pub fn remove(&mut self, path: &str) -> Option<KeyValuesTree> {
+        let (parent, key) = match path.trim_end_matches('/').rsplit_once('/') {
+            Some((parent, key)) => (self.get_mut(parent)?, key),
+            None => (self, path.trim_end_matches('/')),
+        };
+        let KvValue::Section(children) = &mut parent.value else { return None };
+        let index = children.iter().position(|c| c.name.eq_ignore_ascii_case(key))?;
+        Some(children.remove(index))
+    }
+
+    /// Adds what `base` has and this tree lacks, the way the engine applies `#base`
+    /// files: keys missing here are copied over, and sections both have are merged
+    /// recursively. Values already here win.
+    pub fn merge_base(&mut self, base: &KeyValuesTree) {
+        for base_child in base.children() {
+            match self.child_mut(&base_child.name) {
+                Some(own) if own.is_section() && base_child.is_section() => own.merge_base(base_child),
+                Some(_) => {}
+                None => self.children_mut().push(base_child.clone()),
+            }
+        }
+    }
+}
+
+impl fmt::Display for KeyValuesTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_vdf())
+    }
+}
+
+impl From<&KeyValues> for KeyValuesTree {
+    fn from(kv: &KeyValues) -> Self {
+        Self::from_engine(kv)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queries_paths_case_insensitively() {
+        let mut tree = KeyValuesTree::new("GameInfo");
+        tree.set("FileSystem/SteamAppId", "620");
+        tree.set("FileSystem/SearchPaths/Game", "portal2_dlc1");
+        tree.ensure("FileSystem/SearchPaths").children_mut().push(KeyValuesTree::with_value("Game", "portal2"));
+
+        assert_eq!(tree.get_int("filesystem/steamappid"), Some(620));
+        assert_eq!(tree.get_str("FileSystem/SearchPaths/GAME"), Some("portal2_dlc1"));
+        let games: Vec<_> = tree.get("FileSystem/SearchPaths").unwrap().children_named("game").filter_map(|g| g.as_str()).collect();
+        assert_eq!(games, ["portal2_dlc1", "portal2"]);
+        assert_eq!(tree.get("FileSystem/Missing"), None);
+        assert_eq!(tree.get("").map(|n| n.name.as_str()), Some("GameInfo"));
+
+        assert_eq!(tree.remove("FileSystem/SearchPaths/Game").and_then(|n| n.as_str().map(String::from)).as_deref(), Some("portal2_dlc1"));
+        assert_eq!(tree.get_str("FileSystem/SearchPaths/Game"), Some("portal2"));
+    }
+
+    #[test]
+    fn converts_values() {
+        let mut tree = KeyValuesTree::new("root");
+        tree.set("scale", "2.5");
+        tree.set("count", 3);
+        tree.set("on", "1");
+        assert_eq!(tree.get_int("scale"), Some(2));
+        assert_eq!(tree.get_float("count"), Some(3.0));
+        assert_eq!(tree.get_bool("on"), Some(true));
+        assert_eq!(tree.get_str("count"), None);
+        assert_eq!(KvValue::Color([255, 0, 0, 128]).to_string(), "255 0 0 128");
+    }
+
+    #[test]
+    fn merges_base_keys_without_overriding() {
+        let mut own = KeyValuesTree::new("Resource");
+        own.set("Panel/wide", "100");
+        let mut base = KeyValuesTree::new("Base");
+        base.set("Panel/wide", "50");
+        base.set("Panel/tall", "20");
+        base.set("Label/text", "hi");
+
+        own.merge_base(&base);
+        assert_eq!(own.get_str("Panel/wide"), Some("100"));
+        assert_eq!(own.get_str("Panel/tall"), Some("20"));
+        assert_eq!(own.get_str("Label/text"), Some("hi"));
+    }
+}
@@ -0,0 +1,546 @@
+//! Valve's text `KeyValues` format (VDF), as read by `KeyValues::LoadFromBuffer`.
+//!
+//! A file is a list of `"key" "value"` pairs and `"key" { ... }` sections. Quotes are
+//! optional around tokens without whitespace, `//` starts a comment, and any key, value
+//! or section can be followed by a conditional such as `[$WIN32]` or
+//! `[!$X360 && !$PS3]`, which drops it when false. At the top level,
+//! `#include "file"` appends the roots of another file, and `#base "file"` merges
+//! another file's root into this one's (see [`KeyValuesTree::merge_base`]).
+
+use std::collections::HashSet;
+use std::fmt;
+
+use super::{KeyValuesTree, KvValue};
+
+/// How deep `#include` and `#base` may nest before a file is assumed to include itself.
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// Loads the contents of a file named by `#include` or `#base`.
+type IncludeResolver<'a> = Box<dyn Fn(&str) -> Option<String> + 'a>;
+
+/// Parses text VDF into [`KeyValuesTree`]s.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::path::Path;
+/// use portal2_sdk::types::keyvalues::VdfReader;
+///
+/// let dir = Path::new("portal2/scripts");
+/// let manifest = std::fs::read_to_string(dir.join("game_sounds_manifest.txt")).unwrap();
+/// let sounds = VdfReader::new()
+///     .includes(|name| std::fs::read_to_string(dir.join(name)).ok())
+///     .parse(&manifest)
+///     .unwrap();
+/// ```
+pub struct VdfReader<'a> {
+    /// Conditional symbols that are true, upper case and without the `$`.
+    defines: HashSet<String>,
+    escape_sequences: bool,
+    includes: Option<IncludeResolver<'a>>,
+}
+
+impl Default for VdfReader<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> VdfReader<'a> {
+    /// A reader with the conditionals the engine defines on this platform: `$WIN32`
+    /// on every PC platform, plus `$WINDOWS`, or `$LINUX` and `$POSIX`.
+    pub fn new() -> Self {
+        let mut reader = Self { defines: HashSet::new(), escape_sequences: false, includes: None };
+        reader = reader.define("WIN32");
+        if cfg!(target_os = "windows") {
+            reader = reader.define("WINDOWS");
+        } else {
+            reader = reader.define("LINUX").define("POSIX");
+        }
+        reader
+    }
+
+    /// Makes `$symbol` true in conditionals. The `$` is optional.
+    pub fn define(mut self, symbol: &str) -> Self {
+        self.defines.insert(symbol.trim_start_matches('$').to_ascii_uppercase());
+        self
+    }
+
+    /// Makes `$symbol` false in conditionals.
+    pub fn undefine(mut self, symbol: &str) -> Self {
+        self.defines.remove(&symbol.trim_start_matches('$').to_ascii_uppercase());
+        self
+    }
+
+    /// Whether `\n`, `\t`, `\\` and `\"` are unescaped in quoted strings. Off by
+    /// default, as in the engine, so backslashes in paths are kept as they are.
+    pub fn escape_sequences(mut self, enabled: bool) -> Self {
+        self.escape_sequences = enabled;
+        self
+    }
+
+    /// Sets how the files named by `#include` and `#base` are loaded. Without it,
+    /// either directive is an error.
+    pub fn includes<F>(mut self, resolver: F) -> Self
+    where
+        F: Fn(&str) -> Option<String> + 'a,
+    {
+        self.includes = Some(Box::new(resolver));
+        self
+    }
+
+    /// Parses a file holding a single root, like `gameinfo.txt` or a `.res` file.
+    pub fn parse(&self, text: &str) -> Result<KeyValuesTree, String> {
+        self.parse_all(text)?.into_iter().next().ok_or_else(|| "no keys in file".to_string())
+    }
+
+    /// Parses every root in a file, like the entries of a soundscript.
+    pub fn parse_all(&self, text: &str) -> Result<Vec<KeyValuesTree>, String> {
+        self.parse_file(text, 0)
+    }
+
+    fn parse_file(&self, text: &str, depth: usize) -> Result<Vec<KeyValuesTree>, String> {
+        let mut parser = Parser { lexer: Lexer::new(text, self.escape_sequences), reader: self };
+        let mut directives = Vec::new();
+        let mut roots = parser.pairs(Some(&mut directives))?;
+
+        for (directive, path) in directives {
+            let included = self.load(&path, depth)?;
+            match directive {
+                Directive::Include => roots.extend(included),
+                Directive::Base => match roots.first_mut() {
+                    Some(root) => included.iter().for_each(|base| root.merge_base(base)),
+                    None => roots = included,
+                },
+            }
+        }
+        Ok(roots)
+    }
+
+    fn load(&self, path: &str, depth: usize) -> Result<Vec<KeyValuesTree>, String> {
+        if depth >= MAX_INCLUDE_DEPTH {
+            return Err(format!("'{}': includes nested too deeply", path));
+        }
+        let resolver = self.includes.as_ref().ok_or_else(|| format!("cannot include '{}' without an include resolver", path))?;
+        let text = resolver(path).ok_or_else(|| format!("cannot open included file '{}'", path))?;
+        self.parse_file(&text, depth + 1).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    /// Evaluates the inside of a `[...]` conditional.
+    fn evaluate(&self, condition: &str) -> Result<bool, String> {
+        let mut tokens = Vec::new();
+        let mut rest = condition.trim();
+        while !rest.is_empty() {
+            let (token, len) = match rest.as_bytes()[0] {
+                b'!' | b'(' | b')' => (&rest[..1], 1),
+                b'&' | b'|' if rest.len() > 1 && rest.as_bytes()[1] == rest.as_bytes()[0] => (&rest[..2], 2),
+                b'$' => {
+                    let len = rest[1..].find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).map_or(rest.len(), |i| i + 1);
+                    (&rest[..len], len)
+                }
+                _ => return Err(format!("bad conditional [{}]", condition)),
+            };
+            tokens.push(token);
+            rest = rest[len..].trim_start();
+        }
+
+        let mut pos = 0;
+        let value = self.or_expr(&tokens, &mut pos).ok_or_else(|| format!("bad conditional [{}]", condition))?;
+        match pos == tokens.len() {
+            true => Ok(value),
+            false => Err(format!("bad conditional [{}]", condition)),
+        }
+    }
+
+    fn or_expr(&self, tokens: &[&str], pos: &mut usize) -> Option<bool> {
+        let mut value = self.and_expr(tokens, pos)?;
+        while tokens.get(*pos) == Some(&"||") {
+            *pos += 1;
+            value |= self.and_expr(tokens, pos)?;
+        }
+        Some(value)
+    }
+
+    fn and_expr(&self, tokens: &[&str], pos: &mut usize) -> Option<bool> {
+        let mut value = self.unary(tokens, pos)?;
+        while tokens.get(*pos) == Some(&"&&") {
+            *pos += 1;
+            value &= self.unary(tokens, pos)?;
+        }
+        Some(value)
+    }
+
+    fn unary(&self, tokens: &[&str], pos: &mut usize) -> Option<bool> {
+        let token = *tokens.get(*pos)?;
+        *pos += 1;
+        match token {
+            "!" => self.unary(tokens, pos).map(|v| !v),
+            "(" => {
+                let value = self.or_expr(tokens, pos)?;
+                (tokens.get(*pos) == Some(&")")).then(|| *pos += 1)?;
+                Some(value)
+            }
+            symbol if symbol.len() > 1 && symbol.starts_with('$') => Some(self.defines.contains(&symbol[1..].to_ascii_uppercase())),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Directive {
+    Include,
+    Base,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// A key or value, and whether it was quoted.
+    Text(String, bool),
+    Open,
+    Close,
+    /// The inside of `[...]`.
+    Condition(String),
+}
+
+struct Lexer<'t> {
+    text: &'t str,
+    pos: usize,
+    line: usize,
+    escape_sequences: bool,
+    peeked: Option<Option<Token>>,
+}
+
+impl<'t> Lexer<'t> {
+    fn new(text: &'t str, escape_sequences: bool) -> Self {
+        // A UTF-8 byte order mark is common in files saved by editors on Windows.
+        let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+        Self { text, pos: 0, line: 1, escape_sequences, peeked: None }
+    }
+
+    fn error(&self, message: impl fmt::Display) -> String {
+        format!("line {}: {}", self.line, message)
+    }
+
+    fn peek(&mut self) -> Result<Option<&Token>, String> {
+        if self.peeked.is_none() {
+            self.peeked = Some(self.read()?);
+        }
+        Ok(self.peeked.as_ref().unwrap().as_ref())
+    }
+
+    fn next(&mut self) -> Result<Option<Token>, String> {
+        match self.peeked.take() {
+            Some(token) => Ok(token),
+            None => self.read(),
+        }
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.text[self.pos..].chars().next()?;
+        self.pos += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_blank(&mut self) {
+        loop {
+            let rest = &self.text[self.pos..];
+            if rest.starts_with("//") {
+                while self.bump().is_some_and(|c| c != '\n') {}
+            } else if rest.starts_with(|c: char| c.is_whitespace()) {
+                self.bump();
+            } else {
+                return;
+            }
+        }
+    }
+
+    fn read(&mut self) -> Result<Option<Token>, String> {
+        self.skip_blank();
+        let Some(c) = self.bump() else { return Ok(None) };
+        let token = match c {
+            '{' => Token::Open,
+            '}' => Token::Close,
+            '[' => {
+                let start = self.pos;
+                while self.bump().ok_or_else(|| self.error("unterminated conditional"))? != ']' {}
+                Token::Condition(self.text[start..self.pos - 1].to_string())
+            }
+            '"' => Token::Text(self.quoted()?, true),
+            _ => {
+                let start = self.pos - c.len_utf8();
+                let len = self.text[start..].find(|c: char| c.is_whitespace() || matches!(c, '"' | '{' | '}')).unwrap_or(self.text.len() - start);
+                self.pos = start + len;
+                Token::Text(self.text[start..self.pos].to_string(), false)
+            }
+        };
+        Ok(Some(token))
+    }
+
+    /// Reads a quoted string, after its opening quote.
+    fn quoted(&mut self) -> Result<String, String> {
+        let line = self.line;
+        let mut text = String::new();
+        loop {
+            let c = self.bump().ok_or_else(|| format!("line {}: unterminated string", line))?;
+            match c {
+                '"' => return Ok(text),
+                '\\' if self.escape_sequences => match self.bump() {
+                    Some('n') => text.push('\n'),
+                    Some('t') => text.push('\t'),
+                    Some(escaped @ ('\\' | '"')) => text.push(escaped),
+                    // Unknown escapes are kept as written.
+                    Some(other) => {
+                        text.push('\\');
+                        text.push(other);
+                    }
+                    None => return Err(format!("line {}: unterminated string", line)),
+                },
+                _ => text.push(c),
+            }
+        }
+    }
+}
+
+struct Parser<'t, 'r, 'a> {
+    lexer: Lexer<'t>,
+    reader: &'r VdfReader<'a>,
+}
+
+impl Parser<'_, '_, '_> {
+    /// Reads pairs until the end of the file, for the top level (which collects
+    /// `directives`), or until the closing brace of a section.
+    fn pairs(&mut self, mut directives: Option<&mut Vec<(Directive, String)>>) -> Result<Vec<KeyValuesTree>, String> {
+        let top = directives.is_some();
+        let mut pairs = Vec::new();
+        loop {
+            let key = match self.lexer.next()? {
+                None if top => return Ok(pairs),
+                None => return Err(self.lexer.error("unexpected end of file, expected '}'")),
+                Some(Token::Close) if !top => return Ok(pairs),
+                Some(Token::Close) => return Err(self.lexer.error("unexpected '}'")),
+                Some(Token::Open) => return Err(self.lexer.error("expected a key, found '{'")),
+                Some(Token::Condition(c)) => return Err(self.lexer.error(format_args!("unexpected conditional [{}]", c))),
+                Some(Token::Text(key, _)) => key,
+            };
+
+            let directive = match key.to_ascii_lowercase().as_str() {
+                "#include" => Some(Directive::Include),
+                "#base" => Some(Directive::Base),
+                _ => None,
+            };
+            if let (Some(directive), Some(directives)) = (directive, directives.as_deref_mut()) {
+                match self.lexer.next()? {
+                    Some(Token::Text(path, _)) => directives.push((directive, path)),
+                    _ => return Err(self.lexer.error(format_args!("expected a file name after {}", key))),
+                }
+                continue;
+            }
+
+            let mut keep = self.condition()?;
+            let value = match self.lexer.next()? {
+                Some(Token::Open) => KvValue::Section(self.pairs(None)?),
+                Some(Token::Text(value, _)) => KvValue::String(value),
+                _ => return Err(self.lexer.error(format_args!("expected a value or '{{' after \"{}\"", key))),
+            };
+            keep &= self.condition()?;
+            if keep {
+                pairs.push(KeyValuesTree { name: key, value });
+            }
+        }
+    }
+
+    /// Evaluates the conditional that comes next, if any; `true` without one.
+    fn condition(&mut self) -> Result<bool, String> {
+        let Some(Token::Condition(_)) = self.lexer.peek()? else { return Ok(true) };
+        let Some(Token::Condition(condition)) = self.lexer.next()? else { unreachable!() };
+        self.reader.evaluate(&condition).map_err(|e| self.lexer.error(e))
+    }
+}
+
+/// Writes [`KeyValuesTree`]s as text VDF, in the engine's own layout: tab indents,
+/// braces on their own lines and every token quoted.
+#[derive(Debug, Clone, Default)]
+pub struct VdfWriter {
+    escape_sequences: bool,
+}
+
+impl VdfWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether quotes, backslashes, tabs and newlines in strings are escaped, for
+    /// files read back with escape sequences on. Without it they are written as they
+    /// are, and a string holding a `"` cannot be read back.
+    pub fn escape_sequences(mut self, enabled: bool) -> Self {
+        self.escape_sequences = enabled;
+        self
+    }
+
+    /// Writes one tree.
+    pub fn write(&self, tree: &KeyValuesTree) -> String {
+        let mut out = String::new();
+        self.write_node(&mut out, tree, 0);
+        out
+    }
+
+    /// Writes several roots, one after the other.
+    pub fn write_all(&self, trees: &[KeyValuesTree]) -> String {
+        let mut out = String::new();
+        for tree in trees {
+            self.write_node(&mut out, tree, 0);
+        }
+        out
+    }
+
+    fn write_node(&self, out: &mut String, node: &KeyValuesTree, depth: usize) {
+        let indent = "\t".repeat(depth);
+        out.push_str(&indent);
+        self.write_quoted(out, &node.name);
+        match &node.value {
+            KvValue::Section(children) => {
+                out.push('\n');
+                out.push_str(&indent);
+                out.push_str("{\n");
+                for child in children {
+                    self.write_node(out, child, depth + 1);
+                }
+                out.push_str(&indent);
+                out.push_str("}\n");
+            }
+            value => {
+                out.push_str("\t\t");
+                self.write_quoted(out, &value.to_string());
+                out.push('\n');
+            }
+        }
+    }
+
+    fn write_quoted(&self, out: &mut String, text: &str) {
+        out.push('"');
+        for c in text.chars() {
+            match c {
+                '"' if self.escape_sequences => out.push_str("\\\""),
+                '\\' if self.escape_sequences => out.push_str("\\\\"),
+                '\n' if self.escape_sequences => out.push_str("\\n"),
+                '\t' if self.escape_sequences => out.push_str("\\t"),
+                _ => out.push(c),
+            }
+        }
+        out.push('"');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    const GAMEINFO: &str = r#"
+"GameInfo"
+{
+	game		"Portal 2"
+	GameData	"portal2.fgd"
+	SupportsDX8	0 [$WIN32]
+	"icon"		"resource/icon" // trailing comment
+
+	FileSystem
+	{
+		SteamAppId	620
+		SearchPaths
+		{
+			Game				|gameinfo_path|.
+			Game				portal2_dlc2	[!$X360]
+			Game				portal2_x360	[$X360]
+			"Game"				"platform"
+		}
+	}
+}
+"#;
+
+    #[test]
+    fn parses_gameinfo_with_conditionals() {
+        let tree = VdfReader::new().parse(GAMEINFO).unwrap();
+        assert_eq!(tree.name, "GameInfo");
+        assert_eq!(tree.get_str("game"), Some("Portal 2"));
+        assert_eq!(tree.get_int("SupportsDX8"), Some(0));
+        assert_eq!(tree.get_str("icon"), Some("resource/icon"));
+        assert_eq!(tree.get_int("FileSystem/SteamAppId"), Some(620));
+
+        let games: Vec<_> = tree.get("FileSystem/SearchPaths").unwrap().children_named("Game").filter_map(|g| g.as_str()).collect();
+        assert_eq!(games, ["|gameinfo_path|.", "portal2_dlc2", "platform"]);
+
+        let console = VdfReader::new().undefine("WIN32").define("$X360").parse(GAMEINFO).unwrap();
+        assert_eq!(console.get("SupportsDX8"), None);
+        assert_eq!(console.get("FileSystem/SearchPaths").unwrap().children().len(), 3);
+    }
+
+    #[test]
+    fn evaluates_conditional_expressions() {
+        let reader = VdfReader::new().undefine("LINUX").undefine("WINDOWS").define("OSX");
+        assert_eq!(reader.evaluate("$OSX || $LINUX"), Ok(true));
+        assert_eq!(reader.evaluate("$win32 && !$osx"), Ok(false));
+        assert_eq!(reader.evaluate("!($X360 || $PS3) && $WIN32"), Ok(true));
+        assert!(reader.evaluate("$OSX ||").is_err());
+        assert!(reader.evaluate("WIN32").is_err());
+    }
+
+    #[test]
+    fn round_trips_through_the_writer() {
+        let roots = VdfReader::new().escape_sequences(true).parse_all(r#"
+            "sound.one" { "channel" "CHAN_AUTO" "wave" "a\\b.wav" "rndwave" { "wave" "1.wav" "wave" "2.wav" } }
+            "sound.two" { "text" "say \"hi\"\n" "empty" {} }
+        "#).unwrap();
+        assert_eq!(roots.len(), 2);
+        assert_eq!(roots[0].get_str("wave"), Some("a\\b.wav"));
+        assert_eq!(roots[1].get_str("text"), Some("say \"hi\"\n"));
+
+        let writer = VdfWriter::new().escape_sequences(true);
+        let text = writer.write_all(&roots);
+        assert!(text.starts_with("\"sound.one\"\n{\n\t\"channel\"\t\t\"CHAN_AUTO\"\n"));
+        assert_eq!(VdfReader::new().escape_sequences(true).parse_all(&text).unwrap(), roots);
+
+        // Without escapes, backslashes are kept as written both ways.
+        let raw = VdfReader::new().parse(r#""a" { "path" "c:\temp\n" }"#).unwrap();
+        assert_eq!(raw.get_str("path"), Some("c:\\temp\\n"));
+        assert_eq!(KeyValuesTree::parse(&raw.to_vdf()).unwrap(), raw);
+    }
+
+    #[test]
+    fn resolves_includes_and_bases() {
+        let files: HashMap<&str, &str> = HashMap::from([
+            ("base.res", r#""Base" { "Panel" { "wide" "50" "tall" "20" } "Extra" "1" }"#),
+            ("more.txt", r#""More" { "key" "value" } #include "more.txt""#),
+            ("other.txt", r#""Other" { }"#),
+        ]);
+        let reader = VdfReader::new().includes(|name| files.get(name).map(|s| s.to_string()));
+
+        let roots = reader.parse_all(r#"
+            #base "base.res"
+            #include "other.txt"
+            "Resource" { "Panel" { "wide" "100" } }
+        "#).unwrap();
+        let names: Vec<_> = roots.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["Resource", "Other"]);
+        assert_eq!(roots[0].get_str("Panel/wide"), Some("100"));
+        assert_eq!(roots[0].get_str("Panel/tall"), Some("20"));
+        assert_eq!(roots[0].get_str("Extra"), Some("1"));
+
+        assert!(reader.parse(r#"#include "more.txt""#).unwrap_err().contains("nested too deeply"));
+        assert!(reader.parse(r#"#include "missing.txt""#).unwrap_err().contains("cannot open"));
+        assert!(VdfReader::new().parse(r#"#base "base.res""#).unwrap_err().contains("without an include resolver"));
+    }
+
+    #[test]
+    fn reports_errors_with_line_numbers() {
+        let reader = VdfReader::new();
+        assert_eq!(reader.parse("\"a\"\n{\n\"b\" \"c\"\n"), Err("line 4: unexpected end of file, expected '}'".to_string()));
+        assert_eq!(reader.parse("\"a\" }"), Err("line 1: expected a value or '{' after \"a\"".to_string()));
+        assert_eq!(reader.parse("\"a\" { \"b\n"), Err("line 1: unterminated string".to_string()));
+        assert_eq!(reader.parse("\"a\" \"b\" [$FOO |]").unwrap_err(), "line 1: bad conditional [$FOO |]");
+        assert_eq!(reader.parse(""), Err("no keys in file".to_string()));
+    }
+}
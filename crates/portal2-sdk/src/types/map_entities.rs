@@ -0,0 +1,311 @@
+//! The map's entity lump: every entity as the level designer placed it, before
+//! spawning, I/O and scripts changed anything. Read it with
+//! `IVEngineServer::get_map_entities`.
+//!
+//! The lump is a list of `{ "key" "value" ... }` blocks. Outputs are keyvalues too,
+//! whose value is a connection: `target,input,param,delay,refire`. Compilers from the
+//! Left 4 Dead branch on separate the fields with `\x1b` rather than a comma.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use super::Vector;
+
+/// One connection of an output, e.g. `OnPressed` → `door,Open,,0,-1`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityOutput {
+    /// The output that fires the connection, e.g. `OnPressed`.
+    pub output: String,
+    /// A targetname, classname or special name such as `!activator`.
+    pub target: String,
+    pub input: String,
+    /// The parameter passed to the input; empty for none.
+    pub param: String,
+    /// Seconds between the output firing and the input being called.
+    pub delay: f32,
+    /// How many times the connection can fire; -1 for unlimited.
+    pub refire: i32,
+}
+
+impl EntityOutput {
+    /// Parses the value of an output keyvalue, or `None` if it is not a connection.
+    pub fn parse(output: &str, value: &str) -> Option<Self> {
+        let separator = if value.contains('\x1b') { '\x1b' } else { ',' };
+        let fields: Vec<&str> = value.split(separator).collect();
+        let [target, input, param, delay, refire] = fields[..] else { return None };
+        Some(Self {
+            output: output.to_string(),
+            target: target.to_string(),
+            input: input.to_string(),
+            param: param.to_string(),
+            delay: delay.trim().parse().ok()?,
+            refire: refire.trim().parse().ok()?,
+        })
+    }
+}
+
+impl fmt::Display for EntityOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {}.{}({}) after {}s", self.output, self.target, self.input, self.param, self.delay)?;
+        if self.refire >= 0 {
+            write!(f, ", {} times", self.refire)?;
+        }
+        Ok(())
+    }
+}
+
+/// An entity as it is written in the map.
+#[derive(Debug, Clone, Default)]
+pub struct MapEntity {
+    pub classname: String,
+    pub targetname: Option<String>,
+    /// The ID Hammer gave the entity, which survives recompiles.
+    pub hammerid: Option<i32>,
+    pub origin: Option<Vector>,
+    /// Every keyvalue that is not an output, in file order. The fields above are
+    /// included, and keys can repeat.
+    pub keyvalues: Vec<(String, String)>,
+    pub outputs: Vec<EntityOutput>,
+}
+
+impl MapEntity {
+    /// The first value of `key`, ignoring case as the engine does.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.keyvalues.iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn push(&mut self, key: String, value: String) {
+        if let Some(output) = EntityOutput::parse(&key, &value) {
+            self.outputs.push(output);
+            return;
+        }
+
+        match key.to_ascii_lowercase().as_str() {
+            "classname" => self.classname = value.clone(),
+            "targetname" => self.targetname = Some(value.clone()).filter(|name| !name.is_empty()),
+            "hammerid" => self.hammerid = value.trim().parse().ok(),
+            "origin" => self.origin = parse_vector(&value),
+            _ => {}
+        }
+        self.keyvalues.push((key, value));
+    }
+}
+
+fn parse_vector(text: &str) -> Option<Vector> {
+    let mut parts = text.split_whitespace().map(str::parse::<f32>);
+    let vector = Vector::new(parts.next()?.ok()?, parts.next()?.ok()?, parts.next()?.ok()?);
+    parts.next().is_none().then_some(vector)
+}
+
+/// A parsed entity lump, indexed by targetname and hammer ID.
+#[derive(Debug, Clone, Default)]
+pub struct MapEntities {
+    entities: Vec<MapEntity>,
+    /// Lower-cased targetname to indices into `entities`; names need not be unique.
+    by_name: HashMap<String, Vec<usize>>,
+    by_hammer_id: HashMap<i32, usize>,
+}
+
+impl MapEntities {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut entities = Vec::new();
+        let mut tokens = Tokens { text, pos: 0, line: 1 };
+
+        while let Some(token) = tokens.next()? {
+            if token != Token::Open {
+                return Err(tokens.error("expected '{'"));
+            }
+            let mut entity = MapEntity::default();
+            loop {
+                match tokens.next()? {
+                    Some(Token::Close) => break,
+                    Some(Token::String(key)) => match tokens.next()? {
+                        Some(Token::String(value)) => entity.push(key, value),
+                        _ => return Err(tokens.error(format_args!("expected a value after \"{}\"", key))),
+                    },
+                    Some(Token::Open) => return Err(tokens.error("unexpected '{'")),
+                    None => return Err(tokens.error("unexpected end of lump, expected '}'")),
+                }
+            }
+            entities.push(entity);
+        }
+
+        let mut lump = Self { entities, ..Self::default() };
+        for (index, entity) in lump.entities.iter().enumerate() {
+            if let Some(name) = &entity.targetname {
+                lump.by_name.entry(name.to_ascii_lowercase()).or_default().push(index);
+            }
+            if let Some(id) = entity.hammerid {
+                lump.by_hammer_id.entry(id).or_insert(index);
+            }
+        }
+        Ok(lump)
+    }
+
+    /// The entities in lump order; the first one is `worldspawn`.
+    pub fn iter(&self) -> impl Iterator<Item = &MapEntity> {
+        self.entities.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    /// The entities named `name`, ignoring case.
+    pub fn by_targetname(&self, name: &str) -> impl Iterator<Item = &MapEntity> {
+        self.by_name.get(&name.to_ascii_lowercase())
+            .into_iter()
+            .flatten()
+            .map(|&i| &self.entities[i])
+    }
+
+    pub fn by_hammer_id(&self, id: i32) -> Option<&MapEntity> {
+        self.by_hammer_id.get(&id).map(|&i| &self.entities[i])
+    }
+
+    /// Every connection whose target is `name`, with the entity it comes from.
+    pub fn outputs_targeting<'s>(&'s self, name: &'s str) -> impl Iterator<Item = (&'s MapEntity, &'s EntityOutput)> {
+        self.entities.iter().flat_map(move |entity| {
+            entity.outputs.iter()
+                .filter(move |output| output.target.eq_ignore_ascii_case(name))
+                .map(move |output| (entity, output))
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    String(String),
+}
+
+struct Tokens<'t> {
+    text: &'t str,
+    pos: usize,
+    line: usize,
+}
+
+impl Tokens<'_> {
+    fn error(&self, message: impl fmt::Display) -> String {
+        format!("line {}: {}", self.line, message)
+    }
+
+    fn next(&mut self) -> Result<Option<Token>, String> {
+        let rest = &self.text[self.pos..];
+        let trimmed = rest.trim_start();
+        self.line += rest[..rest.len() - trimmed.len()].matches('\n').count();
+        self.pos += rest.len() - trimmed.len();
+
+        let Some(c) = trimmed.chars().next() else { return Ok(None) };
+        match c {
+            '{' => {
+                self.pos += 1;
+                Ok(Some(Token::Open))
+            }
+            '}' => {
+                self.pos += 1;
+                Ok(Some(Token::Close))
+            }
+            '"' => {
+                // The lump has no escape sequences; a string ends at the next quote.
+                let Some(len) = trimmed[1..].find('"') else {
+                    return Err(self.error("unterminated string"));
+                };
+                let value = &trimmed[1..1 + len];
+                self.line += value.matches('\n').count();
+                self.pos += len + 2;
+                Ok(Some(Token::String(value.to_string())))
+            }
+            other => Err(self.error(format_args!("unexpected '{}'", other))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LUMP: &str = r#"{
+"world_maxs" "2048 2048 1024"
+"classname" "worldspawn"
+"hammerid" "1"
+}
+{
+"origin" "-128 64 0.5"
+"targetname" "exit_button"
+"classname" "prop_floor_button"
+"hammerid" "2104"
+"OnPressed" "exit_door,Open,,0,-1"
+"OnPressed" "@relay_vo,Trigger,,1.5,1"
+"OnUnPressed" "exit_door,Close,,0,-1"
+}
+{
+"targetname" "exit_door"
+"classname" "prop_testchamber_door"
+"hammerid" "2110"
+"OnFullyOpen" "!self,SetAnimation,open idle,0.25,-1"
+}
+{
+"targetname" "Exit_Door"
+"classname" "info_target"
+"spawnflags" "0,1"
+}
+"#;
+
+    #[test]
+    fn parses_entities_and_outputs() {
+        let lump = MapEntities::parse(LUMP).unwrap();
+        assert_eq!(lump.len(), 4);
+        assert_eq!(lump.iter().next().unwrap().classname, "worldspawn");
+
+        let button = lump.by_hammer_id(2104).unwrap();
+        assert_eq!(button.classname, "prop_floor_button");
+        assert_eq!(button.targetname.as_deref(), Some("exit_button"));
+        let origin = button.origin.unwrap();
+        assert_eq!((origin.x, origin.y, origin.z), (-128.0, 64.0, 0.5));
+        assert_eq!(button.keyvalues.len(), 4);
+        assert_eq!(button.outputs.len(), 3);
+        assert_eq!(button.outputs[1], EntityOutput {
+            output: "OnPressed".to_string(),
+            target: "@relay_vo".to_string(),
+            input: "Trigger".to_string(),
+            param: String::new(),
+            delay: 1.5,
+            refire: 1,
+        });
+
+        let door = lump.by_hammer_id(2110).unwrap();
+        assert_eq!(door.outputs[0].param, "open idle");
+
+        // Not a connection, so kept as a keyvalue.
+        let target = lump.by_targetname("exit_door").nth(1).unwrap();
+        assert_eq!(target.get("SpawnFlags"), Some("0,1"));
+        assert!(target.outputs.is_empty());
+
+        assert_eq!(lump.by_targetname("EXIT_DOOR").count(), 2);
+        let inputs: Vec<&str> = lump.outputs_targeting("exit_door").map(|(_, o)| o.input.as_str()).collect();
+        assert_eq!(inputs, ["Open", "Close"]);
+    }
+
+    #[test]
+    fn splits_escape_separated_outputs() {
+        let output = EntityOutput::parse("OnTrigger", "lift\x1bSetSpeed\x1b0.5,1\x1b0\x1b-1").unwrap();
+        assert_eq!((output.target.as_str(), output.param.as_str(), output.refire), ("lift", "0.5,1", -1));
+        assert_eq!(output.to_string(), "OnTrigger -> lift.SetSpeed(0.5,1) after 0s");
+        assert!(EntityOutput::parse("model", "models/props/cube.mdl").is_none());
+    }
+
+    #[test]
+    fn reports_errors_with_line_numbers() {
+        assert_eq!(MapEntities::parse("{\n\"classname\" \"a\"\n").unwrap_err(), "line 3: unexpected end of lump, expected '}'");
+        assert_eq!(MapEntities::parse("{\n\"key\" }").unwrap_err(), "line 2: expected a value after \"key\"");
+        assert!(MapEntities::parse("\"classname\" \"a\"").is_err());
+    }
+}
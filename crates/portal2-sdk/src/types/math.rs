@@ -181,6 +181,56 @@ pub struct VMatrix {
     pub m: [[f32; 4]; 4],
 }
 
+impl VMatrix {
+    /// Multiplies `[x, y, z, w]` by the matrix, treating it as a column vector.
+    pub fn transform(&self, v: [f32; 4]) -> [f32; 4] {
+        self.m.map(|row| row.iter().zip(v).map(|(a, b)| a * b).sum())
+    }
+
+    /// The inverse matrix, or `None` if the matrix is singular.
+    pub fn inverse(&self) -> Option<VMatrix> {
+        // Gauss-Jordan elimination with partial pivoting, in f64 for stability.
+        let mut a = self.m.map(|row| row.map(f64::from));
+        let mut inv = [[0.0f64; 4]; 4];
+        for (i, row) in inv.iter_mut().enumerate() {
+            row[i] = 1.0;
+        }
+
+        for col in 0..4 {
+            let pivot = (col..4).max_by(|&x, &y| a[x][col].abs().total_cmp(&a[y][col].abs()))?;
+            if a[pivot][col].abs() < 1e-12 {
+                return None;
+            }
+            a.swap(col, pivot);
+            inv.swap(col, pivot);
+
+            let scale = a[col][col];
+            for j in 0..4 {
+                a[col][j] /= scale;
+                inv[col][j] /= scale;
+            }
+            for row in 0..4 {
+                let factor = a[row][col];
+                if row == col || factor == 0.0 {
+                    continue;
+                }
+                for j in 0..4 {
+                    a[row][j] -= factor * a[col][j];
+                    inv[row][j] -= factor * inv[col][j];
+                }
+            }
+        }
+        Some(VMatrix { m: inv.map(|row| row.map(|v| v as f32)) })
+    }
+
+    /// For a world-to-screen matrix: the world point at normalized device coordinates
+    /// `ndc_x`, `ndc_y` (-1 to 1, up is positive) and depth `ndc_z` (0 is the near plane).
+    pub fn unproject(&self, ndc_x: f32, ndc_y: f32, ndc_z: f32) -> Option<Vector> {
+        let [x, y, z, w] = self.inverse()?.transform([ndc_x, ndc_y, ndc_z, 1.0]);
+        (w.abs() > f32::EPSILON).then(|| Vector::new(x / w, y / w, z / w))
+    }
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
 pub struct BBoxT {
@@ -189,3 +239,27 @@ pub struct BBoxT {
 }
 
 #[repr(C)] pub struct VPlane { _private: [u8; 0] }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unprojects_what_it_projects() {
+        // A perspective projection looking down +x, as the engine builds it.
+        let matrix = VMatrix { m: [
+            [0.0, -1.2, 0.0, 10.0],
+            [0.0, 0.0, 1.6, -20.0],
+            [1.001, 0.0, 0.0, -7.0],
+            [1.0, 0.0, 0.0, -5.0],
+        ] };
+        let point = Vector::new(300.0, -40.0, 75.0);
+
+        let [x, y, z, w] = matrix.transform([point.x, point.y, point.z, 1.0]);
+        let back = matrix.unproject(x / w, y / w, z / w).unwrap();
+        assert!(back.distance(&point) < 0.01, "{}", back);
+
+        let singular = VMatrix { m: [[1.0, 2.0, 3.0, 4.0]; 4] };
+        assert!(singular.inverse().is_none());
+    }
+}
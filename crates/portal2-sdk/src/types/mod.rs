@@ -1,13 +1,21 @@
 pub mod math;
 pub mod entity;
+pub mod datatable;
+pub mod handle;
+pub mod classes;
 pub mod player;
 pub mod keyvalues;
 pub mod opaque;
 pub mod trace;
+pub mod map_entities;
 
 pub use math::*;
 pub use entity::*;
+pub use datatable::*;
+pub use handle::*;
+pub use classes::*;
 pub use player::*;
 pub use keyvalues::*;
 pub use opaque::*;
 pub use trace::*;
+pub use map_entities::*;
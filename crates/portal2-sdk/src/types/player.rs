@@ -88,7 +88,6 @@ impl CSteamID {
     }
 }
 
-#[repr(C)] pub struct SendTable { _private: [u8; 0] }
 #[repr(C)] pub struct INetChannelInfo { _private: [u8; 0] }
 #[repr(C)] pub struct CPlayerBitVec { _private: [u8; 0] }
 #[repr(C)] pub struct CPlayerState { _private: [u8; 0] }
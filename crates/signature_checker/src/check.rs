@@ -0,0 +1,201 @@
+//! Runs a signature database against module files on disk.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use portal2_sdk::platform::Module;
+use portal2_sdk::signatures::{Locator, SignatureDatabase, SignatureOrigin};
+
+use crate::image::Image;
+
+/// What scanning for one signature turned up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    /// Exactly one match, at this RVA (with the entry's offset applied).
+    Found(u64),
+    Missing,
+    /// Every match's RVA.
+    Ambiguous(Vec<u64>),
+    /// The signature could not be run at all, e.g. its module file is missing.
+    Error(String),
+}
+
+impl Status {
+    pub fn is_ok(&self) -> bool {
+        matches!(self, Self::Found(_))
+    }
+}
+
+/// The result for one database entry.
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub name: String,
+    pub module: Module,
+    pub origin: SignatureOrigin,
+    pub status: Status,
+}
+
+/// Where a module's file lives relative to the game folder.
+///
+/// Engine-wide modules sit in `bin/`, the game's own in `portal2/bin/`. The
+/// `linux32/` variants are what other native Source builds use and are cheap to
+/// check as well. File names come from the per-OS tables on [`Module`], so the
+/// Linux tier libraries are looked up as `libvstdlib.so` / `libtier0.so`.
+fn candidate_paths(game_dir: &Path, module: Module) -> Vec<PathBuf> {
+    let dirs: &[&str] = match module {
+        Module::Server | Module::Client => &["portal2/bin", "portal2/bin/linux32"],
+        _ => &["bin", "bin/linux32"],
+    };
+
+    dirs.iter()
+        .flat_map(|dir| {
+            [module.dll_name(), module.so_name()].map(|file| game_dir.join(dir).join(file.to_str().unwrap()))
+        })
+        .collect()
+}
+
+/// Finds and parses the file for `module` under `game_dir`.
+pub fn load_module(game_dir: &Path, module: Module) -> Result<(PathBuf, Image), String> {
+    let path = candidate_paths(game_dir, module)
+        .into_iter()
+        .find(|p| p.is_file())
+        .ok_or_else(|| format!("no {} binary under {}", module.name(), game_dir.display()))?;
+
+    let file = std::fs::read(&path).map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    let image = Image::parse(&file).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok((path, image))
+}
+
+/// Scans `image` for every match of the database entry `name`, or looks its
+/// symbol up for entries that name one.
+pub fn check_one(db: &SignatureDatabase, name: &str, image: &Image) -> Status {
+    let Some((signature, _)) = db.get(name) else {
+        return Status::Error(format!("no entry named '{}'", name));
+    };
+    if let Locator::Symbol(symbol) = &signature.locator {
+        return match image.symbol(symbol) {
+            Some(rva) => Status::Found(rva.wrapping_add_signed(signature.offset as i64)),
+            None => Status::Missing,
+        };
+    }
+    let Some(pattern) = signature.pattern() else {
+        return Status::Error("pattern and mask disagree".to_string());
+    };
+
+    let mut matches: Vec<u64> = image.sections.iter()
+        .flat_map(|section| {
+            pattern.find_all(&section.data)
+                .into_iter()
+                .map(move |offset| section.rva + offset as u64)
+        })
+        .map(|rva| rva.wrapping_add_signed(signature.offset as i64))
+        .collect();
+    matches.sort_unstable();
+
+    match matches.as_slice() {
+        [] => Status::Missing,
+        [rva] => Status::Found(*rva),
+        _ => Status::Ambiguous(matches),
+    }
+}
+
+/// Checks every entry of `db`, loading each module at most once.
+pub fn check_all(db: &SignatureDatabase, game_dir: &Path) -> Vec<Report> {
+    let mut images: HashMap<Module, Result<Image, String>> = HashMap::new();
+
+    db.signatures()
+        .into_iter()
+        .map(|(name, signature, origin)| {
+            let image = images
+                .entry(signature.module)
+                .or_insert_with(|| load_module(game_dir, signature.module).map(|(_, image)| image));
+
+            let status = match image {
+                Ok(image) => check_one(db, name, image),
+                Err(e) => Status::Error(e.clone()),
+            };
+
+            Report { name: name.to_string(), module: signature.module, origin: origin.clone(), status }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::image::fixtures;
+    use portal2_sdk::platform::Abi;
+    use portal2_sdk::signatures::GameBuild;
+
+    const DB: &str = r#"
+        version = 1
+        [[build]]
+        [build.signatures."ICvar::FindVar"]
+        module = "vstdlib"
+        pattern = "55 8B EC ?? C3"
+        offset = 1
+        [build.signatures."IVEngineClient::ServerCmd"]
+        module = "engine"
+        pattern = "90 90"
+        [build.signatures."IVEngineClient::ClientCmd"]
+        module = "engine"
+        pattern = "CC CC CC CC"
+    "#;
+
+    fn db() -> SignatureDatabase {
+        let mut db = SignatureDatabase::builtin_for(Abi::Msvc);
+        db.apply_str(DB, Path::new("test.toml"), None::<&GameBuild>).unwrap();
+        db
+    }
+
+    #[test]
+    fn reports_found_missing_and_ambiguous() {
+        let db = db();
+        let vstdlib = Image::parse(&fixtures::pe(b"\x00\x55\x8B\xEC\x01\xC3", b"\x55\x8B\xEC\x01\xC3")).unwrap();
+        assert_eq!(check_one(&db, "ICvar::FindVar", &vstdlib), Status::Found(0x1002));
+
+        let engine = Image::parse(&fixtures::elf(b"\x90\x90\xC3\x90\x90", b"")).unwrap();
+        assert_eq!(check_one(&db, "IVEngineClient::ServerCmd", &engine), Status::Ambiguous(vec![0x2000, 0x2003]));
+        assert_eq!(check_one(&db, "IVEngineClient::ClientCmd", &engine), Status::Missing);
+    }
+
+    #[test]
+    fn looks_up_symbol_entries() {
+        let db = SignatureDatabase::builtin_for(Abi::Itanium);
+        let engine = Image::parse(&fixtures::elf_with_symbols(b"\x90\xC3", &[("_ZN13CEngineClient9ServerCmdEPKcb", 0x2001)])).unwrap();
+        assert_eq!(check_one(&db, "IVEngineClient::ServerCmd", &engine), Status::Found(0x2001));
+        assert_eq!(check_one(&db, "IVEngineClient::ClientCmd", &engine), Status::Missing);
+
+        let pe = Image::parse(&fixtures::pe(b"\xC3", b"")).unwrap();
+        assert_eq!(check_one(&db, "IVEngineClient::ServerCmd", &pe), Status::Missing);
+    }
+
+    #[test]
+    fn candidate_paths_use_platform_file_names() {
+        let paths = candidate_paths(Path::new("game"), Module::VStdLib);
+        assert!(paths.contains(&Path::new("game/bin/vstdlib.dll").to_path_buf()));
+        assert!(paths.contains(&Path::new("game/bin/libvstdlib.so").to_path_buf()));
+        assert!(!paths.contains(&Path::new("game/bin/vstdlib.so").to_path_buf()));
+
+        let paths = candidate_paths(Path::new("game"), Module::Server);
+        assert!(paths.contains(&Path::new("game/portal2/bin/server.so").to_path_buf()));
+    }
+
+    #[test]
+    fn checks_a_game_folder() {
+        let dir = std::env::temp_dir().join(format!("sigcheck-test-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("bin")).unwrap();
+        std::fs::write(dir.join("bin/libvstdlib.so"), fixtures::elf(b"\x55\x8B\xEC\x00\xC3", b"")).unwrap();
+
+        let reports = check_all(&db(), &dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let find_var = reports.iter().find(|r| r.name == "ICvar::FindVar").unwrap();
+        assert_eq!(find_var.status, Status::Found(0x2001));
+        assert!(matches!(find_var.origin, SignatureOrigin::File { .. }));
+
+        // engine is absent from the folder; every engine entry says so.
+        let server_cmd = reports.iter().find(|r| r.name == "IVEngineClient::ServerCmd").unwrap();
+        assert!(matches!(&server_cmd.status, Status::Error(e) if e.contains("no engine binary")));
+    }
+}
@@ -0,0 +1,309 @@
+//! Just enough PE and ELF parsing to get at a module's code on disk.
+//!
+//! Nothing is mapped or relocated: sections are sliced straight out of the file,
+//! and every address reported is relative to the image base, the same way a
+//! disassembler shows it.
+
+use std::fmt;
+
+/// An executable section of a module file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    pub name: String,
+    /// Address of the section relative to the image base.
+    pub rva: u64,
+    pub data: Vec<u8>,
+}
+
+/// Which loader format a module file turned out to be.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Pe,
+    Elf,
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pe => write!(f, "PE"),
+            Self::Elf => write!(f, "ELF"),
+        }
+    }
+}
+
+/// A parsed module file: its executable sections, plus the raw file for
+/// symbol lookups.
+#[derive(Debug, Clone)]
+pub struct Image {
+    pub format: Format,
+    pub sections: Vec<Section>,
+    file: Vec<u8>,
+}
+
+impl Image {
+    /// Parses a PE or 32-bit ELF file, detected by its magic bytes.
+    pub fn parse(file: &[u8]) -> Result<Self, String> {
+        match file {
+            [b'M', b'Z', ..] => parse_pe(file).map(|sections| Self { format: Format::Pe, sections, file: file.to_vec() }),
+            [0x7F, b'E', b'L', b'F', ..] => parse_elf(file).map(|sections| Self { format: Format::Elf, sections, file: file.to_vec() }),
+            _ => Err("not a PE or ELF file".to_string()),
+        }
+    }
+
+    /// Address of the defined symbol `name`, relative to the image base.
+    ///
+    /// Only ELF symbol tables are read; PE modules only export a handful of
+    /// undecorated names, which the SDK never looks up this way.
+    pub fn symbol(&self, name: &str) -> Option<u64> {
+        match self.format {
+            Format::Elf => portal2_sdk::platform::elf::find_symbol(&self.file, name).map(u64::from),
+            Format::Pe => None,
+        }
+    }
+}
+
+fn read_u16(file: &[u8], offset: usize) -> Result<u16, String> {
+    file.get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or_else(|| format!("truncated file (reading offset {:#x})", offset))
+}
+
+fn read_u32(file: &[u8], offset: usize) -> Result<u32, String> {
+    file.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| format!("truncated file (reading offset {:#x})", offset))
+}
+
+fn slice(file: &[u8], offset: usize, size: usize) -> Result<&[u8], String> {
+    offset.checked_add(size)
+        .and_then(|end| file.get(offset..end))
+        .ok_or_else(|| format!("section data at {:#x}+{:#x} is outside the file", offset, size))
+}
+
+const IMAGE_SCN_CNT_CODE: u32 = 0x0000_0020;
+const IMAGE_SCN_MEM_EXECUTE: u32 = 0x2000_0000;
+
+fn parse_pe(file: &[u8]) -> Result<Vec<Section>, String> {
+    let pe = read_u32(file, 0x3C)? as usize;
+    if file.get(pe..pe + 4) != Some(b"PE\0\0") {
+        return Err("missing PE signature".to_string());
+    }
+
+    let coff = pe + 4;
+    let section_count = read_u16(file, coff + 2)? as usize;
+    let optional_size = read_u16(file, coff + 16)? as usize;
+    let table = coff + 20 + optional_size;
+
+    let mut sections = Vec::new();
+    for i in 0..section_count {
+        let header = table + i * 40;
+        let characteristics = read_u32(file, header + 36)?;
+        if characteristics & (IMAGE_SCN_CNT_CODE | IMAGE_SCN_MEM_EXECUTE) == 0 {
+            continue;
+        }
+
+        let name = slice(file, header, 8)?;
+        let name = String::from_utf8_lossy(name).trim_end_matches('\0').to_string();
+        let virtual_size = read_u32(file, header + 8)? as usize;
+        let rva = read_u32(file, header + 12)?;
+        let raw_size = read_u32(file, header + 16)? as usize;
+        let raw_offset = read_u32(file, header + 20)? as usize;
+
+        // Raw data is padded to the file alignment; the tail past VirtualSize is
+        // not part of the section once loaded.
+        let size = if virtual_size == 0 { raw_size } else { raw_size.min(virtual_size) };
+        let data = slice(file, raw_offset, size)?.to_vec();
+        sections.push(Section { name, rva: rva as u64, data });
+    }
+
+    Ok(sections)
+}
+
+const ELFCLASS32: u8 = 1;
+const ELFDATA2LSB: u8 = 1;
+const SHT_NOBITS: u32 = 8;
+const SHF_EXECINSTR: u32 = 0x4;
+
+fn parse_elf(file: &[u8]) -> Result<Vec<Section>, String> {
+    // Portal 2 only ever shipped 32-bit x86 binaries.
+    if file.get(4) != Some(&ELFCLASS32) || file.get(5) != Some(&ELFDATA2LSB) {
+        return Err("only 32-bit little-endian ELF files are supported".to_string());
+    }
+
+    let table = read_u32(file, 0x20)? as usize;
+    let entry_size = read_u16(file, 0x2E)? as usize;
+    let section_count = read_u16(file, 0x30)? as usize;
+    let names_index = read_u16(file, 0x32)? as usize;
+    if entry_size < 40 {
+        return Err(format!("bad section header size {}", entry_size));
+    }
+
+    let header = |i: usize| table + i * entry_size;
+    let names_offset = read_u32(file, header(names_index) + 16)? as usize;
+    let names_size = read_u32(file, header(names_index) + 20)? as usize;
+    let names = slice(file, names_offset, names_size)?;
+
+    let mut sections = Vec::new();
+    for i in 0..section_count {
+        let h = header(i);
+        let kind = read_u32(file, h + 4)?;
+        let flags = read_u32(file, h + 8)?;
+        if kind == SHT_NOBITS || flags & SHF_EXECINSTR == 0 {
+            continue;
+        }
+
+        let name_at = read_u32(file, h)? as usize;
+        let name = names.get(name_at..)
+            .and_then(|n| n.split(|&b| b == 0).next())
+            .map(|n| String::from_utf8_lossy(n).into_owned())
+            .unwrap_or_default();
+        let addr = read_u32(file, h + 12)?;
+        let offset = read_u32(file, h + 16)? as usize;
+        let size = read_u32(file, h + 20)? as usize;
+
+        let data = slice(file, offset, size)?.to_vec();
+        sections.push(Section { name, rva: addr as u64, data });
+    }
+
+    Ok(sections)
+}
+
+/// Builders for minimal, valid-enough module files. Only the fields the parsers
+/// above look at are filled in.
+#[cfg(test)]
+pub(crate) mod fixtures {
+    /// A PE file with one `.text` section at RVA `0x1000` holding `code`, plus an
+    /// unrelated `.data` section holding `data`.
+    pub fn pe(code: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut file = vec![0u8; 0x200];
+        file[0..2].copy_from_slice(b"MZ");
+        file[0x3C..0x40].copy_from_slice(&0x40u32.to_le_bytes());
+        file[0x40..0x44].copy_from_slice(b"PE\0\0");
+        // COFF header: i386, two sections, 0xE0 bytes of optional header.
+        file[0x44..0x46].copy_from_slice(&0x14Cu16.to_le_bytes());
+        file[0x46..0x48].copy_from_slice(&2u16.to_le_bytes());
+        file[0x54..0x56].copy_from_slice(&0xE0u16.to_le_bytes());
+
+        let table = 0x44 + 20 + 0xE0;
+        let mut section = |i: usize, name: &[u8], rva: u32, bytes: &[u8], flags: u32, file: &mut Vec<u8>| {
+            let raw_offset = file.len() as u32;
+            let raw_size = bytes.len().next_multiple_of(0x200);
+            file.extend_from_slice(bytes);
+            file.resize(raw_offset as usize + raw_size, 0);
+
+            let h = table + i * 40;
+            file[h..h + name.len()].copy_from_slice(name);
+            file[h + 8..h + 12].copy_from_slice(&(bytes.len() as u32).to_le_bytes());
+            file[h + 12..h + 16].copy_from_slice(&rva.to_le_bytes());
+            file[h + 16..h + 20].copy_from_slice(&(raw_size as u32).to_le_bytes());
+            file[h + 20..h + 24].copy_from_slice(&raw_offset.to_le_bytes());
+            file[h + 36..h + 40].copy_from_slice(&flags.to_le_bytes());
+        };
+        section(0, b".text", 0x1000, code, 0x6000_0020, &mut file);
+        section(1, b".data", 0x8000, data, 0xC000_0040, &mut file);
+        file
+    }
+
+    /// A 32-bit ELF file with a `.text` section at `0x2000` holding `code`, plus an
+    /// unrelated `.rodata` section holding `data`.
+    pub fn elf(code: &[u8], data: &[u8]) -> Vec<u8> {
+        build_elf(code, data, &[])
+    }
+
+    /// Like [`elf`], with a `.symtab` defining each `(name, value)` in `.text`.
+    pub fn elf_with_symbols(code: &[u8], symbols: &[(&str, u32)]) -> Vec<u8> {
+        build_elf(code, b"", symbols)
+    }
+
+    fn build_elf(code: &[u8], data: &[u8], symbols: &[(&str, u32)]) -> Vec<u8> {
+        let names = b"\0.text\0.rodata\0.shstrtab\0.symtab\0.strtab\0";
+        let mut file = vec![0u8; 0x34];
+        file[0..4].copy_from_slice(b"\x7FELF");
+        file[4] = 1; // ELFCLASS32
+        file[5] = 1; // ELFDATA2LSB
+
+        let mut strtab = vec![0u8];
+        let mut symtab = vec![0u8; 16]; // STN_UNDEF
+        for &(name, value) in symbols {
+            symtab.extend_from_slice(&(strtab.len() as u32).to_le_bytes());
+            symtab.extend_from_slice(&value.to_le_bytes());
+            symtab.extend_from_slice(&[0, 0, 0, 0, 0x12, 0, 1, 0]); // size, STB_GLOBAL | STT_FUNC, .text
+            strtab.extend_from_slice(name.as_bytes());
+            strtab.push(0);
+        }
+
+        let mut place = |bytes: &[u8]| {
+            let at = file.len() as u32;
+            file.extend_from_slice(bytes);
+            (at, bytes.len() as u32)
+        };
+        let text = place(code);
+        let rodata = place(data);
+        let shstrtab = place(names);
+        let symtab = place(&symtab);
+        let strtab = place(&strtab);
+        let table = file.len();
+
+        // name, type, flags, addr, offset, size, link
+        let headers: [[u32; 7]; 6] = [
+            [0; 7],
+            [1, 1, 0x6, 0x2000, text.0, text.1, 0],
+            [7, 1, 0x2, 0x4000, rodata.0, rodata.1, 0],
+            [15, 3, 0, 0, shstrtab.0, shstrtab.1, 0],
+            [25, 2, 0, 0, symtab.0, symtab.1, 5],
+            [33, 3, 0, 0, strtab.0, strtab.1, 0],
+        ];
+        for header in headers {
+            for field in header {
+                file.extend_from_slice(&field.to_le_bytes());
+            }
+            file.extend_from_slice(&[0u8; 12]);
+        }
+
+        file[0x20..0x24].copy_from_slice(&(table as u32).to_le_bytes());
+        file[0x2E..0x30].copy_from_slice(&40u16.to_le_bytes());
+        file[0x30..0x32].copy_from_slice(&(headers.len() as u16).to_le_bytes());
+        file[0x32..0x34].copy_from_slice(&3u16.to_le_bytes());
+        file
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pe_yields_only_code_sections() {
+        let image = Image::parse(&fixtures::pe(b"\x55\x8B\xEC\xC3", b"\x55\x8B\xEC")).unwrap();
+        assert_eq!(image.format, Format::Pe);
+        assert_eq!(image.sections, vec![Section {
+            name: ".text".to_string(),
+            rva: 0x1000,
+            data: b"\x55\x8B\xEC\xC3".to_vec(),
+        }]);
+    }
+
+    #[test]
+    fn elf_yields_only_code_sections() {
+        let image = Image::parse(&fixtures::elf(b"\x90\xC3", b"hello")).unwrap();
+        assert_eq!(image.format, Format::Elf);
+        assert_eq!(image.sections, vec![Section {
+            name: ".text".to_string(),
+            rva: 0x2000,
+            data: b"\x90\xC3".to_vec(),
+        }]);
+    }
+
+    #[test]
+    fn rejects_garbage_and_truncation() {
+        assert!(Image::parse(b"not a binary").is_err());
+        assert!(Image::parse(b"MZ").is_err());
+
+        let mut elf = fixtures::elf(b"\x90", b"");
+        elf[4] = 2; // ELFCLASS64
+        assert!(Image::parse(&elf).unwrap_err().contains("32-bit"));
+
+        let pe = fixtures::pe(b"\xC3", b"");
+        assert!(Image::parse(&pe[..0x150]).is_err());
+    }
+}
@@ -0,0 +1,107 @@
+//! `sigcheck` - checks the SDK's signatures against a Portal 2 install, offline.
+//!
+//! Scans the module files on disk instead of a running game, so a broken
+//! signature after a game update shows up here rather than as an
+//! "... not found" error at injection time.
+//!
+//! ```text
+//! sigcheck <game-dir> [--db <signatures.toml>] [--build <patch version>] [--linux]
+//! ```
+//!
+//! `<game-dir>` is the folder holding `bin/` and `portal2/`. With `--db`, the
+//! database file is layered over the compiled-in set exactly like the plugin
+//! does at runtime; the build defaults to the one in `portal2/steam.inf`.
+//! `--linux` checks the native Linux build's set (symbol names looked up in the
+//! `.so` files) instead of the Windows byte patterns.
+//!
+//! Exits with status 1 if any signature is missing, ambiguous or could not be checked.
+
+mod check;
+mod image;
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use portal2_sdk::platform::Abi;
+use portal2_sdk::signatures::{GameBuild, SignatureDatabase};
+
+use crate::check::Status;
+
+struct Args {
+    game_dir: PathBuf,
+    db: Option<PathBuf>,
+    build: Option<String>,
+    abi: Abi,
+}
+
+const USAGE: &str = "usage: sigcheck <game-dir> [--db <signatures.toml>] [--build <patch version>] [--linux]";
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = std::env::args().skip(1);
+    let mut game_dir = None;
+    let mut db = None;
+    let mut build = None;
+    let mut abi = Abi::Msvc;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--db" => db = Some(args.next().ok_or("--db needs a file")?.into()),
+            "--build" => build = Some(args.next().ok_or("--build needs a version")?),
+            "--linux" => abi = Abi::Itanium,
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ if arg.starts_with("--") => return Err(format!("unknown option '{}'\n{}", arg, USAGE)),
+            _ if game_dir.is_none() => game_dir = Some(arg.into()),
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+
+    Ok(Args { game_dir: game_dir.ok_or(USAGE)?, db, build, abi })
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+
+    let build = args.build.map(|patch_version| GameBuild { patch_version }).or_else(|| {
+        let inf = std::fs::read_to_string(args.game_dir.join("portal2/steam.inf")).ok()?;
+        GameBuild::from_steam_inf(&inf)
+    });
+    match &build {
+        Some(build) => println!("game build: {}", build.patch_version),
+        None => println!("game build: unknown (only generic database sections apply)"),
+    }
+
+    let mut db = SignatureDatabase::builtin_for(args.abi);
+    if let Some(path) = &args.db {
+        if let Err(e) = db.apply_file(path, build.as_ref()) {
+            eprintln!("{}", e);
+            return ExitCode::from(2);
+        }
+    }
+
+    let reports = check::check_all(&db, &args.game_dir);
+    let width = reports.iter().map(|r| r.name.len()).max().unwrap_or(0);
+
+    for report in &reports {
+        let detail = match &report.status {
+            Status::Found(rva) => format!("found      {:#010x}", rva),
+            Status::Missing => "MISSING".to_string(),
+            Status::Ambiguous(rvas) => {
+                let rvas: Vec<_> = rvas.iter().map(|rva| format!("{:#010x}", rva)).collect();
+                format!("AMBIGUOUS  {} matches: {}", rvas.len(), rvas.join(", "))
+            }
+            Status::Error(e) => format!("ERROR      {}", e),
+        };
+        println!("{:<12} {:<width$}  {}  ({})", report.module.name(), report.name, detail, report.origin);
+    }
+
+    let failed = reports.iter().filter(|r| !r.status.is_ok()).count();
+    println!("\n{} of {} signatures resolved", reports.len() - failed, reports.len());
+
+    if failed == 0 { ExitCode::SUCCESS } else { ExitCode::FAILURE }
+}